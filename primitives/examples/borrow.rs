@@ -0,0 +1,153 @@
+use rust_test::os::{PidAllocator, ProcArena};
+use rust_test::{Proc, State};
+
+//  Using the type &str would have been even better, because then print_str_len could also work for string slices - including those with static lifetimes.
+fn print_str_len_move(s: String) {
+    println!("\'{}\' is {} bytes long.", s, s.len()); 
+}
+
+fn print_str_len_borrow(s: &String) {
+    println!("\'{}\' is {} bytes long ", s, s.len()); 
+}
+
+// A Proc owns its children, so here the processes are built separately and stay where they are, on the stack, while
+// the tree only borrows them. Lifetime annotation 'a says a ProcRef must not outlive the processes it borrows; the
+// borrow checker makes sure none of them is dropped while the tree is still in use.
+struct ProcRef<'a> {
+    proc: &'a Proc,
+    children: Vec<ProcRef<'a>>,
+}
+
+impl<'a> ProcRef<'a> {
+    fn new(proc: &'a Proc, children: Vec<ProcRef<'a>>) -> Self {
+        ProcRef { proc, children }
+    }
+
+    fn print_children(&self) {
+        for child in &self.children {
+            let (parent, proc) = (self.proc, child.proc);
+            println!("{} -> {}({}) @ {:p}", parent.name(), proc.name(), proc.pid(), proc);
+            child.print_children();
+        }
+    }
+
+    // The way out of the lifetime: copy every process into an owning tree, which borrows nothing and so can outlive
+    // (or be moved away from) the processes it was copied from. Copies are new processes, with PIDs of their own. Only
+    // the children in the ProcRef tree are copied, not any a borrowed process owns itself.
+    fn to_owned_tree(&self) -> Proc {
+        let mut owned = self.proc.clone_node();
+        for child in &self.children {
+            owned.add_child(child.to_owned_tree());
+        }
+        owned
+    }
+}
+
+fn main() {
+    let x = "Hello!".to_string();
+    print_str_len_move(x); 
+
+    // This would cause a compile-time error, x is "gone", its value moved!
+    // println!("Owned string: {x}");
+
+    // Variable shadowing
+    let x = "Hello!".to_string(); 
+    print_str_len_borrow(&x);
+
+    // No error this time! x still owns the String.
+    println!("Owned string: {x}");
+
+     // Mutuable (Exclusive) references: i.e. Simulataneously only one mutuable references can 
+     let mut x = "Hello!".to_string(); 
+
+     let r1 = &mut x ;
+     r1.pop();
+     r1.push_str(", world");
+ 
+     println!("Modified via r1 {}", r1); 
+     // End of implicit (no open-close brackets) scope for 1st mutable borrow,
+     // b/c not used again in this function
+
+     let r2 = &mut x ;
+     r2.push_str("!"); 
+     println!("Modified via r2 {}", r2);
+
+    let pids = PidAllocator::default();
+    let proc = |name, state| {
+        let mut proc = Proc::builder(&pids)
+            .name(name)
+            .state(state)
+            .build()
+            .expect("a fresh allocator has PIDs to spare");
+        proc.on_drop(|p| println!("De-alloc-ing \'{}\' Proc @ {:p}", p.name(), p));
+        proc
+    };
+    let bash = proc("bash", State::Running);
+    let rsyslogd = proc("rsyslogd", State::Running);
+    let cron = proc("cron", State::Running);
+    let mut init = proc("init", State::Running);
+    let tree = ProcRef::new(
+        &init,
+        vec![
+            ProcRef::new(&rsyslogd, vec![ProcRef::new(&bash, Vec::new())]),
+            ProcRef::new(&cron, Vec::new()),
+        ],
+    );
+    tree.print_children();
+    let owned = tree.to_owned_tree();
+    // The borrowed tree is done with, so the processes it borrowed can be moved again
+    drop(tree);
+    print!("{}", owned);
+    drop(owned);
+
+    // Borrowing from an owned tree works the same way: `filter` hands back references into it, usable for as long as
+    // the tree is left alone
+    init.add_child(cron);
+    let running = init.filter(|p| p.state() == State::Running);
+    println!("{} processes running", running.len());
+    print!("{}", init);
+
+    // References can't rearrange a tree, but handles can: an arena owns every process and links them by ProcId, so
+    // the same init tree can be built, changed through any one handle while holding the others, and taken apart again
+    let mut arena = ProcArena::new();
+    let init = arena.insert(proc("init", State::Running));
+    let cron = arena.insert_child(init, proc("cron", State::Sleeping)).unwrap();
+    let rsyslogd = arena.insert_child(init, proc("rsyslogd", State::Running)).unwrap();
+    let bash = arena.insert_child(rsyslogd, proc("bash", State::Running)).unwrap();
+    arena.get_mut(cron).unwrap().set_state(State::Running);
+    let parent = arena.parent(bash).and_then(|id| arena.get(id)).unwrap();
+    println!("bash's parent is {}", parent.name());
+    if let Some(init) = arena.remove(init) {
+        print!("{}", init);
+    }
+}
+
+#[test]
+fn test_to_owned_tree_copies_the_borrowed_tree() {
+    let pids = PidAllocator::default();
+    let named = |name| Proc::builder(&pids).name(name).build().unwrap();
+    let (init, cron) = (named("init"), named("cron"));
+    let (rsyslogd, bash) = (named("rsyslogd"), named("bash"));
+    let tree = ProcRef::new(
+        &init,
+        vec![
+            ProcRef::new(&cron, Vec::new()),
+            ProcRef::new(&rsyslogd, vec![ProcRef::new(&bash, Vec::new())]),
+        ],
+    );
+    let owned = tree.to_owned_tree();
+    drop(tree);
+    // The originals can go; the copy owns everything it needs
+    drop((init, cron, rsyslogd, bash));
+    let names: Vec<_> = owned.iter_dfs().map(|p| p.name()).collect();
+    assert_eq!(names, ["init", "cron", "rsyslogd", "bash"]);
+    assert_eq!(pids.in_use(), 4);
+
+    // A borrowed process that owns children of its own is copied without them
+    let mut sshd = named("sshd");
+    sshd.add_child(named("sshd-session"));
+    let vim = named("vim");
+    let tree = ProcRef::new(&sshd, vec![ProcRef::new(&vim, Vec::new())]);
+    let names: Vec<_> = tree.to_owned_tree().iter_dfs().map(|p| p.name().to_string()).collect();
+    assert_eq!(names, ["sshd", "vim"]);
+}
@@ -0,0 +1,202 @@
+use rust_test::os::{PidAllocator, ReparentError, SharedProc};
+use rust_test::{Proc, State};
+
+// The tree is `os::Proc` from the library, the same type its process simulator runs. Each Proc owns its children, so
+// every `children(...)` call below moves the processes it's given into their new parent.
+fn proc(pids: &PidAllocator, name: &'static str, state: State, children: Vec<Proc>) -> Proc {
+    Proc::builder(pids)
+        .name(name)
+        .state(state)
+        .children(children)
+        .build()
+        .expect("a fresh allocator has PIDs to spare")
+}
+
+fn main() {
+    // Build process tree using 3 "moves" (more info soon):
+    //
+    // init
+    //  |- cron
+    //  |- rsyslogd
+    //      |- bash
+    //
+    // Run "pstree -n -g" (in container) to see your OS's real process tree!
+    // Or run "cargo run --features host-procfs" to load that same tree into the os::Proc simulator.
+
+    // None of our values own their string names ("init", "cron", "rsyslogd", and "bash").
+    // They're just borrowing a reference (&) to something that lives "forever" (until process termination) and doesn't need to be freed.
+    // These strings are baked into the compiled binary.
+
+    // Every Proc does own its PID, though, and hands it back to the allocator when dropped
+    let pids = PidAllocator::default();
+
+    // Alloc bash
+    let bash = proc(&pids, "bash", State::Running, Vec::new());
+
+    // Error: can't print an ownerless value!
+    // dbg!(bash);
+
+    // Alloc rsyslogd, 1st move: bash -> rsyslogd
+    let rsyslogd = proc(&pids, "rsyslogd", State::Running, vec![bash]);
+
+    // Alloc cron
+    let cron = proc(&pids, "cron", State::Sleeping, Vec::new());
+
+    // Alloc init, 2nd and 3rd moves: cron -> init, rsyslogd -> init
+    let mut init = proc(&pids, "init", State::Running, vec![cron, rsyslogd]);
+
+    // Report each process as it's freed
+    init.on_drop(|p| println!("De-alloc-ing \'{}\' Proc @ {:p}", p.name(), p));
+
+    // Walk the tree without writing the recursion by hand
+    let dfs: Vec<_> = init.iter_dfs().map(|p| p.name()).collect();
+    let bfs: Vec<_> = init.iter_bfs().map(|p| p.name()).collect();
+    println!("depth-first: {:?}", dfs);
+    println!("breadth-first: {:?}", bfs);
+
+    // Or search it
+    if let Some(bash) = init.find("bash") {
+        println!("found {} ({:?})", bash.name(), bash.state());
+    }
+    let running = init.filter(|p| p.state() == State::Running);
+    println!("{} processes running", running.len());
+
+    println!(
+        "{} processes, {} levels deep: {:?}",
+        init.subtree_size(),
+        init.depth(),
+        init.count_by_state()
+    );
+
+    // Move bash over to cron, and back again. It can't go under itself.
+    init.reparent("bash", "cron").unwrap();
+    assert_eq!(
+        init.reparent("rsyslogd", "rsyslogd"),
+        Err(ReparentError::Cycle)
+    );
+    init.reparent("bash", "rsyslogd").unwrap();
+
+    // Put every running process to sleep
+    for mut proc in init.iter_dfs_mut() {
+        if proc.state() == State::Running {
+            proc.set_state(State::Sleeping);
+        }
+    }
+
+    // Print the tree to see ownership hierarchy
+    print!("{}", init);
+
+    // Moves only ever point down the tree. Sharing each node through an Rc lets a child point back up at its parent,
+    // with a Weak so the two don't keep each other alive forever
+    let shared = SharedProc::new(proc(
+        &pids,
+        "init",
+        State::Running,
+        vec![proc(&pids, "sshd", State::Sleeping, Vec::new())],
+    ));
+    let sshd = shared.children()[0].clone();
+    let ancestors: Vec<_> = sshd
+        .ancestors()
+        .map(|p| p.proc().name().to_string())
+        .collect();
+    println!("sshd's ancestors: {:?}", ancestors);
+    drop(shared);
+    println!(
+        "sshd's parent after dropping init: {:?}",
+        sshd.parent().map(|p| p.proc().pid())
+    );
+    drop(sshd);
+
+    let x = "Hello Rust!!!".to_string();
+
+    // let y = x; didn't create a copy of the heap-allocated String. That'd be expensive for long strings,
+    // and thus requires an explicit call to a data duplication function (e.g. let y = x.clone();). Rust prefers performance.
+    // Instead, we performed a move - a cheap transfer of ownership: a move of a fat pointer from x into y (stack variables)
+
+    let y = x; // x moved into y. y now owns String value "Hello!"
+
+    // This works
+    println!("Owned string: {y}");
+
+    // This would cause a compile-time error, x is "gone", its value moved!
+    //println!("Owned string: {x}");
+
+    // The same choice for a whole tree. clone() copies every process: one allocation each, and since a copy is a new
+    // process it takes new PIDs too. A cow_snapshot() is walked once to take, but after that a copy of it is one Arc
+    // reference count bump, and changing a copy only copies the processes on the way down to the change.
+    let copy = init.clone();
+    println!("{} PIDs in use after a deep copy", pids.in_use());
+    drop(copy);
+    let snapshot = init.cow_snapshot();
+    let mut changed = snapshot.clone();
+    changed.children_mut()[0].set_state(State::Zombie);
+    println!(
+        "snapshot: cron {:?}, copy: cron {:?}, rsyslogd still shared: {}",
+        snapshot.children()[0].state(),
+        changed.children()[0].state(),
+        changed.children()[1].ptr_eq(&snapshot.children()[1])
+    );
+
+    // Dropping init drops its whole subtree, parents before children, and each process gives its PID back
+    println!("{} PIDs in use", pids.in_use());
+    drop(init);
+    println!("{} PIDs in use after dropping init", pids.in_use());
+}
+
+// End of scope, `y` is dropped here.
+
+// A Proc holds only fat pointers to its name and children, never the data itself. The name is a Cow: either a pointer
+// to a string hardcoded into read-only memory, as all the names here are, or an owned String made up at runtime (e.g.
+// "worker-3"). It takes no more room than a String would, as the two cases share the String's 24 bytes. The children
+// are an `os::Children`, which keeps the first two inline, as pointers to their boxes, and only moves them to a
+// Vec's tuple of memory address, total capacity, and current length once a third arrives. Either way it fits the same
+// 24 bytes a Vec would. Pointers are just memory addresses with machine specific widths, hence our caveat about the
+// sizes being for a "64-bit machine".
+//
+// The children themselves can't be stored inline: a Proc with room for even one child Proc would have to be bigger
+// than itself. Storing the two boxes inline spares the most common processes, those with one or two children, a
+// separate Vec allocation, without making a Proc any bigger than it was with a plain Vec.
+#[test]
+fn test_size() {
+    use rust_test::os::Children;
+    use std::borrow::Cow;
+
+    assert_eq!(core::mem::size_of::<&'static str>(), 16);
+    assert_eq!(core::mem::size_of::<Cow<'static, str>>(), 24);
+    assert_eq!(core::mem::size_of::<Children>(), 24);
+    assert_eq!(core::mem::size_of::<Proc>(), 352);
+}
+
+#[test]
+fn test_moves_build_the_tree() {
+    let pids = PidAllocator::default();
+    let bash = proc(&pids, "bash", State::Running, Vec::new());
+    let rsyslogd = proc(&pids, "rsyslogd", State::Running, vec![bash]);
+    let cron = proc(&pids, "cron", State::Sleeping, Vec::new());
+    let init = proc(&pids, "init", State::Running, vec![cron, rsyslogd]);
+
+    let dfs: Vec<_> = init.iter_dfs().map(|p| p.name()).collect();
+    assert_eq!(dfs, ["init", "cron", "rsyslogd", "bash"]);
+    assert_eq!(pids.in_use(), 4);
+    drop(init);
+    assert_eq!(pids.in_use(), 0);
+}
+
+#[test]
+fn test_drop_order_follows_ownership() {
+    use std::sync::mpsc;
+
+    let pids = PidAllocator::default();
+    let bash = proc(&pids, "bash", State::Running, Vec::new());
+    let rsyslogd = proc(&pids, "rsyslogd", State::Running, vec![bash]);
+    let cron = proc(&pids, "cron", State::Sleeping, Vec::new());
+    let mut init = proc(&pids, "init", State::Running, vec![cron, rsyslogd]);
+    let (tx, rx) = mpsc::channel();
+    init.on_drop(move |p| tx.send(p.name().to_string()).unwrap());
+    drop(init);
+    // A parent goes before its children, and children in the order they were moved in
+    assert_eq!(
+        rx.try_iter().collect::<Vec<_>>(),
+        ["init", "cron", "rsyslogd", "bash"]
+    );
+}
@@ -0,0 +1,341 @@
+use std::fmt;
+use std::mem;
+
+pub use error::CarError;
+use orders::OrderError;
+
+pub mod assembly;
+pub mod builder;
+pub mod csv;
+mod error;
+#[cfg(feature = "serde")]
+mod json;
+pub mod orders;
+pub mod pricing;
+pub mod rental;
+pub mod service;
+pub mod sort;
+pub mod telemetry;
+
+// Declare Car struct to describe vehicle with named fields
+#[derive(Debug, PartialEq, Eq)]
+pub struct Car {
+    pub color: String,
+    pub transmission: Transmission,
+    pub convertible: bool,
+    pub mileage: u32,
+    pub powertrain: Powertrain,
+    service: Vec<service::ServiceRecord>, // See `Car::add_service`
+    part_mile: u8, // Sixtieths of a mile driven past the odometer reading, under 60
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
+// Declare enum for Car transmission type
+pub enum Transmission {
+    // Unit struct without fields or datatypes
+    Manual,
+    SemiAuto,
+    Automatic,
+}
+
+// Declare enum for what drives the car
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
+pub enum Powertrain {
+    Gasoline,
+    Diesel,
+    Hybrid,
+    // Struct-like variant with a named field
+    Electric { battery_kwh: u32 },
+}
+
+// Declare enum for how old a car is when it leaves the factory
+#[derive(PartialEq, Debug)]
+pub enum Age {
+    New,
+    Used(u32), // Miles already on the clock
+}
+
+// Build a "Car" by using values from the input arguments
+// - Color of car (String)
+// - Transmission type (enum value)
+// - Convertible (boolean, true if car is a convertible)
+// - Age (enum value, used cars carry their mileage)
+// The factory builds gasoline cars; `Car::builder` can make the others. Specs it can't build are refused, see
+// `BuildError` for the rules
+pub fn car_factory(
+    color: String,
+    transmission: Transmission,
+    convertible: bool,
+    age: Age,
+) -> Result<Car, OrderError> {
+    builder::check_specs(&transmission, convertible, Powertrain::Gasoline)?;
+    Ok(Car::new_unchecked(color, transmission, convertible, age))
+}
+
+impl Car {
+    /// A gasoline car built to these specs, whether or not the factory would build it. For tests, and for specs
+    /// that have been checked already.
+    pub fn new_unchecked(
+        color: String,
+        transmission: Transmission,
+        convertible: bool,
+        age: Age,
+    ) -> Car {
+        // Use the values of the input arguments
+        // All new cars always have zero mileage
+        let mileage = match age {
+            Age::New => 0,
+            Age::Used(miles) => miles,
+        };
+        Car {
+            color,
+            transmission,
+            convertible,
+            mileage,
+            powertrain: Powertrain::Gasoline,
+            service: Vec::new(),
+            part_mile: 0,
+        }
+    }
+
+    /// Adds `miles` to the odometer and returns the new reading. An odometer that would run past `u32::MAX` is left
+    /// as it was and `None` returned, rather than wrapping back round to a low mileage.
+    pub fn drive(&mut self, miles: u32) -> Option<u32> {
+        self.mileage = self.mileage.checked_add(miles)?;
+        Some(self.mileage)
+    }
+}
+
+impl fmt::Display for Car {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}, {:?} transmission, convertible: {}, mileage: {}",
+            self.color, self.transmission, self.convertible, self.mileage
+        )
+    }
+}
+
+/// The cars on order, each under the order number it was given when added. Numbers start at 1 and aren't reused, so
+/// cancelling an order doesn't renumber the ones after it.
+#[derive(Debug, Default)]
+pub struct Fleet {
+    cars: Vec<(u32, Car)>,
+    next: u32,
+}
+
+impl Fleet {
+    pub fn new() -> Fleet {
+        Fleet::default()
+    }
+
+    /// Adds `car` and returns its order number.
+    pub fn add(&mut self, car: Car) -> u32 {
+        self.next += 1;
+        self.cars.push((self.next, car));
+        self.next
+    }
+
+    /// Takes the car with order number `number` out of the fleet, if it's still there.
+    pub fn remove(&mut self, number: u32) -> Option<Car> {
+        let index = self.cars.iter().position(|(n, _)| *n == number)?;
+        Some(self.cars.remove(index).1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cars.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cars.is_empty()
+    }
+
+    /// The cars with their order numbers, oldest order first unless the fleet has been sorted.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &Car)> {
+        self.cars.iter().map(|(n, car)| (*n, car))
+    }
+
+    /// The cars `matches` picks out, with their order numbers. The fleet is searched as the iterator is advanced, so
+    /// stopping at the first match costs no more than that.
+    pub fn find<'a>(
+        &'a self,
+        mut matches: impl FnMut(&Car) -> bool + 'a,
+    ) -> impl Iterator<Item = (u32, &'a Car)> + 'a {
+        self.iter().filter(move |(_, car)| matches(car))
+    }
+
+    /// The cars painted `color`, ignoring case.
+    pub fn by_color<'a>(&'a self, color: &'a str) -> impl Iterator<Item = (u32, &'a Car)> + 'a {
+        self.find(move |car| car.color.eq_ignore_ascii_case(color))
+    }
+
+    pub fn by_transmission(
+        &self,
+        transmission: Transmission,
+    ) -> impl Iterator<Item = (u32, &Car)> + '_ {
+        self.find(move |car| car.transmission == transmission)
+    }
+
+    pub fn by_convertible(&self, convertible: bool) -> impl Iterator<Item = (u32, &Car)> + '_ {
+        self.find(move |car| car.convertible == convertible)
+    }
+
+    /// The cars with the same kind of powertrain as `powertrain`. Electric cars match whatever their battery size.
+    pub fn with_powertrain(&self, powertrain: Powertrain) -> impl Iterator<Item = (u32, &Car)> {
+        self.find(move |car| mem::discriminant(&car.powertrain) == mem::discriminant(&powertrain))
+    }
+}
+
+impl<'a> IntoIterator for &'a Fleet {
+    type Item = (u32, &'a Car);
+    type IntoIter = Box<dyn Iterator<Item = (u32, &'a Car)> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(self.iter())
+    }
+}
+
+#[test]
+fn test_fleet_keeps_order_numbers() {
+    let mut fleet = Fleet::new();
+    let red = fleet.add(Car::new_unchecked(
+        String::from("Red"),
+        Transmission::Manual,
+        false,
+        Age::New,
+    ));
+    let silver = fleet.add(Car::new_unchecked(
+        String::from("Silver"),
+        Transmission::Automatic,
+        true,
+        Age::New,
+    ));
+    let yellow = fleet.add(Car::new_unchecked(
+        String::from("Yellow"),
+        Transmission::SemiAuto,
+        false,
+        Age::New,
+    ));
+    assert_eq!((red, silver, yellow), (1, 2, 3));
+
+    let cancelled = fleet.remove(silver).unwrap();
+    assert!(cancelled.convertible);
+    assert!(fleet.remove(silver).is_none());
+    assert_eq!(fleet.len(), 2);
+    let orders: Vec<_> = fleet
+        .iter()
+        .map(|(n, car)| (n, car.color.as_str()))
+        .collect();
+    assert_eq!(orders, [(1, "Red"), (3, "Yellow")]);
+
+    let ev = Car::builder("White")
+        .powertrain(Powertrain::Electric { battery_kwh: 60 })
+        .build()
+        .unwrap();
+    let ev = fleet.add(ev);
+    let electric: Vec<_> = fleet
+        .with_powertrain(Powertrain::Electric { battery_kwh: 0 })
+        .map(|(n, _)| n)
+        .collect();
+    assert_eq!(electric, [ev]);
+    assert_eq!(fleet.with_powertrain(Powertrain::Gasoline).count(), 2);
+    fleet.remove(ev);
+
+    // Numbers aren't handed out twice
+    assert_eq!(
+        fleet.add(Car::new_unchecked(
+            String::from("Blue"),
+            Transmission::Manual,
+            true,
+            Age::New
+        )),
+        5
+    );
+    assert_eq!(
+        (&fleet).into_iter().last().unwrap().1.to_string(),
+        "Blue, Manual transmission, convertible: true, mileage: 0"
+    );
+}
+
+#[test]
+fn test_fleet_searches_lazily() {
+    let mut fleet = Fleet::new();
+    let orders = "Red,Manual,false\nred,Automatic,true\nBlue,Automatic,true\nBlue,SemiAuto,false\n";
+    fleet.import_orders_csv(orders.as_bytes()).unwrap();
+    fn numbers<'a>(found: impl Iterator<Item = (u32, &'a Car)>) -> Vec<u32> {
+        found.map(|(n, _)| n).collect()
+    }
+    assert_eq!(numbers(fleet.by_color("RED")), [1, 2]);
+    assert_eq!(
+        numbers(fleet.by_transmission(Transmission::Automatic)),
+        [2, 3]
+    );
+    assert_eq!(numbers(fleet.by_convertible(false)), [1, 4]);
+    assert_eq!(
+        numbers(fleet.find(|car| car.color == "Blue" && !car.convertible)),
+        [4]
+    );
+
+    // Only as much of the fleet is looked at as the caller asks for
+    let mut looked_at = 0;
+    let first = fleet
+        .find(|car| {
+            looked_at += 1;
+            car.convertible
+        })
+        .next();
+    assert_eq!(first.map(|(n, _)| n), Some(2));
+    assert_eq!(looked_at, 2);
+}
+
+#[test]
+fn test_factory_refuses_what_it_cant_build() {
+    use builder::BuildError;
+
+    assert_eq!(
+        car_factory(
+            String::from("Black"),
+            Transmission::SemiAuto,
+            true,
+            Age::New
+        )
+        .unwrap_err(),
+        OrderError::Specs(BuildError::SemiAutoConvertible)
+    );
+    let car = car_factory(
+        String::from("Black"),
+        Transmission::SemiAuto,
+        false,
+        Age::New,
+    )
+    .unwrap();
+    assert!(!car.convertible);
+    // Tests that need one anyway can still have it
+    assert!(
+        Car::new_unchecked(
+            String::from("Black"),
+            Transmission::SemiAuto,
+            true,
+            Age::New
+        )
+        .convertible
+    );
+}
+
+#[test]
+fn test_used_cars_keep_driving() {
+    let mut car = Car::new_unchecked(
+        String::from("Blue"),
+        Transmission::Manual,
+        true,
+        Age::Used(42_000),
+    );
+    assert_eq!(car.mileage, 42_000);
+    assert_eq!(car.drive(150), Some(42_150));
+    assert_eq!(car.drive(u32::MAX), None);
+    assert_eq!(car.mileage, 42_150);
+    assert_eq!(
+        Car::new_unchecked(String::from("Red"), Transmission::Manual, false, Age::New).mileage,
+        0
+    );
+}
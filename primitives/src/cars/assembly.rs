@@ -0,0 +1,118 @@
+use super::builder::{check_specs, BuildError};
+use super::{Age, Car, Powertrain, Transmission};
+
+// The assembly line, with each stage its own type. A stage's methods are the only way on to the next one, and each
+// takes the stage by value, so the compiler keeps the order: there's no `finish` on a bare `Chassis`, so a car can't
+// skip the paint shop, and once `fit_drivetrain` has turned a `Painted` car into a `Drivetrain` there's no
+// `fit_drivetrain` left to call, so it can't be given a second transmission. Mistakes like these don't get as far as
+// a test; they don't compile.
+//
+//     let car = Chassis::new(false)
+//         .paint("Red")
+//         .fit_drivetrain(Transmission::Manual, Powertrain::Diesel)?
+//         .finish()
+//         .into_car();
+//
+// The one thing the types can't check is whether the drivetrain suits the body, which `fit_drivetrain` does as
+// `car_factory` would.
+
+/// A bare frame, open-topped or not.
+#[derive(Debug)]
+pub struct Chassis {
+    convertible: bool,
+}
+
+/// Painted, but with nothing to drive it yet.
+#[derive(Debug)]
+pub struct Painted {
+    convertible: bool,
+    color: String,
+}
+
+/// Painted with an engine and gearbox fitted, waiting for final inspection.
+#[derive(Debug)]
+pub struct Drivetrain {
+    convertible: bool,
+    color: String,
+    transmission: Transmission,
+    powertrain: Powertrain,
+}
+
+/// Off the line and ready to go.
+#[derive(Debug)]
+pub struct Finished {
+    car: Car,
+}
+
+impl Chassis {
+    pub fn new(convertible: bool) -> Chassis {
+        Chassis { convertible }
+    }
+
+    pub fn paint(self, color: impl Into<String>) -> Painted {
+        Painted {
+            convertible: self.convertible,
+            color: color.into(),
+        }
+    }
+}
+
+impl Painted {
+    /// Fits the engine and gearbox, unless they can't go in this body.
+    pub fn fit_drivetrain(
+        self,
+        transmission: Transmission,
+        powertrain: Powertrain,
+    ) -> Result<Drivetrain, BuildError> {
+        check_specs(&transmission, self.convertible, powertrain)?;
+        Ok(Drivetrain {
+            convertible: self.convertible,
+            color: self.color,
+            transmission,
+            powertrain,
+        })
+    }
+}
+
+impl Drivetrain {
+    pub fn finish(self) -> Finished {
+        let mut car = Car::new_unchecked(self.color, self.transmission, self.convertible, Age::New);
+        car.powertrain = self.powertrain;
+        Finished { car }
+    }
+}
+
+impl Finished {
+    pub fn car(&self) -> &Car {
+        &self.car
+    }
+
+    pub fn into_car(self) -> Car {
+        self.car
+    }
+}
+
+#[test]
+fn test_assembly_goes_through_every_stage() {
+    let finished = Chassis::new(true)
+        .paint("White")
+        .fit_drivetrain(
+            Transmission::Automatic,
+            Powertrain::Electric { battery_kwh: 75 },
+        )
+        .unwrap()
+        .finish();
+    assert_eq!(finished.car().color, "White");
+    let car = finished.into_car();
+    assert!(car.convertible);
+    assert_eq!(car.mileage, 0);
+    assert_eq!(car.powertrain, Powertrain::Electric { battery_kwh: 75 });
+
+    assert_eq!(
+        Chassis::new(true)
+            .paint("Black")
+            .fit_drivetrain(Transmission::SemiAuto, Powertrain::Gasoline)
+            .unwrap_err(),
+        BuildError::SemiAutoConvertible
+    );
+}
@@ -0,0 +1,132 @@
+use std::error::Error;
+use std::fmt;
+
+use super::{Age, Car, Powertrain, Transmission};
+
+/// Why the factory refused to make a car, whether it was asked through `car_factory`, an order or `CarBuilder`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildError {
+    // An electric motor has no gears to change, so there's nothing for a manual or semi-auto gearbox to do
+    ElectricNotAutomatic,
+    // The semi-auto's clutch actuator sits where a convertible's folded roof goes
+    SemiAutoConvertible,
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::ElectricNotAutomatic => write!(f, "electric cars are automatic only"),
+            BuildError::SemiAutoConvertible => {
+                write!(f, "convertibles can't have a semi-automatic transmission")
+            }
+        }
+    }
+}
+
+impl Error for BuildError {}
+
+// The rules every car has to meet, however it was ordered.
+pub(super) fn check_specs(
+    transmission: &Transmission,
+    convertible: bool,
+    powertrain: Powertrain,
+) -> Result<(), BuildError> {
+    if matches!(powertrain, Powertrain::Electric { .. }) && *transmission != Transmission::Automatic
+    {
+        return Err(BuildError::ElectricNotAutomatic);
+    }
+    if convertible && *transmission == Transmission::SemiAuto {
+        return Err(BuildError::SemiAutoConvertible);
+    }
+    Ok(())
+}
+
+/// Specs a car one option at a time, e.g. `Car::builder("White").powertrain(Powertrain::Diesel).build()`. Anything
+/// left unset is a new gasoline automatic hardtop.
+#[derive(Debug)]
+pub struct CarBuilder {
+    color: String,
+    transmission: Transmission,
+    convertible: bool,
+    age: Age,
+    powertrain: Powertrain,
+}
+
+impl Car {
+    pub fn builder(color: impl Into<String>) -> CarBuilder {
+        CarBuilder {
+            color: color.into(),
+            transmission: Transmission::Automatic,
+            convertible: false,
+            age: Age::New,
+            powertrain: Powertrain::Gasoline,
+        }
+    }
+}
+
+impl CarBuilder {
+    pub fn transmission(mut self, transmission: Transmission) -> Self {
+        self.transmission = transmission;
+        self
+    }
+
+    pub fn convertible(mut self, convertible: bool) -> Self {
+        self.convertible = convertible;
+        self
+    }
+
+    pub fn age(mut self, age: Age) -> Self {
+        self.age = age;
+        self
+    }
+
+    pub fn powertrain(mut self, powertrain: Powertrain) -> Self {
+        self.powertrain = powertrain;
+        self
+    }
+
+    /// Checks the options fit together and makes the car.
+    pub fn build(self) -> Result<Car, BuildError> {
+        check_specs(&self.transmission, self.convertible, self.powertrain)?;
+        let mut car = Car::new_unchecked(self.color, self.transmission, self.convertible, self.age);
+        car.powertrain = self.powertrain;
+        Ok(car)
+    }
+}
+
+#[test]
+fn test_builder_keeps_electric_cars_automatic() {
+    let ev = Car::builder("White")
+        .convertible(true)
+        .powertrain(Powertrain::Electric { battery_kwh: 75 })
+        .build()
+        .unwrap();
+    assert_eq!(ev.transmission, Transmission::Automatic);
+    assert_eq!(ev.powertrain, Powertrain::Electric { battery_kwh: 75 });
+    assert_eq!(
+        Car::builder("White")
+            .transmission(Transmission::Manual)
+            .powertrain(Powertrain::Electric { battery_kwh: 75 })
+            .build()
+            .unwrap_err(),
+        BuildError::ElectricNotAutomatic
+    );
+
+    assert_eq!(
+        Car::builder("Black")
+            .transmission(Transmission::SemiAuto)
+            .convertible(true)
+            .build()
+            .unwrap_err(),
+        BuildError::SemiAutoConvertible
+    );
+
+    let diesel = Car::builder("Grey")
+        .transmission(Transmission::Manual)
+        .age(Age::Used(90_000))
+        .powertrain(Powertrain::Diesel)
+        .build()
+        .unwrap();
+    assert_eq!(diesel.mileage, 90_000);
+    assert!(!diesel.convertible);
+}
@@ -0,0 +1,134 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead};
+
+use super::orders::OrderError;
+use super::{car_factory, Age, Fleet, Transmission};
+
+// Bulk orders, one car per line as `color,transmission,convertible`, e.g. `Red,Manual,false`. A bad line is reported
+// and skipped; the lines around it are still ordered, so one typo doesn't hold up a whole batch.
+
+/// What was wrong with one line of an import, numbered from 1.
+#[derive(Debug, PartialEq, Eq)]
+pub struct LineError {
+    pub line: usize,
+    pub problem: Problem,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Problem {
+    Fields(usize),        // Wrong number of fields, three are needed
+    Transmission(String), // Not Manual, SemiAuto or Automatic
+    Convertible(String),  // Not true or false
+    Order(OrderError),    // Makes sense, but isn't a car the factory can build
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.problem {
+            Problem::Fields(n) => write!(f, "expected 3 fields, found {}", n),
+            Problem::Transmission(s) => write!(f, "unknown transmission `{}`", s),
+            Problem::Convertible(s) => {
+                write!(f, "convertible should be true or false, not `{}`", s)
+            }
+            Problem::Order(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for LineError {}
+
+/// What an import did: the order numbers of the cars it added, and the lines it couldn't use.
+#[derive(Debug, Default)]
+pub struct Import {
+    pub added: Vec<u32>,
+    pub errors: Vec<LineError>,
+}
+
+impl Fleet {
+    /// Orders a new car for every good line of `reader`. Blank lines are skipped. Only failing to read stops the
+    /// import part way, leaving the cars already added in the fleet.
+    pub fn import_orders_csv(&mut self, reader: impl BufRead) -> io::Result<Import> {
+        let mut import = Import::default();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let car = parse_order(&line).and_then(|(color, transmission, convertible)| {
+                car_factory(color, transmission, convertible, Age::New).map_err(Problem::Order)
+            });
+            match car {
+                Ok(car) => import.added.push(self.add(car)),
+                Err(problem) => import.errors.push(LineError {
+                    line: i + 1,
+                    problem,
+                }),
+            }
+        }
+        Ok(import)
+    }
+}
+
+fn parse_order(line: &str) -> Result<(String, Transmission, bool), Problem> {
+    let fields: Vec<_> = line.split(',').map(str::trim).collect();
+    let [color, transmission, convertible] = fields[..] else {
+        return Err(Problem::Fields(fields.len()));
+    };
+    let transmission = match transmission {
+        "Manual" => Transmission::Manual,
+        "SemiAuto" => Transmission::SemiAuto,
+        "Automatic" => Transmission::Automatic,
+        other => return Err(Problem::Transmission(other.to_string())),
+    };
+    let convertible = convertible
+        .parse()
+        .map_err(|_| Problem::Convertible(convertible.to_string()))?;
+    Ok((color.to_string(), transmission, convertible))
+}
+
+#[test]
+fn test_import_skips_bad_lines() {
+    use super::builder::BuildError;
+
+    let orders = "Red,Manual,false\n\
+                  Silver, Automatic, true\n\
+                  \n\
+                  Yellow,Paddle,false\n\
+                  Blue,Manual\n\
+                  Green,SemiAuto,yes\n\
+                  Black,SemiAuto,false\n\
+                  White,SemiAuto,true\n";
+    let mut fleet = Fleet::new();
+    let import = fleet.import_orders_csv(orders.as_bytes()).unwrap();
+    assert_eq!(import.added, [1, 2, 3]);
+    assert_eq!(
+        import.errors,
+        [
+            LineError {
+                line: 4,
+                problem: Problem::Transmission("Paddle".to_string())
+            },
+            LineError {
+                line: 5,
+                problem: Problem::Fields(2)
+            },
+            LineError {
+                line: 6,
+                problem: Problem::Convertible("yes".to_string())
+            },
+            LineError {
+                line: 8,
+                problem: Problem::Order(OrderError::Specs(BuildError::SemiAutoConvertible))
+            },
+        ]
+    );
+    assert_eq!(
+        import.errors[0].to_string(),
+        "line 4: unknown transmission `Paddle`"
+    );
+    let colors: Vec<_> = fleet.iter().map(|(_, car)| car.color.as_str()).collect();
+    assert_eq!(colors, ["Red", "Silver", "Black"]);
+    assert!(fleet.iter().nth(1).unwrap().1.convertible);
+}
@@ -0,0 +1,53 @@
+use std::error::Error;
+use std::fmt;
+
+use super::builder::BuildError;
+use super::csv::LineError;
+use super::orders::OrderError;
+use super::rental::RentalError;
+
+/// Any error from the car modules, for code that builds, orders and rents cars in one go and wants `?` on each step.
+#[derive(Debug, PartialEq)]
+pub enum CarError {
+    Build(BuildError),
+    Csv(LineError),
+    Order(OrderError),
+    Rental(RentalError),
+}
+
+impl fmt::Display for CarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CarError::Build(err) => err.fmt(f),
+            CarError::Csv(err) => err.fmt(f),
+            CarError::Order(err) => err.fmt(f),
+            CarError::Rental(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for CarError {}
+
+impl From<BuildError> for CarError {
+    fn from(err: BuildError) -> Self {
+        CarError::Build(err)
+    }
+}
+
+impl From<LineError> for CarError {
+    fn from(err: LineError) -> Self {
+        CarError::Csv(err)
+    }
+}
+
+impl From<OrderError> for CarError {
+    fn from(err: OrderError) -> Self {
+        CarError::Order(err)
+    }
+}
+
+impl From<RentalError> for CarError {
+    fn from(err: RentalError) -> Self {
+        CarError::Rental(err)
+    }
+}
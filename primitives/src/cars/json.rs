@@ -0,0 +1,347 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::os::{FromJson, Json, JsonError, ToJson};
+
+use super::builder::check_specs;
+use super::service::{Date, ServiceRecord};
+use super::{Car, Fleet, Powertrain, Transmission};
+
+// Cars are saved with the same JSON the os types use, so the encoding is serde's default here too: unit variants are
+// strings, `Electric` is `{"Electric":{"battery_kwh":75}}`. A fleet file also keeps the last order number handed out,
+// so orders placed after loading it don't reuse a number. Loading holds a file to the same rules as making a car or
+// placing an order would: a car the factory wouldn't build, or order numbers that clash, are errors.
+
+/// Version written into saved fleets, bumped whenever their layout changes.
+const FORMAT_VERSION: u64 = 1;
+
+const TRANSMISSIONS: [Transmission; 3] = [
+    Transmission::Manual,
+    Transmission::SemiAuto,
+    Transmission::Automatic,
+];
+
+impl ToJson for Transmission {
+    fn to_json(&self) -> Json {
+        Json::Str(format!("{:?}", self))
+    }
+}
+
+impl FromJson for Transmission {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        let Json::Str(name) = json else {
+            return Err(JsonError::Field("variant"));
+        };
+        TRANSMISSIONS
+            .into_iter()
+            .find(|t| format!("{:?}", t) == *name)
+            .ok_or(JsonError::Field("variant"))
+    }
+}
+
+impl ToJson for Powertrain {
+    fn to_json(&self) -> Json {
+        match self {
+            Powertrain::Electric { battery_kwh } => Json::object(vec![(
+                "Electric",
+                Json::object(vec![("battery_kwh", Json::Int((*battery_kwh).into()))]),
+            )]),
+            unit => Json::Str(format!("{:?}", unit)),
+        }
+    }
+}
+
+impl FromJson for Powertrain {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        if let Some(electric) = json.get("Electric") {
+            return Ok(Powertrain::Electric {
+                battery_kwh: electric.int("battery_kwh")?,
+            });
+        }
+        [Powertrain::Gasoline, Powertrain::Diesel, Powertrain::Hybrid]
+            .into_iter()
+            .find(|p| Json::Str(format!("{:?}", p)) == *json)
+            .ok_or(JsonError::Field("variant"))
+    }
+}
+
+impl ToJson for Date {
+    fn to_json(&self) -> Json {
+        Json::object(vec![
+            ("year", Json::Int(self.year.into())),
+            ("month", Json::Int(self.month.into())),
+            ("day", Json::Int(self.day.into())),
+        ])
+    }
+}
+
+impl FromJson for Date {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        Ok(Date {
+            year: json.int("year")?,
+            month: json.int("month")?,
+            day: json.int("day")?,
+        })
+    }
+}
+
+impl ToJson for ServiceRecord {
+    fn to_json(&self) -> Json {
+        Json::object(vec![
+            ("date", self.date.to_json()),
+            ("mileage", Json::Int(self.mileage.into())),
+            ("work", Json::Str(self.work.clone())),
+        ])
+    }
+}
+
+impl FromJson for ServiceRecord {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        Ok(ServiceRecord {
+            date: json.parse("date")?,
+            mileage: json.int("mileage")?,
+            work: json.string("work")?.to_string(),
+        })
+    }
+}
+
+impl ToJson for Car {
+    fn to_json(&self) -> Json {
+        Json::object(vec![
+            ("color", Json::Str(self.color.clone())),
+            ("transmission", self.transmission.to_json()),
+            ("convertible", Json::Bool(self.convertible)),
+            ("mileage", Json::Int(self.mileage.into())),
+            ("powertrain", self.powertrain.to_json()),
+            (
+                "service",
+                Json::Array(self.service.iter().map(ServiceRecord::to_json).collect()),
+            ),
+            ("part_mile", Json::Int(self.part_mile.into())),
+        ])
+    }
+}
+
+impl FromJson for Car {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        let transmission = json.parse("transmission")?;
+        let convertible = json.bool("convertible")?;
+        let powertrain = json.parse("powertrain")?;
+        check_specs(&transmission, convertible, powertrain)
+            .map_err(|_| JsonError::Field("transmission"))?;
+        let mut car = Car {
+            color: json.string("color")?.to_string(),
+            transmission,
+            convertible,
+            mileage: json.int("mileage")?,
+            powertrain,
+            service: Vec::new(),
+            part_mile: 0,
+        };
+        // Nor the part of a mile not yet on the odometer
+        if json.get("part_mile").is_some() {
+            car.part_mile = json.int("part_mile")?;
+            if car.part_mile >= 60 {
+                return Err(JsonError::Field("part_mile"));
+            }
+        }
+        // Older files have no service history. Records go through `add_service`, so they end up oldest first however
+        // the file lists them.
+        if json.get("service").is_some() {
+            for record in json.array("service")? {
+                car.add_service(ServiceRecord::from_json(record)?);
+            }
+        }
+        Ok(car)
+    }
+}
+
+impl ToJson for Fleet {
+    fn to_json(&self) -> Json {
+        let cars = self
+            .iter()
+            .map(|(number, car)| {
+                Json::object(vec![
+                    ("number", Json::Int(number.into())),
+                    ("car", car.to_json()),
+                ])
+            })
+            .collect();
+        Json::object(vec![
+            ("next", Json::Int(self.next.into())),
+            ("cars", Json::Array(cars)),
+        ])
+    }
+}
+
+impl FromJson for Fleet {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        let mut cars: Vec<(u32, Car)> = Vec::new();
+        for order in json.array("cars")? {
+            let number = order.int("number")?;
+            if number == 0 || cars.iter().any(|&(n, _)| n == number) {
+                return Err(JsonError::Field("number"));
+            }
+            cars.push((number, order.parse("car")?));
+        }
+        // `add` hands out the number after `next`, which mustn't be one already taken
+        let next = json.int("next")?;
+        if cars.iter().any(|&(number, _)| number > next) {
+            return Err(JsonError::Field("next"));
+        }
+        Ok(Fleet { cars, next })
+    }
+}
+
+impl Fleet {
+    /// Writes every car, with its order number, to `path`.
+    pub fn save_json(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let file = Json::object(vec![
+            ("version", Json::Int(FORMAT_VERSION.into())),
+            ("fleet", self.to_json()),
+        ]);
+        fs::write(path, format!("{}\n", file))
+    }
+
+    /// Reads back a fleet written by `save_json`.
+    pub fn load_json(path: impl AsRef<Path>) -> io::Result<Fleet> {
+        let file: Json = fs::read_to_string(path)?.parse()?;
+        let version: u64 = file.int("version")?;
+        if version != FORMAT_VERSION {
+            return Err(JsonError::Version(version).into());
+        }
+        Ok(file.parse("fleet")?)
+    }
+}
+
+#[test]
+fn test_fleet_file_round_trips() {
+    use super::Age;
+
+    let mut fleet = Fleet::new();
+    for (color, transmission) in ["Red", "Silver", "Yellow"].into_iter().zip(TRANSMISSIONS) {
+        let mut car = Car::new_unchecked(color.to_string(), transmission, false, Age::Used(12));
+        car.add_service(ServiceRecord {
+            date: Date {
+                year: 2024,
+                month: 2,
+                day: 29,
+            },
+            mileage: 10,
+            work: format!("{} paint touched up", color),
+        });
+        fleet.add(car);
+    }
+    let cancelled = fleet.add(
+        Car::builder("\"Racing\" green")
+            .convertible(true)
+            .powertrain(Powertrain::Hybrid)
+            .build()
+            .unwrap(),
+    );
+    fleet.add(
+        Car::builder("White")
+            .powertrain(Powertrain::Electric { battery_kwh: 75 })
+            .build()
+            .unwrap(),
+    );
+    fleet.remove(cancelled);
+
+    let path = std::env::temp_dir().join(format!("fleet-{}.json", std::process::id()));
+    fleet.save_json(&path).unwrap();
+    let mut loaded = Fleet::load_json(&path).unwrap();
+    assert_eq!(loaded.to_json(), fleet.to_json());
+    let transmissions: Vec<_> = loaded.iter().map(|(_, car)| &car.transmission).collect();
+    assert_eq!(
+        transmissions,
+        [
+            &Transmission::Manual,
+            &Transmission::SemiAuto,
+            &Transmission::Automatic,
+            &Transmission::Automatic
+        ]
+    );
+    assert_eq!(
+        loaded.iter().last().unwrap().1.powertrain,
+        Powertrain::Electric { battery_kwh: 75 }
+    );
+    assert_eq!(
+        loaded.iter().next().unwrap().1.service_history()[0].work,
+        "Red paint touched up"
+    );
+    // Numbering carries on where the saved fleet left off
+    assert_eq!(
+        loaded.add(Car::new_unchecked(
+            String::from("Blue"),
+            Transmission::Manual,
+            true,
+            Age::New
+        )),
+        6
+    );
+
+    let text = fs::read_to_string(&path).unwrap();
+    fs::write(&path, text.replacen("\"Manual\"", "\"Paddle\"", 1)).unwrap();
+    let err = Fleet::load_json(&path).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(err.to_string(), JsonError::Field("fleet").to_string());
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_loaded_fleet_follows_the_factory_rules() {
+    let car = |transmission: &str, powertrain: &str, service: &[String]| {
+        let text = format!(
+            r#"{{"color": "Red", "transmission": "{}", "convertible": false, "mileage": 0, "powertrain": {},
+                "service": [{}]}}"#,
+            transmission,
+            powertrain,
+            service.join(",")
+        );
+        Car::from_json(&text.parse().unwrap())
+    };
+    let fleet = |next: u32, numbers: &[u32]| {
+        let diesel = car("Manual", r#""Diesel""#, &[]).unwrap().to_json();
+        let cars: Vec<_> = numbers
+            .iter()
+            .map(|n| format!(r#"{{"number": {}, "car": {}}}"#, n, diesel))
+            .collect();
+        let text = format!(r#"{{"next": {}, "cars": [{}]}}"#, next, cars.join(","));
+        Fleet::from_json(&text.parse().unwrap())
+    };
+    let mut loaded = fleet(3, &[1, 3]).unwrap();
+    assert_eq!(loaded.add(Car::builder("Blue").build().unwrap()), 4);
+    assert_eq!(
+        fleet(3, &[1, 3, 1]).unwrap_err(),
+        JsonError::Field("number")
+    );
+    assert_eq!(fleet(0, &[0]).unwrap_err(), JsonError::Field("number"));
+    assert_eq!(fleet(2, &[1, 3]).unwrap_err(), JsonError::Field("next"));
+
+    let electric = r#"{"Electric": {"battery_kwh": 75}}"#;
+    assert_eq!(
+        car("Manual", electric, &[]).unwrap_err(),
+        JsonError::Field("transmission")
+    );
+
+    // Service records come back oldest first even if the file has them out of order
+    let record = |year: u16, work: &str| {
+        format!(
+            r#"{{"date": {{"year": {}, "month": 1, "day": 1}}, "mileage": 0, "work": "{}"}}"#,
+            year, work
+        )
+    };
+    let serviced = car(
+        "Automatic",
+        electric,
+        &[record(2024, "tyres"), record(2022, "brakes")],
+    )
+    .unwrap();
+    let work: Vec<_> = serviced
+        .service_history()
+        .iter()
+        .map(|r| r.work.as_str())
+        .collect();
+    assert_eq!(work, ["brakes", "tyres"]);
+}
@@ -0,0 +1,186 @@
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::mem;
+
+use super::builder::{self, BuildError};
+use super::{Age, Car, Powertrain, Transmission};
+
+// Orders come in faster than cars come off the line, so they wait in a queue. Each one is given a serial number when
+// it's submitted, and the car built from it a VIN when it's fulfilled; neither is ever handed out twice. Until it's
+// built an order can still be corrected, and keeps its serial number and its place in the queue.
+
+/// What the customer asked for.
+#[derive(Debug, PartialEq)]
+pub struct Order {
+    pub color: String,
+    pub transmission: Transmission,
+    pub convertible: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Serial(u32);
+
+/// Vehicle identification number, stamped on a car as it's built.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Vin(u32);
+
+impl fmt::Display for Serial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+impl fmt::Display for Vin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "VIN{:06}", self.0)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum OrderError {
+    Unknown,           // No order was ever submitted under this serial number
+    Fulfilled(Vin),    // Too late, the car has been built
+    Specs(BuildError), // The factory can't build what was asked for
+}
+
+impl fmt::Display for OrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderError::Unknown => write!(f, "no such order"),
+            OrderError::Fulfilled(vin) => write!(f, "order already built as {}", vin),
+            OrderError::Specs(e) => write!(f, "can't build that: {}", e),
+        }
+    }
+}
+
+impl Error for OrderError {}
+
+impl From<BuildError> for OrderError {
+    fn from(err: BuildError) -> Self {
+        OrderError::Specs(err)
+    }
+}
+
+impl Order {
+    fn check(&self) -> Result<(), BuildError> {
+        builder::check_specs(&self.transmission, self.convertible, Powertrain::Gasoline)
+    }
+}
+
+#[derive(Default)]
+pub struct OrderQueue {
+    pending: VecDeque<(Serial, Order)>,
+    fulfilled: HashMap<Serial, Vin>,
+    built: HashMap<Vin, Car>,
+    next_serial: u32,
+    next_vin: u32,
+}
+
+impl OrderQueue {
+    pub fn new() -> OrderQueue {
+        OrderQueue::default()
+    }
+
+    /// Puts `order` at the back of the queue, if it's a car the factory can build. A refused order isn't given a
+    /// serial number.
+    pub fn submit(&mut self, order: Order) -> Result<Serial, OrderError> {
+        order.check()?;
+        self.next_serial += 1;
+        let serial = Serial(self.next_serial);
+        self.pending.push_back((serial, order));
+        Ok(serial)
+    }
+
+    /// Replaces the order submitted as `serial` with `order`, as long as it hasn't been built yet and the factory can
+    /// build the new one.
+    pub fn correct(&mut self, serial: Serial, order: Order) -> Result<(), OrderError> {
+        order.check()?;
+        if let Some(vin) = self.fulfilled.get(&serial) {
+            return Err(OrderError::Fulfilled(*vin));
+        }
+        let (_, pending) = self
+            .pending
+            .iter_mut()
+            .find(|(s, _)| *s == serial)
+            .ok_or(OrderError::Unknown)?;
+        *pending = order;
+        Ok(())
+    }
+
+    /// Builds the car for the order at the front of the queue. `None` once the queue is empty.
+    pub fn fulfil(&mut self) -> Option<(Serial, Vin)> {
+        let (serial, order) = self.pending.pop_front()?;
+        self.next_vin += 1;
+        let vin = Vin(self.next_vin);
+        // Checked when it was submitted
+        let car = Car::new_unchecked(order.color, order.transmission, order.convertible, Age::New);
+        self.fulfilled.insert(serial, vin);
+        self.built.insert(vin, car);
+        Some((serial, vin))
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// The cars built so far, by VIN.
+    pub fn built(&self) -> &HashMap<Vin, Car> {
+        &self.built
+    }
+
+    /// Hands over every car built since the last delivery. Their orders stay fulfilled.
+    pub fn deliver(&mut self) -> HashMap<Vin, Car> {
+        mem::take(&mut self.built)
+    }
+}
+
+#[test]
+fn test_orders_are_numbered_and_corrected_until_built() {
+    let order = |color: &str, transmission, convertible| Order {
+        color: color.to_string(),
+        transmission,
+        convertible,
+    };
+    let mut queue = OrderQueue::new();
+    let red = queue
+        .submit(order("Red", Transmission::Manual, false))
+        .unwrap();
+    let silver = queue
+        .submit(order("Silver", Transmission::Automatic, true))
+        .unwrap();
+    assert_ne!(red, silver);
+    // Orders the factory can't build are turned away, before or after they're queued
+    assert_eq!(
+        queue.submit(order("Black", Transmission::SemiAuto, true)),
+        Err(OrderError::Specs(BuildError::SemiAutoConvertible))
+    );
+    assert_eq!(
+        queue.correct(silver, order("Silver", Transmission::SemiAuto, true)),
+        Err(OrderError::Specs(BuildError::SemiAutoConvertible))
+    );
+
+    let (serial, red_vin) = queue.fulfil().unwrap();
+    assert_eq!(serial, red);
+    // The customer wanted a hardtop after all
+    assert_eq!(
+        queue.correct(silver, order("Silver", Transmission::Automatic, false)),
+        Ok(())
+    );
+    assert_eq!(
+        queue.correct(red, order("Blue", Transmission::Manual, false)),
+        Err(OrderError::Fulfilled(red_vin))
+    );
+    assert_eq!(
+        queue.correct(Serial(99), order("Blue", Transmission::Manual, false)),
+        Err(OrderError::Unknown)
+    );
+
+    let (_, silver_vin) = queue.fulfil().unwrap();
+    assert!(queue.fulfil().is_none());
+    assert_eq!(queue.pending(), 0);
+    assert_ne!(red_vin, silver_vin);
+    assert_eq!(queue.built()[&red_vin].color, "Red");
+    assert!(!queue.built()[&silver_vin].convertible);
+    assert_eq!(red_vin.to_string(), "VIN000001");
+}
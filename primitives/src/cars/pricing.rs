@@ -0,0 +1,108 @@
+use super::{Car, Transmission};
+
+// What a car is worth, in whole dollars. It starts from the sticker price, the base MSRP plus whatever options were
+// fitted, and loses value as the miles go on. Every number is a public field, so a dealer can price with their own
+// model and keep the default for the rest: `PricingModel { base_msrp: 31_000, ..PricingModel::default() }`.
+
+/// How value falls away with mileage.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Depreciation {
+    // Worth the same however far it's been driven
+    None,
+    // A fixed number of dollars off for every thousand miles, down to nothing
+    Linear { per_1000_miles: u32 },
+    // A share of what's left lost over every ten thousand miles, so the early miles cost the most
+    Exponential { percent_per_10k: u32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PricingModel {
+    pub base_msrp: u32,
+    pub semi_auto: u32, // Added to the price of a manual
+    pub automatic: u32,
+    pub convertible: u32,
+    pub depreciation: Depreciation,
+}
+
+impl Default for PricingModel {
+    fn default() -> Self {
+        PricingModel {
+            base_msrp: 25_000,
+            semi_auto: 1_000,
+            automatic: 1_500,
+            convertible: 5_000,
+            depreciation: Depreciation::Exponential {
+                percent_per_10k: 15,
+            },
+        }
+    }
+}
+
+impl PricingModel {
+    /// The price of `car` as it left the factory, before any miles.
+    pub fn sticker_price(&self, car: &Car) -> u32 {
+        let transmission = match car.transmission {
+            Transmission::Manual => 0,
+            Transmission::SemiAuto => self.semi_auto,
+            Transmission::Automatic => self.automatic,
+        };
+        let roof = if car.convertible { self.convertible } else { 0 };
+        self.base_msrp
+            .saturating_add(transmission)
+            .saturating_add(roof)
+    }
+}
+
+impl Car {
+    /// What this car would sell for now under `model`, given its mileage.
+    pub fn estimated_value(&self, model: &PricingModel) -> u32 {
+        let price = model.sticker_price(self);
+        match model.depreciation {
+            Depreciation::None => price,
+            Depreciation::Linear { per_1000_miles } => {
+                let lost = u64::from(self.mileage) * u64::from(per_1000_miles) / 1000;
+                price.saturating_sub(lost.try_into().unwrap_or(u32::MAX))
+            }
+            Depreciation::Exponential { percent_per_10k } => {
+                let kept = 1.0 - f64::from(percent_per_10k.min(100)) / 100.0;
+                (f64::from(price) * kept.powf(f64::from(self.mileage) / 10_000.0)).round() as u32
+            }
+        }
+    }
+}
+
+#[test]
+fn test_value_falls_with_mileage() {
+    use super::Age;
+
+    let model = PricingModel::default();
+    let mut car = Car::new_unchecked(String::from("Red"), Transmission::Automatic, true, Age::New);
+    assert_eq!(car.estimated_value(&model), 31_500);
+    car.drive(10_000);
+    assert_eq!(car.estimated_value(&model), 26_775);
+    car.drive(10_000);
+    assert_eq!(car.estimated_value(&model), 22_759);
+
+    let linear = PricingModel {
+        base_msrp: 20_000,
+        depreciation: Depreciation::Linear {
+            per_1000_miles: 100,
+        },
+        ..PricingModel::default()
+    };
+    let mut manual = Car::new_unchecked(
+        String::from("Blue"),
+        Transmission::Manual,
+        false,
+        Age::Used(25_500),
+    );
+    assert_eq!(manual.estimated_value(&linear), 17_450);
+    manual.drive(u32::MAX - manual.mileage);
+    assert_eq!(manual.estimated_value(&linear), 0);
+
+    let flat = PricingModel {
+        depreciation: Depreciation::None,
+        ..linear
+    };
+    assert_eq!(manual.estimated_value(&flat), 20_000);
+}
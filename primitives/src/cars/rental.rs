@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use super::orders::Vin;
+use super::Car;
+
+// Renting out the cars that come off the line. The desk owns them and keeps a note of every car that's out: who has
+// it and what the odometer read when it left. On its way back the odometer is read again and the car's mileage
+// brought up to date, so a rental car's mileage is always the miles it has really done.
+
+/// Who has a car, and how far it had gone when they took it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkout {
+    pub customer: String,
+    pub odometer: u32,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RentalError {
+    Unknown(Vin),                // Not one of the desk's cars
+    AlreadyRented(Vin, String),  // Out with this customer
+    NotRented(Vin),              // Returned, but it was never taken
+    OdometerBackwards(Vin, u32), // The reading on return was below this one at checkout
+}
+
+impl fmt::Display for RentalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RentalError::Unknown(vin) => write!(f, "no car {} at this desk", vin),
+            RentalError::AlreadyRented(vin, customer) => {
+                write!(f, "{} is already rented to {}", vin, customer)
+            }
+            RentalError::NotRented(vin) => write!(f, "{} isn't rented out", vin),
+            RentalError::OdometerBackwards(vin, odometer) => {
+                write!(
+                    f,
+                    "{} left with {} miles on the clock, it can't come back with fewer",
+                    vin, odometer
+                )
+            }
+        }
+    }
+}
+
+impl Error for RentalError {}
+
+#[derive(Debug, Default)]
+pub struct RentalDesk {
+    cars: HashMap<Vin, Car>,
+    out: HashMap<Vin, Checkout>,
+}
+
+impl RentalDesk {
+    /// A desk renting out `cars`, all of them in the lot to start with.
+    pub fn new(cars: HashMap<Vin, Car>) -> RentalDesk {
+        RentalDesk {
+            cars,
+            out: HashMap::new(),
+        }
+    }
+
+    pub fn car(&self, vin: Vin) -> Option<&Car> {
+        self.cars.get(&vin)
+    }
+
+    /// Hands car `vin` to `customer`, as long as it's in the lot.
+    pub fn checkout(
+        &mut self,
+        vin: Vin,
+        customer: impl Into<String>,
+    ) -> Result<&Checkout, RentalError> {
+        let car = self.cars.get(&vin).ok_or(RentalError::Unknown(vin))?;
+        if let Some(checkout) = self.out.get(&vin) {
+            return Err(RentalError::AlreadyRented(vin, checkout.customer.clone()));
+        }
+        Ok(self.out.entry(vin).or_insert(Checkout {
+            customer: customer.into(),
+            odometer: car.mileage,
+        }))
+    }
+
+    /// Takes car `vin` back with `odometer` miles on the clock, and returns how far it went while it was out.
+    pub fn return_car(&mut self, vin: Vin, odometer: u32) -> Result<u32, RentalError> {
+        let car = self.cars.get_mut(&vin).ok_or(RentalError::Unknown(vin))?;
+        let checkout = self.out.get(&vin).ok_or(RentalError::NotRented(vin))?;
+        let driven = odometer
+            .checked_sub(checkout.odometer)
+            .ok_or(RentalError::OdometerBackwards(vin, checkout.odometer))?;
+        car.mileage = odometer;
+        self.out.remove(&vin);
+        Ok(driven)
+    }
+
+    /// Who has car `vin`, if it's out.
+    pub fn renter(&self, vin: Vin) -> Option<&Checkout> {
+        self.out.get(&vin)
+    }
+
+    /// The cars that are out, in no particular order.
+    pub fn checked_out(&self) -> impl Iterator<Item = (Vin, &Checkout)> {
+        self.out.iter().map(|(vin, checkout)| (*vin, checkout))
+    }
+}
+
+#[test]
+fn test_rentals_track_the_odometer() {
+    use super::orders::{Order, OrderQueue};
+    use super::Transmission;
+
+    let mut queue = OrderQueue::new();
+    for color in ["Red", "Blue"] {
+        queue
+            .submit(Order {
+                color: color.to_string(),
+                transmission: Transmission::Automatic,
+                convertible: false,
+            })
+            .unwrap();
+    }
+    let (_, red) = queue.fulfil().unwrap();
+    let (_, blue) = queue.fulfil().unwrap();
+    let mut desk = RentalDesk::new(queue.deliver());
+
+    assert_eq!(desk.checkout(red, "Ada").unwrap().odometer, 0);
+    assert_eq!(
+        desk.checkout(red, "Grace"),
+        Err(RentalError::AlreadyRented(red, "Ada".to_string()))
+    );
+    assert_eq!(desk.return_car(blue, 10), Err(RentalError::NotRented(blue)));
+    assert_eq!(desk.return_car(red, 320), Ok(320));
+    assert_eq!(desk.car(red).unwrap().mileage, 320);
+    assert!(desk.renter(red).is_none());
+
+    // The next renter picks up where the last one left off
+    desk.checkout(red, "Grace").unwrap();
+    assert_eq!(
+        desk.return_car(red, 100),
+        Err(RentalError::OdometerBackwards(red, 320))
+    );
+    assert_eq!(desk.renter(red).unwrap().customer, "Grace");
+    assert_eq!(desk.checked_out().count(), 1);
+    assert_eq!(desk.return_car(red, 400), Ok(80));
+}
@@ -0,0 +1,104 @@
+use std::fmt;
+
+use super::{Car, Fleet};
+
+// Each car keeps a log of the work done on it, oldest first. How far it's gone since its last service decides when
+// it's due another; a car that's never been serviced counts its miles from zero.
+
+/// A calendar date. Fields are in year, month, day order, so the derived ordering is chronological.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ServiceRecord {
+    pub date: Date,
+    pub mileage: u32, // On the odometer when the work was done
+    pub work: String,
+}
+
+impl Car {
+    /// Logs `record`. Records are kept in date order, whatever order they're added in.
+    pub fn add_service(&mut self, record: ServiceRecord) {
+        let at = self.service.partition_point(|r| r.date <= record.date);
+        self.service.insert(at, record);
+    }
+
+    pub fn service_history(&self) -> &[ServiceRecord] {
+        &self.service
+    }
+
+    /// The odometer reading at the most recent service, if there's been one.
+    pub fn last_service_mileage(&self) -> Option<u32> {
+        self.service.last().map(|r| r.mileage)
+    }
+
+    pub fn miles_since_service(&self) -> u32 {
+        self.mileage
+            .saturating_sub(self.last_service_mileage().unwrap_or(0))
+    }
+}
+
+impl Fleet {
+    /// The cars that have gone `interval` miles or more since their last service.
+    pub fn overdue_for_service(&self, interval: u32) -> impl Iterator<Item = (u32, &Car)> {
+        self.find(move |car| car.miles_since_service() >= interval)
+    }
+}
+
+#[test]
+fn test_service_history_decides_whats_overdue() {
+    use super::{Age, Car, Transmission};
+
+    let date = |year, month, day| Date { year, month, day };
+    let mut fleet = Fleet::new();
+    let mut car = Car::new_unchecked(
+        String::from("Red"),
+        Transmission::Manual,
+        false,
+        Age::Used(25_000),
+    );
+    assert_eq!(car.last_service_mileage(), None);
+    car.add_service(ServiceRecord {
+        date: date(2024, 6, 1),
+        mileage: 22_000,
+        work: String::from("Brake pads"),
+    });
+    // Logged late, but it happened first
+    car.add_service(ServiceRecord {
+        date: date(2023, 11, 20),
+        mileage: 14_500,
+        work: String::from("Oil change"),
+    });
+    assert_eq!(car.last_service_mileage(), Some(22_000));
+    assert_eq!(car.service_history()[0].work, "Oil change");
+    assert_eq!(car.miles_since_service(), 3_000);
+    let serviced = fleet.add(car);
+    let never = fleet.add(Car::new_unchecked(
+        String::from("Blue"),
+        Transmission::Automatic,
+        true,
+        Age::Used(12_000),
+    ));
+    fleet.add(Car::new_unchecked(
+        String::from("Green"),
+        Transmission::Automatic,
+        false,
+        Age::New,
+    ));
+
+    let overdue: Vec<_> = fleet.overdue_for_service(10_000).map(|(n, _)| n).collect();
+    assert_eq!(overdue, [never]);
+    assert_eq!(fleet.overdue_for_service(3_000).count(), 2);
+    assert!(fleet.overdue_for_service(3_000).any(|(n, _)| n == serviced));
+    assert_eq!(date(2024, 6, 1).to_string(), "2024-06-01");
+}
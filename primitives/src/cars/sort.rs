@@ -0,0 +1,115 @@
+use std::cmp::Ordering;
+
+use super::{Car, Fleet};
+
+// Inventory listings need an order that doesn't change from one run to the next. Cars are ordered by mileage, then
+// color, then transmission, which is the order `sort` puts them in; `Fleet::sort_by_keys` takes any other list of
+// keys instead.
+
+/// One thing to order cars by, lowest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Mileage,
+    Color,
+    Transmission, // Manual, SemiAuto, Automatic
+    Convertible,  // Hardtops first
+}
+
+impl SortKey {
+    pub fn compare(self, a: &Car, b: &Car) -> Ordering {
+        match self {
+            SortKey::Mileage => a.mileage.cmp(&b.mileage),
+            SortKey::Color => a.color.cmp(&b.color),
+            SortKey::Transmission => a.transmission.cmp(&b.transmission),
+            SortKey::Convertible => a.convertible.cmp(&b.convertible),
+        }
+    }
+}
+
+// Each key is only looked at when the ones before it tie.
+fn compare_by(keys: &[SortKey], a: &Car, b: &Car) -> Ordering {
+    keys.iter()
+        .map(|key| key.compare(a, b))
+        .find(|o| o.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+const DEFAULT_KEYS: [SortKey; 3] = [SortKey::Mileage, SortKey::Color, SortKey::Transmission];
+
+impl Ord for Car {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Cars that tie on the keys fall back on everything else, so only equal cars compare equal
+        compare_by(&DEFAULT_KEYS, self, other)
+            .then_with(|| self.convertible.cmp(&other.convertible))
+            .then_with(|| self.powertrain.cmp(&other.powertrain))
+            .then_with(|| self.service.cmp(&other.service))
+    }
+}
+
+impl PartialOrd for Car {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Fleet {
+    /// Reorders the fleet by `keys`, the first key first. The sort is stable: cars that tie on every key keep their
+    /// current relative order, so sorting an already listed fleet again never shuffles it.
+    pub fn sort_by_keys(&mut self, keys: &[SortKey]) {
+        self.cars.sort_by(|(_, a), (_, b)| compare_by(keys, a, b));
+    }
+}
+
+#[test]
+fn test_cars_sort_by_keys_in_turn() {
+    use super::{Age, Transmission};
+
+    let mut fleet = Fleet::new();
+    for (color, transmission, convertible, miles) in [
+        ("Red", Transmission::Automatic, false, 500),
+        ("Blue", Transmission::Manual, true, 500),
+        ("Red", Transmission::Manual, true, 500),
+        ("Green", Transmission::Manual, false, 10),
+        ("Red", Transmission::Manual, false, 500),
+    ] {
+        let car = Car::new_unchecked(
+            color.to_string(),
+            transmission,
+            convertible,
+            Age::Used(miles),
+        );
+        fleet.add(car);
+    }
+    let numbers = |fleet: &Fleet| fleet.iter().map(|(n, _)| n).collect::<Vec<_>>();
+
+    // Cars 3 and 5 tie on all three default keys, and keep their order
+    fleet.sort_by_keys(&DEFAULT_KEYS);
+    assert_eq!(numbers(&fleet), [4, 2, 3, 5, 1]);
+    fleet.sort_by_keys(&[SortKey::Convertible, SortKey::Color]);
+    assert_eq!(numbers(&fleet), [4, 5, 1, 2, 3]);
+    fleet.sort_by_keys(&[]);
+    assert_eq!(numbers(&fleet), [4, 5, 1, 2, 3]);
+
+    // Ord agrees with the default keys, and breaks their ties
+    let mut cars: Vec<&Car> = fleet.iter().map(|(_, car)| car).collect();
+    cars.sort();
+    let order: Vec<_> = cars
+        .iter()
+        .map(|car| (car.color.as_str(), car.convertible))
+        .collect();
+    assert_eq!(
+        order,
+        [
+            ("Green", false),
+            ("Blue", true),
+            ("Red", false),
+            ("Red", true),
+            ("Red", false)
+        ]
+    );
+    assert!(cars[2] < cars[3]);
+    assert_eq!(
+        cars.iter().max().unwrap().transmission,
+        Transmission::Automatic
+    );
+}
@@ -0,0 +1,70 @@
+use super::Car;
+use crate::events::Event;
+
+// What a car reports as it's driven. The events go out on the same `EventBus` as the web events, through the
+// `From<CarEvent>` below.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CarEvent {
+    Ignition,
+    DoorOpen,
+    SpeedSample(u16), // In mph, taken once a minute
+}
+
+impl From<CarEvent> for Event {
+    fn from(event: CarEvent) -> Self {
+        Event::Car(event)
+    }
+}
+
+impl Car {
+    /// Drives the car for a minute at each of `speeds` and returns what it reported: the driver's door opening, the
+    /// ignition, then a sample for every minute. The distance covered goes on the odometer, unless it's already as
+    /// high as it goes. The odometer only counts whole miles, so what's left over is kept towards the next drive,
+    /// and many short drives add up the same as one long one.
+    pub fn simulate_drive(&mut self, speeds: &[u16]) -> Vec<CarEvent> {
+        let mut events = vec![CarEvent::DoorOpen, CarEvent::Ignition];
+        events.extend(speeds.iter().map(|&mph| CarEvent::SpeedSample(mph)));
+        // A minute at 1 mph is a sixtieth of a mile
+        let sixtieths =
+            speeds.iter().map(|&mph| u64::from(mph)).sum::<u64>() + u64::from(self.part_mile);
+        let miles = u32::try_from(sixtieths / 60).unwrap_or(u32::MAX);
+        if self.drive(miles).is_some() {
+            self.part_mile = (sixtieths % 60) as u8;
+        }
+        events
+    }
+}
+
+#[test]
+fn test_drive_reports_every_minute() {
+    use super::{Age, Transmission};
+
+    let mut car = Car::new_unchecked(String::from("Red"), Transmission::Manual, false, Age::New);
+    let events = car.simulate_drive(&[30, 60, 90]);
+    assert_eq!(
+        events,
+        [
+            CarEvent::DoorOpen,
+            CarEvent::Ignition,
+            CarEvent::SpeedSample(30),
+            CarEvent::SpeedSample(60),
+            CarEvent::SpeedSample(90)
+        ]
+    );
+    assert_eq!(car.mileage, 3);
+
+    // Half a mile twice over is a whole one
+    car.simulate_drive(&[10, 20]);
+    assert_eq!(car.mileage, 3);
+    car.simulate_drive(&[30]);
+    assert_eq!(car.mileage, 4);
+
+    // 70,000 minutes flat out is more mile-minutes than a u32 holds, but the miles still all count; a full
+    // odometer just stays full
+    car.simulate_drive(&vec![u16::MAX; 70_000]);
+    assert_eq!(car.mileage, 4 + 76_457_500);
+    car.mileage = u32::MAX - 1;
+    car.simulate_drive(&[120]);
+    assert_eq!(car.mileage, u32::MAX - 1);
+}
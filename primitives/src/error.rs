@@ -0,0 +1,163 @@
+// One error type for the whole crate. Each module keeps its own errors and gathers them in a module-level enum
+// (`SchedError`, `CarError`, `GradeError`, `EventError`); `Error` wraps those in turn. Every error converts straight
+// into `Error`, not only the module-level ones, so a single `?` takes any of them to the top.
+use std::fmt;
+use std::io;
+
+use crate::cars::builder::BuildError as CarBuildError;
+use crate::cars::csv::LineError;
+use crate::cars::orders::OrderError;
+use crate::cars::rental::RentalError;
+use crate::cars::CarError;
+use crate::events::control::ControlError;
+use crate::events::keys::ParseKeyError;
+use crate::events::EventError;
+use crate::gradebook::attendance::AttendanceError;
+use crate::gradebook::builder::StudentError;
+use crate::gradebook::csv::RowError;
+use crate::gradebook::roster::RosterError;
+use crate::gradebook::{GradeError, ParseGradeError};
+use crate::log::ParseLevelError;
+use crate::os::repl::{ParseCommandError, ReplError};
+#[cfg(feature = "serde")]
+use crate::os::JsonError;
+use crate::os::{
+    AffinityError, BuildError, CgroupError, ConfigError, GroupError, LockError, MemError,
+    MissingDetail, NiceError, ParseSignalError, ReparentError, SchedError, TransitionError,
+};
+
+#[derive(Debug)]
+pub enum Error {
+    Sched(SchedError),
+    Car(CarError),
+    Grade(GradeError),
+    Event(EventError),
+    // A log level that didn't parse
+    Log(ParseLevelError),
+    // Reading or writing a file; bad contents in a loaded file arrive here too, as `InvalidData`
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Sched(err) => err.fmt(f),
+            Error::Car(err) => err.fmt(f),
+            Error::Grade(err) => err.fmt(f),
+            Error::Event(err) => err.fmt(f),
+            Error::Log(err) => err.fmt(f),
+            Error::Io(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+// `From` for the module-level errors, and for each error they gather through them.
+macro_rules! from_errors {
+    ($variant:ident($module:ty) { $($err:ty),* $(,)? }) => {
+        impl From<$module> for Error {
+            fn from(err: $module) -> Self {
+                Error::$variant(err)
+            }
+        }
+        $(
+            impl From<$err> for Error {
+                fn from(err: $err) -> Self {
+                    Error::$variant(<$module>::from(err))
+                }
+            }
+        )*
+    };
+}
+
+from_errors!(Sched(SchedError) {
+    TransitionError,
+    MissingDetail,
+    BuildError,
+    ReparentError,
+    NiceError,
+    AffinityError,
+    GroupError,
+    CgroupError,
+    LockError,
+    MemError,
+    ParseSignalError,
+    ConfigError,
+    ReplError,
+    ParseCommandError,
+});
+
+#[cfg(feature = "serde")]
+impl From<JsonError> for Error {
+    fn from(err: JsonError) -> Self {
+        Error::Sched(err.into())
+    }
+}
+
+from_errors!(Car(CarError) {
+    CarBuildError,
+    LineError,
+    OrderError,
+    RentalError,
+});
+
+from_errors!(Grade(GradeError) {
+    ParseGradeError,
+    StudentError,
+    RosterError,
+    AttendanceError,
+    RowError,
+});
+
+from_errors!(Event(EventError) { ParseKeyError, ControlError });
+
+impl From<ParseLevelError> for Error {
+    fn from(err: ParseLevelError) -> Self {
+        Error::Log(err)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+#[test]
+fn test_question_mark_reaches_the_top_from_any_module() {
+    use crate::events::control::ProcessControl;
+    use crate::events::{KeyPress, WebEvent};
+    use crate::gradebook::LetterGrade;
+    use crate::os::{PidAllocator, ProcTable, Signal};
+
+    // Three modules' errors behind one `?` each
+    fn press(key: &str, grade: &str, level: &str) -> Result<Vec<Signal>, Error> {
+        let key: KeyPress = key.parse()?;
+        grade.parse::<LetterGrade>()?;
+        level.parse::<crate::log::Level>()?;
+        let mut table = ProcTable::new(crate::Proc::new(&PidAllocator::default()));
+        Ok(ProcessControl::new().handle(&WebEvent::WEPress(key), &mut table)?)
+    }
+
+    assert!(matches!(
+        press("Hyper+z", "A", "info"),
+        Err(Error::Event(EventError::Key(_)))
+    ));
+    assert!(matches!(
+        press("Ctrl+z", "Q", "info"),
+        Err(Error::Grade(GradeError::Grade(_)))
+    ));
+    assert!(matches!(press("Ctrl+z", "A", "loud"), Err(Error::Log(_))));
+    let err = press("Ctrl+z", "A", "info").unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Event(EventError::Control(ControlError::NoneSelected))
+    ));
+    // Displays as the error it wraps
+    assert_eq!(err.to_string(), ControlError::NoneSelected.to_string());
+    assert_eq!(press("x", "A", "info").unwrap(), []);
+
+    let err: Error = "HUP".parse::<Signal>().unwrap_err().into();
+    assert!(matches!(err, Error::Sched(SchedError::Signal(_))));
+}
@@ -0,0 +1,199 @@
+use std::fmt;
+use std::time::Duration;
+
+use crate::cars::telemetry::CarEvent;
+pub use error::EventError;
+use keys::Modifiers;
+
+pub mod control;
+pub mod dispatch;
+mod error;
+pub mod gesture;
+#[cfg(feature = "serde")]
+pub mod json;
+pub mod keys;
+pub mod log;
+pub mod queue;
+pub mod rate;
+pub mod region;
+pub mod router;
+#[cfg(feature = "tokio")]
+pub mod stream;
+
+// One pipeline for everything that happens: pages loading and being clicked on, cars starting up and being driven.
+// Whoever wants to hear about events subscribes to an `EventBus`, and every event published is handed to each
+// subscriber in turn. Each source turns its own events into an `Event` with `From`, so `publish` takes either kind.
+
+/// A key and the modifiers held down with it; see `keys`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyPress(pub Modifiers, pub char);
+
+/// Where the mouse was, and when: `at` is the time since the page loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MouseClick {
+    pub x: i64,
+    pub y: i64,
+    pub at: Duration,
+}
+
+// Code that handles web events matches every variant by name, with no `_` arm, so adding one here is a compile error
+// everywhere it still needs handling.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebEvent {
+    WELoad(bool),
+    WEClick(MouseClick),
+    WEMouseDown(MouseClick),
+    WEMouseMove(MouseClick),
+    WEMouseUp(MouseClick),
+    WEPress(KeyPress),
+    WEKeyRelease(KeyPress),
+    WEScroll { dx: i64, dy: i64 },
+    WEResize { width: u32, height: u32 },
+}
+
+/// Anything the pipeline carries.
+#[derive(Debug)]
+pub enum Event {
+    Web(WebEvent),
+    Car(CarEvent),
+}
+
+impl From<WebEvent> for Event {
+    fn from(event: WebEvent) -> Self {
+        Event::Web(event)
+    }
+}
+
+/// Where the mouse was, as "(100, 50)".
+impl fmt::Display for MouseClick {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+/// What happened, as a sentence: "Click at (100, 50)", "Ctrl+N pressed".
+impl fmt::Display for WebEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WebEvent::WELoad(true) => write!(f, "Page loaded"),
+            WebEvent::WELoad(false) => write!(f, "Page failed to load"),
+            WebEvent::WEClick(click) => write!(f, "Click at {}", click),
+            WebEvent::WEMouseDown(at) => write!(f, "Mouse down at {}", at),
+            WebEvent::WEMouseMove(at) => write!(f, "Mouse moved to {}", at),
+            WebEvent::WEMouseUp(at) => write!(f, "Mouse up at {}", at),
+            WebEvent::WEPress(keys) => write!(f, "{} pressed", keys),
+            WebEvent::WEKeyRelease(keys) => write!(f, "{} released", keys),
+            WebEvent::WEScroll { dx, dy } => write!(f, "Scrolled by ({}, {})", dx, dy),
+            WebEvent::WEResize { width, height } => write!(f, "Resized to {}x{}", width, height),
+        }
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::Web(event) => write!(f, "{}", event),
+            Event::Car(CarEvent::Ignition) => write!(f, "ignition"),
+            Event::Car(CarEvent::DoorOpen) => write!(f, "door open"),
+            Event::Car(CarEvent::SpeedSample(mph)) => write!(f, "speed {} mph", mph),
+        }
+    }
+}
+
+type Subscriber = Box<dyn FnMut(&Event)>;
+
+/// Hands every published event to each subscriber, in the order they subscribed.
+#[derive(Default)]
+pub struct EventBus {
+    subscribers: Vec<Subscriber>,
+}
+
+impl EventBus {
+    pub fn new() -> EventBus {
+        EventBus::default()
+    }
+
+    pub fn subscribe(&mut self, subscriber: impl FnMut(&Event) + 'static) {
+        self.subscribers.push(Box::new(subscriber));
+    }
+
+    pub fn publish(&mut self, event: impl Into<Event>) {
+        let event = event.into();
+        for subscriber in &mut self.subscribers {
+            subscriber(&event);
+        }
+    }
+}
+
+#[test]
+fn test_web_and_car_events_share_the_bus() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let mut bus = EventBus::new();
+    let log = Rc::clone(&seen);
+    bus.subscribe(move |event| log.borrow_mut().push(event.to_string()));
+    let car_events = Rc::new(RefCell::new(0));
+    let count = Rc::clone(&car_events);
+    bus.subscribe(move |event| {
+        if let Event::Car(_) = event {
+            *count.borrow_mut() += 1;
+        }
+    });
+
+    bus.publish(WebEvent::WEClick(MouseClick {
+        x: 100,
+        y: 50,
+        at: Duration::ZERO,
+    }));
+    bus.publish(CarEvent::SpeedSample(30));
+    bus.publish(WebEvent::WEPress(KeyPress(Modifiers::CTRL, 'N')));
+    bus.publish(WebEvent::WEResize {
+        width: 800,
+        height: 600,
+    });
+    assert_eq!(
+        *seen.borrow(),
+        [
+            "Click at (100, 50)",
+            "speed 30 mph",
+            "Ctrl+N pressed",
+            "Resized to 800x600"
+        ]
+    );
+    assert_eq!(*car_events.borrow(), 1);
+}
+
+#[test]
+fn test_web_events_read_as_sentences() {
+    let at = MouseClick {
+        x: 3,
+        y: -4,
+        at: Duration::ZERO,
+    };
+    let shift_tab = KeyPress(Modifiers::SHIFT, '\t');
+    let sentences: Vec<_> = [
+        WebEvent::WELoad(false),
+        WebEvent::WEMouseMove(at),
+        WebEvent::WEKeyRelease(KeyPress(Modifiers::NONE, 'q')),
+        WebEvent::WEScroll { dx: -1, dy: 2 },
+    ]
+    .iter()
+    .map(WebEvent::to_string)
+    .collect();
+    assert_eq!(
+        sentences,
+        [
+            "Page failed to load",
+            "Mouse moved to (3, -4)",
+            "q released",
+            "Scrolled by (-1, 2)"
+        ]
+    );
+    assert_eq!(at.to_string(), "(3, -4)");
+    assert_eq!(
+        WebEvent::WEPress(shift_tab).to_string(),
+        "Shift+Tab pressed"
+    );
+}
@@ -0,0 +1,159 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::os::{Pid, ProcTable, Signal};
+
+use super::keys::Modifiers;
+use super::router::Matcher;
+use super::{KeyPress, WebEvent};
+
+// The page as a job control panel: events are bound to signals, and whichever process is selected gets the signal
+// an event is bound to, delivered straight away. The bindings use the router's matchers, and the first one to match
+// wins. By default Ctrl+Z stops the process (SIGSTOP, which unlike the terminal's SIGTSTP can't be blocked), any
+// click resumes it and Ctrl+C asks it to terminate.
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ControlError {
+    NoneSelected,   // No process has been selected to signal
+    NoProcess(Pid), // The selected process isn't in the table
+}
+
+impl fmt::Display for ControlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ControlError::NoneSelected => write!(f, "no process is selected"),
+            ControlError::NoProcess(pid) => write!(f, "pid {} is not in the table", pid),
+        }
+    }
+}
+
+impl Error for ControlError {}
+
+#[derive(Debug, Clone)]
+pub struct ProcessControl {
+    selected: Option<Pid>,
+    bindings: Vec<(Matcher, Signal)>,
+}
+
+impl Default for ProcessControl {
+    fn default() -> Self {
+        let mut control = ProcessControl::empty();
+        control.bind(
+            Matcher::Keys(vec![KeyPress(Modifiers::CTRL, 'z')]),
+            Signal::Stop,
+        );
+        control.bind(
+            Matcher::Custom(|event| matches!(event, WebEvent::WEClick(_))),
+            Signal::Cont,
+        );
+        control.bind(
+            Matcher::Keys(vec![KeyPress(Modifiers::CTRL, 'c')]),
+            Signal::Term,
+        );
+        control
+    }
+}
+
+impl ProcessControl {
+    /// The default bindings, with no process selected.
+    pub fn new() -> ProcessControl {
+        ProcessControl::default()
+    }
+
+    /// No bindings at all, to set up from scratch.
+    pub fn empty() -> ProcessControl {
+        ProcessControl {
+            selected: None,
+            bindings: Vec::new(),
+        }
+    }
+
+    /// Sends `signal` for events `matcher` matches, unless an earlier binding matches them too.
+    pub fn bind(&mut self, matcher: Matcher, signal: Signal) {
+        self.bindings.push((matcher, signal));
+    }
+
+    /// Makes `pid` the process that events signal.
+    pub fn select(&mut self, pid: Pid) {
+        self.selected = Some(pid);
+    }
+
+    pub fn selected(&self) -> Option<Pid> {
+        self.selected
+    }
+
+    /// The signal `event` is bound to, if any.
+    pub fn signal_for(&self, event: &WebEvent) -> Option<Signal> {
+        self.bindings
+            .iter()
+            .find(|(matcher, _)| matcher.matches(event))
+            .map(|(_, signal)| *signal)
+    }
+
+    /// Sends the selected process the signal `event` is bound to and delivers it, returning the signals that were
+    /// delivered; one the process blocks stays pending. An event bound to nothing does nothing.
+    pub fn handle(
+        &self,
+        event: &WebEvent,
+        table: &mut ProcTable,
+    ) -> Result<Vec<Signal>, ControlError> {
+        let Some(signal) = self.signal_for(event) else {
+            return Ok(Vec::new());
+        };
+        let pid = self.selected.ok_or(ControlError::NoneSelected)?;
+        let proc = table.get_mut(pid).ok_or(ControlError::NoProcess(pid))?;
+        proc.send_signal(signal);
+        Ok(proc.deliver_signals())
+    }
+}
+
+#[test]
+fn test_keys_stop_and_clicks_resume() {
+    use std::time::Duration;
+
+    use crate::os::{PidAllocator, Proc, State};
+
+    use super::MouseClick;
+
+    let pids = PidAllocator::default();
+    let mut table = ProcTable::new(Proc::new(&pids));
+    let mut worker = Proc::new(&pids);
+    worker.set_state(State::Running);
+    let worker = table.insert(worker);
+    let mut control = ProcessControl::new();
+    let ctrl_z = WebEvent::WEPress(KeyPress(Modifiers::CTRL, 'z'));
+    let click = WebEvent::WEClick(MouseClick {
+        x: 0,
+        y: 0,
+        at: Duration::ZERO,
+    });
+    assert_eq!(
+        control.handle(&ctrl_z, &mut table),
+        Err(ControlError::NoneSelected)
+    );
+
+    control.select(worker);
+    assert_eq!(control.handle(&ctrl_z, &mut table), Ok(vec![Signal::Stop]));
+    assert_eq!(table.get(worker).unwrap().state(), State::Stopped);
+    // Unbound events leave it alone
+    let scroll = WebEvent::WEScroll { dx: 0, dy: 1 };
+    assert_eq!(control.handle(&scroll, &mut table), Ok(vec![]));
+    assert_eq!(control.handle(&click, &mut table), Ok(vec![Signal::Cont]));
+    assert_eq!(table.get(worker).unwrap().state(), State::Running);
+
+    // Bindings of its own, and a process that has gone
+    let mut custom = ProcessControl::empty();
+    custom.bind(
+        Matcher::Keys(vec![KeyPress(Modifiers::NONE, 'k')]),
+        Signal::Kill,
+    );
+    assert_eq!(custom.signal_for(&click), None);
+    custom.select(worker);
+    let k = WebEvent::WEPress(KeyPress(Modifiers::NONE, 'k'));
+    assert_eq!(custom.handle(&k, &mut table), Ok(vec![Signal::Kill]));
+    table.remove(worker);
+    assert_eq!(
+        custom.handle(&k, &mut table),
+        Err(ControlError::NoProcess(worker))
+    );
+}
@@ -0,0 +1,211 @@
+use super::region::{RegionId, Regions};
+use super::{KeyPress, MouseClick, WebEvent};
+
+// Where the bus hands every event to everyone, a dispatcher routes each web event by its kind: click handlers only
+// see clicks, and so on. A kind can have any number of handlers, called in the order they were registered, and none
+// at all is fine too; the event is then simply not handled. Clicks can also be handled by the region of the page
+// they landed on: they go to the handlers for the topmost region under them, as well as to the plain click handlers.
+
+type Handler<T> = Box<dyn FnMut(&T)>;
+
+#[derive(Default)]
+pub struct EventDispatcher {
+    load: Vec<Handler<bool>>,
+    click: Vec<Handler<MouseClick>>,
+    regions: Regions,
+    region_click: Vec<(RegionId, Handler<MouseClick>)>,
+    mouse_down: Vec<Handler<MouseClick>>,
+    mouse_move: Vec<Handler<MouseClick>>,
+    mouse_up: Vec<Handler<MouseClick>>,
+    keypress: Vec<Handler<KeyPress>>,
+    keyrelease: Vec<Handler<KeyPress>>,
+    scroll: Vec<Handler<(i64, i64)>>,
+    resize: Vec<Handler<(u32, u32)>>,
+}
+
+impl EventDispatcher {
+    pub fn new() -> EventDispatcher {
+        EventDispatcher::default()
+    }
+
+    /// Called with whether the page loaded.
+    pub fn on_load(&mut self, handler: impl FnMut(&bool) + 'static) {
+        self.load.push(Box::new(handler));
+    }
+
+    pub fn on_click(&mut self, handler: impl FnMut(&MouseClick) + 'static) {
+        self.click.push(Box::new(handler));
+    }
+
+    /// The page's regions, for clicks to be handled by where they land.
+    pub fn regions(&self) -> &Regions {
+        &self.regions
+    }
+
+    pub fn regions_mut(&mut self) -> &mut Regions {
+        &mut self.regions
+    }
+
+    /// Called with clicks that land in `region`, unless another region on top of it is in the way.
+    pub fn on_region_click(
+        &mut self,
+        region: RegionId,
+        handler: impl FnMut(&MouseClick) + 'static,
+    ) {
+        self.region_click.push((region, Box::new(handler)));
+    }
+
+    pub fn on_mouse_down(&mut self, handler: impl FnMut(&MouseClick) + 'static) {
+        self.mouse_down.push(Box::new(handler));
+    }
+
+    pub fn on_mouse_move(&mut self, handler: impl FnMut(&MouseClick) + 'static) {
+        self.mouse_move.push(Box::new(handler));
+    }
+
+    pub fn on_mouse_up(&mut self, handler: impl FnMut(&MouseClick) + 'static) {
+        self.mouse_up.push(Box::new(handler));
+    }
+
+    pub fn on_keypress(&mut self, handler: impl FnMut(&KeyPress) + 'static) {
+        self.keypress.push(Box::new(handler));
+    }
+
+    pub fn on_keyrelease(&mut self, handler: impl FnMut(&KeyPress) + 'static) {
+        self.keyrelease.push(Box::new(handler));
+    }
+
+    /// Called with how far the page scrolled, across and down.
+    pub fn on_scroll(&mut self, handler: impl FnMut(&(i64, i64)) + 'static) {
+        self.scroll.push(Box::new(handler));
+    }
+
+    /// Called with the new width and height.
+    pub fn on_resize(&mut self, handler: impl FnMut(&(u32, u32)) + 'static) {
+        self.resize.push(Box::new(handler));
+    }
+
+    /// Hands `event` to every handler for its kind, and says how many there were.
+    pub fn dispatch(&mut self, event: &WebEvent) -> usize {
+        match event {
+            WebEvent::WELoad(loaded) => call(&mut self.load, loaded),
+            WebEvent::WEClick(click) => {
+                let hit = self.regions.hit_test(click);
+                let mut handled = call(&mut self.click, click);
+                for (region, handler) in &mut self.region_click {
+                    if Some(*region) == hit {
+                        handler(click);
+                        handled += 1;
+                    }
+                }
+                handled
+            }
+            WebEvent::WEMouseDown(at) => call(&mut self.mouse_down, at),
+            WebEvent::WEMouseMove(at) => call(&mut self.mouse_move, at),
+            WebEvent::WEMouseUp(at) => call(&mut self.mouse_up, at),
+            WebEvent::WEPress(keys) => call(&mut self.keypress, keys),
+            WebEvent::WEKeyRelease(keys) => call(&mut self.keyrelease, keys),
+            WebEvent::WEScroll { dx, dy } => call(&mut self.scroll, &(*dx, *dy)),
+            WebEvent::WEResize { width, height } => call(&mut self.resize, &(*width, *height)),
+        }
+    }
+}
+
+fn call<T>(handlers: &mut [Handler<T>], payload: &T) -> usize {
+    for handler in handlers.iter_mut() {
+        handler(payload);
+    }
+    handlers.len()
+}
+
+#[test]
+fn test_events_reach_only_their_handlers() {
+    use super::keys::Modifiers;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let mut dispatcher = EventDispatcher::new();
+    let log = Rc::clone(&seen);
+    dispatcher.on_click(move |click| {
+        log.borrow_mut()
+            .push(format!("click {},{}", click.x, click.y))
+    });
+    let log = Rc::clone(&seen);
+    dispatcher.on_click(move |_| log.borrow_mut().push("second click handler".to_string()));
+    let log = Rc::clone(&seen);
+    dispatcher.on_keypress(move |keys| log.borrow_mut().push(format!("press {}", keys.1)));
+
+    assert_eq!(
+        dispatcher.dispatch(&WebEvent::WEClick(MouseClick {
+            x: 1,
+            y: 2,
+            at: std::time::Duration::ZERO,
+        })),
+        2
+    );
+    assert_eq!(
+        dispatcher.dispatch(&WebEvent::WEPress(KeyPress(Modifiers::NONE, 'q'))),
+        1
+    );
+    // Nothing listens for loads yet
+    assert_eq!(dispatcher.dispatch(&WebEvent::WELoad(true)), 0);
+    let log = Rc::clone(&seen);
+    dispatcher.on_load(move |loaded| log.borrow_mut().push(format!("loaded {}", loaded)));
+    assert_eq!(dispatcher.dispatch(&WebEvent::WELoad(false)), 1);
+    assert_eq!(
+        *seen.borrow(),
+        [
+            "click 1,2",
+            "second click handler",
+            "press q",
+            "loaded false"
+        ]
+    );
+}
+
+#[test]
+fn test_clicks_go_to_the_region_they_land_in() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    use super::region::Rect;
+
+    let mut dispatcher = EventDispatcher::new();
+    let panel = dispatcher.regions_mut().add(
+        "panel",
+        Rect {
+            x: 0,
+            y: 0,
+            width: 100,
+            height: 100,
+        },
+    );
+    let button = dispatcher.regions_mut().add(
+        "button",
+        Rect {
+            x: 10,
+            y: 10,
+            width: 20,
+            height: 10,
+        },
+    );
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    for region in [panel, button] {
+        let log = Rc::clone(&seen);
+        dispatcher.on_region_click(region, move |_| log.borrow_mut().push(region));
+    }
+    let click = |x, y| {
+        WebEvent::WEClick(MouseClick {
+            x,
+            y,
+            at: Duration::ZERO,
+        })
+    };
+    assert_eq!(dispatcher.dispatch(&click(15, 15)), 1);
+    assert_eq!(dispatcher.dispatch(&click(50, 50)), 1);
+    assert_eq!(dispatcher.dispatch(&click(150, 50)), 0);
+    assert_eq!(*seen.borrow(), [button, panel]);
+    assert_eq!(dispatcher.regions().get(button).unwrap().name, "button");
+}
@@ -0,0 +1,35 @@
+use std::error::Error;
+use std::fmt;
+
+use super::control::ControlError;
+use super::keys::ParseKeyError;
+
+/// Any error from the event modules.
+#[derive(Debug, PartialEq, Eq)]
+pub enum EventError {
+    Key(ParseKeyError),
+    Control(ControlError),
+}
+
+impl fmt::Display for EventError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EventError::Key(err) => err.fmt(f),
+            EventError::Control(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for EventError {}
+
+impl From<ParseKeyError> for EventError {
+    fn from(err: ParseKeyError) -> Self {
+        EventError::Key(err)
+    }
+}
+
+impl From<ControlError> for EventError {
+    fn from(err: ControlError) -> Self {
+        EventError::Control(err)
+    }
+}
@@ -0,0 +1,140 @@
+use std::fmt;
+use std::time::Duration;
+
+use super::{MouseClick, WebEvent};
+
+// Turns the raw mouse events into what the user meant by them. Two clicks close together in time and place are a
+// double click; pressing, moving further than a few pixels and letting go is a drag. A press that never moves that
+// far isn't a drag, and a third click starts counting again rather than making a second double click.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gesture {
+    DoubleClick(MouseClick), // Where and when the second click was
+    Drag { from: MouseClick, to: MouseClick }, // Where the button went down and where it came up
+}
+
+impl fmt::Display for Gesture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Gesture::DoubleClick(at) => write!(f, "Double click at {}", at),
+            Gesture::Drag { from, to } => write!(f, "Drag from {} to {}", from, to),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GestureDetector {
+    double_click_time: Duration,
+    slop: i64,
+    last_click: Option<MouseClick>,
+    pressed: Option<(MouseClick, bool)>, // Where the button went down, and whether it's moved far enough to drag
+}
+
+/// Half a second between clicks, and 4 pixels of movement, as most desktops use.
+impl Default for GestureDetector {
+    fn default() -> Self {
+        GestureDetector::new(Duration::from_millis(500), 4)
+    }
+}
+
+impl GestureDetector {
+    /// Double clicks must come within `double_click_time` of each other, and within `slop` pixels either way. A
+    /// press must move further than `slop` to become a drag.
+    pub fn new(double_click_time: Duration, slop: i64) -> GestureDetector {
+        GestureDetector {
+            double_click_time,
+            slop,
+            last_click: None,
+            pressed: None,
+        }
+    }
+
+    fn near(&self, a: &MouseClick, b: &MouseClick) -> bool {
+        (a.x - b.x).abs() <= self.slop && (a.y - b.y).abs() <= self.slop
+    }
+
+    /// Takes the next event, and returns the gesture it completes, if any.
+    pub fn feed(&mut self, event: &WebEvent) -> Option<Gesture> {
+        match event {
+            WebEvent::WEClick(click) => match self.last_click.take() {
+                Some(first)
+                    if click.at.saturating_sub(first.at) <= self.double_click_time
+                        && self.near(&first, click) =>
+                {
+                    Some(Gesture::DoubleClick(*click))
+                }
+                _ => {
+                    self.last_click = Some(*click);
+                    None
+                }
+            },
+            WebEvent::WEMouseDown(at) => {
+                self.pressed = Some((*at, false));
+                None
+            }
+            WebEvent::WEMouseMove(at) => {
+                if let Some((from, dragging)) = self.pressed {
+                    let dragging = dragging || !self.near(&from, at);
+                    self.pressed = Some((from, dragging));
+                }
+                None
+            }
+            WebEvent::WEMouseUp(to) => match self.pressed.take() {
+                Some((from, dragging)) if dragging || !self.near(&from, to) => {
+                    // A drag isn't the first half of a double click
+                    self.last_click = None;
+                    Some(Gesture::Drag { from, to: *to })
+                }
+                _ => None,
+            },
+            WebEvent::WELoad(_)
+            | WebEvent::WEPress(_)
+            | WebEvent::WEKeyRelease(_)
+            | WebEvent::WEScroll { .. }
+            | WebEvent::WEResize { .. } => None,
+        }
+    }
+}
+
+#[test]
+fn test_double_clicks_and_drags() {
+    let at = |x, y, ms| MouseClick {
+        x,
+        y,
+        at: Duration::from_millis(ms),
+    };
+    let mut detector = GestureDetector::default();
+    let mut feed = |event| detector.feed(&event);
+
+    assert_eq!(feed(WebEvent::WEClick(at(10, 10, 0))), None);
+    assert_eq!(
+        feed(WebEvent::WEClick(at(12, 9, 300))),
+        Some(Gesture::DoubleClick(at(12, 9, 300)))
+    );
+    // A third click starts over
+    assert_eq!(feed(WebEvent::WEClick(at(12, 9, 400))), None);
+    // Too slow, then too far away
+    assert_eq!(feed(WebEvent::WEClick(at(12, 9, 1000))), None);
+    assert_eq!(feed(WebEvent::WEClick(at(40, 9, 1100))), None);
+
+    // Press, move and let go somewhere else
+    assert_eq!(feed(WebEvent::WEMouseDown(at(0, 0, 2000))), None);
+    assert_eq!(feed(WebEvent::WEMouseMove(at(30, 5, 2100))), None);
+    assert_eq!(feed(WebEvent::WEScroll { dx: 0, dy: 1 }), None);
+    assert_eq!(
+        feed(WebEvent::WEMouseUp(at(60, 10, 2200))),
+        Some(Gesture::Drag {
+            from: at(0, 0, 2000),
+            to: at(60, 10, 2200)
+        })
+    );
+    // Dragging away and back still counts, but a jiggle doesn't
+    feed(WebEvent::WEMouseDown(at(0, 0, 3000)));
+    feed(WebEvent::WEMouseMove(at(50, 0, 3100)));
+    assert!(feed(WebEvent::WEMouseUp(at(1, 0, 3200))).is_some());
+    feed(WebEvent::WEMouseDown(at(0, 0, 4000)));
+    feed(WebEvent::WEMouseMove(at(3, 3, 4100)));
+    assert_eq!(feed(WebEvent::WEMouseUp(at(2, 2, 4200))), None);
+    // Letting go without having pressed
+    assert_eq!(feed(WebEvent::WEMouseUp(at(90, 90, 5000))), None);
+}
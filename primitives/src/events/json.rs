@@ -0,0 +1,206 @@
+use std::time::Duration;
+
+use crate::os::{FromJson, Json, JsonError, ToJson};
+
+use super::log::EventLog;
+use super::{KeyPress, MouseClick, WebEvent};
+
+// Web events in the same JSON as everything else: serde's defaults, with a variant that carries data written as an
+// object keyed by its name, like `{"WEScroll":{"dx":0,"dy":3}}`. Key presses are written the way they're shown,
+// "Ctrl+N", and the mouse's time as `at_ms`. An event log is JSON lines, one `{"at_ms":..,"event":..}` object to a line, so a log can be appended to
+// and read a line at a time.
+
+impl ToJson for KeyPress {
+    fn to_json(&self) -> Json {
+        Json::Str(self.to_string())
+    }
+}
+
+impl FromJson for KeyPress {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        match json {
+            Json::Str(keys) => keys.parse().map_err(|_| JsonError::Field("keys")),
+            _ => Err(JsonError::Field("keys")),
+        }
+    }
+}
+
+impl ToJson for MouseClick {
+    fn to_json(&self) -> Json {
+        Json::object(vec![
+            ("x", Json::Int(self.x.into())),
+            ("y", Json::Int(self.y.into())),
+            ("at_ms", Json::Int(self.at.as_millis() as i128)),
+        ])
+    }
+}
+
+/// Logs from before clicks were timed have them at 0.
+impl FromJson for MouseClick {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        let at_ms = match json.get("at_ms") {
+            Some(_) => json.int("at_ms")?,
+            None => 0,
+        };
+        Ok(MouseClick {
+            x: json.int("x")?,
+            y: json.int("y")?,
+            at: Duration::from_millis(at_ms),
+        })
+    }
+}
+
+impl ToJson for WebEvent {
+    fn to_json(&self) -> Json {
+        let (name, data) = match self {
+            WebEvent::WELoad(loaded) => ("WELoad", Json::Bool(*loaded)),
+            WebEvent::WEClick(click) => ("WEClick", click.to_json()),
+            WebEvent::WEMouseDown(at) => ("WEMouseDown", at.to_json()),
+            WebEvent::WEMouseMove(at) => ("WEMouseMove", at.to_json()),
+            WebEvent::WEMouseUp(at) => ("WEMouseUp", at.to_json()),
+            WebEvent::WEPress(keys) => ("WEPress", keys.to_json()),
+            WebEvent::WEKeyRelease(keys) => ("WEKeyRelease", keys.to_json()),
+            WebEvent::WEScroll { dx, dy } => (
+                "WEScroll",
+                Json::object(vec![
+                    ("dx", Json::Int((*dx).into())),
+                    ("dy", Json::Int((*dy).into())),
+                ]),
+            ),
+            WebEvent::WEResize { width, height } => (
+                "WEResize",
+                Json::object(vec![
+                    ("width", Json::Int((*width).into())),
+                    ("height", Json::Int((*height).into())),
+                ]),
+            ),
+        };
+        Json::object(vec![(name, data)])
+    }
+}
+
+impl FromJson for WebEvent {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        let Json::Object(fields) = json else {
+            return Err(JsonError::Field("variant"));
+        };
+        let [(name, data)] = &fields[..] else {
+            return Err(JsonError::Field("variant"));
+        };
+        Ok(match name.as_str() {
+            "WELoad" => match data {
+                Json::Bool(loaded) => WebEvent::WELoad(*loaded),
+                _ => return Err(JsonError::Field("WELoad")),
+            },
+            "WEClick" => WebEvent::WEClick(json.parse("WEClick")?),
+            "WEMouseDown" => WebEvent::WEMouseDown(json.parse("WEMouseDown")?),
+            "WEMouseMove" => WebEvent::WEMouseMove(json.parse("WEMouseMove")?),
+            "WEMouseUp" => WebEvent::WEMouseUp(json.parse("WEMouseUp")?),
+            "WEPress" => WebEvent::WEPress(json.parse("WEPress")?),
+            "WEKeyRelease" => WebEvent::WEKeyRelease(json.parse("WEKeyRelease")?),
+            "WEScroll" => WebEvent::WEScroll {
+                dx: data.int("dx")?,
+                dy: data.int("dy")?,
+            },
+            "WEResize" => WebEvent::WEResize {
+                width: data.int("width")?,
+                height: data.int("height")?,
+            },
+            _ => return Err(JsonError::Field("variant")),
+        })
+    }
+}
+
+impl EventLog {
+    /// One line of JSON for each event, in order, each ending in a newline.
+    pub fn to_json_lines(&self) -> String {
+        self.entries()
+            .map(|(at, event)| {
+                let line = Json::object(vec![
+                    ("at_ms", Json::Int(at.as_millis() as i128)),
+                    ("event", event.to_json()),
+                ]);
+                format!("{}\n", line)
+            })
+            .collect()
+    }
+
+    /// Reads back a log written by `to_json_lines`, timed to the millisecond. Blank lines are skipped; a bad one
+    /// is reported with its line number, counting from 1.
+    pub fn from_json_lines(lines: &str) -> Result<EventLog, (usize, JsonError)> {
+        let mut log = EventLog::new();
+        for (i, line) in lines.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry = line.parse::<Json>().and_then(|json| {
+                let at: u64 = json.int("at_ms")?;
+                Ok((Duration::from_millis(at), json.parse("event")?))
+            });
+            let (at, event) = entry.map_err(|err| (i + 1, err))?;
+            log.record_at(at, event);
+        }
+        Ok(log)
+    }
+}
+
+#[test]
+fn test_event_log_round_trips_as_json_lines() {
+    use super::keys::Modifiers;
+
+    let mut log = EventLog::new();
+    let events = [
+        WebEvent::WELoad(true),
+        WebEvent::WEClick(MouseClick {
+            x: -5,
+            y: 20,
+            at: Duration::from_millis(250),
+        }),
+        WebEvent::WEMouseUp(MouseClick {
+            x: 0,
+            y: 0,
+            at: Duration::from_millis(300),
+        }),
+        WebEvent::WEPress(KeyPress(Modifiers::CTRL | Modifiers::SHIFT, 'N')),
+        WebEvent::WEKeyRelease(KeyPress(Modifiers::NONE, '+')),
+        WebEvent::WEScroll { dx: 0, dy: -120 },
+        WebEvent::WEResize {
+            width: 1280,
+            height: 720,
+        },
+    ];
+    for (ms, event) in (0..).step_by(250).zip(events) {
+        log.record_at(Duration::from_millis(ms), event);
+    }
+    let lines = log.to_json_lines();
+    assert_eq!(lines.lines().count(), 7);
+    assert_eq!(
+        lines.lines().nth(3),
+        Some(r#"{"at_ms":750,"event":{"WEPress":"Ctrl+Shift+N"}}"#)
+    );
+    let loaded = EventLog::from_json_lines(&format!("\n{}", lines)).unwrap();
+    assert!(loaded.entries().eq(log.entries()));
+
+    let bad = format!(
+        "{}{}\n",
+        lines, r#"{"at_ms":1750,"event":{"WEPress":"Hyper+N"}}"#
+    );
+    assert_eq!(
+        EventLog::from_json_lines(&bad).unwrap_err(),
+        (8, JsonError::Field("event"))
+    );
+    // Clicks logged before they carried a time
+    let untimed = r#"{"at_ms":0,"event":{"WEClick":{"x":1,"y":2}}}"#;
+    let (_, event) = EventLog::from_json_lines(untimed)
+        .unwrap()
+        .entries
+        .remove(0);
+    assert_eq!(
+        event,
+        WebEvent::WEClick(MouseClick {
+            x: 1,
+            y: 2,
+            at: Duration::ZERO
+        })
+    );
+}
@@ -0,0 +1,197 @@
+use std::error::Error;
+use std::fmt;
+use std::ops::{BitOr, BitOrAssign};
+use std::str::FromStr;
+
+use super::KeyPress;
+
+// The modifier keys held down with a key press, one bit each, so they can be combined with `|` and compared or
+// matched on directly: `Modifiers::CTRL | Modifiers::SHIFT`. Key presses are written the way shortcuts are shown in
+// menus, "Ctrl+Shift+N", and read back from the same form. Keys that don't show as a character of their own, like
+// Tab or Space, go by their names: "Shift+Tab".
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Modifiers(u8);
+
+/// Names in the order they're written, with their bits.
+const NAMES: [(&str, Modifiers); 4] = [
+    ("Ctrl", Modifiers::CTRL),
+    ("Alt", Modifiers::ALT),
+    ("Shift", Modifiers::SHIFT),
+    ("Meta", Modifiers::META),
+];
+
+/// Keys written by name rather than as the character they type.
+const KEY_NAMES: [(&str, char); 6] = [
+    ("Tab", '\t'),
+    ("Enter", '\n'),
+    ("Space", ' '),
+    ("Backspace", '\u{8}'),
+    ("Esc", '\u{1b}'),
+    ("Delete", '\u{7f}'),
+];
+
+impl Modifiers {
+    pub const NONE: Modifiers = Modifiers(0);
+    pub const CTRL: Modifiers = Modifiers(1);
+    pub const ALT: Modifiers = Modifiers(1 << 1);
+    pub const SHIFT: Modifiers = Modifiers(1 << 2);
+    pub const META: Modifiers = Modifiers(1 << 3);
+
+    /// Whether every modifier in `other` is held as well.
+    pub fn contains(self, other: Modifiers) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for Modifiers {
+    type Output = Modifiers;
+
+    fn bitor(self, other: Modifiers) -> Modifiers {
+        Modifiers(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for Modifiers {
+    fn bitor_assign(&mut self, other: Modifiers) {
+        self.0 |= other.0;
+    }
+}
+
+/// Each modifier held followed by a +, ready for the key to go after: "Ctrl+Shift+", or nothing at all.
+impl fmt::Display for Modifiers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, modifier) in NAMES {
+            if self.contains(modifier) {
+                write!(f, "{}+", name)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Why a key press couldn't be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseKeyError {
+    Modifier(String), // Not Ctrl, Alt, Shift or Meta
+    Key(String),      // Not a single character or a key name
+}
+
+impl fmt::Display for ParseKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseKeyError::Modifier(name) => write!(f, "`{}` isn't a modifier key", name),
+            ParseKeyError::Key(key) => write!(f, "`{}` isn't a key", key),
+        }
+    }
+}
+
+impl Error for ParseKeyError {}
+
+// Modifier and key names are matched ignoring case, and "Control" is taken for Ctrl. Otherwise the key is the last
+// character, which can itself be a +, as in "Ctrl++".
+impl FromStr for KeyPress {
+    type Err = ParseKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (names, key) = match s.strip_suffix("++") {
+            Some(names) => (Some(names), "+"),
+            None if s == "+" => (None, "+"),
+            None => match s.rsplit_once('+') {
+                Some((names, key)) => (Some(names), key),
+                None => (None, s),
+            },
+        };
+        let mut chars = key.chars();
+        let key = match (chars.next(), chars.next()) {
+            (Some(key), None) => key,
+            _ => KEY_NAMES
+                .into_iter()
+                .find(|(name, _)| key.eq_ignore_ascii_case(name))
+                .map(|(_, c)| c)
+                .ok_or_else(|| ParseKeyError::Key(key.to_string()))?,
+        };
+        let mut modifiers = Modifiers::NONE;
+        for name in names.into_iter().flat_map(|names| names.split('+')) {
+            let name_is = |n: &str| name.eq_ignore_ascii_case(n);
+            modifiers |= NAMES
+                .into_iter()
+                .find(|(n, _)| name_is(n))
+                .map(|(_, m)| m)
+                .or_else(|| name_is("Control").then_some(Modifiers::CTRL))
+                .ok_or_else(|| ParseKeyError::Modifier(name.to_string()))?;
+        }
+        Ok(KeyPress(modifiers, key))
+    }
+}
+
+/// Named keys by name, any other control character escaped ("Ctrl+\u{1}"), and the rest as themselves.
+impl fmt::Display for KeyPress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)?;
+        match KEY_NAMES.into_iter().find(|&(_, c)| c == self.1) {
+            Some((name, _)) => write!(f, "{}", name),
+            None if self.1.is_control() => write!(f, "{}", self.1.escape_unicode()),
+            None => write!(f, "{}", self.1),
+        }
+    }
+}
+
+#[test]
+fn test_shortcuts_parse_into_modifiers() {
+    let new_window: KeyPress = "Ctrl+Shift+N".parse().unwrap();
+    assert_eq!(
+        new_window,
+        KeyPress(Modifiers::CTRL | Modifiers::SHIFT, 'N')
+    );
+    assert!(new_window.0.contains(Modifiers::SHIFT));
+    assert!(!new_window.0.contains(Modifiers::CTRL | Modifiers::ALT));
+    // Matching needs no strings
+    let action = match new_window.0 {
+        Modifiers::NONE => "type",
+        Modifiers::CTRL => "command",
+        _ => "other shortcut",
+    };
+    assert_eq!(action, "other shortcut");
+
+    assert_eq!("q".parse(), Ok(KeyPress(Modifiers::NONE, 'q')));
+    assert_eq!(
+        "control+alt+meta+x".parse(),
+        Ok(KeyPress(
+            Modifiers::CTRL | Modifiers::ALT | Modifiers::META,
+            'x'
+        ))
+    );
+    assert_eq!("Ctrl++".parse(), Ok(KeyPress(Modifiers::CTRL, '+')));
+    assert_eq!("+".parse(), Ok(KeyPress(Modifiers::NONE, '+')));
+    // Written in the usual order, whatever order it was read in
+    let shifted: KeyPress = "Shift+Ctrl+N".parse().unwrap();
+    assert_eq!(shifted.to_string(), "Ctrl+Shift+N");
+
+    assert_eq!(
+        "Hyper+N".parse::<KeyPress>(),
+        Err(ParseKeyError::Modifier("Hyper".to_string()))
+    );
+    assert_eq!(
+        "Ctrl+".parse::<KeyPress>(),
+        Err(ParseKeyError::Key(String::new()))
+    );
+    assert_eq!(
+        "Ctrl+Fn".parse::<KeyPress>(),
+        Err(ParseKeyError::Key("Fn".to_string()))
+    );
+
+    // Keys that aren't characters you can see are written by name, and read back from it
+    let back_tab = KeyPress(Modifiers::SHIFT, '\t');
+    assert_eq!(back_tab.to_string(), "Shift+Tab");
+    assert_eq!("shift+tab".parse(), Ok(back_tab));
+    assert_eq!("Space".parse(), Ok(KeyPress(Modifiers::NONE, ' ')));
+    assert_eq!(
+        KeyPress(Modifiers::CTRL, '\u{1}').to_string(),
+        "Ctrl+\\u{1}"
+    );
+    assert_eq!(
+        "+N".parse::<KeyPress>(),
+        Err(ParseKeyError::Modifier(String::new()))
+    );
+}
@@ -0,0 +1,140 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::dispatch::EventDispatcher;
+use super::WebEvent;
+
+// A recording of the web events a dispatcher was given, each stamped with when it came since the log was started.
+// Played back through a dispatcher, it hands the handlers the same events with the same gaps between them, or shorter
+// ones to get through it faster, which makes a bug seen once in the UI something that can be seen again. Logs are
+// saved as JSON lines, one event to a line; see `json`.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventLog {
+    started: Instant,
+    pub(super) entries: Vec<(Duration, WebEvent)>,
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        EventLog {
+            started: Instant::now(),
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl EventLog {
+    /// Starts an empty log, timing events from now.
+    pub fn new() -> EventLog {
+        EventLog::default()
+    }
+
+    /// Hands `event` to `dispatcher`, and records it. Returns how many handlers it went to.
+    pub fn dispatch(&mut self, dispatcher: &mut EventDispatcher, event: WebEvent) -> usize {
+        let handled = dispatcher.dispatch(&event);
+        self.record(event);
+        handled
+    }
+
+    /// Records `event` as happening now.
+    pub fn record(&mut self, event: WebEvent) {
+        let at = self.started.elapsed();
+        self.record_at(at, event);
+    }
+
+    /// Records `event` as happening `at` after the log started. Events are kept in time order, and one recorded at
+    /// the same time as others goes after them.
+    pub fn record_at(&mut self, at: Duration, event: WebEvent) {
+        let i = self.entries.partition_point(|(time, _)| *time <= at);
+        self.entries.insert(i, (at, event));
+    }
+
+    /// Every event with when it happened, in order.
+    pub fn entries(&self) -> impl Iterator<Item = (Duration, &WebEvent)> {
+        self.entries.iter().map(|(at, event)| (*at, event))
+    }
+
+    /// Hands every event to `dispatcher` again, waiting between them as long as they were apart when recorded,
+    /// divided by `speed`: 1.0 plays the log back as it happened, 10.0 ten times faster. `f64::INFINITY` doesn't wait
+    /// at all.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `speed` isn't a positive number.
+    pub fn replay(&self, dispatcher: &mut EventDispatcher, speed: f64) {
+        self.replay_with(dispatcher, speed, thread::sleep);
+    }
+
+    fn replay_with(
+        &self,
+        dispatcher: &mut EventDispatcher,
+        speed: f64,
+        mut sleep: impl FnMut(Duration),
+    ) {
+        assert!(speed > 0.0, "replay speed must be positive");
+        let mut previous = Duration::ZERO;
+        for (at, event) in &self.entries {
+            let gap = at.saturating_sub(previous).div_f64(speed);
+            if !gap.is_zero() {
+                sleep(gap);
+            }
+            previous = *at;
+            dispatcher.dispatch(event);
+        }
+    }
+}
+
+#[test]
+fn test_replay_keeps_the_gaps() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::MouseClick;
+
+    let mut log = EventLog::new();
+    log.record_at(
+        Duration::from_millis(500),
+        WebEvent::WEScroll { dx: 0, dy: 3 },
+    );
+    log.record_at(Duration::from_millis(100), WebEvent::WELoad(true));
+    log.record_at(
+        Duration::from_millis(500),
+        WebEvent::WEClick(MouseClick {
+            x: 4,
+            y: 2,
+            at: Duration::from_millis(500),
+        }),
+    );
+    let mut dispatcher = EventDispatcher::new();
+    let clicks = Rc::new(RefCell::new(0));
+    let count = Rc::clone(&clicks);
+    dispatcher.on_click(move |_| *count.borrow_mut() += 1);
+    let order: Vec<_> = log.entries().map(|(at, _)| at.as_millis()).collect();
+    assert_eq!(order, [100, 500, 500]);
+    assert_eq!(
+        log.entries().nth(1).unwrap().1,
+        &WebEvent::WEScroll { dx: 0, dy: 3 }
+    );
+
+    // Replayed five times as fast, from the mock sleep's point of view
+    let mut waits = Vec::new();
+    log.replay_with(&mut dispatcher, 5.0, |gap| waits.push(gap.as_millis()));
+    assert_eq!(waits, [20, 80]);
+    assert_eq!(*clicks.borrow(), 1);
+    log.replay(&mut dispatcher, f64::INFINITY);
+    assert_eq!(*clicks.borrow(), 2);
+
+    // Recording as it goes still dispatches
+    let mut live = EventLog::new();
+    live.dispatch(
+        &mut dispatcher,
+        WebEvent::WEClick(MouseClick {
+            x: 1,
+            y: 1,
+            at: Duration::ZERO,
+        }),
+    );
+    assert_eq!(*clicks.borrow(), 3);
+    assert_eq!(live.entries().count(), 1);
+}
@@ -0,0 +1,140 @@
+use std::panic;
+use std::sync::mpsc::{self, Sender};
+use std::thread::{self, JoinHandle};
+
+use super::WebEvent;
+
+// Events can come from any number of threads at once. Each producer holds its own end of one channel, and a single
+// consumer thread takes the events off the other end and handles them one at a time, in the order they arrived. The
+// queue shuts down gracefully: everything sent before `shutdown` is still handled, and only then does the consumer
+// stop. Producers that send after that get their event back.
+
+enum Message {
+    Event(WebEvent),
+    Shutdown,
+}
+
+/// One thread's way into the queue. Clone it for each producer thread.
+#[derive(Clone)]
+pub struct Producer(Sender<Message>);
+
+impl Producer {
+    /// Queues `event`, or hands it back if the queue has shut down.
+    pub fn send(&self, event: WebEvent) -> Result<(), WebEvent> {
+        self.0
+            .send(Message::Event(event))
+            .map_err(|err| match err.0 {
+                Message::Event(event) => event,
+                Message::Shutdown => unreachable!("producers only send events"),
+            })
+    }
+}
+
+pub struct EventQueue {
+    sender: Sender<Message>,
+    consumer: Option<JoinHandle<usize>>,
+}
+
+impl EventQueue {
+    /// Starts the consumer thread, which calls `handler` with every event sent.
+    pub fn start(mut handler: impl FnMut(WebEvent) + Send + 'static) -> EventQueue {
+        let (sender, receiver) = mpsc::channel();
+        let consumer = thread::spawn(move || {
+            let mut handled = 0;
+            for message in receiver {
+                match message {
+                    Message::Event(event) => handler(event),
+                    Message::Shutdown => break,
+                }
+                handled += 1;
+            }
+            handled
+        });
+        EventQueue {
+            sender,
+            consumer: Some(consumer),
+        }
+    }
+
+    pub fn producer(&self) -> Producer {
+        Producer(self.sender.clone())
+    }
+
+    /// Waits for every event already sent to be handled, stops the consumer and says how many it handled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the handler did.
+    pub fn shutdown(mut self) -> usize {
+        self.stop()
+            .unwrap_or_else(|payload| panic::resume_unwind(payload))
+    }
+
+    fn stop(&mut self) -> thread::Result<usize> {
+        let Some(consumer) = self.consumer.take() else {
+            return Ok(0);
+        };
+        // The consumer only goes once it has seen this, so it can't have hung up yet
+        let _ = self.sender.send(Message::Shutdown);
+        consumer.join()
+    }
+}
+
+/// A queue dropped without `shutdown` still finishes the events it was sent.
+impl Drop for EventQueue {
+    fn drop(&mut self) {
+        let _ = self.stop();
+    }
+}
+
+#[test]
+fn test_events_from_many_threads_are_all_handled() {
+    use std::sync::{Arc, Mutex};
+
+    use super::MouseClick;
+
+    let clicks = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&clicks);
+    let queue = EventQueue::start(move |event| {
+        if let WebEvent::WEClick(click) = event {
+            log.lock().unwrap().push((click.x, click.y));
+        }
+    });
+    let producers: Vec<_> = (0..4)
+        .map(|thread| {
+            let producer = queue.producer();
+            thread::spawn(move || {
+                for i in 0..25 {
+                    producer
+                        .send(WebEvent::WEClick(MouseClick {
+                            x: thread,
+                            y: i,
+                            at: std::time::Duration::ZERO,
+                        }))
+                        .unwrap();
+                }
+            })
+        })
+        .collect();
+    for producer in producers {
+        producer.join().unwrap();
+    }
+    let late = queue.producer();
+    assert_eq!(queue.shutdown(), 100);
+
+    // Every event made it, and each thread's arrived in the order it sent them
+    let clicks = clicks.lock().unwrap();
+    assert_eq!(clicks.len(), 100);
+    for thread in 0..4 {
+        let sent: Vec<_> = clicks
+            .iter()
+            .filter(|c| c.0 == thread)
+            .map(|c| c.1)
+            .collect();
+        assert_eq!(sent, (0..25).collect::<Vec<_>>());
+    }
+    assert!(matches!(
+        late.send(WebEvent::WELoad(true)),
+        Err(WebEvent::WELoad(true))
+    ));
+}
@@ -0,0 +1,159 @@
+use std::time::{Duration, Instant};
+
+use super::WebEvent;
+
+// Bursts of the same event, a held key repeating or a scroll wheel spun, can come faster than is worth handling. A
+// throttle lets the first of a burst straight through and drops repeats of it for a while after. A debounce holds
+// on to an event until it has stopped repeating, and lets the last one through once things go quiet. Either way an
+// event that's different from the one before is never dropped. Both read the time from a `Clock`, so tests can
+// move time on by hand.
+
+/// Where the adapters get the time from, as time since some fixed starting point.
+pub trait Clock {
+    fn now(&self) -> Duration;
+}
+
+/// The real time, since the clock was made.
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock(Instant);
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        SystemClock(Instant::now())
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.0.elapsed()
+    }
+}
+
+/// Lets an event through, then drops events equal to it until `window` has passed.
+pub fn throttle(window: Duration) -> Throttle<SystemClock> {
+    Throttle::new(window, SystemClock::default())
+}
+
+/// Holds each event until nothing equal to it has come for `quiet`.
+pub fn debounce(quiet: Duration) -> Debounce<SystemClock> {
+    Debounce::new(quiet, SystemClock::default())
+}
+
+#[derive(Debug)]
+pub struct Throttle<C> {
+    window: Duration,
+    clock: C,
+    last: Option<(WebEvent, Duration)>, // The last event let through, and when
+}
+
+impl<C: Clock> Throttle<C> {
+    pub fn new(window: Duration, clock: C) -> Throttle<C> {
+        Throttle {
+            window,
+            clock,
+            last: None,
+        }
+    }
+
+    /// `event`, unless it repeats the last one let through within the window.
+    pub fn push(&mut self, event: WebEvent) -> Option<WebEvent> {
+        let now = self.clock.now();
+        if let Some((last, at)) = &self.last {
+            if *last == event && now - *at < self.window {
+                return None;
+            }
+        }
+        self.last = Some((event.clone(), now));
+        Some(event)
+    }
+}
+
+#[derive(Debug)]
+pub struct Debounce<C> {
+    quiet: Duration,
+    clock: C,
+    held: Option<(WebEvent, Duration)>, // The latest event, and when it came
+}
+
+impl<C: Clock> Debounce<C> {
+    pub fn new(quiet: Duration, clock: C) -> Debounce<C> {
+        Debounce {
+            quiet,
+            clock,
+            held: None,
+        }
+    }
+
+    /// Holds `event` in place of any repeat of it. A different event being held is let through now, as its burst
+    /// is over.
+    pub fn push(&mut self, event: WebEvent) -> Option<WebEvent> {
+        let now = self.clock.now();
+        let ended = match self.held.take() {
+            Some((held, _)) if held != event => Some(held),
+            _ => None,
+        };
+        self.held = Some((event, now));
+        ended
+    }
+
+    /// The held event, once it has gone `quiet` without repeating. Call this every so often.
+    pub fn poll(&mut self) -> Option<WebEvent> {
+        let (_, at) = self.held.as_ref()?;
+        if self.clock.now() - *at < self.quiet {
+            return None;
+        }
+        self.flush()
+    }
+
+    /// The held event, straight away, e.g. when the stream ends.
+    pub fn flush(&mut self) -> Option<WebEvent> {
+        self.held.take().map(|(event, _)| event)
+    }
+}
+
+#[test]
+fn test_bursts_collapse_on_a_mock_clock() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    struct MockClock(Rc<Cell<Duration>>);
+
+    impl Clock for MockClock {
+        fn now(&self) -> Duration {
+            self.0.get()
+        }
+    }
+
+    let time = Rc::new(Cell::new(Duration::ZERO));
+    let advance = |ms| time.set(time.get() + Duration::from_millis(ms));
+    let scroll = WebEvent::WEScroll { dx: 0, dy: 1 };
+    let load = WebEvent::WELoad(true);
+
+    let mut throttle = Throttle::new(Duration::from_millis(100), MockClock(Rc::clone(&time)));
+    assert_eq!(throttle.push(scroll.clone()), Some(scroll.clone()));
+    advance(50);
+    assert_eq!(throttle.push(scroll.clone()), None);
+    // Something else always goes through, and then the scroll counts as new
+    assert_eq!(throttle.push(load.clone()), Some(load.clone()));
+    assert_eq!(throttle.push(scroll.clone()), Some(scroll.clone()));
+    advance(99);
+    assert_eq!(throttle.push(scroll.clone()), None);
+    advance(1);
+    assert_eq!(throttle.push(scroll.clone()), Some(scroll.clone()));
+
+    let mut debounce = Debounce::new(Duration::from_millis(100), MockClock(Rc::clone(&time)));
+    for _ in 0..5 {
+        assert_eq!(debounce.push(scroll.clone()), None);
+        advance(60);
+        // Each repeat starts the quiet period over
+        assert_eq!(debounce.poll(), None);
+    }
+    advance(40);
+    assert_eq!(debounce.poll(), Some(scroll.clone()));
+    assert_eq!(debounce.poll(), None);
+    // A different event ends the burst before it
+    debounce.push(scroll.clone());
+    assert_eq!(debounce.push(load.clone()), Some(scroll));
+    assert_eq!(debounce.flush(), Some(load));
+    assert_eq!(debounce.flush(), None);
+}
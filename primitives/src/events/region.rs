@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+
+use super::MouseClick;
+
+// Named areas of the page, so a click can be put down to the button or panel it landed on. Regions can overlap, a
+// button inside a toolbar say, and are stacked in the order they were added: a click goes to the topmost region
+// under it, the one added last. Each region gets an ID when it's added, which isn't given to another after it's
+// removed.
+
+/// An area of the page, from its top left corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i64,
+    pub y: i64,
+    pub width: i64,
+    pub height: i64,
+}
+
+impl Rect {
+    /// Whether `click` landed inside, counting the top and left edges but not the bottom and right ones.
+    pub fn contains(&self, click: &MouseClick) -> bool {
+        (self.x..self.x + self.width).contains(&click.x)
+            && (self.y..self.y + self.height).contains(&click.y)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RegionId(u32);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Region {
+    pub name: String,
+    pub rect: Rect,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Regions {
+    regions: BTreeMap<RegionId, Region>, // Bottom of the stack first, as IDs only go up
+    next_id: u32,
+}
+
+impl Regions {
+    pub fn new() -> Regions {
+        Regions::default()
+    }
+
+    /// Puts a region called `name` on top of the others.
+    pub fn add(&mut self, name: impl Into<String>, rect: Rect) -> RegionId {
+        self.next_id += 1;
+        let id = RegionId(self.next_id);
+        let name = name.into();
+        self.regions.insert(id, Region { name, rect });
+        id
+    }
+
+    pub fn remove(&mut self, id: RegionId) -> Option<Region> {
+        self.regions.remove(&id)
+    }
+
+    pub fn get(&self, id: RegionId) -> Option<&Region> {
+        self.regions.get(&id)
+    }
+
+    /// The topmost region `click` landed in, if any.
+    pub fn hit_test(&self, click: &MouseClick) -> Option<RegionId> {
+        self.regions
+            .iter()
+            .rev()
+            .find(|(_, region)| region.rect.contains(click))
+            .map(|(id, _)| *id)
+    }
+}
+
+#[test]
+fn test_clicks_hit_the_topmost_region() {
+    use std::time::Duration;
+
+    let mut regions = Regions::new();
+    let toolbar = regions.add(
+        "toolbar",
+        Rect {
+            x: 0,
+            y: 0,
+            width: 800,
+            height: 40,
+        },
+    );
+    let save = regions.add(
+        "save",
+        Rect {
+            x: 10,
+            y: 5,
+            width: 30,
+            height: 30,
+        },
+    );
+    let click = |x, y| MouseClick {
+        x,
+        y,
+        at: Duration::ZERO,
+    };
+    assert_eq!(regions.hit_test(&click(20, 20)), Some(save));
+    assert_eq!(regions.hit_test(&click(100, 20)), Some(toolbar));
+    assert_eq!(regions.hit_test(&click(100, 40)), None);
+    assert_eq!(regions.get(save).unwrap().name, "save");
+
+    // Without the button, the toolbar underneath gets its clicks, and the ID isn't reused
+    assert_eq!(regions.remove(save).unwrap().name, "save");
+    assert_eq!(regions.hit_test(&click(20, 20)), Some(toolbar));
+    let menu = regions.add(
+        "menu",
+        Rect {
+            x: 0,
+            y: 40,
+            width: 200,
+            height: 400,
+        },
+    );
+    assert_ne!(menu, save);
+    assert_eq!(regions.hit_test(&click(100, 40)), Some(menu));
+}
@@ -0,0 +1,168 @@
+use super::region::Rect;
+use super::{KeyPress, WebEvent};
+
+// The dispatcher hands an event to every handler for its kind; a router picks exactly one handler for it. Each route
+// says which events it wants with a `Matcher`, and the matching route with the highest priority wins, the one
+// declared first among equals. Whatever no route wants goes to the fallback, if there is one.
+
+#[derive(Debug, Clone)]
+pub enum Matcher {
+    ClickWithin(Rect),             // A click inside this area
+    Keys(Vec<KeyPress>),           // A press of any of these keys, with exactly these modifiers
+    Custom(fn(&WebEvent) -> bool), // Anything this says yes to
+}
+
+impl Matcher {
+    pub fn matches(&self, event: &WebEvent) -> bool {
+        match (self, event) {
+            (Matcher::ClickWithin(rect), WebEvent::WEClick(click)) => rect.contains(click),
+            (Matcher::Keys(keys), WebEvent::WEPress(pressed)) => keys.contains(pressed),
+            (Matcher::Custom(matches), event) => matches(event),
+            (Matcher::ClickWithin(_) | Matcher::Keys(_), _) => false,
+        }
+    }
+}
+
+type Handler = Box<dyn FnMut(&WebEvent)>;
+
+struct Route {
+    name: String,
+    priority: i32,
+    matcher: Matcher,
+    handler: Handler,
+}
+
+#[derive(Default)]
+pub struct EventRouter {
+    routes: Vec<Route>, // Highest priority first, in the order declared within a priority
+    fallback: Option<Handler>,
+}
+
+impl EventRouter {
+    pub fn new() -> EventRouter {
+        EventRouter::default()
+    }
+
+    /// Sends events that `matcher` matches to `handler`, unless a route of higher priority, or one of the same
+    /// priority declared earlier, matches them too.
+    pub fn route(
+        &mut self,
+        name: impl Into<String>,
+        priority: i32,
+        matcher: Matcher,
+        handler: impl FnMut(&WebEvent) + 'static,
+    ) {
+        let i = self.routes.partition_point(|r| r.priority >= priority);
+        let route = Route {
+            name: name.into(),
+            priority,
+            matcher,
+            handler: Box::new(handler),
+        };
+        self.routes.insert(i, route);
+    }
+
+    /// Sends events no route matches to `handler`, replacing any earlier fallback.
+    pub fn fallback(&mut self, handler: impl FnMut(&WebEvent) + 'static) {
+        self.fallback = Some(Box::new(handler));
+    }
+
+    /// The route names, in the order they're tried, with their priorities.
+    pub fn routes(&self) -> impl Iterator<Item = (&str, i32)> {
+        self.routes.iter().map(|r| (r.name.as_str(), r.priority))
+    }
+
+    /// Hands `event` to the first route that matches it, and says which one. `None` means it went to the fallback,
+    /// or nowhere if there isn't one.
+    pub fn dispatch(&mut self, event: &WebEvent) -> Option<&str> {
+        match self.routes.iter_mut().find(|r| r.matcher.matches(event)) {
+            Some(route) => {
+                (route.handler)(event);
+                Some(&route.name)
+            }
+            None => {
+                if let Some(fallback) = &mut self.fallback {
+                    fallback(event);
+                }
+                None
+            }
+        }
+    }
+}
+
+#[test]
+fn test_highest_priority_match_wins() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::keys::Modifiers;
+    use super::MouseClick;
+
+    let close_button = Rect {
+        x: 780,
+        y: 0,
+        width: 20,
+        height: 20,
+    };
+    let title_bar = Rect {
+        x: 0,
+        y: 0,
+        width: 800,
+        height: 20,
+    };
+    let mut router = EventRouter::new();
+    router.route("title bar", 0, Matcher::ClickWithin(title_bar), |_| {});
+    router.route("close", 10, Matcher::ClickWithin(close_button), |_| {});
+    router.route(
+        "shortcuts",
+        0,
+        Matcher::Keys(vec![
+            KeyPress(Modifiers::CTRL, 'w'),
+            KeyPress(Modifiers::CTRL, 'q'),
+        ]),
+        |_| {},
+    );
+    router.route(
+        "scrolling",
+        -1,
+        Matcher::Custom(|e| matches!(e, WebEvent::WEScroll { .. })),
+        |_| {},
+    );
+    let unrouted = Rc::new(RefCell::new(0));
+    let count = Rc::clone(&unrouted);
+    router.fallback(move |_| *count.borrow_mut() += 1);
+    assert_eq!(
+        router.routes().collect::<Vec<_>>(),
+        [
+            ("close", 10),
+            ("title bar", 0),
+            ("shortcuts", 0),
+            ("scrolling", -1)
+        ]
+    );
+
+    let click = |x, y| {
+        WebEvent::WEClick(MouseClick {
+            x,
+            y,
+            at: std::time::Duration::ZERO,
+        })
+    };
+    // The close button sits in the title bar, but outranks it
+    assert_eq!(router.dispatch(&click(790, 5)), Some("close"));
+    assert_eq!(router.dispatch(&click(400, 5)), Some("title bar"));
+    // Right and bottom edges are outside
+    assert_eq!(router.dispatch(&click(800, 5)), None);
+    assert_eq!(router.dispatch(&click(10, 20)), None);
+    let press = |modifiers, key| WebEvent::WEPress(KeyPress(modifiers, key));
+    assert_eq!(
+        router.dispatch(&press(Modifiers::CTRL, 'q')),
+        Some("shortcuts")
+    );
+    assert_eq!(router.dispatch(&press(Modifiers::NONE, 'q')), None);
+    assert_eq!(
+        router.dispatch(&WebEvent::WEScroll { dx: 0, dy: 1 }),
+        Some("scrolling")
+    );
+    assert_eq!(*unrouted.borrow(), 3);
+}
@@ -0,0 +1,89 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+use tokio::sync::mpsc::{self, error::TrySendError};
+
+use super::WebEvent;
+
+// The async side of the event pipeline: where `EventQueue` hands events to a thread, this hands them to a task, as a
+// `Stream`. The buffer between the two is bounded, so a producer that gets ahead has to wait: `send` doesn't finish
+// until there's room, and `try_send` gives the event back instead of waiting. The stream ends once every sender has
+// gone and the buffer is empty.
+
+/// A bounded buffer of `capacity` events, with the sending end and the stream reading from it.
+///
+/// # Panics
+///
+/// Panics if `capacity` is 0.
+pub fn channel(capacity: usize) -> (EventSender, EventStream) {
+    let (sender, receiver) = mpsc::channel(capacity);
+    (EventSender(sender), EventStream(receiver))
+}
+
+/// One producer's way into the stream. Clone it for each producer.
+#[derive(Debug, Clone)]
+pub struct EventSender(mpsc::Sender<WebEvent>);
+
+impl EventSender {
+    /// Queues `event`, waiting for room if the buffer is full. Hands it back if the stream has been dropped.
+    pub async fn send(&self, event: WebEvent) -> Result<(), WebEvent> {
+        self.0.send(event).await.map_err(|err| err.0)
+    }
+
+    /// Queues `event` if there's room right now, and hands it back if not.
+    pub fn try_send(&self, event: WebEvent) -> Result<(), WebEvent> {
+        self.0.try_send(event).map_err(|err| match err {
+            TrySendError::Full(event) | TrySendError::Closed(event) => event,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct EventStream(mpsc::Receiver<WebEvent>);
+
+impl EventStream {
+    /// The next event, once there is one. `None` once the stream has ended.
+    pub async fn next(&mut self) -> Option<WebEvent> {
+        self.0.recv().await
+    }
+}
+
+impl Stream for EventStream {
+    type Item = WebEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<WebEvent>> {
+        self.0.poll_recv(cx)
+    }
+}
+
+#[tokio::test]
+async fn test_stream_applies_backpressure() {
+    let (sender, mut stream) = channel(2);
+    sender.send(WebEvent::WELoad(true)).await.unwrap();
+    sender.try_send(WebEvent::WELoad(false)).unwrap();
+    // Full: the event comes back rather than going over the bound
+    let scroll = WebEvent::WEScroll { dx: 0, dy: 1 };
+    assert_eq!(sender.try_send(scroll.clone()), Err(scroll.clone()));
+
+    // A producer that waits for room gets it as soon as the stream takes an event
+    let producer = {
+        let sender = sender.clone();
+        let scroll = scroll.clone();
+        tokio::spawn(async move { sender.send(scroll).await })
+    };
+    assert_eq!(stream.next().await, Some(WebEvent::WELoad(true)));
+    producer.await.unwrap().unwrap();
+    drop(sender);
+    let mut rest = Vec::new();
+    while let Some(event) = stream.next().await {
+        rest.push(event);
+    }
+    assert_eq!(rest, [WebEvent::WELoad(false), scroll]);
+
+    // Polled as a `Stream`, it reports the end too
+    let (sender, mut stream) = channel(1);
+    drop(sender);
+    let ended = std::future::poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await;
+    assert_eq!(ended, None);
+}
@@ -0,0 +1,201 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+pub use error::GradeError;
+use roster::{Roster, RosterError, StudentId};
+
+pub mod attendance;
+pub mod builder;
+pub mod csv;
+pub mod curve;
+mod error;
+#[cfg(feature = "serde")]
+pub mod json;
+pub mod rank;
+pub mod roster;
+pub mod scheme;
+pub mod stats;
+pub mod transcript;
+
+// classic struct, built with `Student::builder` so every student has a name and a real level
+#[derive(Debug, Clone, PartialEq)]
+pub struct Student {
+    name: String,
+    level: u8,
+    remote: bool,
+}
+
+impl Student {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    pub fn remote(&self) -> bool {
+        self.remote
+    }
+}
+
+/// The courses every student takes, in the order `Grades` holds their marks.
+pub const COURSES: [&str; 4] = ["Rust basics", "Ownership", "Traits", "Concurrency"];
+
+// tuple struct
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grades(
+    pub LetterGrade,
+    pub LetterGrade,
+    pub LetterGrade,
+    pub LetterGrade,
+    pub f32,
+);
+
+/// A letter grade, best first. There's no E, and no F+ or F-.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LetterGrade {
+    APlus,
+    A,
+    AMinus,
+    BPlus,
+    B,
+    BMinus,
+    CPlus,
+    C,
+    CMinus,
+    DPlus,
+    D,
+    DMinus,
+    F,
+}
+
+/// Why a grade couldn't be read: what was given.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseGradeError(pub String);
+
+impl fmt::Display for ParseGradeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` isn't a letter grade", self.0)
+    }
+}
+
+impl Error for ParseGradeError {}
+
+// Grades are written as a letter and an optional + or -, like "A", "B+" or "C-".
+impl FromStr for LetterGrade {
+    type Err = ParseGradeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use LetterGrade::*;
+
+        Ok(match s {
+            "A+" => APlus,
+            "A" => A,
+            "A-" => AMinus,
+            "B+" => BPlus,
+            "B" => B,
+            "B-" => BMinus,
+            "C+" => CPlus,
+            "C" => C,
+            "C-" => CMinus,
+            "D+" => DPlus,
+            "D" => D,
+            "D-" => DMinus,
+            "F" => F,
+            _ => return Err(ParseGradeError(s.to_string())),
+        })
+    }
+}
+
+/// A bare letter, with no + or -.
+impl TryFrom<char> for LetterGrade {
+    type Error = ParseGradeError;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        c.to_string().parse()
+    }
+}
+
+impl fmt::Display for LetterGrade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use LetterGrade::*;
+
+        let (letter, sign) = match self {
+            APlus => ('A', "+"),
+            A => ('A', ""),
+            AMinus => ('A', "-"),
+            BPlus => ('B', "+"),
+            B => ('B', ""),
+            BMinus => ('B', "-"),
+            CPlus => ('C', "+"),
+            C => ('C', ""),
+            CMinus => ('C', "-"),
+            DPlus => ('D', "+"),
+            D => ('D', ""),
+            DMinus => ('D', "-"),
+            F => ('F', ""),
+        };
+        write!(f, "{}{}", letter, sign)
+    }
+}
+
+/// The class and its marks: every student on the roster has one set of grades.
+#[derive(Clone, Default)]
+pub struct Gradebook {
+    roster: Roster,
+    grades: BTreeMap<StudentId, Grades>,
+    scheme: scheme::GradingScheme,
+    attendance: attendance::Attendance,
+}
+
+impl Gradebook {
+    /// A gradebook where every mark counts the same; see `with_scheme` to weight them.
+    pub fn new() -> Gradebook {
+        Gradebook::default()
+    }
+
+    /// Enrolls `student` with their `grades`.
+    pub fn add(&mut self, student: Student, grades: Grades) -> Result<StudentId, RosterError> {
+        let id = self.roster.enroll(student)?;
+        self.grades.insert(id, grades);
+        Ok(id)
+    }
+
+    pub fn roster(&self) -> &Roster {
+        &self.roster
+    }
+
+    pub fn grades(&self, id: StudentId) -> Option<&Grades> {
+        self.grades.get(&id)
+    }
+
+    /// Every student with their grades, in the order they enrolled.
+    pub fn iter(&self) -> impl Iterator<Item = (StudentId, &Student, &Grades)> {
+        self.roster
+            .iter()
+            .map(|(id, student)| (id, student, &self.grades[&id]))
+    }
+}
+
+#[test]
+fn test_only_real_grades_parse() {
+    assert_eq!("B+".parse(), Ok(LetterGrade::BPlus));
+    assert_eq!(LetterGrade::try_from('F'), Ok(LetterGrade::F));
+    for bad in ["Z", "E", "F+", "a", "A++", ""] {
+        assert_eq!(
+            bad.parse::<LetterGrade>(),
+            Err(ParseGradeError(bad.to_string()))
+        );
+    }
+    assert!(LetterGrade::try_from('Z').is_err());
+    assert!(LetterGrade::APlus < LetterGrade::AMinus);
+
+    // Every grade reads back as itself
+    let all = "A+ A A- B+ B B- C+ C C- D+ D D- F";
+    let parsed: Vec<LetterGrade> = all.split(' ').map(|g| g.parse().unwrap()).collect();
+    let written: Vec<_> = parsed.iter().map(LetterGrade::to_string).collect();
+    assert_eq!(written.join(" "), all);
+}
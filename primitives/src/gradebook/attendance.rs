@@ -0,0 +1,167 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+use super::roster::StudentId;
+use super::Gradebook;
+
+// Who came to each class. The gradebook numbers sessions as they're held, and a student is marked present, absent or
+// remote in each; joining remotely counts as attending. A student's rate only counts the sessions they were marked
+// in, so someone who enrolled part way through the term isn't held to the classes before they joined.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Presence {
+    Present,
+    Absent,
+    Remote,
+}
+
+/// A class session, numbered from 1 in the order they were held.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Session(pub(super) usize);
+
+impl fmt::Display for Session {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "session {}", self.0)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum AttendanceError {
+    UnknownSession(Session),   // Not held yet
+    UnknownStudent(StudentId), // Not in the gradebook
+}
+
+impl fmt::Display for AttendanceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttendanceError::UnknownSession(session) => write!(f, "no {} was held", session),
+            AttendanceError::UnknownStudent(id) => write!(f, "no student {}", id),
+        }
+    }
+}
+
+impl Error for AttendanceError {}
+
+/// The register for every session held, first session first.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Attendance {
+    pub(super) sessions: Vec<BTreeMap<StudentId, Presence>>,
+}
+
+impl Attendance {
+    /// The share of the sessions `id` was marked in that they attended, from 0 to 1. `None` if they were never
+    /// marked.
+    pub fn rate(&self, id: StudentId) -> Option<f32> {
+        let marks: Vec<Presence> = self
+            .sessions
+            .iter()
+            .filter_map(|register| register.get(&id).copied())
+            .collect();
+        if marks.is_empty() {
+            return None;
+        }
+        let attended = marks.iter().filter(|&&p| p != Presence::Absent).count();
+        Some(attended as f32 / marks.len() as f32)
+    }
+
+    pub fn get(&self, session: Session, id: StudentId) -> Option<Presence> {
+        self.sessions
+            .get(session.0.checked_sub(1)?)?
+            .get(&id)
+            .copied()
+    }
+
+    pub fn sessions(&self) -> usize {
+        self.sessions.len()
+    }
+}
+
+impl Gradebook {
+    pub fn attendance(&self) -> &Attendance {
+        &self.attendance
+    }
+
+    /// Opens the register for a new session, with no one marked yet.
+    pub fn hold_session(&mut self) -> Session {
+        self.attendance.sessions.push(BTreeMap::new());
+        Session(self.attendance.sessions.len())
+    }
+
+    /// Marks student `id` in `session`, replacing any earlier mark, which is returned.
+    pub fn mark(
+        &mut self,
+        session: Session,
+        id: StudentId,
+        presence: Presence,
+    ) -> Result<Option<Presence>, AttendanceError> {
+        if !self.grades.contains_key(&id) {
+            return Err(AttendanceError::UnknownStudent(id));
+        }
+        let register = session
+            .0
+            .checked_sub(1)
+            .and_then(|i| self.attendance.sessions.get_mut(i))
+            .ok_or(AttendanceError::UnknownSession(session))?;
+        Ok(register.insert(id, presence))
+    }
+
+    /// Students whose attendance rate is under `threshold`, worst first. Students never marked aren't listed.
+    pub fn attendance_below(&self, threshold: f32) -> Vec<(StudentId, f32)> {
+        let mut below: Vec<_> = self
+            .iter()
+            .filter_map(|(id, _, _)| Some((id, self.attendance.rate(id)?)))
+            .filter(|(_, rate)| *rate < threshold)
+            .collect();
+        below.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+        below
+    }
+}
+
+#[test]
+fn test_attendance_rates_and_report() {
+    use super::{Grades, LetterGrade::*, Student};
+    use Presence::*;
+
+    let mut book = Gradebook::new();
+    let mut add = |name: &str| {
+        book.add(
+            Student::builder(name).build().unwrap(),
+            Grades(A, A, A, A, 4.0),
+        )
+        .unwrap()
+    };
+    let (ada, alan, grace) = (add("Ada"), add("Alan"), add("Grace"));
+    let first = book.hold_session();
+    assert_eq!(book.mark(first, ada, Present), Ok(None));
+    assert_eq!(book.mark(first, alan, Absent), Ok(None));
+    let second = book.hold_session();
+    book.mark(second, ada, Remote).unwrap();
+    book.mark(second, alan, Present).unwrap();
+    // Grace joined late, so only this one counts for her
+    book.mark(second, grace, Absent).unwrap();
+    // Marking again replaces the old mark
+    assert_eq!(book.mark(first, alan, Absent), Ok(Some(Absent)));
+
+    assert_eq!(book.attendance().rate(ada), Some(1.0));
+    assert_eq!(book.attendance().rate(alan), Some(0.5));
+    assert_eq!(book.attendance().rate(grace), Some(0.0));
+    assert_eq!(book.attendance().get(second, ada), Some(Remote));
+    assert_eq!(book.attendance().get(first, grace), None);
+    assert_eq!(book.attendance_below(0.75), [(grace, 0.0), (alan, 0.5)]);
+
+    assert_eq!(
+        book.mark(Session(3), ada, Present),
+        Err(AttendanceError::UnknownSession(Session(3)))
+    );
+    assert_eq!(
+        book.mark(Session(0), ada, Present),
+        Err(AttendanceError::UnknownSession(Session(0)))
+    );
+    let nobody = StudentId(99);
+    assert_eq!(
+        book.mark(first, nobody, Present),
+        Err(AttendanceError::UnknownStudent(nobody))
+    );
+    assert_eq!(book.attendance().sessions(), 2);
+}
@@ -0,0 +1,104 @@
+use std::error::Error;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use super::Student;
+
+/// The levels students can be in, first year to last.
+pub const LEVELS: RangeInclusive<u8> = 1..=12;
+
+/// Why `StudentBuilder::build` refused to make a student.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StudentError {
+    EmptyName, // Nothing but whitespace, which no one could look up
+    Level(u8), // Outside `LEVELS`
+}
+
+impl fmt::Display for StudentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StudentError::EmptyName => write!(f, "a student needs a name"),
+            StudentError::Level(level) => write!(
+                f,
+                "level {} is outside {} to {}",
+                level,
+                LEVELS.start(),
+                LEVELS.end()
+            ),
+        }
+    }
+}
+
+impl Error for StudentError {}
+
+/// Sets up a student, e.g. `Student::builder("Ada").level(2).remote(true).build()`. Anything left unset is an
+/// in-person first-year.
+#[derive(Debug)]
+pub struct StudentBuilder {
+    name: String,
+    level: u8,
+    remote: bool,
+}
+
+impl Student {
+    pub fn builder(name: impl Into<String>) -> StudentBuilder {
+        StudentBuilder {
+            name: name.into(),
+            level: *LEVELS.start(),
+            remote: false,
+        }
+    }
+}
+
+impl StudentBuilder {
+    pub fn level(mut self, level: u8) -> Self {
+        self.level = level;
+        self
+    }
+
+    pub fn remote(mut self, remote: bool) -> Self {
+        self.remote = remote;
+        self
+    }
+
+    /// Checks the name and level and makes the student.
+    pub fn build(self) -> Result<Student, StudentError> {
+        if self.name.trim().is_empty() {
+            return Err(StudentError::EmptyName);
+        }
+        if !LEVELS.contains(&self.level) {
+            return Err(StudentError::Level(self.level));
+        }
+        Ok(Student {
+            name: self.name,
+            level: self.level,
+            remote: self.remote,
+        })
+    }
+}
+
+#[test]
+fn test_builder_checks_name_and_level() {
+    let ada = Student::builder("Ada")
+        .level(12)
+        .remote(true)
+        .build()
+        .unwrap();
+    assert_eq!((ada.name(), ada.level(), ada.remote()), ("Ada", 12, true));
+    assert_eq!(Student::builder("Alan").build().unwrap().level(), 1);
+
+    assert_eq!(
+        Student::builder(" ").build().unwrap_err(),
+        StudentError::EmptyName
+    );
+    for level in [0, 13, u8::MAX] {
+        assert_eq!(
+            Student::builder("Grace").level(level).build().unwrap_err(),
+            StudentError::Level(level)
+        );
+    }
+    assert_eq!(
+        StudentError::Level(0).to_string(),
+        "level 0 is outside 1 to 12"
+    );
+}
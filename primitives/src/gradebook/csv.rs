@@ -0,0 +1,216 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+use super::builder::StudentError;
+use super::roster::{RosterError, StudentId};
+use super::{Gradebook, Grades, LetterGrade, ParseGradeError, Student};
+
+// Students in and out of spreadsheets, one per row: name, level, remote, the four grades and the average. Exports
+// start with a header row, and an import skips one if it's there. As with car orders, a bad row is reported and the
+// rest still go in. A name with a comma or a quote in it is written quoted, the way spreadsheets do.
+
+const HEADER: &str = "name,level,remote,grade_1,grade_2,grade_3,grade_4,average";
+
+/// What was wrong with one row of an import, numbered from 1 as the spreadsheet shows it.
+#[derive(Debug, PartialEq)]
+pub struct RowError {
+    pub row: usize,
+    pub problem: Problem,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Problem {
+    Fields(usize),          // Wrong number of fields, eight are needed
+    Level(String),          // Not a number from 0 to 255
+    Student(StudentError),  // No name, or a level outside the school's
+    Remote(String),         // Not true or false
+    Grade(ParseGradeError), // Not a letter grade
+    Average(String),        // Not a number
+    Roster(RosterError),    // Fine, but the student is already in the gradebook
+}
+
+impl fmt::Display for RowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "row {}: ", self.row)?;
+        match &self.problem {
+            Problem::Fields(n) => write!(f, "expected 8 fields, found {}", n),
+            Problem::Level(s) => write!(f, "level should be a number, not `{}`", s),
+            Problem::Remote(s) => write!(f, "remote should be true or false, not `{}`", s),
+            Problem::Grade(e) => write!(f, "{}", e),
+            Problem::Average(s) => write!(f, "average should be a number, not `{}`", s),
+            Problem::Student(e) => write!(f, "{}", e),
+            Problem::Roster(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for RowError {}
+
+/// What an import did: the IDs of the students it added, and the rows it couldn't use.
+#[derive(Debug, Default)]
+pub struct Import {
+    pub added: Vec<StudentId>,
+    pub errors: Vec<RowError>,
+}
+
+impl Gradebook {
+    /// Writes a header row, then a row for every student in the order they enrolled.
+    pub fn export_csv(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(writer, "{}", HEADER)?;
+        for (_, student, grades) in self.iter() {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{}",
+                quote(student.name()),
+                student.level(),
+                student.remote(),
+                grades.0,
+                grades.1,
+                grades.2,
+                grades.3,
+                grades.4
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Adds a student for every good row of `reader`. Blank rows, and a header in the first row, are skipped. Only
+    /// failing to read stops the import part way.
+    pub fn import_csv(&mut self, reader: impl BufRead) -> io::Result<Import> {
+        let mut import = Import::default();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() || (i == 0 && line.trim().eq_ignore_ascii_case(HEADER)) {
+                continue;
+            }
+            let added = parse_row(&line)
+                .and_then(|(student, grades)| self.add(student, grades).map_err(Problem::Roster));
+            match added {
+                Ok(id) => import.added.push(id),
+                Err(problem) => import.errors.push(RowError {
+                    row: i + 1,
+                    problem,
+                }),
+            }
+        }
+        Ok(import)
+    }
+}
+
+fn quote(field: &str) -> String {
+    if field.contains([',', '"']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// Splits a row on commas outside quotes, undoing `quote`.
+fn split_row(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+fn parse_row(line: &str) -> Result<(Student, Grades), Problem> {
+    let fields = split_row(line);
+    let fields: Vec<_> = fields.iter().map(|f| f.trim()).collect();
+    let [name, level, remote, g1, g2, g3, g4, average] = fields[..] else {
+        return Err(Problem::Fields(fields.len()));
+    };
+    let grade = |g: &str| g.parse::<LetterGrade>().map_err(Problem::Grade);
+    let level = level
+        .parse()
+        .map_err(|_| Problem::Level(level.to_string()))?;
+    let remote = remote
+        .parse()
+        .map_err(|_| Problem::Remote(remote.to_string()))?;
+    let student = Student::builder(name)
+        .level(level)
+        .remote(remote)
+        .build()
+        .map_err(Problem::Student)?;
+    let grades = Grades(
+        grade(g1)?,
+        grade(g2)?,
+        grade(g3)?,
+        grade(g4)?,
+        average
+            .parse()
+            .map_err(|_| Problem::Average(average.to_string()))?,
+    );
+    Ok((student, grades))
+}
+
+#[test]
+fn test_csv_round_trips_and_reports_bad_rows() {
+    let rows = "Name,Level,Remote,Grade_1,Grade_2,Grade_3,Grade_4,Average\n\
+                Ada,2,true,A,A-,B+,A,3.8\n\
+                \"Hopper, Grace\",3,false,A+,A,A,A,4.0\n\
+                \n\
+                Alan,1,maybe,B,B,B,B,3.0\n\
+                Linus,1,false,B,Z,B,B,3.0\n\
+                Ada,4,false,C,C,C,C,2.0\n\
+                Ken,300,false,C,C,C,C,2.0\n\
+                Dennis,1,false,C,C,C\n\
+                ,1,false,C,C,C,C,2.0\n\
+                Bjarne,0,false,C,C,C,C,2.0\n";
+    let mut book = Gradebook::new();
+    let import = book.import_csv(rows.as_bytes()).unwrap();
+    assert_eq!(import.added.len(), 2);
+    let problems: Vec<_> = import.errors.iter().map(|e| e.row).collect();
+    assert_eq!(problems, [5, 6, 7, 8, 9, 10, 11]);
+    assert_eq!(
+        import.errors[0].problem,
+        Problem::Remote("maybe".to_string())
+    );
+    assert_eq!(
+        import.errors[1].to_string(),
+        "row 6: `Z` isn't a letter grade"
+    );
+    assert_eq!(
+        import.errors[2].problem,
+        Problem::Roster(RosterError::AlreadyEnrolled(import.added[0]))
+    );
+    assert_eq!(import.errors[3].problem, Problem::Level("300".to_string()));
+    assert_eq!(import.errors[4].problem, Problem::Fields(6));
+    assert_eq!(
+        import.errors[5].problem,
+        Problem::Student(StudentError::EmptyName)
+    );
+    assert_eq!(
+        import.errors[6].problem,
+        Problem::Student(StudentError::Level(0))
+    );
+
+    // What goes out comes back in the same
+    let mut out = Vec::new();
+    book.export_csv(&mut out).unwrap();
+    let text = String::from_utf8(out).unwrap();
+    assert_eq!(
+        text,
+        format!(
+            "{}\nAda,2,true,A,A-,B+,A,3.8\n\"Hopper, Grace\",3,false,A+,A,A,A,4\n",
+            HEADER
+        )
+    );
+    let mut copy = Gradebook::new();
+    let import = copy.import_csv(text.as_bytes()).unwrap();
+    assert!(import.errors.is_empty());
+    let (_, grace, grades) = copy.iter().nth(1).unwrap();
+    assert_eq!(grace.name(), "Hopper, Grace");
+    assert_eq!(grades.0, LetterGrade::APlus);
+}
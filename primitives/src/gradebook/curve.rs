@@ -0,0 +1,68 @@
+use super::stats;
+use super::Gradebook;
+
+// Curving moves every student's average score the same way; the letter grades are left as they were marked. The
+// curve is applied to a copy, so the marks as given are still there to compare with, or to curve differently. No
+// curve takes a score below zero.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Curve {
+    Flat(f32),      // Add this to every score
+    Scale(f32),     // Multiply every score by this
+    Normalize(f32), // Shift every score by the same amount so that they average this
+}
+
+impl Curve {
+    fn apply(self, score: f32, mean: f32) -> f32 {
+        let curved = match self {
+            Curve::Flat(boost) => score + boost,
+            Curve::Scale(factor) => score * factor,
+            Curve::Normalize(target) => score + (target - mean),
+        };
+        curved.max(0.0)
+    }
+}
+
+impl Gradebook {
+    /// A copy of this gradebook with `curve` applied to every average score.
+    pub fn apply_curve(&self, curve: Curve) -> Gradebook {
+        let mean = stats::mean(&self.averages()).unwrap_or(0.0);
+        let mut curved = self.clone();
+        for grades in curved.grades.values_mut() {
+            grades.4 = curve.apply(grades.4, mean);
+        }
+        curved
+    }
+}
+
+#[test]
+fn test_curves_leave_the_original_alone() {
+    use super::{Grades, LetterGrade::*, Student};
+
+    let mut book = Gradebook::new();
+    for (name, average) in [("Ada", 60.0), ("Alan", 70.0), ("Grace", 95.0)] {
+        let student = Student::builder(name).build().unwrap();
+        book.add(student, Grades(B, B, B, B, average)).unwrap();
+    }
+
+    let averages = |book: &Gradebook| book.averages();
+    assert_eq!(
+        averages(&book.apply_curve(Curve::Flat(5.0))),
+        [65.0, 75.0, 100.0]
+    );
+    assert_eq!(
+        averages(&book.apply_curve(Curve::Scale(1.1))),
+        [66.0, 77.0, 104.5]
+    );
+    let normalised = book.apply_curve(Curve::Normalize(80.0));
+    assert_eq!(averages(&normalised), [65.0, 75.0, 100.0]);
+    assert_eq!(stats::mean(&normalised.averages()), Some(80.0));
+    assert_eq!(
+        averages(&book.apply_curve(Curve::Flat(-65.0))),
+        [0.0, 5.0, 30.0]
+    );
+    // Only the scores move
+    assert_eq!(averages(&book), [60.0, 70.0, 95.0]);
+    assert_eq!(normalised.iter().next().unwrap().2 .0, B);
+    assert_eq!(normalised.roster().len(), 3);
+}
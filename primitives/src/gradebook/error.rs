@@ -0,0 +1,62 @@
+use std::error::Error;
+use std::fmt;
+
+use super::attendance::AttendanceError;
+use super::builder::StudentError;
+use super::csv::RowError;
+use super::roster::RosterError;
+use super::ParseGradeError;
+
+/// Any error from the gradebook, so enrolling, grading and taking attendance can share one `?`-able result type.
+#[derive(Debug, PartialEq)]
+pub enum GradeError {
+    Grade(ParseGradeError),
+    Student(StudentError),
+    Roster(RosterError),
+    Attendance(AttendanceError),
+    Csv(RowError),
+}
+
+impl fmt::Display for GradeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GradeError::Grade(err) => err.fmt(f),
+            GradeError::Student(err) => err.fmt(f),
+            GradeError::Roster(err) => err.fmt(f),
+            GradeError::Attendance(err) => err.fmt(f),
+            GradeError::Csv(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for GradeError {}
+
+impl From<ParseGradeError> for GradeError {
+    fn from(err: ParseGradeError) -> Self {
+        GradeError::Grade(err)
+    }
+}
+
+impl From<StudentError> for GradeError {
+    fn from(err: StudentError) -> Self {
+        GradeError::Student(err)
+    }
+}
+
+impl From<RosterError> for GradeError {
+    fn from(err: RosterError) -> Self {
+        GradeError::Roster(err)
+    }
+}
+
+impl From<AttendanceError> for GradeError {
+    fn from(err: AttendanceError) -> Self {
+        GradeError::Attendance(err)
+    }
+}
+
+impl From<RowError> for GradeError {
+    fn from(err: RowError) -> Self {
+        GradeError::Csv(err)
+    }
+}
@@ -0,0 +1,388 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::os::{FromJson, Json, JsonError, ToJson};
+
+use super::attendance::{Attendance, Presence};
+use super::roster::{Roster, StudentId};
+use super::scheme::{Category, GradingScheme};
+use super::{Gradebook, Grades, LetterGrade, Student};
+
+// Gradebooks are saved with the same JSON as fleets and process trees. That JSON has no fractions, so averages and
+// weights are written as strings, "87.5" rather than 87.5. Fields added since the first files were written, a
+// student's `remote` and the gradebook's `scheme` and `attendance`, may be left out and then take their defaults.
+// Only JSON is written: YAML would need a parser this crate doesn't have.
+
+/// Version written into saved gradebooks, bumped whenever their layout changes.
+const FORMAT_VERSION: u64 = 1;
+
+const CATEGORIES: [Category; 3] = [Category::Homework, Category::Exam, Category::Project];
+
+const PRESENCES: [Presence; 3] = [Presence::Present, Presence::Absent, Presence::Remote];
+
+fn float(value: f32) -> Json {
+    Json::Str(value.to_string())
+}
+
+fn read_float(json: &Json, name: &'static str) -> Result<f32, JsonError> {
+    json.string(name)?
+        .parse()
+        .map_err(|_| JsonError::Field(name))
+}
+
+impl ToJson for LetterGrade {
+    fn to_json(&self) -> Json {
+        Json::Str(self.to_string())
+    }
+}
+
+impl FromJson for LetterGrade {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        match json {
+            Json::Str(grade) => grade.parse().map_err(|_| JsonError::Field("grade")),
+            _ => Err(JsonError::Field("grade")),
+        }
+    }
+}
+
+impl ToJson for Student {
+    fn to_json(&self) -> Json {
+        Json::object(vec![
+            ("name", Json::Str(self.name.clone())),
+            ("level", Json::Int(self.level.into())),
+            ("remote", Json::Bool(self.remote)),
+        ])
+    }
+}
+
+/// Checked the same way `StudentBuilder::build` checks a new student.
+impl FromJson for Student {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        let remote = match json.get("remote") {
+            Some(_) => json.bool("remote")?,
+            None => false,
+        };
+        Student::builder(json.string("name")?)
+            .level(json.int("level")?)
+            .remote(remote)
+            .build()
+            .map_err(|_| JsonError::Field("student"))
+    }
+}
+
+impl ToJson for Grades {
+    fn to_json(&self) -> Json {
+        let marks = [self.0, self.1, self.2, self.3];
+        Json::object(vec![
+            (
+                "marks",
+                Json::Array(marks.iter().map(LetterGrade::to_json).collect()),
+            ),
+            ("average", float(self.4)),
+        ])
+    }
+}
+
+impl FromJson for Grades {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        let marks = json
+            .array("marks")?
+            .iter()
+            .map(LetterGrade::from_json)
+            .collect::<Result<Vec<_>, _>>()?;
+        let [a, b, c, d] = marks[..] else {
+            return Err(JsonError::Field("marks"));
+        };
+        Ok(Grades(a, b, c, d, read_float(json, "average")?))
+    }
+}
+
+impl ToJson for Category {
+    fn to_json(&self) -> Json {
+        Json::Str(format!("{:?}", self))
+    }
+}
+
+impl FromJson for Category {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        CATEGORIES
+            .into_iter()
+            .find(|c| Json::Str(format!("{:?}", c)) == *json)
+            .ok_or(JsonError::Field("variant"))
+    }
+}
+
+impl ToJson for GradingScheme {
+    fn to_json(&self) -> Json {
+        Json::object(vec![
+            (
+                "categories",
+                Json::Array(self.categories.iter().map(Category::to_json).collect()),
+            ),
+            ("homework", float(self.homework)),
+            ("exam", float(self.exam)),
+            ("project", float(self.project)),
+        ])
+    }
+}
+
+impl FromJson for GradingScheme {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        let categories = json
+            .array("categories")?
+            .iter()
+            .map(Category::from_json)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(GradingScheme {
+            categories: categories
+                .try_into()
+                .map_err(|_| JsonError::Field("categories"))?,
+            homework: read_float(json, "homework")?,
+            exam: read_float(json, "exam")?,
+            project: read_float(json, "project")?,
+        })
+    }
+}
+
+impl ToJson for Presence {
+    fn to_json(&self) -> Json {
+        Json::Str(format!("{:?}", self))
+    }
+}
+
+impl FromJson for Presence {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        PRESENCES
+            .into_iter()
+            .find(|p| Json::Str(format!("{:?}", p)) == *json)
+            .ok_or(JsonError::Field("variant"))
+    }
+}
+
+/// One array per session, listing who was marked in it.
+impl ToJson for Attendance {
+    fn to_json(&self) -> Json {
+        let sessions = self
+            .sessions
+            .iter()
+            .map(|register| {
+                let marks = register
+                    .iter()
+                    .map(|(id, presence)| {
+                        Json::object(vec![
+                            ("id", Json::Int(id.0.into())),
+                            ("presence", presence.to_json()),
+                        ])
+                    })
+                    .collect();
+                Json::Array(marks)
+            })
+            .collect();
+        Json::object(vec![("sessions", Json::Array(sessions))])
+    }
+}
+
+impl FromJson for Attendance {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        let sessions = json
+            .array("sessions")?
+            .iter()
+            .map(|register| match register {
+                Json::Array(marks) => marks
+                    .iter()
+                    .map(|mark| Ok((StudentId(mark.int("id")?), mark.parse("presence")?)))
+                    .collect(),
+                _ => Err(JsonError::Field("sessions")),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Attendance { sessions })
+    }
+}
+
+/// Students keep their IDs, and the next one handed out carries on from the saved roster's.
+impl ToJson for Roster {
+    fn to_json(&self) -> Json {
+        let students = self
+            .iter()
+            .map(|(id, student)| {
+                Json::object(vec![
+                    ("id", Json::Int(id.0.into())),
+                    ("student", student.to_json()),
+                ])
+            })
+            .collect();
+        Json::object(vec![
+            ("next_id", Json::Int(self.next_id.into())),
+            ("students", Json::Array(students)),
+        ])
+    }
+}
+
+impl FromJson for Roster {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        let mut roster = Roster {
+            students: BTreeMap::new(),
+            next_id: json.int("next_id")?,
+        };
+        for entry in json.array("students")? {
+            let id: u32 = entry.int("id")?;
+            let student: Student = entry.parse("student")?;
+            // IDs and names are unique, and no ID is past the next one to hand out
+            if id > roster.next_id || roster.find(&student.name).is_some() {
+                return Err(JsonError::Field("students"));
+            }
+            if roster.students.insert(StudentId(id), student).is_some() {
+                return Err(JsonError::Field("students"));
+            }
+        }
+        Ok(roster)
+    }
+}
+
+impl ToJson for Gradebook {
+    fn to_json(&self) -> Json {
+        let grades = self
+            .iter()
+            .map(|(id, _, grades)| {
+                Json::object(vec![
+                    ("id", Json::Int(id.0.into())),
+                    ("grades", grades.to_json()),
+                ])
+            })
+            .collect();
+        Json::object(vec![
+            ("roster", self.roster.to_json()),
+            ("grades", Json::Array(grades)),
+            ("scheme", self.scheme.to_json()),
+            ("attendance", self.attendance.to_json()),
+        ])
+    }
+}
+
+impl FromJson for Gradebook {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        let roster: Roster = json.parse("roster")?;
+        let grades = json
+            .array("grades")?
+            .iter()
+            .map(|entry| Ok((StudentId(entry.int("id")?), entry.parse("grades")?)))
+            .collect::<Result<BTreeMap<_, _>, JsonError>>()?;
+        // Every student on the roster has grades, and only they do
+        if !grades.keys().eq(roster.students.keys()) {
+            return Err(JsonError::Field("grades"));
+        }
+        let scheme = match json.get("scheme") {
+            Some(_) => json.parse("scheme")?,
+            None => GradingScheme::default(),
+        };
+        let attendance: Attendance = match json.get("attendance") {
+            Some(_) => json.parse("attendance")?,
+            None => Attendance::default(),
+        };
+        // And only they are marked in the register
+        let mut marked = attendance
+            .sessions
+            .iter()
+            .flat_map(|register| register.keys());
+        if marked.any(|id| !grades.contains_key(id)) {
+            return Err(JsonError::Field("attendance"));
+        }
+        Ok(Gradebook {
+            roster,
+            grades,
+            scheme,
+            attendance,
+        })
+    }
+}
+
+impl Gradebook {
+    /// Writes the roster, every student's grades and the grading scheme to `path`.
+    pub fn save_json(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let file = Json::object(vec![
+            ("version", Json::Int(FORMAT_VERSION.into())),
+            ("gradebook", self.to_json()),
+        ]);
+        fs::write(path, format!("{}\n", file))
+    }
+
+    /// Reads back a gradebook written by `save_json`.
+    pub fn load_json(path: impl AsRef<Path>) -> io::Result<Gradebook> {
+        let file: Json = fs::read_to_string(path)?.parse()?;
+        let version: u64 = file.int("version")?;
+        if version != FORMAT_VERSION {
+            return Err(JsonError::Version(version).into());
+        }
+        Ok(file.parse("gradebook")?)
+    }
+}
+
+#[test]
+fn test_gradebook_file_round_trips() {
+    use LetterGrade::*;
+
+    let mut book = Gradebook::with_scheme(GradingScheme {
+        categories: [
+            Category::Homework,
+            Category::Homework,
+            Category::Exam,
+            Category::Project,
+        ],
+        exam: 2.5,
+        ..GradingScheme::default()
+    });
+    let student = |name: &str| Student::builder(name).level(3).build().unwrap();
+    book.add(
+        student("Ada, \"the Countess\""),
+        Grades(A, AMinus, B, F, 87.5),
+    )
+    .unwrap();
+    let alan = book
+        .add(student("Alan"), Grades(C, C, A, A, 70.25))
+        .unwrap();
+    let session = book.hold_session();
+    book.mark(session, alan, Presence::Remote).unwrap();
+    book.roster.drop("Ada, \"the Countess\"");
+    book.grades.retain(|id, _| *id == alan);
+
+    let path = std::env::temp_dir().join(format!("gradebook-{}.json", std::process::id()));
+    book.save_json(&path).unwrap();
+    let mut loaded = Gradebook::load_json(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.to_json(), book.to_json());
+    assert_eq!(loaded.grades(alan), Some(&Grades(C, C, A, A, 70.25)));
+    assert_eq!(loaded.scheme(), book.scheme());
+    assert_eq!(
+        loaded.attendance().get(session, alan),
+        Some(Presence::Remote)
+    );
+    // IDs aren't handed out twice
+    let grace = loaded
+        .add(student("Grace"), Grades(A, A, A, A, 95.0))
+        .unwrap();
+    assert!(grace > alan);
+
+    // A file from before students could be remote, or gradebooks weighted
+    let old: Json =
+        r#"{"roster":{"next_id":1,"students":[{"id":1,"student":{"name":"Ada","level":2}}]},
+        "grades":[{"id":1,"grades":{"marks":["A","B","C","D"],"average":"60"}}]}"#
+            .parse()
+            .unwrap();
+    let old = Gradebook::from_json(&old).unwrap();
+    let (_, ada, _) = old.iter().next().unwrap();
+    assert!(!ada.remote());
+    assert_eq!(old.scheme(), &GradingScheme::default());
+    assert_eq!(old.attendance().sessions(), 0);
+
+    // Students without grades, or grades for no one, are refused
+    let orphan: Json = r#"{"roster":{"next_id":0,"students":[]},
+        "grades":[{"id":1,"grades":{"marks":["A","B","C","D"],"average":"60"}}]}"#
+        .parse()
+        .unwrap();
+    assert_eq!(
+        Gradebook::from_json(&orphan).err(),
+        Some(JsonError::Field("grades"))
+    );
+}
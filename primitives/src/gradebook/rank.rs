@@ -0,0 +1,119 @@
+use std::cmp::Ordering;
+
+use super::roster::StudentId;
+use super::scheme::GradingScheme;
+use super::{Gradebook, Grades, LetterGrade};
+
+// Class ranking goes by GPA, worked out from the four letter grades on the usual 4.0 scale. Students with the same
+// GPA share a rank, and the next rank down skips the places they took up ("1, 2, 2, 4"). Within a shared rank they're
+// listed by name, so the order never depends on who enrolled first.
+
+impl LetterGrade {
+    /// Grade points on the 4.0 scale. An A+ counts the same as an A.
+    pub fn points(self) -> f32 {
+        use LetterGrade::*;
+
+        match self {
+            APlus | A => 4.0,
+            AMinus => 3.7,
+            BPlus => 3.3,
+            B => 3.0,
+            BMinus => 2.7,
+            CPlus => 2.3,
+            C => 2.0,
+            CMinus => 1.7,
+            DPlus => 1.3,
+            D => 1.0,
+            DMinus => 0.7,
+            F => 0.0,
+        }
+    }
+}
+
+impl Grades {
+    /// The GPA with every mark counting the same. A gradebook may weight them differently; see `Gradebook::gpa`.
+    pub fn gpa(&self) -> f32 {
+        GradingScheme::default().gpa(self)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ranked {
+    pub rank: usize, // From 1
+    pub id: StudentId,
+    pub gpa: f32,
+}
+
+impl Gradebook {
+    /// Every student, best GPA first.
+    pub fn rank(&self) -> Vec<Ranked> {
+        let mut students: Vec<_> = self
+            .iter()
+            .map(|(id, student, grades)| (id, student, self.scheme.gpa(grades)))
+            .collect();
+        students.sort_by(|(_, a, gpa_a), (_, b, gpa_b)| {
+            gpa_b.total_cmp(gpa_a).then_with(|| a.name.cmp(&b.name))
+        });
+        let mut ranked: Vec<Ranked> = Vec::with_capacity(students.len());
+        for (i, (id, _, gpa)) in students.into_iter().enumerate() {
+            let rank = match ranked.last() {
+                Some(prev) if prev.gpa == gpa => prev.rank,
+                _ => i + 1,
+            };
+            ranked.push(Ranked { rank, id, gpa });
+        }
+        ranked
+    }
+
+    /// The percentile rank of student `id`: the share of the class with a lower GPA, counting those level with them
+    /// (themselves included) as half below. `None` if they aren't in the gradebook.
+    pub fn percentile(&self, id: StudentId) -> Option<f32> {
+        let gpa = self.gpa(id)?;
+        let (mut below, mut level) = (0, 0);
+        for (_, _, grades) in self.iter() {
+            match self.scheme.gpa(grades).total_cmp(&gpa) {
+                Ordering::Less => below += 1,
+                Ordering::Equal => level += 1,
+                Ordering::Greater => {}
+            }
+        }
+        Some((2 * below + level) as f32 * 50.0 / self.roster().len() as f32)
+    }
+}
+
+#[test]
+fn test_ties_share_a_rank() {
+    use super::Student;
+    use LetterGrade::*;
+
+    let mut book = Gradebook::new();
+    assert!(book.rank().is_empty());
+
+    let mut add = |name: &str, grades: [LetterGrade; 4]| {
+        let student = Student {
+            name: name.to_string(),
+            level: 1,
+            remote: false,
+        };
+        let [a, b, c, d] = grades;
+        book.add(student, Grades(a, b, c, d, 0.0)).unwrap()
+    };
+    let zed = add("Zed", [A, A, B, B]);
+    let ada = add("Ada", [B, B, A, A]);
+    let top = add("Grace", [APlus, A, A, A]);
+    let low = add("Alan", [C, C, F, D]);
+    let mid = add("Linus", [BPlus, B, B, B]);
+
+    let ranked: Vec<_> = book.rank().iter().map(|r| (r.rank, r.id)).collect();
+    // Ada and Zed tie, and are listed by name; Linus comes fourth, not third
+    assert_eq!(ranked, [(1, top), (2, ada), (2, zed), (4, mid), (5, low)]);
+    assert_eq!(book.rank()[0].gpa, 4.0);
+
+    assert_eq!(book.percentile(top), Some(90.0));
+    assert_eq!(book.percentile(ada), book.percentile(zed));
+    assert_eq!(book.percentile(ada), Some(60.0));
+    assert_eq!(book.percentile(low), Some(10.0));
+
+    let empty = Gradebook::new();
+    assert_eq!(empty.percentile(top), None);
+}
@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+use super::Student;
+
+// Who's in the class. Each student is given an ID when they enroll, which stays theirs until they drop out and isn't
+// given to anyone after them. Names are unique as well, so a student can be looked up by either.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StudentId(pub(super) u32);
+
+impl fmt::Display for StudentId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "S{:04}", self.0)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RosterError {
+    AlreadyEnrolled(StudentId), // A student with that name is, under this ID
+}
+
+impl fmt::Display for RosterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RosterError::AlreadyEnrolled(id) => write!(f, "already enrolled as {}", id),
+        }
+    }
+}
+
+impl Error for RosterError {}
+
+#[derive(Clone, Default)]
+pub struct Roster {
+    pub(super) students: BTreeMap<StudentId, Student>,
+    pub(super) next_id: u32,
+}
+
+impl Roster {
+    pub fn new() -> Roster {
+        Roster::default()
+    }
+
+    /// Adds `student` to the class, unless someone of the same name is already in it.
+    pub fn enroll(&mut self, student: Student) -> Result<StudentId, RosterError> {
+        if let Some((id, _)) = self.find(&student.name) {
+            return Err(RosterError::AlreadyEnrolled(id));
+        }
+        self.next_id += 1;
+        let id = StudentId(self.next_id);
+        self.students.insert(id, student);
+        Ok(id)
+    }
+
+    /// Takes the student called `name` off the roster.
+    pub fn drop(&mut self, name: &str) -> Option<Student> {
+        let (id, _) = self.find(name)?;
+        self.students.remove(&id)
+    }
+
+    pub fn get(&self, id: StudentId) -> Option<&Student> {
+        self.students.get(&id)
+    }
+
+    pub fn find(&self, name: &str) -> Option<(StudentId, &Student)> {
+        self.iter().find(|(_, s)| s.name == name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.students.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.students.is_empty()
+    }
+
+    /// The students in the order they enrolled.
+    pub fn iter(&self) -> impl Iterator<Item = (StudentId, &Student)> {
+        self.students.iter().map(|(id, s)| (*id, s))
+    }
+}
+
+#[test]
+fn test_roster_refuses_duplicates() {
+    let student = |name: &str, level| Student {
+        name: name.to_string(),
+        level,
+        remote: false,
+    };
+    let mut roster = Roster::new();
+    let ada = roster.enroll(student("Ada", 2)).unwrap();
+    let alan = roster.enroll(student("Alan", 1)).unwrap();
+    assert_eq!(
+        roster.enroll(student("Ada", 3)).unwrap_err(),
+        RosterError::AlreadyEnrolled(ada)
+    );
+    assert_eq!(roster.get(ada).unwrap().level, 2);
+    assert_eq!(roster.find("Alan").unwrap().0, alan);
+    assert!(roster.find("Grace").is_none());
+
+    assert_eq!(roster.drop("Ada").unwrap().level, 2);
+    assert!(roster.drop("Ada").is_none());
+    assert!(roster.get(ada).is_none());
+    // Once dropped, the name is free again, but the ID isn't reused
+    let again = roster.enroll(student("Ada", 3)).unwrap();
+    assert_ne!(again, ada);
+    assert_eq!(roster.len(), 2);
+    assert_eq!(again.to_string(), "S0003");
+}
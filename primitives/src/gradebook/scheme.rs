@@ -0,0 +1,141 @@
+use super::roster::StudentId;
+use super::transcript::Transcript;
+use super::{Gradebook, Grades, LetterGrade};
+
+// Not every mark counts the same. A grading scheme says which kind of work each of the four marks was for, and how
+// much each kind counts: the GPA is the weighted mean of the per-category averages. A gradebook grades by its scheme
+// everywhere it works out a GPA, in rankings and percentiles as well as transcripts.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    Homework,
+    Exam,
+    Project,
+}
+
+const CATEGORIES: [Category; 3] = [Category::Homework, Category::Exam, Category::Project];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GradingScheme {
+    pub categories: [Category; 4], // What each mark in `Grades` was for, in order
+    pub homework: f32,
+    pub exam: f32,
+    pub project: f32,
+}
+
+/// All four marks are homework, so they count the same.
+impl Default for GradingScheme {
+    fn default() -> Self {
+        GradingScheme {
+            categories: [Category::Homework; 4],
+            homework: 1.0,
+            exam: 1.0,
+            project: 1.0,
+        }
+    }
+}
+
+impl GradingScheme {
+    pub fn weight(&self, category: Category) -> f32 {
+        match category {
+            Category::Homework => self.homework,
+            Category::Exam => self.exam,
+            Category::Project => self.project,
+        }
+    }
+
+    /// The weighted GPA for `grades`. Categories with no marks in them don't count, and if the ones left all weigh
+    /// nothing every mark counts the same.
+    pub fn gpa(&self, grades: &Grades) -> f32 {
+        let marks: [LetterGrade; 4] = [grades.0, grades.1, grades.2, grades.3];
+        let (mut total, mut weights) = (0.0, 0.0);
+        for category in CATEGORIES {
+            let points: Vec<f32> = marks
+                .iter()
+                .zip(self.categories)
+                .filter(|(_, c)| *c == category)
+                .map(|(g, _)| g.points())
+                .collect();
+            if points.is_empty() {
+                continue;
+            }
+            let average = points.iter().sum::<f32>() / points.len() as f32;
+            total += self.weight(category) * average;
+            weights += self.weight(category);
+        }
+        if weights > 0.0 {
+            total / weights
+        } else {
+            marks.iter().map(|g| g.points()).sum::<f32>() / 4.0
+        }
+    }
+}
+
+impl Gradebook {
+    /// A gradebook that works out GPAs by `scheme`.
+    pub fn with_scheme(scheme: GradingScheme) -> Gradebook {
+        Gradebook {
+            scheme,
+            ..Gradebook::default()
+        }
+    }
+
+    pub fn scheme(&self) -> &GradingScheme {
+        &self.scheme
+    }
+
+    /// Student `id`'s GPA under this gradebook's scheme.
+    pub fn gpa(&self, id: StudentId) -> Option<f32> {
+        self.grades(id).map(|grades| self.scheme.gpa(grades))
+    }
+
+    /// Student `id`'s transcript, with their GPA under this gradebook's scheme.
+    pub fn transcript(&self, id: StudentId) -> Option<Transcript<'_>> {
+        let student = self.roster().get(id)?;
+        Some(student.transcript(self.grades(id)?).with_gpa(self.gpa(id)?))
+    }
+}
+
+#[test]
+fn test_weights_change_the_gpa() {
+    use super::Student;
+    use LetterGrade::*;
+
+    let grades = Grades(A, A, C, B, 0.0);
+    assert_eq!(GradingScheme::default().gpa(&grades), 3.25);
+
+    // Two homeworks, an exam and a project, with the exam counting double
+    let scheme = GradingScheme {
+        categories: [
+            Category::Homework,
+            Category::Homework,
+            Category::Exam,
+            Category::Project,
+        ],
+        exam: 2.0,
+        ..GradingScheme::default()
+    };
+    assert_eq!(scheme.gpa(&grades), (4.0 + 2.0 * 2.0 + 3.0) / 4.0);
+    // Weighing nothing falls back on the plain mean
+    let nothing = GradingScheme {
+        homework: 0.0,
+        exam: 0.0,
+        project: 0.0,
+        ..scheme
+    };
+    assert_eq!(nothing.gpa(&grades), 3.25);
+
+    // The gradebook ranks by its own scheme
+    let mut book = Gradebook::with_scheme(scheme);
+    let student = |name: &str| Student::builder(name).build().unwrap();
+    let crammer = book.add(student("Ada"), Grades(C, C, A, A, 0.0)).unwrap();
+    let steady = book.add(student("Alan"), Grades(A, A, C, C, 0.0)).unwrap();
+    assert_eq!(book.rank()[0].id, crammer);
+    assert!(book.gpa(crammer) > book.gpa(steady));
+    assert_eq!(book.scheme().weight(Category::Exam), 2.0);
+    assert!(book
+        .transcript(steady)
+        .unwrap()
+        .to_string()
+        .contains("GPA 2.50"));
+}
@@ -0,0 +1,164 @@
+use std::fmt;
+
+use super::Gradebook;
+
+// Summary statistics over plain `f32` scores, so anything with numbers to summarise can use them, not just the
+// gradebook. An empty slice has no mean, median or spread, so those come back as `None` rather than NaN. The spread
+// is the population standard deviation: the scores are the whole class, not a sample of it.
+
+pub fn mean(scores: &[f32]) -> Option<f32> {
+    if scores.is_empty() {
+        return None;
+    }
+    Some(scores.iter().sum::<f32>() / scores.len() as f32)
+}
+
+/// The middle score, or halfway between the middle two when there's an even number of them.
+pub fn median(scores: &[f32]) -> Option<f32> {
+    if scores.is_empty() {
+        return None;
+    }
+    let mut sorted = scores.to_vec();
+    sorted.sort_by(f32::total_cmp);
+    let mid = sorted.len() / 2;
+    Some(if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    })
+}
+
+pub fn std_dev(scores: &[f32]) -> Option<f32> {
+    let mean = mean(scores)?;
+    let variance = scores.iter().map(|s| (s - mean).powi(2)).sum::<f32>() / scores.len() as f32;
+    Some(variance.sqrt())
+}
+
+/// How many scores fall in each bucket of equal width, from the one holding the lowest score to the one holding the
+/// highest. Each bucket takes in its lower bound but not its upper one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    start: f32,
+    width: f32,
+    counts: Vec<usize>,
+}
+
+/// The most buckets a histogram will have, however far apart the scores are.
+pub const MAX_BUCKETS: usize = 1000;
+
+impl Histogram {
+    /// Scores that aren't finite numbers (NaN or infinite) belong in no bucket and are left out. If the scores are
+    /// spread so far apart that buckets of `width` would number more than `MAX_BUCKETS`, the width is doubled until
+    /// they don't; `width()` gives the one used.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` isn't a positive, finite number.
+    pub fn new(scores: &[f32], width: f32) -> Histogram {
+        assert!(
+            width > 0.0 && width.is_finite(),
+            "buckets need a positive, finite width"
+        );
+        let scores: Vec<f32> = scores.iter().copied().filter(|s| s.is_finite()).collect();
+        let Some(low) = scores.iter().copied().reduce(f32::min) else {
+            return Histogram {
+                start: 0.0,
+                width,
+                counts: Vec::new(),
+            };
+        };
+        let high = scores.iter().copied().fold(low, f32::max);
+        // Bucket numbers are counted in f32, so a spread too wide for usize still compares correctly here
+        let mut width = width;
+        while (high / width).floor() - (low / width).floor() >= MAX_BUCKETS as f32 {
+            width *= 2.0;
+        }
+        let lowest = (low / width).floor();
+        let mut counts = vec![0; ((high / width).floor() - lowest) as usize + 1];
+        for score in scores {
+            counts[((score / width).floor() - lowest) as usize] += 1;
+        }
+        Histogram {
+            start: lowest * width,
+            width,
+            counts,
+        }
+    }
+
+    /// The width of every bucket: the one asked for, unless it had to be widened to stay within `MAX_BUCKETS`.
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    /// Each bucket's lower bound with the number of scores in it, lowest first. Buckets in between with no scores
+    /// are included, with a count of 0.
+    pub fn buckets(&self) -> impl Iterator<Item = (f32, usize)> + '_ {
+        self.counts
+            .iter()
+            .enumerate()
+            .map(|(i, &count)| (self.start + i as f32 * self.width, count))
+    }
+}
+
+/// One line per bucket, e.g. "80-90  ###".
+impl fmt::Display for Histogram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (low, count) in self.buckets() {
+            let range = format!("{}-{}", low, low + self.width);
+            writeln!(f, "{:<8}{}", range, "#".repeat(count))?;
+        }
+        Ok(())
+    }
+}
+
+impl Gradebook {
+    /// Every student's average score, in the order they enrolled, ready for the functions above.
+    pub fn averages(&self) -> Vec<f32> {
+        self.iter().map(|(_, _, grades)| grades.4).collect()
+    }
+}
+
+#[test]
+fn test_stats_over_scores() {
+    let scores = [72.0, 85.0, 91.0, 85.0, 60.0, 99.5];
+    assert_eq!(mean(&scores), Some(82.083336));
+    assert_eq!(median(&scores), Some(85.0));
+    assert_eq!(median(&[3.0, 1.0, 2.0]), Some(2.0));
+    assert_eq!(
+        std_dev(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]),
+        Some(2.0)
+    );
+    assert_eq!(mean(&[]), None);
+    assert_eq!(median(&[]), None);
+    assert_eq!(std_dev(&[]), None);
+
+    let histogram = Histogram::new(&scores, 10.0);
+    let buckets: Vec<_> = histogram.buckets().collect();
+    assert_eq!(buckets, [(60.0, 1), (70.0, 1), (80.0, 2), (90.0, 2)]);
+    assert_eq!(histogram.to_string().lines().nth(2), Some("80-90   ##"));
+    // A gap shows as an empty bucket, and a score on a boundary goes in the bucket above
+    let gappy = Histogram::new(&[50.0, 70.0], 10.0);
+    assert_eq!(
+        gappy.buckets().collect::<Vec<_>>(),
+        [(50.0, 1), (60.0, 0), (70.0, 1)]
+    );
+    assert_eq!(Histogram::new(&[], 5.0).buckets().count(), 0);
+}
+
+#[test]
+fn test_histogram_leaves_out_scores_that_arent_numbers() {
+    let histogram = Histogram::new(
+        &[f32::NAN, 72.0, f32::INFINITY, 78.0, f32::NEG_INFINITY],
+        10.0,
+    );
+    assert_eq!(histogram.buckets().collect::<Vec<_>>(), [(70.0, 2)]);
+    assert_eq!(Histogram::new(&[f32::NAN], 10.0).buckets().count(), 0);
+
+    // Scores as far apart as an f32 allows still make a bounded histogram, in wider buckets
+    let wide = Histogram::new(&[-f32::MAX, 0.0, f32::MAX], 1.0);
+    assert!(wide.buckets().count() <= MAX_BUCKETS);
+    assert_eq!(wide.buckets().map(|(_, count)| count).sum::<usize>(), 3);
+    assert!(wide.width() > 1.0);
+    assert_eq!(Histogram::new(&[0.0, 999.5], 1.0).width(), 1.0);
+    assert_eq!(Histogram::new(&[0.0, 1000.0], 1.0).width(), 2.0);
+}
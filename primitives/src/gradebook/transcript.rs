@@ -0,0 +1,80 @@
+use std::fmt;
+
+use super::{Grades, Student, COURSES};
+
+// A student's report card, laid out for printing: one line per course, then their GPA and recorded average.
+//
+//     Transcript for Srinath (level 1, remote)
+//       Rust basics   A
+//       Ownership     A
+//       Traits        A-
+//       Concurrency   A
+//       GPA 3.92, average 5
+
+/// Borrows a student and their grades to print them; see `Student::transcript`.
+pub struct Transcript<'a> {
+    student: &'a Student,
+    grades: &'a Grades,
+    gpa: f32,
+}
+
+impl Student {
+    /// A transcript with every mark counting the same towards the GPA. `Gradebook::transcript` uses the
+    /// gradebook's weights instead.
+    pub fn transcript<'a>(&'a self, grades: &'a Grades) -> Transcript<'a> {
+        Transcript {
+            student: self,
+            grades,
+            gpa: grades.gpa(),
+        }
+    }
+}
+
+impl Transcript<'_> {
+    pub(super) fn with_gpa(mut self, gpa: f32) -> Self {
+        self.gpa = gpa;
+        self
+    }
+}
+
+impl fmt::Display for Transcript<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let attendance = if self.student.remote {
+            "remote"
+        } else {
+            "in person"
+        };
+        writeln!(
+            f,
+            "Transcript for {} (level {}, {})",
+            self.student.name, self.student.level, attendance
+        )?;
+        let grades = [self.grades.0, self.grades.1, self.grades.2, self.grades.3];
+        let width = COURSES.iter().map(|c| c.len()).max().unwrap_or(0);
+        for (course, grade) in COURSES.iter().zip(grades) {
+            writeln!(f, "  {:<width$}   {}", course, grade, width = width)?;
+        }
+        writeln!(f, "  GPA {:.2}, average {}", self.gpa, self.grades.4)
+    }
+}
+
+#[test]
+fn test_transcript_lists_every_course() {
+    use super::LetterGrade::*;
+
+    let student = Student {
+        name: String::from("Ferris"),
+        level: 3,
+        remote: false,
+    };
+    let grades = Grades(BPlus, AMinus, B, BPlus, 3.4);
+    assert_eq!(
+        student.transcript(&grades).to_string(),
+        "Transcript for Ferris (level 3, in person)\n\
+         \x20 Rust basics   B+\n\
+         \x20 Ownership     A-\n\
+         \x20 Traits        B\n\
+         \x20 Concurrency   B+\n\
+         \x20 GPA 3.33, average 3.4\n"
+    );
+}
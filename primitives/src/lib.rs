@@ -0,0 +1,12 @@
+// Everything the samples are made of, as a library: the process simulator, the car factory, the gradebook and the
+// event pipeline. The binary in main.rs walks through them, and the ownership examples under `examples/` build their
+// process trees out of the same Proc. Their errors all convert into the crate-wide `Error`.
+pub mod cars;
+mod error;
+pub mod events;
+pub mod gradebook;
+pub mod log;
+pub mod os;
+
+pub use error::Error;
+pub use os::{Proc, State};
@@ -0,0 +1,367 @@
+// A small logger in the spirit of the `log` crate. It's written here, like the JSON, because what the demos and tests
+// want from it (sections, JSON lines, records captured as values) is a page of code, where `log` would also need a
+// logger crate behind it; the only dependencies are the optional ones the async runtime needs. Every record carries a
+// level and a target, the module it was logged from, and goes through the `error!` .. `trace!` macros:
+//
+//     rust_test::debug!("admitted {} processes", n);
+//
+// Records at or below the maximum level are written out: Info as plain lines on stdout, since that is what the
+// demos print, and everything else on stderr with its level and target in front. Tests call `capture` instead to
+// get the records back as values.
+//
+// For scripts there is `Format::Json`, which writes every record to stdout as one JSON object per line, tagged with
+// the section of the program it came from (see `set_section`), so two runs can be compared with `diff` or `jq`.
+use std::cell::RefCell;
+use std::fmt;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::Mutex;
+
+/// How important a record is, most important first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    Error = 1,
+    Warn = 2,
+    Info = 3,
+    Debug = 4,
+    Trace = 5,
+}
+
+const LEVELS: [Level; 5] = [
+    Level::Error,
+    Level::Warn,
+    Level::Info,
+    Level::Debug,
+    Level::Trace,
+];
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        };
+        // Padded only when asked to, so levels can be lined up
+        f.pad(name)
+    }
+}
+
+/// `Level::from_str` was given something other than a level name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseLevelError(pub String);
+
+impl fmt::Display for ParseLevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown log level {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseLevelError {}
+
+// Level names in any case, as in `RUST_LOG=debug`.
+impl FromStr for Level {
+    type Err = ParseLevelError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        LEVELS
+            .into_iter()
+            .find(|level| level.to_string().eq_ignore_ascii_case(s))
+            .ok_or_else(|| ParseLevelError(s.to_string()))
+    }
+}
+
+/// One logged message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub level: Level,
+    pub target: &'static str, // Module path of the call, e.g. "rust_test::os::sim"
+    pub section: Option<&'static str>, // As set by `set_section` when the record was logged
+    pub message: String,
+}
+
+impl Record {
+    /// The record as a single line of JSON, e.g.
+    /// `{"section":"cars","level":"INFO","target":"rust_test","message":"Built 3 cars"}`. Keys always come in this
+    /// order and a record without a section has `"section":null`, so equal records make equal lines.
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("{\"section\":");
+        match self.section {
+            Some(section) => write_json_str(&mut out, section),
+            None => out.push_str("null"),
+        }
+        write!(out, ",\"level\":\"{}\",\"target\":", self.level).unwrap();
+        write_json_str(&mut out, self.target);
+        out.push_str(",\"message\":");
+        write_json_str(&mut out, &self.message);
+        out.push('}');
+        out
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:<5} {}: {}", self.level, self.target, self.message)
+    }
+}
+
+// The same escaping as `os::Json`, which is only built with the serde feature.
+fn write_json_str(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// How records are written out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    // Info as bare lines on stdout, the rest with level and target on stderr
+    Text,
+    // Every record as a line of JSON on stdout
+    Json,
+}
+
+/// `Format::from_str` was given something other than `text` or `json`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseFormatError(pub String);
+
+impl fmt::Display for ParseFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown output format {:?} (expected text or json)",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseFormatError {}
+
+impl FromStr for Format {
+    type Err = ParseFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            _ => Err(ParseFormatError(s.to_string())),
+        }
+    }
+}
+
+// 0 turns everything off
+static MAX_LEVEL: AtomicU8 = AtomicU8::new(Level::Info as u8);
+static JSON: AtomicBool = AtomicBool::new(false);
+static SECTION: Mutex<Option<&'static str>> = Mutex::new(None);
+
+thread_local! {
+    static CAPTURED: RefCell<Option<Vec<Record>>> = const { RefCell::new(None) };
+}
+
+/// Writes out records up to and including `level` from now on. Info is the default.
+pub fn set_max_level(level: Level) {
+    MAX_LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// Writes records out as `format` from now on. Text is the default.
+pub fn set_format(format: Format) {
+    JSON.store(format == Format::Json, Ordering::Relaxed);
+}
+
+pub fn format() -> Format {
+    if JSON.load(Ordering::Relaxed) {
+        Format::Json
+    } else {
+        Format::Text
+    }
+}
+
+/// Tags every record from now on, on any thread, with `section`, e.g. the part of a demo being run.
+pub fn set_section(section: &'static str) {
+    *SECTION.lock().unwrap_or_else(|err| err.into_inner()) = Some(section);
+}
+
+/// Writes out nothing at all, not even errors.
+pub fn disable() {
+    MAX_LEVEL.store(0, Ordering::Relaxed);
+}
+
+/// The most detailed level written out, `None` once `disable`d.
+pub fn max_level() -> Option<Level> {
+    let max = MAX_LEVEL.load(Ordering::Relaxed);
+    LEVELS.into_iter().find(|&level| level as u8 == max)
+}
+
+/// Whether a record at `level` would go anywhere, to skip work that only feeds a log message.
+pub fn enabled(level: Level) -> bool {
+    level as u8 <= MAX_LEVEL.load(Ordering::Relaxed) || CAPTURED.with(|c| c.borrow().is_some())
+}
+
+/// Runs `f` and returns what it logged, at every level, instead of writing it out. Only records from this thread
+/// are captured, so tests running side by side don't see each other's.
+pub fn capture<T>(f: impl FnOnce() -> T) -> (T, Vec<Record>) {
+    let outer = CAPTURED.with(|c| c.borrow_mut().replace(Vec::new()));
+    let value = f();
+    let records = CAPTURED.with(|c| std::mem::replace(&mut *c.borrow_mut(), outer));
+    (value, records.unwrap_or_default())
+}
+
+/// What the macros expand to. A trailing newline in the message is dropped, as every record is a line of its own.
+#[doc(hidden)]
+pub fn log(level: Level, target: &'static str, args: fmt::Arguments) {
+    if !enabled(level) {
+        return;
+    }
+    let mut message = args.to_string();
+    if message.ends_with('\n') {
+        message.pop();
+    }
+    let record = Record {
+        level,
+        target,
+        section: *SECTION.lock().unwrap_or_else(|err| err.into_inner()),
+        message,
+    };
+
+    let record = CAPTURED.with(|c| match c.borrow_mut().as_mut() {
+        Some(records) => {
+            records.push(record);
+            None
+        }
+        None => Some(record),
+    });
+    // Output that can't be written, such as a closed pipe, is dropped rather than panicking like `println!`
+    if let Some(record) = record {
+        if format() == Format::Json {
+            let _ = writeln!(io::stdout().lock(), "{}", record.to_json());
+        } else if record.level == Level::Info {
+            let _ = writeln!(io::stdout().lock(), "{}", record.message);
+        } else {
+            let _ = writeln!(io::stderr().lock(), "{}", record);
+        }
+    }
+}
+
+/// Logs at `Level::Error`, with `format!` arguments.
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)+) => {
+        $crate::log::log($crate::log::Level::Error, module_path!(), format_args!($($arg)+))
+    };
+}
+
+/// Logs at `Level::Warn`, with `format!` arguments.
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)+) => {
+        $crate::log::log($crate::log::Level::Warn, module_path!(), format_args!($($arg)+))
+    };
+}
+
+/// Logs at `Level::Info`, with `format!` arguments.
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)+) => {
+        $crate::log::log($crate::log::Level::Info, module_path!(), format_args!($($arg)+))
+    };
+}
+
+/// Logs at `Level::Debug`, with `format!` arguments.
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)+) => {
+        $crate::log::log($crate::log::Level::Debug, module_path!(), format_args!($($arg)+))
+    };
+}
+
+/// Logs at `Level::Trace`, with `format!` arguments.
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)+) => {
+        $crate::log::log($crate::log::Level::Trace, module_path!(), format_args!($($arg)+))
+    };
+}
+
+#[test]
+fn test_capture_keeps_level_and_target() {
+    let ((), records) = capture(|| {
+        crate::warn!("disk {} is {}% full", 0, 95);
+        crate::trace!("multi-line\n");
+    });
+    assert_eq!(
+        records,
+        [
+            Record {
+                level: Level::Warn,
+                target: "rust_test::log",
+                section: None,
+                message: "disk 0 is 95% full".to_string(),
+            },
+            Record {
+                level: Level::Trace,
+                target: "rust_test::log",
+                section: None,
+                message: "multi-line".to_string(),
+            },
+        ]
+    );
+    assert_eq!(
+        records[0].to_string(),
+        "WARN  rust_test::log: disk 0 is 95% full"
+    );
+}
+
+#[test]
+fn test_capture_nests() {
+    let (inner, outer) = capture(|| {
+        crate::info!("outer");
+        let ((), inner) = capture(|| crate::info!("inner"));
+        inner
+    });
+    assert_eq!(inner.len(), 1);
+    assert_eq!(outer.len(), 1);
+    assert_eq!(outer[0].message, "outer");
+}
+
+#[test]
+fn test_parse_level() {
+    assert_eq!("debug".parse(), Ok(Level::Debug));
+    assert_eq!("WARN".parse(), Ok(Level::Warn));
+    assert!("loud".parse::<Level>().is_err());
+    assert!(Level::Error < Level::Trace);
+}
+
+#[test]
+fn test_record_as_json() {
+    let record = Record {
+        level: Level::Info,
+        target: "rust_test",
+        section: Some("cars"),
+        message: "a \"quoted\"\tword\non two lines".to_string(),
+    };
+    assert_eq!(
+        record.to_json(),
+        r#"{"section":"cars","level":"INFO","target":"rust_test","message":"a \"quoted\"\tword\non two lines"}"#
+    );
+    let record = Record {
+        section: None,
+        ..record
+    };
+    assert!(record.to_json().starts_with(r#"{"section":null,"#));
+    assert_eq!("JSON".parse(), Ok(Format::Json));
+    assert!("yaml".parse::<Format>().is_err());
+}
@@ -1,7 +1,32 @@
 use std::any::type_name_of_val;
 use std::collections::{BTreeSet, HashSet};
-
-mod os;
+use std::time::Duration;
+
+use rust_test::{cars, error, events, gradebook, info, log, os};
+
+use cars::assembly::Chassis;
+use cars::orders::{Order, OrderQueue};
+use cars::pricing::{Depreciation, PricingModel};
+use cars::rental::RentalDesk;
+use cars::service::{Date, ServiceRecord};
+use cars::sort::SortKey;
+use cars::{car_factory, Age, Car, Fleet, Powertrain, Transmission};
+use events::control::{ControlError, ProcessControl};
+use events::dispatch::EventDispatcher;
+use events::gesture::GestureDetector;
+use events::keys::Modifiers;
+use events::log::EventLog;
+use events::queue::EventQueue;
+use events::rate::{debounce, throttle, Debounce, SystemClock};
+use events::region::{Rect, Regions};
+use events::router::{EventRouter, Matcher};
+use events::{EventBus, KeyPress, MouseClick, WebEvent};
+use gradebook::attendance::Presence;
+use gradebook::curve::Curve;
+use gradebook::roster::Roster;
+use gradebook::scheme::{Category, GradingScheme};
+use gradebook::stats;
+use gradebook::{Gradebook, Grades, LetterGrade, Student};
 
 fn sum(x: u128, y: u128) -> u128 {
     x + y
@@ -30,35 +55,9 @@ fn count_total_bytes(byte_slice: &[u8]) -> usize {
     cnt
 }
 
-// classic struct
-struct Student {
-    name: String,
-    level: u8,
-    remote: bool,
-}
-
-// tuple struct
-struct Grades(char, char, char, char, f32);
-
 // Unit struct
 struct Unit;
 
-#[derive(Debug)]
-struct KeyPress(String, char);
-
-#[derive(Debug)]
-struct MouseClick {
-    x: i64,
-    y: i64,
-}
-
-#[derive(Debug)]
-enum WebEvent {
-    WELoad(bool),
-    WEClick(MouseClick),
-    WEPress(KeyPress),
-}
-
 fn divide_by_7(num: i32) -> i32 {
     if num == 0 {
         return 0;
@@ -68,95 +67,167 @@ fn divide_by_7(num: i32) -> i32 {
 }
 
 fn goodbye(str: &str) -> bool {
-    println!("\n{}", str);
+    info!("\n{}", str);
     return true;
 }
 
-// Declare Car struct to describe vehicle with four named fields
-struct Car {
-    color: String,
-    transmission: Transmission,
-    convertible: bool,
-    mileage: u32,
+fn conditional_print(num: usize) {
+    if num > 10 {
+        info!("{} is greater than 10.", num);
+    } else if num % 2 == 0 {
+        info!("{} is even.", num);
+    } else {
+        info!("{} is odd.", num);
+    }
 }
 
-#[derive(PartialEq, Debug)]
-// Declare enum for Car transmission type
-enum Transmission {
-    // Unit struct without fields or datatypes
-    Manual,
-    SemiAuto,
-    Automatic,
+// Presses `shortcut` and then sends `signal` straight to the selected process. The key, the signal name and the
+// control each fail with their own module's error, and `?` turns every one of them into a `rust_test::Error`.
+fn signal_by_shortcut(
+    control: &ProcessControl,
+    shortcut: &str,
+    signal: &str,
+    table: &mut os::ProcTable,
+) -> Result<Vec<os::Signal>, rust_test::Error> {
+    let key: KeyPress = shortcut.parse()?;
+    let signal: os::Signal = signal.parse()?;
+    let mut delivered = control.handle(&WebEvent::WEPress(key), table)?;
+    let pid = control.selected().ok_or(ControlError::NoneSelected)?;
+    let proc = table.get_mut(pid).ok_or(ControlError::NoProcess(pid))?;
+    proc.send_signal(signal);
+    delivered.extend(proc.deliver_signals());
+    Ok(delivered)
 }
 
-// Build a "Car" by using values from the input arguments
-// - Color of car (String)
-// - Transmission type (enum value)
-// - Convertible (boolean, true if car is a convertible)
-fn car_factory(color: String, transmission: Transmission, convertible: bool) -> Car {
-    // Use the values of the input arguments
-    // All new cars always have zero mileage
-    let car: Car = Car {
-        color: color,
-        transmission: transmission,
-        convertible: convertible,
-        mileage: 0,
+// The simulation simulation.toml describes, set up from `pids`. Exits like a bad config file does if it can't be.
+fn configured_simulation(
+    config: &os::SimConfig,
+    pids: &os::PidAllocator,
+) -> os::Simulation<Box<dyn os::Scheduler>> {
+    match config.build(pids) {
+        Ok(sim) => sim,
+        Err(err) => {
+            error!("simulation.toml: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn main() {
+    // Demo output is logged at info level: --quiet leaves only errors, --verbose adds the library's debug records.
+    // --json (or RUST_TEST_OUTPUT=json) writes every record as a line of JSON, for scripts to diff between runs
+    match std::env::var("RUST_TEST_OUTPUT").map(|format| format.parse()) {
+        Ok(Ok(format)) => log::set_format(format),
+        Ok(Err(err)) => {
+            error!("RUST_TEST_OUTPUT: {}", err);
+            std::process::exit(2);
+        }
+        Err(_) => {}
+    }
+    let mut repl_mode = false;
+    let mut dashboard_mode = false;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "repl" => repl_mode = true,
+            "dashboard" => dashboard_mode = true,
+            "-q" | "--quiet" => log::set_max_level(log::Level::Error),
+            "-v" | "--verbose" => log::set_max_level(log::Level::Debug),
+            "--json" => log::set_format(log::Format::Json),
+            other => {
+                error!(
+                    "unknown argument {:?} (expected repl, dashboard, --quiet, --verbose or --json)",
+                    other
+                );
+                std::process::exit(2);
+            }
+        }
+    }
+
+    // The simulator's setup comes from simulation.toml when there is one, and is the default otherwise
+    let config = match os::SimConfig::load("simulation.toml") {
+        Ok(config) => config,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => os::SimConfig::default(),
+        Err(err) => {
+            error!("simulation.toml: {}", err);
+            std::process::exit(1);
+        }
     };
 
-    return car;
-}
+    // `cargo run -- repl` drops into an interactive shell over the process simulator instead of the demos
+    if repl_mode {
+        let pids = os::PidAllocator::default();
+        let sim = configured_simulation(&config, &pids);
+        let mut repl = os::repl::Repl::with_simulation(pids, sim);
+        if let Err(err) = repl.run(std::io::stdin().lock(), std::io::stdout()) {
+            error!("repl: {}", err);
+        }
+        return;
+    }
 
-fn conditional_print(num: usize) {
-    if num > 10 {
-        println!("{} is greater than 10.", num);
-    } else if num % 2 == 0 {
-        println!("{} is even.", num);
-    } else {
-        print!("{} is odd.", num);
+    // `cargo run --features dashboard -- dashboard` watches the configured simulation run, a frame per tick
+    if dashboard_mode {
+        #[cfg(feature = "dashboard")]
+        {
+            let pids = os::PidAllocator::default();
+            let mut sim = configured_simulation(&config, &pids);
+            let watched = os::dashboard::Dashboard::new(std::io::stdout()).watch(
+                &mut sim,
+                40,
+                std::time::Duration::from_millis(250),
+            );
+            if let Err(err) = watched {
+                error!("dashboard: {}", err);
+            }
+            return;
+        }
+        #[cfg(not(feature = "dashboard"))]
+        {
+            error!("dashboard: built without the dashboard feature (cargo run --features dashboard -- dashboard)");
+            std::process::exit(2);
+        }
     }
-}
 
-fn main() {
-    println!("Hello {}, Welcome to Rust!", "Srinath");
+    log::set_section("basics");
+    info!("Hello {}, Welcome to Rust!", "Srinath");
 
     let a;
     a = 1;
-    println!("Value of a is {}", a);
+    info!("Value of a is {}", a);
 
     // Throw error due to variable immutability
     // a = 2;
-    // println!("Value of a is {}", a);
+    // info!("Value of a is {}", a);
 
     // Variable shadowing -  We don't define the variable as mutable because each let operation
     // creates a new variable named `n` while shadowing the previous variable binding.
     let n = 2;
-    println!("Value of n is {}", n);
+    info!("Value of n is {}", n);
 
     let n = n + 10;
-    println!("Value of n is {}", n);
+    info!("Value of n is {}", n);
 
     let n = n * 10;
-    println!("Value of n is {}", n);
+    info!("Value of n is {}", n);
 
     // Addition
-    println!("1 + 2 = {}", 1i32 + 2);
+    info!("1 + 2 = {}", 1i32 + 2);
 
     // Subtraction
-    println!("1 - 2 = {}", 1i32 - 2);
+    info!("1 - 2 = {}", 1i32 - 2);
     // ^ Try changing `1i32` to `1u32` to see why the type is important
 
     // Integer Division
-    println!("9 / 2 = {}", 9u32 / 2);
+    info!("9 / 2 = {}", 9u32 / 2);
 
     // Float Division
-    println!("9 / 2 = {}", 9.0 / 2.0);
+    info!("9 / 2 = {}", 9.0 / 2.0);
 
     // Multiplication
-    println!("3 * 6 = {}", 3 * 6);
+    info!("3 * 6 = {}", 3 * 6);
 
     // Declare variable to store result of "greater than" test, Is 1 > 4? -- false
     let is_bigger = 1 > 4;
-    println!("Is 1 > 4? {}", is_bigger);
+    info!("Is 1 > 4? {}", is_bigger);
 
     let a_char = 'A';
 
@@ -164,8 +235,8 @@ fn main() {
     let code_point = a_char as u32;
 
     // Print the original char and its Unicode code point
-    println!("Character: {}", a_char);
-    println!("Unicode Code Point: {}", code_point);
+    info!("Character: {}", a_char);
+    info!("Unicode Code Point: {}", code_point);
 
     // Define a char containing a Unicode emoji (🚀)
     let rocket_emoji = '🚀';
@@ -174,33 +245,199 @@ fn main() {
     let emoji_code_point = rocket_emoji as u32;
 
     // Print the original char (emoji) and its Unicode code point
-    println!("Emoji: {}", rocket_emoji);
-    println!("Unicode Code Point (Emoji): {}", emoji_code_point);
+    info!("Emoji: {}", rocket_emoji);
+    info!("Unicode Code Point (Emoji): {}", emoji_code_point);
+
+    log::set_section("gradebook");
+    // Students join the class through the roster, which gives each one an ID
+    let mut roster = Roster::new();
+    // Every student needs a name and a level the school has; the builder checks both
+    let srinath = Student::builder("Srinath").level(1).remote(true).build();
+    let student_1 = roster
+        .enroll(srinath.expect("a valid student"))
+        .expect("the roster starts out empty");
+    let duplicate = Student::builder("Srinath").level(2).build();
+    if let Err(e) = roster.enroll(duplicate.expect("a valid student")) {
+        info!("Can't enroll Srinath twice: {}", e);
+    }
+    for bad in [
+        Student::builder("").build(),
+        Student::builder("Ferris").level(40).build(),
+    ] {
+        if let Err(e) = bad {
+            info!("Can't make that student: {}", e);
+        }
+    }
+    let ferris = Student::builder("Ferris").level(3).build();
+    roster
+        .enroll(ferris.expect("a valid student"))
+        .expect("a new name");
+    // Grades are checked as they're read, so a 'Z' can't end up on a report card
+    let a = LetterGrade::try_from('A').unwrap();
+    let mark_1 = Grades(a, a, "A-".parse().unwrap(), a, 5.0);
+    if let Err(e) = LetterGrade::try_from('Z') {
+        info!("{}", e);
+    }
 
-    let student_1 = Student {
-        name: String::from("Srinath"),
-        remote: true,
-        level: 1,
-    };
-    let mark_1 = Grades('A', 'A', 'A', 'A', 5.0);
-
-    println!(
-        "{}, Level: {}. Remote: {}. Grades: {}, {}, {}, {}, Average: {}",
-        student_1.name,
-        student_1.level,
-        student_1.remote,
-        mark_1.0,
-        mark_1.1,
-        mark_1.2,
-        mark_1.3,
-        mark_1.4
-    );
+    if let Some(student) = roster.get(student_1) {
+        info!("{}: {}", student_1, student.transcript(&mark_1));
+    }
+    if let Some((id, ferris)) = roster.find("Ferris") {
+        info!("Found {} as {}", ferris.name(), id);
+    }
+    if let Some(dropped) = roster.drop("Ferris") {
+        info!("{} dropped the class", dropped.name());
+    }
+    info!("{} enrolled, empty: {}", roster.len(), roster.is_empty());
+    for (id, student) in roster.iter() {
+        info!("Roster: {} {}", id, student.name());
+    }
 
-    let click = MouseClick { x: 100, y: 50 };
-    println!("Mouse click location: {}, {}", click.x, click.y);
+    // A gradebook keeps each student's marks, and they go in and out of spreadsheets as CSV
+    let mut book = Gradebook::new();
+    if let Some(student) = roster.get(student_1) {
+        let id = book
+            .add(student.clone(), mark_1)
+            .expect("an empty gradebook");
+        if let Some(grades) = book.grades(id) {
+            info!(
+                "{} has an average of {}",
+                book.roster().get(id).unwrap().name(),
+                grades.4
+            );
+        }
+    }
+    let sheet = "name,level,remote,grade_1,grade_2,grade_3,grade_4,average\n\
+                 Ferris,3,false,B+,A-,B,B+,3.4\n\
+                 Corro,2,true,A,Q,A,A,4.0\n";
+    match book.import_csv(sheet.as_bytes()) {
+        Ok(import) => {
+            info!("Imported {} students", import.added.len());
+            for e in &import.errors {
+                info!("Skipped {}", e);
+            }
+        }
+        Err(e) => info!("Couldn't read the spreadsheet: {}", e),
+    }
+    for ranked in book.rank() {
+        let name = book.roster().get(ranked.id).unwrap().name();
+        info!(
+            "#{} {} with a GPA of {:.2}, percentile {:.0}",
+            ranked.rank,
+            name,
+            ranked.gpa,
+            book.percentile(ranked.id).unwrap_or(0.0)
+        );
+    }
+    // Weighting the exam (the third mark) double changes who comes out on top
+    let scheme = GradingScheme {
+        categories: [
+            Category::Homework,
+            Category::Homework,
+            Category::Exam,
+            Category::Project,
+        ],
+        exam: 2.0,
+        ..GradingScheme::default()
+    };
+    let mut weighted = Gradebook::with_scheme(scheme);
+    for (_, student, grades) in book.iter() {
+        weighted
+            .add(student.clone(), grades.clone())
+            .expect("names are unique in the first book");
+    }
+    if let Some(top) = weighted.rank().first() {
+        if let Some(transcript) = weighted.transcript(top.id) {
+            info!("Top with the exam counting double: {}", transcript);
+        }
+    }
+    info!("Exams weigh {}", weighted.scheme().weight(Category::Exam));
+    // Taking the register for a couple of classes
+    let ids: Vec<_> = book.iter().map(|(id, _, _)| id).collect();
+    for presences in [
+        [Presence::Present, Presence::Remote],
+        [Presence::Absent, Presence::Present],
+    ] {
+        let session = book.hold_session();
+        for (&id, presence) in ids.iter().zip(presences) {
+            if let Err(err) = book.mark(session, id, presence) {
+                info!("Couldn't take the register: {}", err);
+            }
+            if let Some(marked) = book.attendance().get(session, id) {
+                info!("{}: {} was {:?}", session, id, marked);
+            }
+        }
+    }
+    for (id, rate) in book.attendance_below(0.75) {
+        info!(
+            "{} attended only {:.0}% of {} sessions",
+            id,
+            rate * 100.0,
+            book.attendance().sessions()
+        );
+    }
+    // How the class did overall
+    let averages = book.averages();
+    if let (Some(mean), Some(median), Some(spread)) = (
+        stats::mean(&averages),
+        stats::median(&averages),
+        stats::std_dev(&averages),
+    ) {
+        info!(
+            "Averages: mean {:.1}, median {:.1}, std dev {:.1}",
+            mean, median, spread
+        );
+    }
+    info!("{}", stats::Histogram::new(&averages, 1.0));
+    // Curving makes a new gradebook, so the marks as given are still there
+    for curve in [Curve::Flat(0.5), Curve::Scale(1.1), Curve::Normalize(4.5)] {
+        info!(
+            "{:?} gives averages {:?}, from {:?}",
+            curve,
+            book.apply_curve(curve).averages(),
+            averages
+        );
+    }
+    // Or saved whole, weights and all, and read back later
+    #[cfg(feature = "serde")]
+    {
+        let path = std::env::temp_dir().join(format!("gradebook-{}.json", std::process::id()));
+        match weighted
+            .save_json(&path)
+            .and_then(|()| Gradebook::load_json(&path))
+        {
+            Ok(reloaded) => info!(
+                "reloaded {} students from {}",
+                reloaded.roster().len(),
+                path.display()
+            ),
+            Err(err) => info!("could not save or load {}: {}", path.display(), err),
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+    let mut csv = Vec::new();
+    if book.export_csv(&mut csv).is_ok() {
+        info!("{}", String::from_utf8_lossy(&csv));
+    }
 
-    let keys = KeyPress(String::from("Ctrl+"), 'N');
-    println!("\n Key press: {},{}", keys.0, keys.1);
+    log::set_section("events");
+    let click = MouseClick {
+        x: 100,
+        y: 50,
+        at: Duration::from_millis(1200),
+    };
+    info!("Mouse click location: {}, {}", click.x, click.y);
+
+    let keys = KeyPress(Modifiers::CTRL, 'N');
+    info!("\n Key press: {},{}", keys.0, keys.1);
+    // Shortcuts can be read from how menus write them, and their modifiers matched on directly
+    match "Ctrl+Shift+N".parse::<KeyPress>() {
+        Ok(KeyPress(modifiers, key)) if modifiers == Modifiers::CTRL | Modifiers::SHIFT => {
+            info!("Ctrl+Shift with {} opens a private window", key)
+        }
+        Ok(other) => info!("{} isn't bound to anything", other),
+        Err(err) => info!("Couldn't read the shortcut: {}", err),
+    }
 
     // Instantiate the WebEvent enum variants
 
@@ -213,37 +450,500 @@ fn main() {
     // Set the WEKeys variant to use the data in the keys tuple
     let we_key = WebEvent::WEPress(keys);
 
-    println!(
-        "\nWeb Event enum struct: \n\n {:#?} \n\n {:#?}  \n\n {:#?}",
-        we_load, we_click, we_key
+    info!("\nWeb events:\n {}\n {}\n {}", we_load, we_click, we_key);
+
+    // A dispatcher hands each kind of web event to its own handlers
+    let mut dispatcher = EventDispatcher::new();
+    dispatcher.on_load(|loaded| info!("Handled a page load: {}", loaded));
+    dispatcher.on_click(|click| info!("Handled a click at {}, {}", click.x, click.y));
+    dispatcher.on_keypress(|keys| info!("Handled {}", keys));
+    dispatcher.on_keyrelease(|keys| info!("Handled releasing {}", keys));
+    dispatcher.on_scroll(|(dx, dy)| info!("Handled a scroll by {}, {}", dx, dy));
+    dispatcher.on_resize(|(width, height)| info!("Handled a resize to {}x{}", width, height));
+    let we_release = WebEvent::WEKeyRelease(keys);
+    let we_scroll = WebEvent::WEScroll { dx: 0, dy: 120 };
+    let we_resize = WebEvent::WEResize {
+        width: 1280,
+        height: 720,
+    };
+    for event in [
+        &we_load,
+        &we_click,
+        &we_key,
+        &we_release,
+        &we_scroll,
+        &we_resize,
+    ] {
+        dispatcher.dispatch(event);
+    }
+    // Clicks can be put down to the named area of the page they landed on
+    let sidebar = dispatcher.regions_mut().add(
+        "sidebar",
+        Rect {
+            x: 0,
+            y: 0,
+            width: 120,
+            height: 600,
+        },
+    );
+    dispatcher.on_region_click(sidebar, |click| {
+        info!("Handled a sidebar click at {}", click)
+    });
+    if let Some(id) = dispatcher.regions().hit_test(&click) {
+        if let Some(region) = dispatcher.regions().get(id) {
+            info!("{} landed in the {}", we_click, region.name);
+        }
+    }
+    dispatcher.dispatch(&we_click);
+    let mut panels = Regions::new();
+    let popup = panels.add(
+        "popup",
+        Rect {
+            x: 0,
+            y: 0,
+            width: 10,
+            height: 10,
+        },
     );
+    if panels.remove(popup).is_some() && panels.hit_test(&click).is_none() {
+        info!("Nothing left to click on once the popup closed");
+    }
+
+    // Raw mouse events add up to gestures: a second click straight after the first is a double click, and pressing,
+    // moving and letting go somewhere else is a drag
+    let mut gestures = GestureDetector::new(Duration::from_millis(400), 4);
+    let second_click = MouseClick {
+        at: click.at + Duration::from_millis(250),
+        ..click
+    };
+    let drag = [
+        WebEvent::WEMouseDown(click),
+        WebEvent::WEMouseMove(MouseClick { x: 150, ..click }),
+        WebEvent::WEMouseUp(MouseClick { x: 200, ..click }),
+    ];
+    dispatcher.on_mouse_down(|at| info!("Handled pressing at {}, {}", at.x, at.y));
+    dispatcher.on_mouse_move(|at| info!("Handled moving to {}, {}", at.x, at.y));
+    dispatcher.on_mouse_up(|at| info!("Handled letting go at {}, {}", at.x, at.y));
+    for event in [we_click.clone(), WebEvent::WEClick(second_click)]
+        .iter()
+        .chain(&drag)
+    {
+        dispatcher.dispatch(event);
+        if let Some(gesture) = gestures.feed(event) {
+            info!("Gesture: {}", gesture);
+        }
+    }
+    let mut default_gestures = GestureDetector::default();
+    let slow_second = WebEvent::WEClick(MouseClick {
+        at: click.at + Duration::from_secs(2),
+        ..click
+    });
+    for event in [&we_click, &slow_second] {
+        if default_gestures.feed(event).is_none() {
+            info!("No gesture yet after {}", event);
+        }
+    }
+
+    // A spun scroll wheel sends the same event over and over: a throttle lets the first through, a debounce the last
+    let mut throttled = throttle(Duration::from_millis(100));
+    let mut debounced = Debounce::new(Duration::from_millis(100), SystemClock::default());
+    for _ in 0..3 {
+        if let Some(event) = throttled.push(we_scroll.clone()) {
+            info!("Throttled to {}", event);
+        }
+        if let Some(event) = debounced.push(we_scroll.clone()) {
+            info!("Debounced to {}", event);
+        }
+    }
+    if debounced.poll().is_none() {
+        if let Some(event) = debounced.flush() {
+            info!("Still settling, but let {} through anyway", event);
+        }
+    }
+    let mut keys_debounced = debounce(Duration::ZERO);
+    keys_debounced.push(we_key.clone());
+    if let Some(event) = keys_debounced.poll() {
+        info!("Debounced to {}", event);
+    }
+
+    // A router sends each event to one handler: the first route, by priority, that wants it
+    let mut router = EventRouter::new();
+    let close_button = Rect {
+        x: 90,
+        y: 40,
+        width: 20,
+        height: 20,
+    };
+    router.route(
+        "close button",
+        10,
+        Matcher::ClickWithin(close_button),
+        |_| info!("Routed: closing the window"),
+    );
+    router.route(
+        "new window",
+        0,
+        Matcher::Keys(vec![KeyPress(Modifiers::CTRL, 'N')]),
+        |_| info!("Routed: opening a window"),
+    );
+    router.route(
+        "resizing",
+        0,
+        Matcher::Custom(|event| matches!(event, WebEvent::WEResize { .. })),
+        |event| info!("Routed: laying out again after {}", event),
+    );
+    router.fallback(|event| info!("Nothing routed {}", event));
+    for (name, priority) in router.routes() {
+        info!("Route {} has priority {}", name, priority);
+    }
+    for event in [&we_click, &we_key, &we_scroll, &we_resize] {
+        if let Some(route) = router.dispatch(event) {
+            info!("Took the {} route", route);
+        }
+    }
+    // Recording what the dispatcher was given lets it be played back later, here ten times as fast
+    let mut event_log = EventLog::new();
+    event_log.dispatch(&mut dispatcher, we_click.clone());
+    event_log.record(we_scroll.clone());
+    info!("Replaying {} recorded events", event_log.entries().count());
+    event_log.replay(&mut dispatcher, 10.0);
+    #[cfg(feature = "serde")]
+    {
+        let lines = event_log.to_json_lines();
+        info!("{}", lines);
+        match EventLog::from_json_lines(&lines) {
+            Ok(reloaded) => info!("reloaded {} events", reloaded.entries().count()),
+            Err((line, err)) => info!("line {} of the log: {}", line, err),
+        }
+    }
+
+    // Or queued from several threads at once, and handled on one thread of their own
+    let queue = EventQueue::start(|event| info!("Queued: {}", event));
+    let producers: Vec<_> = (0..3)
+        .map(|thread| {
+            let producer = queue.producer();
+            std::thread::spawn(move || {
+                let click = MouseClick {
+                    x: thread,
+                    y: 0,
+                    at: Duration::ZERO,
+                };
+                if producer.send(WebEvent::WEClick(click)).is_err() {
+                    info!("The queue shut down before thread {} could send", thread);
+                }
+            })
+        })
+        .collect();
+    for producer in producers {
+        let _ = producer.join();
+    }
+    info!("The queue handled {} events", queue.shutdown());
+
+    // Or streamed to an async task, through a buffer small enough that the producer has to wait its turn
+    #[cfg(feature = "tokio")]
+    {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .expect("a single-threaded runtime");
+        runtime.block_on(async {
+            let (sender, mut stream) = events::stream::channel(1);
+            let producer = tokio::spawn(async move {
+                for dy in [40, 80, 120] {
+                    if sender.send(WebEvent::WEScroll { dx: 0, dy }).await.is_err() {
+                        break;
+                    }
+                }
+                sender.try_send(WebEvent::WELoad(true)).is_ok()
+            });
+            while let Some(event) = stream.next().await {
+                info!("Streamed: {}", event);
+            }
+            let _ = producer.await;
+        });
+    }
+
+    // Web events and a car's telemetry go down the same pipeline, to whoever has subscribed
+    let mut bus = EventBus::new();
+    bus.subscribe(|event| info!("Event: {}", event));
+    bus.publish(we_load);
+    bus.publish(we_click);
+    bus.publish(we_key);
+    bus.publish(we_release);
+    bus.publish(we_scroll);
+    bus.publish(we_resize);
+    let mut test_car =
+        Car::new_unchecked(String::from("Red"), Transmission::Manual, false, Age::New);
+    for event in test_car.simulate_drive(&[25, 40, 55]) {
+        bus.publish(event);
+    }
 
     let num = 28;
-    println!("{} divided by 7 is: {} ", num, divide_by_7(num));
+    info!("{} divided by 7 is: {} ", num, divide_by_7(num));
 
     goodbye("Formal: Good bye.");
     goodbye("Casual: See you later");
 
+    log::set_section("cars");
     // We have orders for three new cars!
-    // We'll declare a mutable car variable and reuse it for all the cars
-    let mut car = car_factory(String::from("Red"), Transmission::Manual, false);
-    println!(
-        "Car 1 = {}, {:?} transmission, convertible: {}, mileage: {}",
-        car.color, car.transmission, car.convertible, car.mileage
+    // We'll add them to a fleet, which gives each one an order number. The factory can build all three
+    let mut fleet = Fleet::new();
+    fleet.add(car_factory(String::from("Red"), Transmission::Manual, false, Age::New).unwrap());
+    let silver = fleet.add(
+        car_factory(
+            String::from("Silver"),
+            Transmission::Automatic,
+            true,
+            Age::New,
+        )
+        .unwrap(),
+    );
+    fleet.add(
+        car_factory(
+            String::from("Yellow"),
+            Transmission::SemiAuto,
+            false,
+            Age::New,
+        )
+        .unwrap(),
     );
+    for (number, car) in &fleet {
+        info!("Car {} = {}", number, car);
+    }
 
-    car = car_factory(String::from("Silver"), Transmission::Automatic, true);
-    println!(
-        "Car 2 = {}, {:?} transmission, convertible: {}, mileage: {}",
-        car.color, car.transmission, car.convertible, car.mileage
+    // The silver convertible's order is cancelled; the others keep their numbers
+    if let Some(car) = fleet.remove(silver) {
+        info!("Cancelled order {}: {}", silver, car);
+    }
+    info!("{} cars left on order", fleet.len());
+    if !fleet.is_empty() {
+        for (number, car) in fleet.iter() {
+            info!("Car {} = {}", number, car);
+        }
+    }
+
+    // The factory also sells used cars, which keep adding miles as they're driven
+    let mut used = car_factory(
+        String::from("Blue"),
+        Transmission::Manual,
+        true,
+        Age::Used(42_000),
+    )
+    .unwrap();
+    match used.drive(150) {
+        Some(mileage) => info!("Test drive done, mileage now {}", mileage),
+        None => info!("Odometer can't go any higher"),
+    }
+    info!("Used car = {}", used);
+
+    // Other powertrains come from the builder, which won't fit an electric car with a gearbox
+    for transmission in [Transmission::Manual, Transmission::Automatic] {
+        match Car::builder("White")
+            .transmission(transmission)
+            .convertible(true)
+            .powertrain(Powertrain::Electric { battery_kwh: 75 })
+            .build()
+        {
+            Ok(car) => {
+                fleet.add(car);
+            }
+            Err(e) => info!("Can't build that: {}", e),
+        }
+    }
+    fleet.add(
+        Car::builder("Grey")
+            .transmission(Transmission::Manual)
+            .age(Age::Used(90_000))
+            .powertrain(Powertrain::Diesel)
+            .build()
+            .unwrap(),
+    );
+    fleet.add(
+        Car::builder("Green")
+            .powertrain(Powertrain::Hybrid)
+            .build()
+            .unwrap(),
     );
+    for (number, car) in fleet.with_powertrain(Powertrain::Electric { battery_kwh: 0 }) {
+        info!("Electric car {} = {} ({:?})", number, car, car.powertrain);
+    }
 
-    car = car_factory(String::from("Yellow"), Transmission::SemiAuto, false);
-    println!(
-        "Car 3 = {}, {:?} transmission, convertible: {}, mileage: {}",
-        car.color, car.transmission, car.convertible, car.mileage
+    // Cars lose value as they're driven; the pricing model says how fast
+    let showroom = PricingModel::default();
+    let dealer = PricingModel {
+        base_msrp: 22_000,
+        depreciation: Depreciation::Linear {
+            per_1000_miles: 150,
+        },
+        ..showroom
+    };
+    for (number, car) in &fleet {
+        info!(
+            "Car {} is worth ${} (showroom) or ${} (dealer)",
+            number,
+            car.estimated_value(&showroom),
+            car.estimated_value(&dealer)
+        );
+    }
+    // A collector pays for the options and doesn't mind the miles
+    let collector = PricingModel {
+        depreciation: Depreciation::None,
+        ..showroom
+    };
+    info!(
+        "The used car is worth ${}, or ${} to a collector",
+        used.estimated_value(&showroom),
+        used.estimated_value(&collector)
     );
+    info!("It was ${} new", showroom.sticker_price(&used));
+
+    // The used car comes with its service history, and it's been a while
+    used.add_service(ServiceRecord {
+        date: Date {
+            year: 2023,
+            month: 9,
+            day: 14,
+        },
+        mileage: 31_000,
+        work: String::from("Timing belt"),
+    });
+    if let Some(last) = used.service_history().last() {
+        info!(
+            "Last serviced {} at {} miles ({}), {} miles ago",
+            last.date,
+            used.last_service_mileage().unwrap_or(0),
+            last.work,
+            used.miles_since_service()
+        );
+    }
+    fleet.add(used);
+    for (number, car) in fleet.overdue_for_service(10_000) {
+        info!("Car {} is overdue for service: {}", number, car);
+    }
 
+    // On the assembly line every stage is its own type, so a car can't skip a stage or have two gearboxes fitted
+    let painted = Chassis::new(false).paint("Teal");
+    match painted.fit_drivetrain(Transmission::Manual, Powertrain::Diesel) {
+        Ok(drivetrain) => {
+            let finished = drivetrain.finish();
+            info!("Off the line: {}", finished.car());
+            fleet.add(finished.into_car());
+        }
+        Err(e) => info!("Can't fit that drivetrain: {}", e),
+    }
+
+    // Inventory is listed in a fixed order: lowest mileage first by default, or by whatever keys are asked for
+    fleet.sort_by_keys(&[SortKey::Transmission, SortKey::Convertible, SortKey::Color]);
+    for (number, car) in &fleet {
+        info!("Inventory: car {} = {}", number, car);
+    }
+    if let Some(lowest) = fleet.iter().map(|(_, car)| car).min() {
+        info!("Lowest mileage: {}", lowest);
+    }
+    fleet.sort_by_keys(&[SortKey::Mileage]);
+
+    // Bulk orders come in as CSV; lines that don't make sense are reported and the rest still go through
+    let orders = "Orange,Manual,true\nPurple,Automatic,maybe\nBrown,SemiAuto,false\n";
+    match fleet.import_orders_csv(orders.as_bytes()) {
+        Ok(import) => {
+            info!("Imported orders {:?}", import.added);
+            for e in &import.errors {
+                info!("Skipped {}", e);
+            }
+        }
+        Err(e) => info!("Couldn't read the orders: {}", e),
+    }
+
+    // With more than a handful of cars, the fleet is searched rather than listed
+    info!(
+        "{} Blue cars, {} manuals, {} convertibles",
+        fleet.by_color("blue").count(),
+        fleet.by_transmission(Transmission::Manual).count(),
+        fleet.by_convertible(true).count()
+    );
+    if let Some((number, car)) = fleet.find(|car| car.mileage > 50_000).next() {
+        info!("Car {} is past 50,000 miles: {}", number, car);
+    }
+
+    // The fleet can be saved and picked up again by the next run
+    #[cfg(feature = "serde")]
+    {
+        let path = std::env::temp_dir().join(format!("fleet-{}.json", std::process::id()));
+        match fleet.save_json(&path).and_then(|_| Fleet::load_json(&path)) {
+            Ok(loaded) => info!("Reloaded {} cars from {}", loaded.len(), path.display()),
+            Err(e) => info!("Couldn't save the fleet: {}", e),
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+
+    // Orders for the factory queue up and are built in turn; until then they can still be corrected. Orders for cars
+    // the factory can't build are turned away, and can be sent in again once they're fixed
+    let mut queue = OrderQueue::new();
+    let green = Order {
+        color: String::from("Green"),
+        transmission: Transmission::Manual,
+        convertible: false,
+    };
+    let green = queue.submit(green).expect("a manual hardtop can be built");
+    let black = Order {
+        color: String::from("Black"),
+        transmission: Transmission::SemiAuto,
+        convertible: true,
+    };
+    if let Err(e) = queue.submit(black) {
+        info!("Black convertible refused: {}", e);
+        let fixed = Order {
+            color: String::from("Black"),
+            transmission: Transmission::Automatic,
+            convertible: true,
+        };
+        if let Ok(serial) = queue.submit(fixed) {
+            info!("Resubmitted as order {}", serial);
+        }
+    }
+    if let Err(e) = queue.correct(
+        green,
+        Order {
+            color: String::from("Green"),
+            transmission: Transmission::Automatic,
+            convertible: false,
+        },
+    ) {
+        info!("Can't correct order {}: {}", green, e);
+    }
+    info!("{} orders waiting", queue.pending());
+    let mut vins = Vec::new();
+    while let Some((serial, vin)) = queue.fulfil() {
+        info!("Order {} built as {}: {}", serial, vin, queue.built()[&vin]);
+        vins.push(vin);
+    }
+
+    // The new cars go to the rental desk, which notes who takes each one and how far they drive it
+    let mut desk = RentalDesk::new(queue.deliver());
+    let vin = vins[0];
+    for customer in ["Ada", "Grace"] {
+        match desk.checkout(vin, customer) {
+            Ok(checkout) => info!(
+                "{} rented to {} at {} miles",
+                vin, checkout.customer, checkout.odometer
+            ),
+            Err(e) => info!("Can't rent to {}: {}", customer, e),
+        }
+    }
+    info!("{} cars out", desk.checked_out().count());
+    if let Some(checkout) = desk.renter(vin) {
+        info!("{} is with {}", vin, checkout.customer);
+    }
+    for odometer in [275, 300] {
+        match desk.return_car(vin, odometer) {
+            Ok(driven) => info!("{} back after {} miles", vin, driven),
+            Err(e) => info!("Can't take it back: {}", e),
+        }
+    }
+    if let Some(car) = desk.car(vin) {
+        info!("{} = {}", vin, car);
+    }
+
+    log::set_section("collections");
     // Arrays
 
     // Initialize array elements using comma-separated list of values
@@ -256,33 +956,33 @@ fn main() {
         "Friday",
         "Saturday",
     ];
-    println!("Days in every week {:?}", days);
+    info!("Days in every week {:?}", days);
 
     // Declare array, specify length = 5, specify first element value = "0"
     // Declaration initializes every array element with value = "0"
     // Short form of: let bytes = ["0", "0", "0", "0", "0"]
     let bytes = [0; 5];
-    println!("Bytes buffer: {:?}", bytes);
+    info!("Bytes buffer: {:?}", bytes);
 
     let first = days[0];
     let second = days[1];
-    println!("First = {:?}, Second = {:?}", first, second);
+    info!("First = {:?}, Second = {:?}", first, second);
 
     // Declare MUTABLE array, number of days in february changes
     let mut february = [28; 1];
-    println!("February days {:?}", february[0]);
+    info!("February days {:?}", february[0]);
 
     february[0] = 29;
-    println!("February Leap days {:?}", february[0]);
+    info!("February Leap days {:?}", february[0]);
 
     // Vectors
 
     // Declare a vector with three values
     let three_nums = vec![15, 3, 46];
-    println!("Initial vector: {:?}", three_nums);
+    info!("Initial vector: {:?}", three_nums);
 
     let zeroes = vec![0; 5];
-    println!("Zeros {:?}", zeroes);
+    info!("Zeros {:?}", zeroes);
 
     // Create empty vector, make vector mutable so it can grow and shrink
     let mut fruit = Vec::new();
@@ -293,16 +993,16 @@ fn main() {
     fruit.push("Banana");
     fruit.push("Cherry");
 
-    println!("Fruits: {:?}", fruit);
-    println!("Pop off: {:?}", fruit.pop());
-    println!("Fruits: {:?}", fruit);
+    info!("Fruits: {:?}", fruit);
+    info!("Pop off: {:?}", fruit.pop());
+    info!("Fruits: {:?}", fruit);
 
     // Declare vector with three values
     let mut index_vec = vec![1, 2, 3];
-    println!("Vector: {:?}", index_vec);
+    info!("Vector: {:?}", index_vec);
 
     index_vec[2] = index_vec[2] + 1;
-    println!("Vector: {:?}", index_vec);
+    info!("Vector: {:?}", index_vec);
 
     // Try to access the vector with an out-of-bounds index = 10
     // Program compiles, but panics and stops at the invalid expression
@@ -315,9 +1015,9 @@ fn main() {
     let a = &mut val;
     let b = &step;
 
-    println!("Before incr: a == {}, b == {}", a, b);
+    info!("Before incr: a == {}, b == {}", a, b);
     incr(a, b);
-    println!("After incr: a={}, b=={}", a, b);
+    info!("After incr: a={}, b=={}", a, b);
 
     // First, primitive types were inferred from a function signature.
     // If the function sum wasn't part of the program, let a = 1; would be equivalent to let a: i32 = 1;. i32,
@@ -326,16 +1026,16 @@ fn main() {
     let a = 1;
     let b = 3;
     let c = sum(a, b);
-    println!("a is a {} with value {:?}", type_name_of_val(&a), a);
-    println!("b is a {} with value {:?}", type_name_of_val(&b), b);
-    println!("c is a {} with value {:?}", type_name_of_val(&c), c);
+    info!("a is a {} with value {:?}", type_name_of_val(&a), a);
+    info!("b is a {} with value {:?}", type_name_of_val(&b), b);
+    info!("c is a {} with value {:?}", type_name_of_val(&c), c);
 
     // Second, the type of a dynamic collection was inferred from the type of the item stored
     let mut list = Vec::new();
     list.push(a);
     list.push(b);
     list.push(c);
-    println!(
+    info!(
         "list is a {} with value {:?}",
         type_name_of_val(&list),
         list
@@ -355,7 +1055,7 @@ fn main() {
 
     // Explicit array type declaration
     let numbers: [u64; 3] = [42, 1337, 0];
-    println!(
+    info!(
         "number is a {} with value {:?}",
         type_name_of_val(&numbers),
         numbers
@@ -363,7 +1063,7 @@ fn main() {
 
     // Inferred array type (`[&str; 4]`, array of read-only string references)
     let operating_systems = ["Linux", "FreeBSD", "Tock", "VxWorks"];
-    println!(
+    info!(
         "operating_systems is a {} with value {:?}",
         type_name_of_val(&operating_systems),
         operating_systems
@@ -440,15 +1140,40 @@ fn main() {
     assert_eq!(count_total_bytes(&byte_arr), 4);
     assert_eq!(count_total_bytes(&byte_vec), 7);
 
+    log::set_section("os");
     // From mod `os`
-    let mut my_proc_stopped = os::Proc::new(1);
-    os::Proc::set_state(&mut my_proc_stopped, os::State::Stopped);
-
-    let mut my_proc_sleeping = os::Proc::new(3);
-    os::Proc::set_state(&mut my_proc_sleeping, os::State::Sleeping);
+    // PIDs come from an allocator and are recycled when their Proc is dropped
+    let pids = os::PidAllocator::default();
+
+    let mut my_proc_stopped = os::Proc::builder(&pids)
+        .name("vim")
+        .state(os::State::Stopped)
+        .build()
+        .expect("fresh pid and a live state");
+
+    let mut my_proc_sleeping = os::Proc::builder(&pids)
+        .name("sshd")
+        .state(os::State::Sleeping)
+        .build()
+        .expect("fresh pid and a live state");
+
+    // The checked setter refuses lifecycle changes outside the transition table
+    if let Err(err) = my_proc_sleeping.try_set_state(os::State::Stopped) {
+        info!("Rejected: {}", err);
+    }
 
-    let mut my_proc_running = os::Proc::new(2);
-    os::Proc::set_state(&mut my_proc_running, os::State::Running);
+    let mut my_proc_running = os::Proc::builder(&pids)
+        .name("make")
+        .state(os::State::Running)
+        .build()
+        .expect("fresh pid and a live state");
+
+    // Niceness decides who goes first; renice rejects values outside -20..=19
+    my_proc_stopped.set_nice(-5).unwrap();
+    my_proc_running.set_nice(10).unwrap();
+    if let Err(err) = my_proc_running.renice(15) {
+        info!("Rejected: {}", err);
+    }
 
     let mut proc_queue = vec![my_proc_stopped, my_proc_sleeping, my_proc_running];
 
@@ -458,10 +1183,419 @@ fn main() {
     // {
     //     // ...code here
     // }
+    //
+    // Proc isn't Ord (a process is only equal to itself), so wrap each one in ByPriority to order them by State
+
+    proc_queue.sort_by(|a, b| os::ByPriority(a).cmp(&os::ByPriority(b)));
+    info!("{:#?}", proc_queue);
+
+    // sort_by_key only needs the key to be Ord, so we can order by nice-adjusted priority instead of State
+    proc_queue.sort_by_key(|proc| proc.priority());
+    for proc in &proc_queue {
+        info!("pid {} priority {}", proc.pid(), proc.priority());
+    }
+
+    // Same processes, but time-shared by a round-robin scheduler with a 2-tick quantum, driven one clock tick at a time
+    let table = os::ProcTable::new(os::Proc::new(&pids));
+    let mut sim = os::Simulation::new(os::RoundRobinScheduler::new(2), table);
+    let spawned: Vec<_> = proc_queue.drain(..).map(|proc| sim.spawn(proc)).collect();
+    let disk = sim.add_device(os::Device::new());
+    for tick in 0..8 {
+        if let Some(pid) = sim.step() {
+            info!("tick {}: pid {} on cpu", tick, pid);
+            // Halfway through its slice the second process blocks on a 3-tick timer, and the third on a disk read
+            if tick == 2 && sim.proc_mut(pid).is_some_and(|p| p.sleep_for(3).is_ok()) {
+                info!("pid {} sleeps for 3 ticks", pid);
+            }
+            if tick == 3 {
+                if let Some(Ok(req)) = sim.proc_mut(pid).map(|p| p.start_io(2)) {
+                    info!("pid {} waits on a 2-tick disk read", pid);
+                    sim.submit_io(disk, req);
+                }
+            }
+        }
+    }
+    let report = sim.stats().report();
+    info!(
+        "{} context switches, average wait {:.1} ticks",
+        report.context_switches, report.avg_wait
+    );
+    for proc in &report.procs {
+        info!(
+            "pid {}: cpu {} wait {}",
+            proc.pid, proc.cpu_time, proc.wait_time
+        );
+    }
+    for proc in sim.table().iter().filter(|p| spawned.contains(&p.pid())) {
+        let times = proc.cpu_times();
+        info!(
+            "pid {}: {} user + {} system ticks",
+            proc.pid(),
+            times.user,
+            times.system
+        );
+    }
+    info!("{}", os::gantt::render(&sim.timeline(), sim.now()));
+    // Everything that happened to the first process, tick by tick
+    for record in sim.audit().for_pid(spawned[0]) {
+        info!("audit: {}", record);
+    }
 
-    proc_queue.sort();
-    println!("{:#?}", proc_queue);
+    // And by a multi-level feedback queue, which demotes processes that keep using their whole quantum
+    let mut sim = os::Simulation::new(os::MlfqScheduler::new(&[1, 2, 4], 20), sim.into_table());
+    for &pid in &spawned {
+        sim.admit(pid);
+    }
+    let mlfq_order: Vec<_> = sim.run(12).into_iter().flatten().collect();
+    info!("MLFQ cpu order: {:?}", mlfq_order);
+    for (level, stats) in sim.scheduler().queue_stats().iter().enumerate() {
+        info!("MLFQ queue {}: {:?}", level, stats);
+    }
+    info!("{}", os::gantt::render(&sim.timeline(), sim.now()));
+
+    // And by lottery: every tick a random ticket is drawn, and better priorities hold more tickets
+    let mut sim = os::Simulation::new(os::LotteryScheduler::new(42), sim.into_table());
+    for &pid in &spawned {
+        sim.admit(pid);
+    }
+    let lottery_order: Vec<_> = sim.run(12).into_iter().flatten().collect();
+    info!("lottery cpu order: {:?}", lottery_order);
+
+    // And by control group: the first process alone in a group weighted 2:1 against the rest, which also share a
+    // 64-page memory limit
+    let mut cgroups = os::CgroupTree::new();
+    let fg = cgroups.create(os::ROOT_CGROUP, "fg").expect("root exists");
+    let bg = cgroups.create(os::ROOT_CGROUP, "bg").expect("root exists");
+    cgroups.set_cpu_shares(fg, 2048).expect("fg exists");
+    cgroups.set_memory_limit(bg, Some(64)).expect("bg exists");
+    for (i, &pid) in spawned.iter().enumerate() {
+        cgroups
+            .attach(pid, if i == 0 { fg } else { bg })
+            .expect("groups exist");
+    }
+    let mut table = sim.into_table();
+    for &pid in &spawned[1..] {
+        if let Err(err) = cgroups.alloc(&mut table, pid, 40) {
+            info!("pid {}: {}", pid, err);
+        }
+    }
+    let mut sim = os::Simulation::new(os::CgroupScheduler::new(cgroups), table);
+    for &pid in &spawned {
+        sim.admit(pid);
+    }
+    let cgroup_order: Vec<_> = sim.run(12).into_iter().flatten().collect();
+    info!("cgroup cpu order: {:?}", cgroup_order);
+    let mut table = sim.into_table();
+
+    // A made-up workload, the same every run: a seeded mix of CPU-bound and I/O-bound jobs arriving over time
+    let jobs: Vec<os::Job> = os::WorkloadGen::new(7, os::WorkloadConfig::default())
+        .take(6)
+        .collect();
+    let io_bound = jobs
+        .iter()
+        .filter(|job| job.kind == os::JobKind::IoBound)
+        .count();
+    info!("{} jobs, {} of them I/O-bound", jobs.len(), io_bound);
+    let mut sim = os::Simulation::new(
+        os::RoundRobinScheduler::new(2),
+        os::ProcTable::new(os::Proc::new(&pids)),
+    );
+    for (pid, job) in sim.run_workload(jobs.clone(), 200).into_iter().zip(&jobs) {
+        let stats = sim.stats().proc_stats(pid).expect("every job was spawned");
+        info!(
+            "pid {} ({:?}, arrives at {}, needs {}): turnaround {:?}",
+            pid,
+            job.kind,
+            job.arrival,
+            job.cpu,
+            stats.turnaround()
+        );
+    }
+
+    // The same jobs under every scheduler, side by side
+    let bench: os::BenchTable = os::bench_schedulers(&jobs, 500);
+    info!("{}", bench);
+
+    // One seed for the whole run: the workload and the lottery draw from the same stream, so replaying the seed
+    // replays everything
+    let seeded_run = |seed| {
+        let rng = os::Rng::new(seed);
+        let pids = os::PidAllocator::default();
+        let mut sim = os::Simulation::new(
+            os::LotteryScheduler::with_rng(rng.clone()),
+            os::ProcTable::new(os::Proc::new(&pids)),
+        );
+        let jobs = os::WorkloadGen::with_rng(rng, os::WorkloadConfig::default()).take(4);
+        sim.run_workload(jobs, 100);
+        sim.timeline()
+    };
+    info!(
+        "seed 11 twice gives the same run: {}",
+        seeded_run(11) == seeded_run(11)
+    );
+
+    // A two-core CPU: manage_process decides what each process needs and apply_action carries it out
+    let mut cpu = os::Cpu::new(2);
+    // Pin the first process to core 1, as `taskset -p 0x2` would
+    table
+        .set_affinity(spawned[0], 0b10)
+        .expect("mask is not empty");
+    for &pid in &spawned {
+        let Some(proc) = table.get_mut(pid) else {
+            continue;
+        };
+        let Ok(state) = os::DetailedState::try_from(proc.state()) else {
+            continue;
+        };
+        if let Some(action) = os::manage_process(&state, proc, &cpu) {
+            os::apply_action(action, proc, &mut cpu);
+            info!("pid {}: {:?}", proc.pid(), action);
+        }
+    }
+    cpu.tick();
+    for load in cpu.load_report() {
+        info!(
+            "core {}: {:?}, utilization {:.0}%",
+            load.core,
+            load.running,
+            load.utilization() * 100.0
+        );
+    }
+
+    // Grow the init/cron/rsyslogd/bash tree from `examples/` with fork and exec
+    let mut init = os::Proc::new(&pids);
+    init.exec("init", &[]);
+    init.setenv("PATH", "/usr/bin:/bin");
+    let cron = init.fork();
+    let rsyslogd = init.fork();
+    if let Some(cron) = init.child_mut(cron) {
+        cron.exec("cron", &["-f"]);
+    }
+    if let Some(rsyslogd) = init.child_mut(rsyslogd) {
+        rsyslogd.exec("rsyslogd", &["-n"]);
+        rsyslogd.alloc(24).expect("rsyslogd is running");
+        let bash = rsyslogd.fork();
+        if let Some(bash) = rsyslogd.child_mut(bash) {
+            bash.exec("bash", &["-l"]);
+            bash.alloc(48).expect("bash is running");
+            info!("bash inherited PATH={:?}", bash.getenv("PATH"));
+        }
+    }
+    // With command lines, like `pstree -a`
+    info!("{:#}", init);
+
+    // cron exits, but stays a zombie until init reaps it
+    if let Some(cron) = init.child_mut(cron) {
+        cron.exit(0);
+    }
+    info!("{:#?}", init);
+    info!("init reaped {:?}", init.wait());
+
+    // Save the tree as JSON and load it back; a fresh allocator, since the PIDs in it are still in use here
+    #[cfg(feature = "serde")]
+    {
+        use os::{FromJson, ToJson};
+        let json: os::Json = init.to_json();
+        info!("{}", json);
+        let copy = os::Proc::from_json(&json, &os::PidAllocator::default()).expect("just written");
+        let state = json.get("state").map(os::State::from_json);
+        info!(
+            "reloaded {} with {} children, state {:?}",
+            copy.name(),
+            copy.children().len(),
+            state
+        );
+    }
+
+    // When rsyslogd dies, its bash child is handed over to init instead of vanishing
+    let mut table = os::ProcTable::new(init);
+    table.init_mut().alloc(16).expect("init is running");
+    info!("resident pages: {}", table.total_memory());
+    // With only 72 pages to go round, the OOM killer picks the biggest process other than init (bash)
+    let mut oom = os::OomKiller::new(72);
+    info!("oom scores: {:?}", oom.scores(&table));
+    info!("oom killed {:?}", oom.check(&mut table));
+    if let Some(rsyslogd) = table.init_mut().child_mut(rsyslogd) {
+        rsyslogd.exit(1);
+    }
+    info!(
+        "resident pages after rsyslogd exits: {}",
+        table.total_memory()
+    );
+    info!("re-parented to init: {:?}", table.reparent_orphans());
+    // A snapshot is a plain copy, so it can be printed ps-style while the table keeps changing
+    for snap in table.snapshot() {
+        info!(
+            "{:>4} {:>4} {:?} {}",
+            snap.pid.0,
+            snap.parent.map_or(0, |pid| pid.0),
+            snap.state,
+            snap.name
+        );
+    }
+    // Or in tree order, indented by depth
+    let flat: Vec<os::ProcSnapshot> = table.init().flatten();
+    for snap in &flat {
+        info!("{}{} {:?}", "  ".repeat(snap.depth), snap.name, snap.state);
+    }
+    info!("init reaped {:?}", table.init_mut().wait());
+    // The same tree for Graphviz: pipe this into `dot -Tpng -o tree.png`
+    let dot_options = os::DotOptions {
+        pids: true,
+        cpu_time: true,
+    };
+    info!("{}", table.init().to_dot(dot_options));
+    // The page can drive the simulator: Ctrl+Z stops the selected process and a click sets it going again
+    let mut control = ProcessControl::new();
+    let mut worker = os::Proc::new(&pids);
+    worker.set_state(os::State::Running);
+    control.select(table.insert(worker));
+    let ctrl_z = WebEvent::WEPress(KeyPress(Modifiers::CTRL, 'z'));
+    let resume = WebEvent::WEClick(MouseClick {
+        x: 0,
+        y: 0,
+        at: Duration::ZERO,
+    });
+    for event in [&ctrl_z, &resume] {
+        match control.handle(event, &mut table) {
+            Ok(delivered) => info!(
+                "{} delivered {:?} to pid {:?}",
+                event,
+                delivered,
+                control.selected()
+            ),
+            Err(err) => info!("{} went nowhere: {}", event, err),
+        }
+    }
+    for (shortcut, signal) in [("Ctrl+z", "CONT"), ("Ctrl+x", "TERM"), ("Ctrl+c", "HUP")] {
+        match signal_by_shortcut(&control, shortcut, signal, &mut table) {
+            Ok(delivered) => info!("{} then {}: delivered {:?}", shortcut, signal, delivered),
+            Err(err) => info!("{} then {}: {}", shortcut, signal, err),
+        }
+    }
+    let mut quit_only = ProcessControl::empty();
+    quit_only.bind(
+        Matcher::Keys(vec![KeyPress(Modifiers::CTRL, 'q')]),
+        os::Signal::Term,
+    );
+    info!(
+        "With only Ctrl+Q bound, Ctrl+Z sends {:?}",
+        quit_only.signal_for(&ctrl_z)
+    );
+    // Or saved to disk and read back later
+    #[cfg(feature = "serde")]
+    {
+        let path = std::env::temp_dir().join(format!("procs-{}.json", std::process::id()));
+        let reloaded = table
+            .save_json(&path)
+            .and_then(|()| os::ProcTable::load_json(&path));
+        match reloaded {
+            Ok(reloaded) => info!(
+                "reloaded {} processes from {}",
+                reloaded.len(),
+                path.display()
+            ),
+            Err(err) => info!("could not save or load {}: {}", path.display(), err),
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+
+    // Two workers each take one lock and then wait for the other's: neither can ever continue
+    let mut locks = os::LockManager::new();
+    let (log, db) = (locks.create(), locks.create());
+    let workers = [
+        table.insert(os::Proc::new(&pids)),
+        table.insert(os::Proc::new(&pids)),
+    ];
+    for &worker in &workers {
+        if let Some(proc) = table.get_mut(worker) {
+            proc.set_state(os::State::Running);
+        }
+    }
+    for (idx, res) in [(0, log), (1, db), (0, db), (1, log)] {
+        let worker = workers[idx];
+        info!(
+            "pid {} acquires resource {}: {:?}",
+            worker,
+            res,
+            locks.acquire(worker, res, &mut table)
+        );
+    }
+    info!("deadlocked: {:?}", locks.deadlocked());
+
+    // Threads are scheduled one by one, but share their process's PID, memory and CPU time
+    let mut httpd = os::Proc::new(&pids);
+    httpd.exec("httpd", &[]);
+    httpd.spawn_thread();
+    httpd.spawn_thread();
+    info!(
+        "httpd {} has {} extra threads",
+        httpd.pid(),
+        httpd.threads().len()
+    );
+    let table = os::ProcTable::new(os::Proc::new(&pids));
+    let mut sim = os::Simulation::new(os::RoundRobinScheduler::new(1), table);
+    let httpd = sim.spawn(httpd);
+    info!("task order: {:?}", sim.run(6));
+    if let Some(proc) = sim.table().get(httpd) {
+        info!("httpd cpu ticks: {}", proc.cpu_times().own());
+    }
+    // A checkpoint picks up exactly where the simulation left off
+    #[cfg(feature = "serde")]
+    {
+        let path = std::env::temp_dir().join(format!("sim-{}.json", std::process::id()));
+        match sim
+            .save(&path)
+            .and_then(|()| os::Simulation::<os::RoundRobinScheduler>::resume(&path))
+        {
+            Ok(mut resumed) => info!(
+                "resumed at tick {}: {:?}, original: {:?}",
+                resumed.now(),
+                resumed.run(3),
+                sim.run(3)
+            ),
+            Err(err) => info!("could not checkpoint to {}: {}", path.display(), err),
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+
+    // The REPL's commands are plain values, so a session can be scripted as well as typed
+    let mut repl = os::repl::Repl::new(os::RoundRobinScheduler::new(2));
+    for line in [
+        "spawn bash",
+        "spawn vim notes.txt",
+        "tick 3",
+        "signal 3 STOP",
+        "ps",
+    ] {
+        match repl.eval(line) {
+            Ok(reply) => info!("> {}\n{}", line, reply),
+            Err(err) => info!("> {}\nerror: {}", line, err),
+        }
+    }
+    info!("repl ran until tick {}", repl.simulation().now());
+
+    // The configured simulation, run for a few ticks
+    let pids = os::PidAllocator::default();
+    let mut sim = configured_simulation(&config, &pids);
+    info!(
+        "{} scheduler, quantum {}, {} core cpu, {} processes:",
+        config.scheduler,
+        config.quantum,
+        sim.num_cores(),
+        sim.table().len()
+    );
+    sim.run(12);
+    info!("{}", os::gantt::render(&sim.timeline(), sim.now()));
+
+    // The same tree types can hold the machine's real processes, read from /proc: the live version of `pstree -n -g`
+    #[cfg(all(feature = "host-procfs", target_os = "linux"))]
+    match os::ProcTable::from_host() {
+        Ok(host) => info!("{}", host.init()),
+        Err(err) => info!("could not read /proc: {}", err),
+    }
 
+    log::set_section("control flow");
     // If conditional
     conditional_print(11);
     conditional_print(4);
@@ -470,14 +1604,14 @@ fn main() {
     // While loop
     let mut countdown = 10;
     while countdown > 0 {
-        println!("{}...", countdown);
+        info!("{}...", countdown);
         countdown -= 1;
     }
 
     // Alternative loop construct
     let mut count = 10;
     loop {
-        println!("{}....", count);
+        info!("{}....", count);
         if count == 0 {
             break;
         }
@@ -486,63 +1620,63 @@ fn main() {
 
     // For loop construct
     for i in 0..10 {
-        println!("{}", i);
+        info!("{}", i);
     }
 
-    println!("Iterating over vector:");
+    info!("Iterating over vector:");
     let list = vec![3, 2, 1];
     for item in list {
-        println!("list item: {}", item);
+        info!("list item: {}", item);
     }
 
     // Iterator trait
     let list1 = vec![3, 2, 1];
     for (i, item) in list1.iter().enumerate() {
-        println!("list item {}: {}", i, item);
+        info!("list item {}: {}", i, item);
     }
 
     // Functional transformations:
     let triple_list: Vec<_> = list1.iter().map(|x| x * 3).collect();
     for item in triple_list {
-        println!("triple_list item: {}", item);
+        info!("triple_list item: {}", item);
     }
 
-    println!("\n Iterating over ordered set:");
+    info!("\n Iterating over ordered set:");
     let mut o_set = BTreeSet::new();
     o_set.insert(1);
     o_set.insert(2);
     o_set.insert(3);
     for item in o_set {
-        println!("{} in BTree set", item);
+        info!("{} in BTree set", item);
     }
 
-    println!("\nIterating over hash set:");
+    info!("\nIterating over hash set:");
     let mut h_Set = HashSet::new();
     h_Set.insert(1);
     h_Set.insert(2);
     h_Set.insert(3);
     for item in h_Set {
-        println!("{} in hash set", item);
+        info!("{} in hash set", item);
     }
 
     // Pattern matching
     let x = 10; // Compiler infers i32 by default
     match x {
-        1 | 2 | 3 => println!("number is 1 or 2 or 3"), // Specify three literal values
-        4..=10 => println!("number is between 4 and 10 inclusive"), // Specify a inclusive range
-        x if x * x < 250 => println!("number squared is less than 250"), // Gaurd expression
-        _ => println!("number does not meet any previous condition"), // Default case
+        1 | 2 | 3 => info!("number is 1 or 2 or 3"), // Specify three literal values
+        4..=10 => info!("number is between 4 and 10 inclusive"), // Specify a inclusive range
+        x if x * x < 250 => info!("number squared is less than 250"), // Gaurd expression
+        _ => info!("number does not meet any previous condition"), // Default case
     }
 
     // Condensed pattern matching 
     let curr_state = os::State::Running;
 
     // match curr_state {
-    //     State::Running => println!("Process is running!"),
+    //     State::Running => info!("Process is running!"),
     //     State::Stopped => {},   // Do nothing
     //     State::Sleeping => {},  // Do nothing
     // };
     if let os::State::Running = curr_state {
-        println!("Process is running!");
+        info!("Process is running!");
     }
 }
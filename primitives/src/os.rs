@@ -1,36 +1,233 @@
+use std::borrow::{Borrow, Cow};
 use std::cmp::Ordering;
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+mod affinity;
+mod arena;
+mod audit;
+mod bench;
+mod builder;
+mod cgroup;
+mod children;
+mod class;
+mod config;
+mod cpu;
+#[cfg(feature = "dashboard")]
+pub mod dashboard;
+mod dot;
+mod env;
+mod error;
+pub mod gantt;
+mod group;
+mod hook;
+mod io;
+#[cfg(feature = "serde")]
+mod json;
+mod lock;
+mod lottery;
+mod mem;
+mod mlfq;
+mod oom;
+mod pid;
+mod prio;
+#[cfg(all(feature = "host-procfs", target_os = "linux"))]
+mod procfs;
+pub mod repl;
+mod rng;
+mod rr;
+mod shared;
+mod signal;
+mod sim;
+mod snapshot;
+mod stats;
+mod table;
+mod task;
+mod timer;
+mod times;
+mod tree;
+mod workload;
+
+pub use affinity::AffinityError;
+pub use arena::{ProcArena, ProcId};
+pub use audit::{AuditEvent, AuditLog, AuditRecord};
+pub use bench::{bench_schedulers, BenchTable};
+pub use builder::BuildError;
+pub use cgroup::{CgroupError, CgroupScheduler, CgroupTree, ROOT_CGROUP};
+pub use children::Children;
+pub use class::SchedClass;
+pub use config::{ConfigError, ProcConfig, SchedulerKind, SimConfig};
+pub use cpu::{CoreId, CoreLoad, Cpu};
+pub use dot::DotOptions;
+pub use error::SchedError;
+pub use group::GroupError;
+pub use hook::DropHook;
+pub use io::Device;
+#[cfg(feature = "serde")]
+pub use json::{FromJson, Json, JsonError, ToJson};
+pub use lock::{LockError, LockManager};
+pub use lottery::LotteryScheduler;
+pub use mem::{MemError, MemUsage};
+pub use mlfq::MlfqScheduler;
+pub use oom::OomKiller;
+pub use pid::{Pid, PidAllocator, PidHandle};
+pub use prio::NiceError;
+pub use rng::Rng;
+pub use rr::RoundRobinScheduler;
+pub use shared::{Ancestors, SharedProc};
+pub use signal::{ParseSignalError, Signal, SignalMask};
+pub use sim::{Scheduler, Simulation};
+pub use snapshot::{CowSnapshot, ProcSnapshot, SimSnapshot};
+pub use stats::SchedStats;
+pub use table::ProcTable;
+pub use task::Task;
+pub use timer::TimerWheel;
+pub use times::CpuTimes;
+pub use tree::{Bfs, BfsMut, Dfs, DfsMut, ProcMut, ReparentError};
+pub use workload::{Job, JobKind, WorkloadConfig, WorkloadGen};
 
 // Enums are a natural way to express mutually exclusive but related possibilities
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 // Assume we have three priorities based solely on the current State. Any Sleeping process should be the highest priority for execution,
-// followed by Stopped processes and then the running process.
+// followed by Stopped processes and then the running process. Processes that have exited sort last, they never run again.
 pub enum State {
-    Running = 3,  // 0 by default
-    Stopped = 2,  // 1 by default
-    Sleeping = 1, // 2 by default
+    Running = 3,    // 0 by default
+    Stopped = 2,    // 1 by default
+    Sleeping = 1,   // 2 by default
+    Zombie = 4,     // Exited, exit code not yet collected by the parent
+    Terminated = 5, // Exited and reaped
+}
+
+impl State {
+    /// Lifecycle transition table. A process only gets on a CPU from Stopped, only blocks while it is Running, and a
+    /// Sleeping process is woken straight onto a CPU. Any live process can exit into a Zombie, which only moves on once
+    /// reaped. Staying in the same state is always allowed.
+    pub fn can_transition_to(self, next: State) -> bool {
+        matches!(
+            (self, next),
+            (State::Stopped, State::Running)
+                | (State::Running, State::Stopped)
+                | (State::Running, State::Sleeping)
+                | (State::Sleeping, State::Running)
+                | (
+                    State::Running | State::Stopped | State::Sleeping,
+                    State::Zombie
+                )
+                | (State::Zombie, State::Terminated)
+        ) || self == next
+    }
+
+    /// Whether the process has exited (reaped or not).
+    pub fn is_exited(self) -> bool {
+        matches!(self, State::Zombie | State::Terminated)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransitionError {
+    pub from: State,
+    pub to: State,
 }
 
-pub fn stop_and_schedule_another_process() {
-    println!("stopping and scheduling another process");
+impl fmt::Display for TransitionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid state transition {:?} -> {:?}",
+            self.from, self.to
+        )
+    }
 }
 
-pub fn assign_to_available_cpu_core() {
-    println!("assigning to available cpu core");
+impl Error for TransitionError {}
+
+/// What `manage_process` decided should happen to a process. Deciding and doing are kept apart so the decisions can
+/// be tested without a CPU changing underneath them; `apply_action` carries one out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchedAction {
+    // Take the running process off its core and let another one have it
+    Preempt,
+    // Put the process on this (idle) core
+    Dispatch { core: CoreId },
+    // Its data is ready: wake the sleeping process
+    Wake { pid: Pid },
 }
 
-pub fn check_if_data_ready_and_wake_if_so() {
-    println!("check if data is ready and wakes if so");
+/// Decides what to do with a process in `state`, normally its own state as a `DetailedState`. Returns `None` when
+/// there is nothing to do: the process has exited, or it is ready but every core it may use is busy.
+pub fn manage_process(state: &DetailedState, proc: &Proc, cpu: &Cpu) -> Option<SchedAction> {
+    match State::from(state) {
+        State::Running => Some(SchedAction::Preempt),
+        State::Stopped => cpu
+            .core_of(proc.pid())
+            .filter(|&core| proc.allows_core(core))
+            .or_else(|| cpu.idle_cores().find(|&core| proc.allows_core(core)))
+            .map(|core| SchedAction::Dispatch { core }),
+        State::Sleeping => Some(SchedAction::Wake { pid: proc.pid() }),
+        State::Zombie | State::Terminated => None, // Nothing left to schedule
+    }
 }
 
-pub fn manage_process(curr_state: State) {
-    match curr_state {
-        State::Running => stop_and_schedule_another_process(),
-        State::Stopped => assign_to_available_cpu_core(),
-        State::Sleeping => check_if_data_ready_and_wake_if_so(),
+/// Carries out `action` on `proc`.
+pub fn apply_action(action: SchedAction, proc: &mut Proc, cpu: &mut Cpu) {
+    match action {
+        SchedAction::Preempt => {
+            cpu.release(proc.pid());
+            proc.set_state(State::Stopped);
+        }
+        SchedAction::Dispatch { core } => {
+            if cpu.core_of(proc.pid()) != Some(core) {
+                cpu.release(proc.pid());
+                cpu.assign_to(core, proc);
+            }
+        }
+        SchedAction::Wake { .. } => {
+            proc.wake();
+        }
     }
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[test]
+fn test_manage_process_decides_and_apply_action_executes() {
+    let pids = PidAllocator::default();
+    let mut cpu = Cpu::new(1);
+    let mut ready = Proc::new(&pids);
+    let mut other = Proc::new(&pids);
+    let mut sleeper = Proc::new(&pids);
+    sleeper.set_state(State::Running);
+    sleeper.set_state(State::Sleeping);
+
+    let decide = |proc: &Proc, cpu: &Cpu| {
+        let state = DetailedState::try_from(proc.state()).unwrap();
+        manage_process(&state, proc, cpu)
+    };
+    let action = decide(&ready, &cpu);
+    assert_eq!(action, Some(SchedAction::Dispatch { core: 0 }));
+    // Deciding alone changes nothing
+    assert_eq!(cpu.running_on(0), None);
+    apply_action(action.unwrap(), &mut ready, &mut cpu);
+    assert_eq!(cpu.running_on(0), Some(ready.pid()));
+
+    assert_eq!(decide(&other, &cpu), None);
+    assert_eq!(decide(&ready, &cpu), Some(SchedAction::Preempt));
+    apply_action(SchedAction::Preempt, &mut ready, &mut cpu);
+    assert_eq!(ready.state(), State::Stopped);
+    assert_eq!(
+        decide(&other, &cpu),
+        Some(SchedAction::Dispatch { core: 0 })
+    );
+
+    let action = decide(&sleeper, &cpu).unwrap();
+    assert_eq!(action, SchedAction::Wake { pid: sleeper.pid() });
+    apply_action(action, &mut sleeper, &mut cpu);
+    assert_eq!(sleeper.state(), State::Running);
+    other.exit(0);
+    assert!(DetailedState::try_from(other.state()).is_err());
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum StopKind {
     Mandatory, // Linux SIGSTOP
     Ignorable, // Linux SIGSTP
@@ -39,6 +236,7 @@ pub enum StopKind {
 // In-memory size of an enum is determined by its largest variant.
 //An instance of the Running variant is the same size as an instance of Sleeping variant,
 // despite the latter holding more information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DetailedState {
     // An enum variant can be like a unit struct without fields or data types
     Running,
@@ -46,6 +244,67 @@ pub enum DetailedState {
     // An enum variant can be like a classic struct with named fields and their data types
     Stopped { reason: StopKind },
     Sleeping { start_time: u64 },
+    Zombie { exit_code: i32 },
+    Terminated { exit_code: i32 },
+}
+
+// Dropping the details always works, so every DetailedState has a State.
+impl From<&DetailedState> for State {
+    fn from(state: &DetailedState) -> Self {
+        match state {
+            DetailedState::Running => State::Running,
+            DetailedState::Stopped { .. } => State::Stopped,
+            DetailedState::Sleeping { .. } => State::Sleeping,
+            DetailedState::Zombie { .. } => State::Zombie,
+            DetailedState::Terminated { .. } => State::Terminated,
+        }
+    }
+}
+
+/// A State that can't be widened into a DetailedState without making up its details.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MissingDetail(pub State);
+
+impl fmt::Display for MissingDetail {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} has no default details", self.0)
+    }
+}
+
+impl Error for MissingDetail {}
+
+// Going the other way, details get defaults where one is harmless: a bare Stopped is taken to be an ordinary
+// (ignorable) stop and a bare Sleeping to have started at tick 0. An exit code can't be defaulted, since 0 would
+// claim the process succeeded.
+impl TryFrom<State> for DetailedState {
+    type Error = MissingDetail;
+
+    fn try_from(state: State) -> Result<Self, Self::Error> {
+        match state {
+            State::Running => Ok(DetailedState::Running),
+            State::Stopped => Ok(DetailedState::Stopped {
+                reason: StopKind::Ignorable,
+            }),
+            State::Sleeping => Ok(DetailedState::Sleeping { start_time: 0 }),
+            State::Zombie | State::Terminated => Err(MissingDetail(state)),
+        }
+    }
+}
+
+#[test]
+fn test_state_conversions_round_trip() {
+    for state in [State::Running, State::Stopped, State::Sleeping] {
+        let detailed = DetailedState::try_from(state).unwrap();
+        assert_eq!(State::from(&detailed), state);
+    }
+    assert_eq!(
+        DetailedState::try_from(State::Zombie),
+        Err(MissingDetail(State::Zombie))
+    );
+    assert_eq!(
+        State::from(&DetailedState::Terminated { exit_code: 3 }),
+        State::Terminated
+    );
 }
 
 #[test]
@@ -61,50 +320,441 @@ fn test_detailed_stop_match() {
     }
 }
 
-// Use Generic typing: The Rust compiler implements generics via monomorphization.
-// Hence generics have no runtime cost
+// The PID is held through an RAII handle: when a Proc is dropped, its PID goes back to the allocator it came from.
+// Like the process trees in `examples/`, a Proc owns its children, so dropping a parent drops its whole subtree.
 #[derive(Debug)]
-pub struct Proc<T> {
-    pid: T,           // Process ID (unsigned integer)
-    state: State,     // Current state (enum)
-    children: Vec<T>, // Child IDs (dynamic list)
+pub struct Proc {
+    pid: PidHandle,                  // Process ID (owned, recycled on drop)
+    name: Cow<'static, str>,         // Program image name, replaced by exec
+    argv: Vec<String>,               // Arguments the image was started with
+    env: HashMap<String, String>,    // Environment variables, inherited on fork
+    pgid: Pid,                       // Process group (shell job)
+    sid: Pid,                        // Session
+    state: State,                    // Current state (enum)
+    nice: i8,                        // Static priority adjustment, -20..=19
+    class: SchedClass,               // How long a slice it gets, and when
+    boost: u8,                       // Dynamic priority bonus
+    inherited: Option<u8>,           // Priority lent by a lock waiter
+    exit_code: Option<i32>,          // Set once the process exits
+    pending: VecDeque<Signal>,       // Signals waiting to be delivered
+    mask: SignalMask,                // Blocked signals
+    stop: Option<(StopKind, State)>, // Set while stopped by a signal, with the state to resume into
+    sleep: Option<u64>,              // Requested sleep length, until a timer is armed for it
+    slept_at: Option<u64>,           // Tick the current sleep or I/O wait began
+    times: CpuTimes,                 // CPU ticks used by this process and its reaped children
+    mem: MemUsage,                   // Simulated memory footprint
+    threads: Vec<Task>,              // Threads besides the leader, sharing the PID and memory
+    affinity: u64,                   // Cores the process may run on, one bit each
+    audit: Vec<(Pid, AuditEvent)>,   // Lifecycle events not yet collected into an AuditLog
+    drop_hook: Option<DropHook>,     // Called when the process is dropped
+    children: Children,              // Forked children (owned), the first two inline
+}
+
+/// What a parent learns about a child when reaping it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExitStatus {
+    pub pid: Pid,
+    pub code: i32,
+}
+
+// Traits are powerful: by implementing them manually, we decide what it means for two Proc structs to be equal. A
+// process is identified by its PID, so that's all equality and hashing look at, and a Proc can go in a HashSet or be a
+// HashMap key.
+impl PartialEq for Proc {
+    fn eq(&self, other: &Self) -> bool {
+        self.pid() == other.pid()
+    }
 }
 
-// Traits are powerful: n implementing a trait manually, we've changed not only how Proc structs
-// should be ordered for sorting but also what it means for two Proc structs to be equal.
-impl<T> Ord for Proc<T> {
+impl Eq for Proc {}
+
+impl Hash for Proc {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.pid().hash(state);
+    }
+}
+
+/// A deep copy: the process and its whole subtree, each copy with a fresh PID (and fresh TIDs for its threads) from
+/// the same allocator, so the copies are new processes and never equal the originals. Their audit logs start over at
+/// creation. Costs a walk and an allocation per process; `cow_snapshot` is the cheap, read-only alternative.
+///
+/// # Panics
+///
+/// Panics if the allocator runs out of PIDs.
+impl Clone for Proc {
+    fn clone(&self) -> Self {
+        let mut copy = self.clone_node();
+        copy.children = self.children.clone();
+        copy
+    }
+}
+
+impl Proc {
+    /// Copies this process alone, as `clone` does but leaving out its children, e.g. to build a tree of copies with
+    /// a different shape.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the allocator runs out of PIDs.
+    pub fn clone_node(&self) -> Proc {
+        let pids = self.pid.allocator();
+        let mut copy = self.inherit(pids.alloc().expect("pid space exhausted"), None);
+        // What fork leaves behind, a copy keeps
+        copy.state = self.state;
+        copy.boost = self.boost;
+        copy.inherited = self.inherited;
+        copy.exit_code = self.exit_code;
+        copy.pending = self.pending.clone();
+        copy.stop = self.stop;
+        copy.sleep = self.sleep;
+        copy.slept_at = self.slept_at;
+        copy.times = self.times;
+        copy.mem = self.mem;
+        copy.threads = self
+            .threads
+            .iter()
+            .map(|task| Task {
+                tid: pids.alloc().expect("pid space exhausted"),
+                state: task.state,
+            })
+            .collect();
+        copy
+    }
+}
+
+/// Orders processes by scheduling priority, which is solely their State (see `State`): `proc_queue.sort_by(|a, b|
+/// ByPriority(a).cmp(&ByPriority(b)))`, or `BinaryHeap<ByPriority<Proc>>`. Kept apart from Proc itself, as two
+/// different processes in the same state are equally urgent but not equal.
+#[derive(Debug)]
+pub struct ByPriority<P>(pub P);
+
+impl<P: Borrow<Proc>> Ord for ByPriority<P> {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.state.cmp(&other.state)
+        self.0.borrow().state.cmp(&other.0.borrow().state)
     }
 }
 
-impl<T> PartialOrd for Proc<T> {
+impl<P: Borrow<Proc>> PartialOrd for ByPriority<P> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
-impl<T> PartialEq for Proc<T> {
+impl<P: Borrow<Proc>> PartialEq for ByPriority<P> {
     fn eq(&self, other: &Self) -> bool {
-        self.state == other.state
+        self.cmp(other) == Ordering::Equal
     }
 }
 
-impl<T> Eq for Proc<T> {}
+impl<P: Borrow<Proc>> Eq for ByPriority<P> {}
 
-impl<T> Proc<T> {
-    /// Associated function (constructor)
-    pub fn new(pid: T) -> Self {
+impl Proc {
+    /// Associated function (constructor), taking the next free PID from `pids`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the allocator has no free PID left.
+    pub fn new(pids: &PidAllocator) -> Self {
+        Proc::with_pid(pids.alloc().expect("pid space exhausted"))
+    }
+
+    // A fresh, Stopped process around an already allocated PID.
+    fn with_pid(pid: PidHandle) -> Self {
+        Proc::with_parent(pid, None)
+    }
+
+    // The one place every field is set: all the other constructors, `fork` and `clone_node` included, start here.
+    fn with_parent(pid: PidHandle, parent: Option<Pid>) -> Self {
+        let created = (pid.pid(), AuditEvent::Created { parent });
         Proc {
+            pgid: pid.pid(),
+            sid: pid.pid(),
             pid,
+            name: Cow::Borrowed(""),
+            argv: Vec::new(),
+            env: HashMap::new(),
             state: State::Stopped,
-            children: Vec::new(),
+            nice: 0,
+            class: SchedClass::default(),
+            boost: 0,
+            inherited: None,
+            exit_code: None,
+            pending: VecDeque::new(),
+            mask: SignalMask::default(),
+            stop: None,
+            sleep: None,
+            slept_at: None,
+            times: CpuTimes::default(),
+            mem: MemUsage::default(),
+            threads: Vec::new(),
+            affinity: affinity::ALL_CORES,
+            audit: vec![created],
+            drop_hook: None,
+            children: Children::default(),
+        }
+    }
+
+    // A fresh process around `pid` with just what a child inherits from this one when it forks.
+    fn inherit(&self, pid: PidHandle, parent: Option<Pid>) -> Proc {
+        let mut child = Proc::with_parent(pid, parent);
+        child.name = self.name.clone();
+        child.argv = self.argv.clone();
+        child.env = self.env.clone();
+        child.pgid = self.pgid;
+        child.sid = self.sid;
+        child.nice = self.nice;
+        child.class = self.class;
+        child.mask = self.mask;
+        child.affinity = self.affinity;
+        child.drop_hook = self.drop_hook.clone();
+        child
+    }
+
+    pub fn pid(&self) -> Pid {
+        self.pid.pid()
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    /// Exit code, once the process has exited.
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit_code
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn argv(&self) -> &[String] {
+        &self.argv
+    }
+
+    /// Replaces the arguments but not the program, like a process rewriting its own command line.
+    pub fn set_argv(&mut self, args: &[&str]) {
+        self.argv = args.iter().map(|arg| arg.to_string()).collect();
+    }
+
+    pub fn children(&self) -> &Children {
+        &self.children
+    }
+
+    /// Direct child with the given PID.
+    pub fn child_mut(&mut self, pid: Pid) -> Option<&mut Proc> {
+        self.children.iter_mut().find(|c| c.pid() == pid)
+    }
+
+    /// Duplicates this process into a new child with a fresh PID from the same allocator, appends it to `children`
+    /// and returns the child's PID. The child inherits the program image, environment, process group, session, nice
+    /// value and signal mask but not the parent's own children, pending signals or priority boost, and starts out Stopped,
+    /// waiting to be scheduled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the allocator has no free PID left.
+    pub fn fork(&mut self) -> Pid {
+        let handle = self.pid.allocator().alloc().expect("pid space exhausted");
+        let child = self.inherit(handle, Some(self.pid()));
+        let pid = child.pid();
+        self.add_child(child);
+        pid
+    }
+
+    /// Terminates the process with `code`, releasing its memory. It stays around as a Zombie, still listed among its
+    /// parent's children, until the parent collects the code with `wait`. Exiting a second time has no effect.
+    pub fn exit(&mut self, code: i32) {
+        if self.state.is_exited() {
+            return;
         }
+        self.change_state(State::Zombie);
+        self.exit_code = Some(code);
+        self.note(AuditEvent::Exit { code });
+        self.stop = None;
+        self.mem.resident = 0;
+        self.threads.clear();
+    }
+
+    /// Reaps one Zombie child: removes it from `children` (releasing its PID) and returns its exit status.
+    /// Returns `None` if no child has exited yet. Children the zombie still had (see `ProcTable::reparent_orphans`)
+    /// are adopted by this process rather than dropped along with it.
+    pub fn wait(&mut self) -> Option<ExitStatus> {
+        let idx = self
+            .children
+            .iter()
+            .position(|c| c.state == State::Zombie)?;
+        let mut zombie = self.children.remove(idx);
+        self.children.append(&mut zombie.children);
+        self.collect_child_times(&zombie);
+        zombie.set_state(State::Terminated);
+        self.audit.append(&mut zombie.audit);
+        Some(ExitStatus {
+            pid: zombie.pid(),
+            code: zombie.exit_code.unwrap_or_default(),
+        })
+    }
+
+    /// Replaces the program image (name and arguments) while keeping the PID, state and children.
+    /// The name can be a literal, kept without copying, or a `String` made up at runtime such as `format!("worker-{}",
+    /// n)`.
+    pub fn exec(&mut self, name: impl Into<Cow<'static, str>>, args: &[&str]) {
+        self.name = name.into();
+        self.argv = args.iter().map(|arg| arg.to_string()).collect();
     }
 
     /// Method (takes self, mutable setter in this case)
     pub fn set_state(&mut self, new_state: State) {
-        self.state = new_state;
+        self.change_state(new_state);
+    }
+
+    /// Checked setter: rejects transitions not allowed by `State::can_transition_to`, leaving the state untouched.
+    pub fn try_set_state(&mut self, new_state: State) -> Result<(), TransitionError> {
+        if !self.state.can_transition_to(new_state) {
+            return Err(TransitionError {
+                from: self.state,
+                to: new_state,
+            });
+        }
+        self.change_state(new_state);
+        Ok(())
     }
     // ...more methods/functions here
 }
+
+#[test]
+fn test_try_set_state_follows_transition_table() {
+    let mut proc = Proc::new(&PidAllocator::default());
+    assert_eq!(proc.try_set_state(State::Running), Ok(()));
+    assert_eq!(proc.try_set_state(State::Sleeping), Ok(()));
+    assert_eq!(
+        proc.try_set_state(State::Stopped),
+        Err(TransitionError {
+            from: State::Sleeping,
+            to: State::Stopped,
+        })
+    );
+    // A rejected transition leaves the process where it was
+    assert_eq!(proc.state, State::Sleeping);
+    assert_eq!(proc.try_set_state(State::Running), Ok(()));
+    assert_eq!(proc.try_set_state(State::Running), Ok(()));
+}
+
+#[test]
+fn test_procs_are_equal_by_pid_and_ordered_by_state() {
+    use std::collections::hash_map::RandomState;
+    use std::collections::BinaryHeap;
+    use std::hash::BuildHasher;
+
+    let pids = PidAllocator::default();
+    let mut a = Proc::new(&pids);
+    let b = Proc::new(&pids);
+    // Same state, still different processes
+    assert_ne!(a, b);
+    assert_eq!(a, a);
+    assert_eq!(ByPriority(&a), ByPriority(&b));
+    // Hashing agrees with equality: a process hashes as its PID does
+    let hasher = RandomState::new();
+    assert_eq!(hasher.hash_one(&a), hasher.hash_one(a.pid()));
+    assert_ne!(hasher.hash_one(&a), hasher.hash_one(&b));
+
+    a.set_state(State::Running);
+    assert!(ByPriority(&a) > ByPriority(&b));
+    let c = Proc::new(&pids);
+    let mut heap = BinaryHeap::from([ByPriority(b), ByPriority(a), ByPriority(c)]);
+    assert_eq!(heap.pop().unwrap().0.state(), State::Running);
+    assert_eq!(heap.len(), 2);
+}
+
+#[test]
+fn test_names_can_be_made_at_runtime() {
+    let pids = PidAllocator::default();
+    let mut pool = Proc::builder(&pids).name("pool").build().unwrap();
+    // A literal is kept as it is, without copying it
+    assert!(matches!(pool.name, Cow::Borrowed("pool")));
+    for n in 0..3 {
+        let worker = pool.fork();
+        let worker = pool.child_mut(worker).unwrap();
+        worker.exec(format!("worker-{}", n), &[]);
+        assert!(matches!(worker.name, Cow::Owned(_)));
+    }
+    let names: Vec<_> = pool.children().iter().map(Proc::name).collect();
+    assert_eq!(names, ["worker-0", "worker-1", "worker-2"]);
+    assert_eq!(
+        pool.find("worker-2").unwrap().pid(),
+        pool.children()[2].pid()
+    );
+}
+
+#[test]
+fn test_stopped_cannot_sleep() {
+    assert!(State::Stopped.can_transition_to(State::Running));
+    assert!(!State::Stopped.can_transition_to(State::Sleeping));
+}
+
+#[test]
+fn test_fork_and_exec_build_a_tree() {
+    let pids = PidAllocator::default();
+    let mut init = Proc::new(&pids);
+    init.exec("init", &[]);
+    init.set_state(State::Running);
+
+    let rsyslogd = init.fork();
+    let cron = init.fork();
+    assert_ne!(rsyslogd, cron);
+    assert_eq!(pids.in_use(), 3);
+
+    let child = init.child_mut(rsyslogd).unwrap();
+    assert_eq!(child.name(), "init");
+    assert_eq!(child.state, State::Stopped);
+    child.exec("rsyslogd", &["-n"]);
+    let bash = child.fork();
+
+    let child = &init.children()[0];
+    assert_eq!(child.pid(), rsyslogd);
+    assert_eq!(child.name(), "rsyslogd");
+    assert_eq!(child.argv(), ["-n"]);
+    assert_eq!(child.children()[0].pid(), bash);
+    assert_eq!(child.children()[0].argv(), ["-n"]);
+
+    // Dropping the tree hands every PID back
+    drop(init);
+    assert_eq!(pids.in_use(), 0);
+}
+
+#[test]
+fn test_unreaped_zombies_stay_visible() {
+    let pids = PidAllocator::default();
+    let mut bash = Proc::new(&pids);
+    let first = bash.fork();
+    let second = bash.fork();
+
+    // Nothing to reap while both children are alive
+    assert_eq!(bash.wait(), None);
+
+    bash.child_mut(second).unwrap().exit(3);
+    bash.child_mut(second).unwrap().exit(9);
+    bash.child_mut(first).unwrap().exit(0);
+
+    // Exited but not yet reaped: still in the table, still holding their pids
+    assert_eq!(bash.children().len(), 2);
+    assert!(bash.children().iter().all(|c| c.state() == State::Zombie));
+    assert_eq!(pids.in_use(), 3);
+
+    assert_eq!(
+        bash.wait(),
+        Some(ExitStatus {
+            pid: first,
+            code: 0
+        })
+    );
+    assert_eq!(
+        bash.wait(),
+        Some(ExitStatus {
+            pid: second,
+            code: 3
+        })
+    );
+    assert_eq!(bash.wait(), None);
+    assert!(bash.children().is_empty());
+    assert_eq!(pids.in_use(), 1);
+}
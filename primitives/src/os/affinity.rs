@@ -0,0 +1,90 @@
+use std::error::Error;
+use std::fmt;
+
+use super::{CoreId, Pid, Proc, ProcTable};
+
+// Like `sched_setaffinity(2)`, a process can be restricted to a subset of the cores: bit n of the mask allows core n.
+// Forked children inherit the mask.
+
+/// Every core allowed, the default.
+pub const ALL_CORES: u64 = u64::MAX;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AffinityError {
+    NoSuchProcess(Pid),
+    // A process allowed on no core at all could never run
+    EmptyMask,
+}
+
+impl fmt::Display for AffinityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AffinityError::NoSuchProcess(pid) => write!(f, "no process with pid {}", pid),
+            AffinityError::EmptyMask => write!(f, "affinity mask allows no cores"),
+        }
+    }
+}
+
+impl Error for AffinityError {}
+
+impl Proc {
+    pub fn affinity(&self) -> u64 {
+        self.affinity
+    }
+
+    /// Whether the affinity mask lets the process run on `core`. Only the first 64 cores can be named in a mask.
+    pub fn allows_core(&self, core: CoreId) -> bool {
+        core < 64 && self.affinity & (1 << core) != 0
+    }
+}
+
+impl ProcTable {
+    /// Restricts `pid` to the cores set in `mask`. A process already running elsewhere moves the next time it is
+    /// placed on a core.
+    pub fn set_affinity(&mut self, pid: Pid, mask: u64) -> Result<(), AffinityError> {
+        if mask == 0 {
+            return Err(AffinityError::EmptyMask);
+        }
+        let proc = self.get_mut(pid).ok_or(AffinityError::NoSuchProcess(pid))?;
+        proc.affinity = mask;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_pinned_proc_never_runs_on_another_core() {
+    use super::Cpu;
+
+    let pids = super::PidAllocator::default();
+    let (mut table, procs) = super::table::flat_table(&pids, 3);
+    let pinned = procs[0];
+    assert_eq!(table.set_affinity(pinned, 0), Err(AffinityError::EmptyMask));
+    assert_eq!(
+        table.set_affinity(Pid(999), 1),
+        Err(AffinityError::NoSuchProcess(Pid(999)))
+    );
+    table.set_affinity(pinned, 0b10).unwrap();
+    let child = table.get_mut(pinned).unwrap().fork();
+    assert_eq!(table.get(child).unwrap().affinity(), 0b10);
+
+    // Rotate the three processes over two cores, most recently released first, so each would drift across both
+    let mut cpu = Cpu::new(2);
+    let mut order = procs.clone();
+    for _ in 0..20 {
+        for &pid in &order {
+            let core = cpu.assign(table.get_mut(pid).unwrap());
+            if pid == pinned {
+                assert_ne!(core, Some(0));
+            }
+        }
+        order.rotate_left(1);
+        let released = order[0];
+        cpu.release(released);
+        table
+            .get_mut(released)
+            .unwrap()
+            .set_state(super::State::Stopped);
+    }
+    assert!(cpu.load_report()[1].dispatches > 0);
+    assert_ne!(cpu.running_on(0), Some(pinned));
+}
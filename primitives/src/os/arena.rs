@@ -0,0 +1,247 @@
+use std::mem;
+
+use super::{Proc, ReparentError};
+
+// A Proc owns its children, which makes the tree easy to drop but hard to rearrange, and a tree of borrowed children
+// ties every node to the lifetime of the ones below it. An arena sidesteps both: it owns every process in one flat
+// Vec, and the tree is just indices into it, kept in each slot as a parent and a list of children. Nothing borrows
+// anything, so any process can be changed, moved or removed while handles to the others are held.
+//
+// A slot is reused once its process is removed, so handles carry the slot's generation as well as its index: a handle
+// to a removed process never finds whatever moved into the slot after it.
+
+/// Handle to a process in a `ProcArena`. Only meaningful for the arena it came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ProcId {
+    index: usize,
+    generation: u32,
+}
+
+#[derive(Debug)]
+struct Node {
+    proc: Proc,
+    parent: Option<ProcId>,
+    children: Vec<ProcId>,
+}
+
+#[derive(Debug)]
+struct Slot {
+    generation: u32,
+    node: Option<Node>,
+}
+
+/// Process trees stored flat, linked by `ProcId`s rather than ownership. Can hold several trees at once.
+#[derive(Debug, Default)]
+pub struct ProcArena {
+    slots: Vec<Slot>,
+    free: Vec<usize>, // Empty slots, to fill before growing
+    len: usize,
+}
+
+impl ProcArena {
+    pub fn new() -> Self {
+        ProcArena::default()
+    }
+
+    /// Adds `proc` as the root of a new tree. Any children it owns are moved into the arena below it.
+    pub fn insert(&mut self, proc: Proc) -> ProcId {
+        self.insert_node(None, proc)
+    }
+
+    /// Adds `proc` (and any children it owns) as the last child of `parent`. `None` if `parent` isn't in the arena.
+    pub fn insert_child(&mut self, parent: ProcId, proc: Proc) -> Option<ProcId> {
+        self.node(parent)?;
+        let id = self.insert_node(Some(parent), proc);
+        self.node_mut(parent)
+            .expect("checked above")
+            .children
+            .push(id);
+        Some(id)
+    }
+
+    fn insert_node(&mut self, parent: Option<ProcId>, mut proc: Proc) -> ProcId {
+        let owned = mem::take(&mut proc.children);
+        let node = Node {
+            proc,
+            parent,
+            children: Vec::new(),
+        };
+        let id = match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index];
+                slot.node = Some(node);
+                ProcId {
+                    index,
+                    generation: slot.generation,
+                }
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    node: Some(node),
+                });
+                ProcId {
+                    index: self.slots.len() - 1,
+                    generation: 0,
+                }
+            }
+        };
+        self.len += 1;
+        for child in owned {
+            let child = self.insert_node(Some(id), child);
+            self.node_mut(id)
+                .expect("just inserted")
+                .children
+                .push(child);
+        }
+        id
+    }
+
+    /// Takes the process out of the arena with its whole subtree, which it owns again as ordinary children. Handles to
+    /// any of them stop working.
+    pub fn remove(&mut self, id: ProcId) -> Option<Proc> {
+        let parent = self.node(id)?.parent;
+        if let Some(parent) = parent {
+            let siblings = &mut self
+                .node_mut(parent)
+                .expect("parents outlive children")
+                .children;
+            siblings.retain(|&child| child != id);
+        }
+        Some(self.take_subtree(id))
+    }
+
+    fn take_subtree(&mut self, id: ProcId) -> Proc {
+        let slot = &mut self.slots[id.index];
+        let node = slot.node.take().expect("linked nodes are present");
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(id.index);
+        self.len -= 1;
+        let mut proc = node.proc;
+        for child in node.children {
+            let child = self.take_subtree(child);
+            proc.add_child(child);
+        }
+        proc
+    }
+
+    /// Moves `id`, with its subtree, under `new_parent`. Refused if `new_parent` is `id` or one of its descendants.
+    pub fn reparent(&mut self, id: ProcId, new_parent: ProcId) -> Result<(), ReparentError> {
+        if self.node(id).is_none() || self.node(new_parent).is_none() {
+            return Err(ReparentError::NotFound);
+        }
+        // Following parent pointers up from the new parent finds `id` only if the new parent is inside its subtree
+        let mut up = Some(new_parent);
+        while let Some(ancestor) = up {
+            if ancestor == id {
+                return Err(ReparentError::Cycle);
+            }
+            up = self.parent(ancestor);
+        }
+        if let Some(old) = self.parent(id) {
+            self.node_mut(old)
+                .expect("parents outlive children")
+                .children
+                .retain(|&child| child != id);
+        }
+        self.node_mut(id).expect("checked above").parent = Some(new_parent);
+        self.node_mut(new_parent)
+            .expect("checked above")
+            .children
+            .push(id);
+        Ok(())
+    }
+
+    pub fn get(&self, id: ProcId) -> Option<&Proc> {
+        self.node(id).map(|node| &node.proc)
+    }
+
+    /// The process itself. Its own `children` are separate from its children in the arena: anything it `fork`s
+    /// stays owned by it until `remove` hands the two back together.
+    pub fn get_mut(&mut self, id: ProcId) -> Option<&mut Proc> {
+        self.node_mut(id).map(|node| &mut node.proc)
+    }
+
+    /// `None` for a root, or for a handle no longer in the arena.
+    pub fn parent(&self, id: ProcId) -> Option<ProcId> {
+        self.node(id)?.parent
+    }
+
+    /// In insertion order. Empty for a handle no longer in the arena.
+    pub fn children(&self, id: ProcId) -> &[ProcId] {
+        self.node(id).map_or(&[], |node| &node.children)
+    }
+
+    /// Number of processes, across all trees.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn node(&self, id: ProcId) -> Option<&Node> {
+        let slot = self.slots.get(id.index)?;
+        slot.node
+            .as_ref()
+            .filter(|_| slot.generation == id.generation)
+    }
+
+    fn node_mut(&mut self, id: ProcId) -> Option<&mut Node> {
+        let slot = self.slots.get_mut(id.index)?;
+        slot.node
+            .as_mut()
+            .filter(|_| slot.generation == id.generation)
+    }
+}
+
+#[test]
+fn test_arena_links_both_ways() {
+    use super::{PidAllocator, State};
+
+    let pids = PidAllocator::default();
+    let named = |name| Proc::builder(&pids).name(name).build().unwrap();
+    let mut arena = ProcArena::new();
+    let init = arena.insert(named("init"));
+    let cron = arena.insert_child(init, named("cron")).unwrap();
+    let rsyslogd = arena.insert_child(init, named("rsyslogd")).unwrap();
+    let bash = arena.insert_child(rsyslogd, named("bash")).unwrap();
+    assert_eq!(arena.len(), 4);
+    assert_eq!(arena.children(init), [cron, rsyslogd]);
+    assert_eq!(arena.parent(bash), Some(rsyslogd));
+    assert_eq!(arena.parent(init), None);
+
+    // Change one process while holding handles to the others
+    arena.get_mut(bash).unwrap().set_state(State::Running);
+    assert_eq!(arena.get(bash).unwrap().state(), State::Running);
+
+    arena.reparent(bash, cron).unwrap();
+    assert_eq!(arena.children(rsyslogd), []);
+    assert_eq!(arena.parent(bash), Some(cron));
+    assert_eq!(arena.reparent(cron, bash), Err(ReparentError::Cycle));
+    assert_eq!(arena.reparent(init, init), Err(ReparentError::Cycle));
+
+    // Removing hands the subtree back as an owned tree
+    let cron_tree = arena.remove(cron).unwrap();
+    assert_eq!(cron_tree.children()[0].name(), "bash");
+    assert_eq!(arena.len(), 2);
+    assert_eq!(arena.children(init), [rsyslogd]);
+    assert!(arena.get(bash).is_none());
+    assert_eq!(arena.reparent(bash, init), Err(ReparentError::NotFound));
+    assert!(arena.insert_child(cron, named("logrotate")).is_none());
+
+    // Putting an owned tree back brings its children along, into reused slots with fresh handles
+    let cron = arena.insert_child(rsyslogd, cron_tree).unwrap();
+    let bash_again = arena.children(cron)[0];
+    assert_ne!(bash_again, bash);
+    assert!(arena.get(bash).is_none());
+    assert_eq!(arena.get(bash_again).unwrap().name(), "bash");
+    assert_eq!(arena.get(bash_again).unwrap().state(), State::Running);
+    assert!(arena.get(cron).unwrap().children().is_empty());
+    assert_eq!(arena.len(), 4);
+
+    drop(arena.remove(init));
+    assert!(arena.is_empty());
+    assert_eq!(pids.in_use(), 0);
+}
@@ -0,0 +1,277 @@
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use super::json::{FromJson, Json, JsonError, ToJson};
+use super::{Pid, Proc, ProcTable, Signal, State};
+
+// Processes note their own lifecycle events as they happen, but have no idea what time it is. Whoever owns the clock
+// (normally the simulation) collects the notes every tick and stamps them into an AuditLog.
+
+/// Something that happened to a process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditEvent {
+    Created { parent: Option<Pid> },
+    StateChange { from: State, to: State },
+    // A signal taking effect, logged before whatever it changed
+    Signal(Signal),
+    Exit { code: i32 },
+}
+
+impl fmt::Display for AuditEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuditEvent::Created { parent: None } => write!(f, "created"),
+            AuditEvent::Created {
+                parent: Some(parent),
+            } => write!(f, "created by {}", parent),
+            AuditEvent::StateChange { from, to } => write!(f, "{:?} -> {:?}", from, to),
+            AuditEvent::Signal(sig) => write!(f, "signal {}", sig),
+            AuditEvent::Exit { code } => write!(f, "exit {}", code),
+        }
+    }
+}
+
+/// One line of the audit log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuditRecord {
+    pub tick: u64,
+    pub pid: Pid,
+    pub event: AuditEvent,
+}
+
+impl fmt::Display for AuditRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.tick, self.pid, self.event)
+    }
+}
+
+/// Append-only record of process lifecycle events, oldest first.
+#[derive(Debug, Default)]
+pub struct AuditLog {
+    records: Vec<AuditRecord>,
+}
+
+impl AuditLog {
+    pub fn new() -> Self {
+        AuditLog::default()
+    }
+
+    /// Moves every event noted by processes in `table` since the last collection into the log, stamped with `now`.
+    pub fn collect(&mut self, now: u64, table: &mut ProcTable) {
+        let records = &mut self.records;
+        table.for_each_mut(&mut |proc| {
+            records.extend(proc.audit.drain(..).map(|(pid, event)| AuditRecord {
+                tick: now,
+                pid,
+                event,
+            }));
+        });
+    }
+
+    pub fn records(&self) -> &[AuditRecord] {
+        &self.records
+    }
+
+    /// Everything that happened to `pid`, oldest first.
+    pub fn for_pid(&self, pid: Pid) -> impl Iterator<Item = &AuditRecord> + '_ {
+        self.records.iter().filter(move |r| r.pid == pid)
+    }
+
+    /// The whole log as text, one `tick pid event` line per record.
+    pub fn export(&self) -> String {
+        self.records.iter().map(|r| format!("{}\n", r)).collect()
+    }
+}
+
+impl Proc {
+    // Notes an event for the next `AuditLog::collect`. Events are tagged with a PID so a parent can hold on to those
+    // of a child it just reaped.
+    pub(super) fn note(&mut self, event: AuditEvent) {
+        self.audit.push((self.pid(), event));
+    }
+
+    // Every state change goes through here so that none escape the log.
+    pub(super) fn change_state(&mut self, to: State) {
+        if self.state != to {
+            self.note(AuditEvent::StateChange {
+                from: self.state,
+                to,
+            });
+            self.state = to;
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ToJson for AuditEvent {
+    fn to_json(&self) -> Json {
+        let (variant, data) = match *self {
+            AuditEvent::Created { parent } => (
+                "Created",
+                Json::object(vec![("parent", parent.map_or(Json::Null, |p| p.to_json()))]),
+            ),
+            AuditEvent::StateChange { from, to } => (
+                "StateChange",
+                Json::object(vec![("from", from.to_json()), ("to", to.to_json())]),
+            ),
+            AuditEvent::Signal(sig) => ("Signal", sig.to_json()),
+            AuditEvent::Exit { code } => {
+                ("Exit", Json::object(vec![("code", Json::Int(code.into()))]))
+            }
+        };
+        Json::object(vec![(variant, data)])
+    }
+}
+
+#[cfg(feature = "serde")]
+impl FromJson for AuditEvent {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        let Json::Object(fields) = json else {
+            return Err(JsonError::Field("variant"));
+        };
+        let [(variant, data)] = fields.as_slice() else {
+            return Err(JsonError::Field("variant"));
+        };
+        match variant.as_str() {
+            "Created" => Ok(AuditEvent::Created {
+                parent: data.opt_pid("parent")?,
+            }),
+            "StateChange" => Ok(AuditEvent::StateChange {
+                from: data.parse("from")?,
+                to: data.parse("to")?,
+            }),
+            "Signal" => Ok(AuditEvent::Signal(Signal::from_json(data)?)),
+            "Exit" => Ok(AuditEvent::Exit {
+                code: data.int("code")?,
+            }),
+            _ => Err(JsonError::Field("variant")),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ToJson for AuditRecord {
+    fn to_json(&self) -> Json {
+        Json::object(vec![
+            ("tick", Json::Int(self.tick.into())),
+            ("pid", self.pid.to_json()),
+            ("event", self.event.to_json()),
+        ])
+    }
+}
+
+#[cfg(feature = "serde")]
+impl FromJson for AuditRecord {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        Ok(AuditRecord {
+            tick: json.int("tick")?,
+            pid: json.parse("pid")?,
+            event: json.parse("event")?,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ToJson for AuditLog {
+    fn to_json(&self) -> Json {
+        Json::Array(self.records.iter().map(AuditRecord::to_json).collect())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl FromJson for AuditLog {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        let Json::Array(records) = json else {
+            return Err(JsonError::Field("audit"));
+        };
+        Ok(AuditLog {
+            records: records
+                .iter()
+                .map(AuditRecord::from_json)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+// Events noted by processes in `table` but not collected yet, each with the process holding it. `Proc::to_json`
+// leaves these out, so a saved simulation keeps them here; given back to the same holders, they are collected in the
+// same order as they would have been.
+#[cfg(feature = "serde")]
+pub(super) fn uncollected_to_json(table: &ProcTable) -> Json {
+    let mut notes = Vec::new();
+    for proc in table.iter() {
+        notes.extend(proc.audit.iter().map(|(pid, event)| {
+            Json::object(vec![
+                ("holder", proc.pid().to_json()),
+                ("pid", pid.to_json()),
+                ("event", event.to_json()),
+            ])
+        }));
+    }
+    Json::Array(notes)
+}
+
+// Hands the notes written by `uncollected_to_json` back to the processes that held them.
+#[cfg(feature = "serde")]
+pub(super) fn uncollected_from_json(json: &Json, table: &mut ProcTable) -> Result<(), JsonError> {
+    let Json::Array(notes) = json else {
+        return Err(JsonError::Field("uncollected"));
+    };
+    for note in notes {
+        let holder: Pid = note.parse("holder")?;
+        let proc = table.get_mut(holder).ok_or(JsonError::Field("holder"))?;
+        proc.audit.push((note.parse("pid")?, note.parse("event")?));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_lifecycle_is_logged_with_ticks() {
+    use super::{RoundRobinScheduler, Simulation};
+
+    let pids = super::PidAllocator::default();
+    let mut table = ProcTable::new(Proc::new(&pids));
+    let init = table.init().pid();
+    let shell = table.init_mut().fork();
+    let mut sim = Simulation::new(RoundRobinScheduler::new(5), table);
+    sim.admit(shell);
+    sim.run(2);
+    sim.proc_mut(shell).unwrap().send_signal(Signal::Term);
+    sim.proc_mut(shell).unwrap().deliver_signals();
+    sim.step();
+
+    let events: Vec<_> = sim
+        .audit()
+        .for_pid(shell)
+        .map(|r| (r.tick, r.event))
+        .collect();
+    assert_eq!(
+        events,
+        vec![
+            (0, AuditEvent::Created { parent: Some(init) }),
+            (
+                0,
+                AuditEvent::StateChange {
+                    from: State::Stopped,
+                    to: State::Running
+                }
+            ),
+            (2, AuditEvent::Signal(Signal::Term)),
+            (
+                2,
+                AuditEvent::StateChange {
+                    from: State::Running,
+                    to: State::Zombie
+                }
+            ),
+            (2, AuditEvent::Exit { code: 143 }),
+        ]
+    );
+
+    // Reaping is logged against the child even though it is gone from the table by the time the log is collected
+    let mut table = sim.into_table();
+    table.init_mut().wait();
+    let mut log = AuditLog::new();
+    log.collect(9, &mut table);
+    assert_eq!(log.export(), format!("9 {} Zombie -> Terminated\n", shell));
+}
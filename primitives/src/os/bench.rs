@@ -0,0 +1,136 @@
+use std::fmt;
+
+use super::sim::{Scheduler, Simulation};
+use super::{
+    CgroupScheduler, CgroupTree, Job, LotteryScheduler, MlfqScheduler, PidAllocator, Proc,
+    ProcTable, RoundRobinScheduler,
+};
+
+// Runs one workload under each scheduler on a fresh machine and lines up what the statistics collector saw, to show
+// the trade-offs: round robin's short waits against its context switches, MLFQ favouring short and I/O-bound jobs,
+// and so on.
+
+/// How one scheduler did on the workload.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchRow {
+    pub scheduler: &'static str,
+    pub avg_wait: f64,
+    pub avg_turnaround: Option<f64>, // Over the jobs that finished, None if none did
+    pub context_switches: u64,
+    pub unfinished: usize, // Jobs still going when the run hit its tick limit
+}
+
+/// The rows of a benchmark, one per scheduler in registration order. Displays as a plain-text table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchTable {
+    pub rows: Vec<BenchRow>,
+}
+
+impl fmt::Display for BenchTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .rows
+            .iter()
+            .map(|row| row.scheduler.len())
+            .chain(["scheduler".len()])
+            .max()
+            .unwrap_or(0);
+        writeln!(
+            f,
+            "{:<width$}  {:>8}  {:>10}  {:>8}  {:>10}",
+            "scheduler", "avg wait", "turnaround", "switches", "unfinished"
+        )?;
+        for row in &self.rows {
+            let turnaround = row
+                .avg_turnaround
+                .map_or_else(|| "-".to_string(), |t| format!("{:.1}", t));
+            writeln!(
+                f,
+                "{:<width$}  {:>8.1}  {:>10}  {:>8}  {:>10}",
+                row.scheduler, row.avg_wait, turnaround, row.context_switches, row.unfinished
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Every scheduler the simulator ships, with settings that suit short demo workloads. The lottery draws from a
+/// fixed seed so the results are reproducible.
+pub fn registered_schedulers() -> Vec<(&'static str, Box<dyn Scheduler>)> {
+    vec![
+        ("round robin", Box::new(RoundRobinScheduler::new(2))),
+        ("mlfq", Box::new(MlfqScheduler::new(&[1, 2, 4], 20))),
+        ("lottery", Box::new(LotteryScheduler::new(1))),
+        ("cgroup", Box::new(CgroupScheduler::new(CgroupTree::new()))),
+    ]
+}
+
+/// Runs `jobs` under each of `registered_schedulers`, giving each run at most `max_ticks`.
+pub fn bench_schedulers(jobs: &[Job], max_ticks: u64) -> BenchTable {
+    bench(registered_schedulers(), jobs, max_ticks)
+}
+
+/// Runs `jobs` under each of `schedulers` in turn, each on its own empty process table.
+pub fn bench(
+    schedulers: Vec<(&'static str, Box<dyn Scheduler>)>,
+    jobs: &[Job],
+    max_ticks: u64,
+) -> BenchTable {
+    let rows = schedulers
+        .into_iter()
+        .map(|(name, scheduler)| {
+            let pids = PidAllocator::default();
+            let mut sim = Simulation::new(scheduler, ProcTable::new(Proc::new(&pids)));
+            let spawned = sim.run_workload(jobs.iter().cloned(), max_ticks);
+            let report = sim.stats().report();
+            let finished = spawned
+                .iter()
+                .filter(|&&pid| sim.table().get(pid).is_some_and(|p| p.state().is_exited()))
+                .count();
+            BenchRow {
+                scheduler: name,
+                avg_wait: report.avg_wait,
+                avg_turnaround: report.avg_turnaround,
+                context_switches: report.context_switches,
+                unfinished: jobs.len() - finished,
+            }
+        })
+        .collect();
+    BenchTable { rows }
+}
+
+#[test]
+fn test_every_scheduler_finishes_the_same_workload() {
+    use super::{WorkloadConfig, WorkloadGen};
+
+    let jobs: Vec<_> = WorkloadGen::new(5, WorkloadConfig::default())
+        .take(12)
+        .collect();
+    let table = bench_schedulers(&jobs, 2_000);
+    let names: Vec<_> = table.rows.iter().map(|row| row.scheduler).collect();
+    assert_eq!(names, ["round robin", "mlfq", "lottery", "cgroup"]);
+    for row in &table.rows {
+        assert_eq!(row.unfinished, 0, "{}", table);
+        assert!(row.avg_turnaround.is_some());
+    }
+    // Same workload, same numbers
+    assert_eq!(table, bench_schedulers(&jobs, 2_000));
+
+    // Round robin with a 1-tick quantum switches at least as often as with a long one
+    let table = bench(
+        vec![
+            ("rr 1", Box::new(RoundRobinScheduler::new(1))),
+            ("rr 50", Box::new(RoundRobinScheduler::new(50))),
+        ],
+        &jobs,
+        2_000,
+    );
+    assert!(table.rows[0].context_switches >= table.rows[1].context_switches);
+    let text = table.to_string();
+    assert!(text.starts_with("scheduler"));
+    assert_eq!(text.lines().count(), 3);
+
+    // Too few ticks to get through it all
+    let table = bench_schedulers(&jobs, 10);
+    assert!(table.rows.iter().all(|row| row.unfinished > 0));
+}
@@ -0,0 +1,199 @@
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fmt;
+
+use super::{Pid, PidAllocator, Proc, State};
+
+/// Why `ProcBuilder::build` refused to make a process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildError {
+    // The requested PID is out of range or already taken
+    PidUnavailable(Pid),
+    PidsExhausted,
+    // Only `exit` makes a process a Zombie, since it needs an exit code
+    ExitedState(State),
+    // The same PID turns up twice in the tree
+    DuplicatePid(Pid),
+    // A reaped child is no longer a child at all
+    ReapedChild(Pid),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::PidUnavailable(pid) => write!(f, "pid {} is not available", pid),
+            BuildError::PidsExhausted => write!(f, "pid space exhausted"),
+            BuildError::ExitedState(state) => {
+                write!(f, "a new process can't start out {:?}", state)
+            }
+            BuildError::DuplicatePid(pid) => write!(f, "pid {} appears twice in the tree", pid),
+            BuildError::ReapedChild(pid) => write!(f, "child {} has already been reaped", pid),
+        }
+    }
+}
+
+impl Error for BuildError {}
+
+/// Sets up a process in one expression, e.g. `Proc::builder(&pids).name("sshd").state(State::Sleeping).build()`.
+/// Anything left unset is as `Proc::new` would have it.
+#[derive(Debug)]
+pub struct ProcBuilder<'a> {
+    pids: &'a PidAllocator,
+    pid: Option<Pid>,
+    name: Cow<'static, str>,
+    state: State,
+    children: Vec<Proc>,
+}
+
+impl Proc {
+    /// A builder drawing the new process's PID from `pids`.
+    pub fn builder(pids: &PidAllocator) -> ProcBuilder<'_> {
+        ProcBuilder {
+            pids,
+            pid: None,
+            name: Cow::Borrowed(""),
+            state: State::Stopped,
+            children: Vec::new(),
+        }
+    }
+}
+
+impl ProcBuilder<'_> {
+    /// Claims this PID rather than the allocator's next free one.
+    pub fn pid(mut self, pid: Pid) -> Self {
+        self.pid = Some(pid);
+        self
+    }
+
+    /// A literal or a `String`, as for `Proc::exec`.
+    pub fn name(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// The state to start in, Stopped (ready) by default. Set directly: unlike `set_state` it needn't be reachable
+    /// from Stopped.
+    pub fn state(mut self, state: State) -> Self {
+        self.state = state;
+        self
+    }
+
+    /// Adopts already built processes as children.
+    pub fn children(mut self, children: impl IntoIterator<Item = Proc>) -> Self {
+        self.children.extend(children);
+        self
+    }
+
+    /// Checks the settings fit together and makes the process.
+    pub fn build(self) -> Result<Proc, BuildError> {
+        if self.state.is_exited() {
+            return Err(BuildError::ExitedState(self.state));
+        }
+        let mut seen = BTreeSet::new();
+        if let Some(pid) = self.pid {
+            seen.insert(pid);
+        }
+        for child in &self.children {
+            check_tree(child, &mut seen)?;
+        }
+
+        let handle = match self.pid {
+            Some(pid) => self
+                .pids
+                .reserve(pid)
+                .ok_or(BuildError::PidUnavailable(pid))?,
+            None => self.pids.alloc().ok_or(BuildError::PidsExhausted)?,
+        };
+        if self.pid.is_none() && seen.contains(&handle.pid()) {
+            return Err(BuildError::DuplicatePid(handle.pid()));
+        }
+        let mut proc = Proc::with_pid(handle);
+        proc.name = self.name;
+        proc.state = self.state;
+        proc.children = self.children.into_iter().collect();
+        Ok(proc)
+    }
+}
+
+// Records every PID in the subtree, refusing repeats and reaped processes.
+fn check_tree(proc: &Proc, seen: &mut BTreeSet<Pid>) -> Result<(), BuildError> {
+    if proc.state() == State::Terminated {
+        return Err(BuildError::ReapedChild(proc.pid()));
+    }
+    if !seen.insert(proc.pid()) {
+        return Err(BuildError::DuplicatePid(proc.pid()));
+    }
+    proc.children
+        .iter()
+        .try_for_each(|child| check_tree(child, seen))
+}
+
+#[test]
+fn test_builder_sets_everything_at_once() {
+    let pids = PidAllocator::default();
+    let bash = Proc::builder(&pids)
+        .name("bash")
+        .state(State::Sleeping)
+        .build()
+        .unwrap();
+    let bash_pid = bash.pid();
+    let sshd = Proc::builder(&pids)
+        .pid(Pid(22))
+        .name("sshd")
+        .children([bash])
+        .build()
+        .unwrap();
+    assert_eq!(sshd.pid(), Pid(22));
+    assert_eq!(sshd.name(), "sshd");
+    assert_eq!(sshd.state(), State::Stopped);
+    assert_eq!(sshd.children()[0].pid(), bash_pid);
+    assert_eq!(sshd.children()[0].state(), State::Sleeping);
+}
+
+#[test]
+fn test_builder_rejects_inconsistent_settings() {
+    let pids = PidAllocator::new(3);
+    let first = Proc::builder(&pids).build().unwrap();
+    assert_eq!(
+        Proc::builder(&pids).pid(first.pid()).build().unwrap_err(),
+        BuildError::PidUnavailable(first.pid())
+    );
+    assert_eq!(
+        Proc::builder(&pids)
+            .state(State::Zombie)
+            .build()
+            .unwrap_err(),
+        BuildError::ExitedState(State::Zombie)
+    );
+
+    // Two processes with the same PID, which takes two allocators, can't share a tree
+    let other = PidAllocator::new(3);
+    let clash = Proc::builder(&other).pid(first.pid()).build().unwrap();
+    assert_eq!(
+        Proc::builder(&pids)
+            .children([first, clash])
+            .build()
+            .unwrap_err(),
+        BuildError::DuplicatePid(Pid(1))
+    );
+
+    let mut parent = Proc::new(&pids);
+    let child = parent.fork();
+    parent
+        .child_mut(child)
+        .unwrap()
+        .set_state(State::Terminated);
+    assert_eq!(
+        Proc::builder(&pids).children([parent]).build().unwrap_err(),
+        BuildError::ReapedChild(child)
+    );
+    // Processes handed to failed builds were dropped, giving back all three PIDs
+    let _held: Vec<_> = (0..3)
+        .map(|_| Proc::builder(&pids).build().unwrap())
+        .collect();
+    assert_eq!(
+        Proc::builder(&pids).build().unwrap_err(),
+        BuildError::PidsExhausted
+    );
+}
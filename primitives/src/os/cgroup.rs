@@ -0,0 +1,549 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use super::json::{self, FromJson, Json, JsonError, ToJson};
+use super::mem::MemError;
+use super::{Pid, Proc, ProcTable, State};
+
+// Control groups, after Linux cgroups: a tree of groups, like the process tree, that processes are attached to.
+// Every group has a CPU weight and may have a memory limit, and both apply to everything below it: a group's children
+// split its share of the CPU between them, and their memory counts against its limit.
+
+pub type CgroupId = usize;
+
+/// The root group, which every process belongs to until attached elsewhere.
+pub const ROOT_CGROUP: CgroupId = 0;
+
+/// CPU weight of a new group, and of each process competing with the child groups of its own group.
+pub const DEFAULT_SHARES: u64 = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CgroupError {
+    NoSuchGroup(CgroupId),
+    NoSuchProcess(Pid),
+    // A group with no CPU weight would never be scheduled
+    ZeroShares,
+    // Allocating would take `group` past its limit
+    MemoryLimit {
+        group: CgroupId,
+        limit: u64,
+        requested: u64,
+    },
+    Mem(MemError),
+}
+
+impl fmt::Display for CgroupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CgroupError::NoSuchGroup(id) => write!(f, "no cgroup {}", id),
+            CgroupError::NoSuchProcess(pid) => write!(f, "no process with pid {}", pid),
+            CgroupError::ZeroShares => write!(f, "cpu shares must be positive"),
+            CgroupError::MemoryLimit {
+                group,
+                limit,
+                requested,
+            } => write!(
+                f,
+                "{} more pages would exceed the {}-page limit of cgroup {}",
+                requested, limit, group
+            ),
+            CgroupError::Mem(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for CgroupError {}
+
+#[derive(Debug)]
+struct Cgroup {
+    name: String,
+    parent: Option<CgroupId>,
+    children: Vec<CgroupId>,
+    shares: u64,
+    memory_limit: Option<u64>, // In pages
+}
+
+/// The group hierarchy and which group each process is in.
+#[derive(Debug)]
+pub struct CgroupTree {
+    groups: Vec<Cgroup>,
+    members: BTreeMap<Pid, CgroupId>,
+}
+
+impl Default for CgroupTree {
+    fn default() -> Self {
+        CgroupTree {
+            groups: vec![Cgroup {
+                name: "/".to_string(),
+                parent: None,
+                children: Vec::new(),
+                shares: DEFAULT_SHARES,
+                memory_limit: None,
+            }],
+            members: BTreeMap::new(),
+        }
+    }
+}
+
+impl CgroupTree {
+    /// A tree holding only the root group, with no limits.
+    pub fn new() -> Self {
+        CgroupTree::default()
+    }
+
+    /// Adds an empty group named `name` under `parent`, with default shares and no memory limit.
+    pub fn create(&mut self, parent: CgroupId, name: &str) -> Result<CgroupId, CgroupError> {
+        self.group(parent)?;
+        let id = self.groups.len();
+        self.groups.push(Cgroup {
+            name: name.to_string(),
+            parent: Some(parent),
+            children: Vec::new(),
+            shares: DEFAULT_SHARES,
+            memory_limit: None,
+        });
+        self.groups[parent].children.push(id);
+        Ok(id)
+    }
+
+    /// Full path of the group, like `/batch/nightly`.
+    pub fn path(&self, id: CgroupId) -> Option<String> {
+        let group = self.groups.get(id)?;
+        Some(match group.parent {
+            None => group.name.clone(),
+            Some(ROOT_CGROUP) => format!("/{}", group.name),
+            Some(parent) => format!("{}/{}", self.path(parent)?, group.name),
+        })
+    }
+
+    /// Sets the group's CPU weight relative to its siblings (and to processes attached directly to its parent).
+    pub fn set_cpu_shares(&mut self, id: CgroupId, shares: u64) -> Result<(), CgroupError> {
+        if shares == 0 {
+            return Err(CgroupError::ZeroShares);
+        }
+        self.group_mut(id)?.shares = shares;
+        Ok(())
+    }
+
+    /// Caps the resident pages of everything in the group and below it. `None` lifts the cap.
+    pub fn set_memory_limit(
+        &mut self,
+        id: CgroupId,
+        pages: Option<u64>,
+    ) -> Result<(), CgroupError> {
+        self.group_mut(id)?.memory_limit = pages;
+        Ok(())
+    }
+
+    /// Moves `pid` into group `id`. Memory it already holds moves with it, even past the new group's limit; only
+    /// later allocations are refused.
+    pub fn attach(&mut self, pid: Pid, id: CgroupId) -> Result<(), CgroupError> {
+        self.group(id)?;
+        if id == ROOT_CGROUP {
+            self.members.remove(&pid);
+        } else {
+            self.members.insert(pid, id);
+        }
+        Ok(())
+    }
+
+    /// Group `pid` belongs to; the root unless it was attached elsewhere.
+    pub fn group_of(&self, pid: Pid) -> CgroupId {
+        self.members.get(&pid).copied().unwrap_or(ROOT_CGROUP)
+    }
+
+    /// Resident pages of every process in the group or below it.
+    pub fn memory_usage(&self, id: CgroupId, table: &ProcTable) -> u64 {
+        table
+            .iter()
+            .filter(|p| self.contains(id, self.group_of(p.pid())))
+            .map(|p| p.memory().resident)
+            .sum()
+    }
+
+    /// `Proc::alloc`, but refused if it would take the process's group, or any group above it, over its limit.
+    pub fn alloc(&self, table: &mut ProcTable, pid: Pid, pages: u64) -> Result<(), CgroupError> {
+        if table.get(pid).is_none() {
+            return Err(CgroupError::NoSuchProcess(pid));
+        }
+        let mut at = Some(self.group_of(pid));
+        while let Some(id) = at {
+            let group = &self.groups[id];
+            if let Some(limit) = group.memory_limit {
+                if self.memory_usage(id, table) + pages > limit {
+                    return Err(CgroupError::MemoryLimit {
+                        group: id,
+                        limit,
+                        requested: pages,
+                    });
+                }
+            }
+            at = group.parent;
+        }
+        let proc = table.get_mut(pid).unwrap();
+        proc.alloc(pages).map_err(CgroupError::Mem)
+    }
+
+    // Whether `id` is `ancestor` or somewhere below it.
+    fn contains(&self, ancestor: CgroupId, mut id: CgroupId) -> bool {
+        loop {
+            if id == ancestor {
+                return true;
+            }
+            match self.groups[id].parent {
+                Some(parent) => id = parent,
+                None => return false,
+            }
+        }
+    }
+
+    // Fraction of the CPU task `tid` is entitled to while exactly the tasks in `runnable` compete for it. At each
+    // level a group's share is split between the processes attached to it directly, each weighing DEFAULT_SHARES,
+    // and its child groups that have anything runnable, each weighing its own shares.
+    fn cpu_fraction(&self, tid: Pid, runnable: &[Pid], table: &ProcTable) -> f64 {
+        let group_of_task = |tid: Pid| table.owner_of(tid).map(|p| self.group_of(p.pid()));
+        let mut direct = vec![0u64; self.groups.len()];
+        for &task in runnable {
+            if let Some(id) = group_of_task(task) {
+                direct[id] += 1;
+            }
+        }
+        let active =
+            |id: CgroupId| (0..self.groups.len()).any(|g| direct[g] > 0 && self.contains(id, g));
+        let active_weight = |id: CgroupId| {
+            let tasks = direct[id] * DEFAULT_SHARES;
+            let groups: u64 = self.groups[id]
+                .children
+                .iter()
+                .filter(|&&child| active(child))
+                .map(|&child| self.groups[child].shares)
+                .sum();
+            (tasks + groups) as f64
+        };
+
+        let Some(mut id) = group_of_task(tid) else {
+            return 0.0;
+        };
+        let mut fraction = DEFAULT_SHARES as f64 / active_weight(id);
+        while let Some(parent) = self.groups[id].parent {
+            fraction *= self.groups[id].shares as f64 / active_weight(parent);
+            id = parent;
+        }
+        fraction
+    }
+
+    fn group(&self, id: CgroupId) -> Result<&Cgroup, CgroupError> {
+        self.groups.get(id).ok_or(CgroupError::NoSuchGroup(id))
+    }
+
+    fn group_mut(&mut self, id: CgroupId) -> Result<&mut Cgroup, CgroupError> {
+        self.groups.get_mut(id).ok_or(CgroupError::NoSuchGroup(id))
+    }
+}
+
+/// Shares the CPU according to a cgroup tree, one tick at a time. Each task accumulates virtual time at a rate
+/// inversely proportional to its entitlement, and the runnable task furthest behind runs next, as in Linux's CFS.
+#[derive(Debug)]
+pub struct CgroupScheduler {
+    cgroups: CgroupTree,
+    tasks: Vec<Pid>,
+    vruntime: BTreeMap<Pid, f64>,
+    current: Option<Pid>,
+}
+
+impl CgroupScheduler {
+    pub fn new(cgroups: CgroupTree) -> Self {
+        CgroupScheduler {
+            cgroups,
+            tasks: Vec::new(),
+            vruntime: BTreeMap::new(),
+            current: None,
+        }
+    }
+
+    pub fn cgroups(&self) -> &CgroupTree {
+        &self.cgroups
+    }
+
+    pub fn cgroups_mut(&mut self) -> &mut CgroupTree {
+        &mut self.cgroups
+    }
+
+    /// Adds `proc` and its threads. They start level with the task furthest behind, so they can't claim a backlog.
+    pub fn admit(&mut self, proc: &mut Proc) {
+        proc.set_state(State::Stopped);
+        let start = self
+            .vruntime
+            .values()
+            .copied()
+            .fold(None, |min: Option<f64>, v| {
+                Some(min.map_or(v, |m| m.min(v)))
+            });
+        for tid in proc.task_ids() {
+            self.vruntime.insert(tid, start.unwrap_or(0.0));
+            self.tasks.push(tid);
+        }
+    }
+
+    /// Runs the runnable task with the least virtual time (the earliest admitted on a tie) for one tick.
+    pub fn tick(&mut self, table: &mut ProcTable) -> Option<Pid> {
+        if let Some(pid) = self.current.take() {
+            if table.task_state(pid) == Some(State::Running) {
+                table.set_task_state(pid, State::Stopped);
+            }
+        }
+        let vruntime = &mut self.vruntime;
+        self.tasks.retain(|&tid| {
+            let alive = table.task_state(tid).is_some_and(|s| !s.is_exited());
+            if !alive {
+                vruntime.remove(&tid);
+            }
+            alive
+        });
+
+        let runnable: Vec<Pid> = self
+            .tasks
+            .iter()
+            .copied()
+            .filter(|&tid| table.task_runnable(tid))
+            .collect();
+        let &next = runnable
+            .iter()
+            .min_by(|a, b| self.vruntime[a].total_cmp(&self.vruntime[b]))?;
+        let fraction = self.cgroups.cpu_fraction(next, &runnable, table);
+        *self.vruntime.get_mut(&next).unwrap() += 1.0 / fraction;
+        table.set_task_state(next, State::Running);
+        self.current = Some(next);
+        Some(next)
+    }
+
+    /// Forgets `tasks` and their virtual time, e.g. for a process moving to another core. One of them holding the CPU
+    /// gives it up and goes back to Stopped.
+    pub fn remove(&mut self, table: &mut ProcTable, tasks: &[Pid]) {
+        self.tasks.retain(|tid| !tasks.contains(tid));
+        self.vruntime.retain(|tid, _| !tasks.contains(tid));
+        if let Some(pid) = self.current.filter(|pid| tasks.contains(pid)) {
+            self.current = None;
+            if table.task_state(pid) == Some(State::Running) {
+                table.set_task_state(pid, State::Stopped);
+            }
+        }
+    }
+
+    /// Every other task, least virtual time first (the earliest admitted on a tie), as `tick` would choose them.
+    pub fn queued(&self) -> Vec<Pid> {
+        let mut queued: Vec<Pid> = self
+            .tasks
+            .iter()
+            .copied()
+            .filter(|&tid| self.current != Some(tid))
+            .collect();
+        queued.sort_by(|a, b| self.vruntime[a].total_cmp(&self.vruntime[b]));
+        queued
+    }
+
+    /// Nothing to requeue: woken tasks simply compete on their virtual time again.
+    pub fn wake(&mut self, table: &mut ProcTable, woken: &[Pid]) {
+        for &pid in woken.iter().filter(|&&pid| self.current != Some(pid)) {
+            if table.task_state(pid) == Some(State::Running) {
+                table.set_task_state(pid, State::Stopped);
+            }
+        }
+    }
+}
+
+// Groups are written in id order with their parents; `children` follows from that, as groups only ever get added.
+#[cfg(feature = "serde")]
+impl ToJson for CgroupTree {
+    fn to_json(&self) -> Json {
+        let groups = self
+            .groups
+            .iter()
+            .map(|group| {
+                Json::object(vec![
+                    ("name", Json::Str(group.name.clone())),
+                    ("parent", json::opt_int(group.parent.map(|id| id as u64))),
+                    ("shares", Json::Int(group.shares.into())),
+                    ("memory_limit", json::opt_int(group.memory_limit)),
+                ])
+            })
+            .collect();
+        let members = self
+            .members
+            .iter()
+            .map(|(pid, &id)| {
+                Json::object(vec![
+                    ("pid", pid.to_json()),
+                    ("group", Json::Int(id as i128)),
+                ])
+            })
+            .collect();
+        Json::object(vec![
+            ("groups", Json::Array(groups)),
+            ("members", Json::Array(members)),
+        ])
+    }
+}
+
+#[cfg(feature = "serde")]
+impl FromJson for CgroupTree {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        let mut groups: Vec<Cgroup> = Vec::new();
+        for group in json.array("groups")? {
+            let parent: Option<CgroupId> = group.opt_int("parent")?;
+            let shares = group.int("shares")?;
+            // Only the root has no parent, and parents come before their children
+            if parent.is_none() != groups.is_empty() || parent.is_some_and(|id| id >= groups.len())
+            {
+                return Err(JsonError::Field("parent"));
+            }
+            if shares == 0 {
+                return Err(JsonError::Field("shares"));
+            }
+            let id = groups.len();
+            if let Some(parent) = parent {
+                groups[parent].children.push(id);
+            }
+            groups.push(Cgroup {
+                name: group.string("name")?.to_string(),
+                parent,
+                children: Vec::new(),
+                shares,
+                memory_limit: group.opt_int("memory_limit")?,
+            });
+        }
+        if groups.is_empty() {
+            return Err(JsonError::Field("groups"));
+        }
+        let mut members = BTreeMap::new();
+        for member in json.array("members")? {
+            let id: CgroupId = member.int("group")?;
+            if id >= groups.len() {
+                return Err(JsonError::Field("group"));
+            }
+            members.insert(member.parse("pid")?, id);
+        }
+        Ok(CgroupTree { groups, members })
+    }
+}
+
+// Virtual times are saved as their bit patterns, as JSON here has no fractions and a resumed scheduler has to compare
+// them exactly as before.
+#[cfg(feature = "serde")]
+impl ToJson for CgroupScheduler {
+    fn to_json(&self) -> Json {
+        let vruntime = self
+            .vruntime
+            .iter()
+            .map(|(pid, v)| {
+                Json::object(vec![
+                    ("pid", pid.to_json()),
+                    ("bits", Json::Int(v.to_bits().into())),
+                ])
+            })
+            .collect();
+        Json::object(vec![
+            ("cgroups", self.cgroups.to_json()),
+            ("tasks", json::pid_list(&self.tasks)),
+            ("vruntime", Json::Array(vruntime)),
+            ("current", json::opt_int(self.current.map(|pid| pid.0))),
+        ])
+    }
+}
+
+#[cfg(feature = "serde")]
+impl FromJson for CgroupScheduler {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        let mut vruntime = BTreeMap::new();
+        for v in json.array("vruntime")? {
+            vruntime.insert(v.parse("pid")?, f64::from_bits(v.int("bits")?));
+        }
+        let tasks = json.pids("tasks")?;
+        if tasks.iter().any(|tid| !vruntime.contains_key(tid)) {
+            return Err(JsonError::Field("vruntime"));
+        }
+        Ok(CgroupScheduler {
+            cgroups: json.parse("cgroups")?,
+            tasks,
+            vruntime,
+            current: json.opt_pid("current")?,
+        })
+    }
+}
+
+#[test]
+fn test_cpu_is_split_by_group_not_by_process() {
+    let pids = super::PidAllocator::default();
+    let (mut table, procs) = super::table::flat_table(&pids, 4);
+    let mut cgroups = CgroupTree::new();
+    let web = cgroups.create(ROOT_CGROUP, "web").unwrap();
+    let batch = cgroups.create(ROOT_CGROUP, "batch").unwrap();
+    let nightly = cgroups.create(batch, "nightly").unwrap();
+    assert_eq!(cgroups.path(nightly).unwrap(), "/batch/nightly");
+    assert_eq!(cgroups.set_cpu_shares(web, 0), Err(CgroupError::ZeroShares));
+    cgroups.set_cpu_shares(web, 2048).unwrap();
+    // web gets twice what batch gets, however many processes batch (and its subgroup) runs
+    cgroups.attach(procs[0], web).unwrap();
+    cgroups.attach(procs[1], batch).unwrap();
+    cgroups.attach(procs[2], batch).unwrap();
+    cgroups.attach(procs[3], nightly).unwrap();
+
+    let mut sched = CgroupScheduler::new(cgroups);
+    for &pid in &procs {
+        sched.admit(table.get_mut(pid).unwrap());
+    }
+    let mut ticks = [0; 4];
+    for _ in 0..300 {
+        let pid = sched.tick(&mut table).unwrap();
+        ticks[procs.iter().position(|&p| p == pid).unwrap()] += 1;
+    }
+    // Give or take a tick where virtual times tie
+    assert!((199..=201).contains(&ticks[0]), "{:?}", ticks);
+    // Within batch, two processes and the nightly group each get a third
+    for count in &ticks[1..] {
+        assert!((32..=35).contains(count), "{:?}", ticks);
+    }
+
+    // Once web's process is gone, batch has the CPU to itself
+    table.get_mut(procs[0]).unwrap().exit(0);
+    let ran: Vec<_> = (0..30).filter_map(|_| sched.tick(&mut table)).collect();
+    assert!(!ran.contains(&procs[0]));
+    assert_eq!(ran.len(), 30);
+}
+
+#[test]
+fn test_memory_limits_apply_to_the_whole_subtree() {
+    let pids = super::PidAllocator::default();
+    let (mut table, procs) = super::table::flat_table(&pids, 2);
+    let mut cgroups = CgroupTree::new();
+    let batch = cgroups.create(ROOT_CGROUP, "batch").unwrap();
+    let nightly = cgroups.create(batch, "nightly").unwrap();
+    cgroups.set_memory_limit(batch, Some(10)).unwrap();
+    cgroups.attach(procs[0], batch).unwrap();
+    cgroups.attach(procs[1], nightly).unwrap();
+
+    cgroups.alloc(&mut table, procs[0], 6).unwrap();
+    assert_eq!(
+        cgroups.alloc(&mut table, procs[1], 5),
+        Err(CgroupError::MemoryLimit {
+            group: batch,
+            limit: 10,
+            requested: 5
+        })
+    );
+    cgroups.alloc(&mut table, procs[1], 4).unwrap();
+    assert_eq!(cgroups.memory_usage(batch, &table), 10);
+    assert_eq!(cgroups.memory_usage(nightly, &table), 4);
+    assert_eq!(
+        cgroups.alloc(&mut table, Pid(999), 1),
+        Err(CgroupError::NoSuchProcess(Pid(999)))
+    );
+
+    // Lifting the limit, or moving the process out from under it, lets it grow again
+    cgroups.attach(procs[1], ROOT_CGROUP).unwrap();
+    cgroups.alloc(&mut table, procs[1], 100).unwrap();
+    assert_eq!(cgroups.group_of(procs[1]), ROOT_CGROUP);
+    assert_eq!(cgroups.memory_usage(ROOT_CGROUP, &table), 110);
+}
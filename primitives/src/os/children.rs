@@ -0,0 +1,308 @@
+use std::fmt;
+use std::mem;
+use std::ops::{Index, IndexMut};
+
+use super::Proc;
+
+// Most processes have no children, and nearly all the rest have one or two, so a parent keeps its first two in the
+// Proc itself and only moves them into a Vec once a third arrives, the way a SmallVec does. The slots can't hold the
+// children by value, though: a Proc with room for even one child Proc would have to be bigger than itself. Each slot
+// holds a Box instead, so each inline child is an allocation of its own, and spilling moves them out of their boxes
+// into the Vec. Two boxes fit in the 24 bytes a Vec takes up anyway, so the container is no bigger than the Vec it
+// replaces.
+
+/// The children a process owns, in the order they were added.
+#[derive(Clone, Default)]
+pub struct Children(Repr);
+
+#[derive(Clone, Default)]
+enum Repr {
+    #[default]
+    Empty,
+    One(Box<Proc>),
+    Two([Box<Proc>; 2]),
+    Spilled(Vec<Proc>), // Once a third child arrives, even if some are removed later
+}
+
+impl Children {
+    pub fn len(&self) -> usize {
+        let (inline, spilled) = self.parts();
+        inline.len() + spilled.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether the children have outgrown the two inline slots and moved to the heap.
+    pub fn spilled(&self) -> bool {
+        matches!(self.0, Repr::Spilled(_))
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        let (inline, spilled) = self.parts();
+        Iter {
+            inline: inline.iter(),
+            spilled: spilled.iter(),
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_> {
+        let (inline, spilled) = self.parts_mut();
+        IterMut {
+            inline: inline.iter_mut(),
+            spilled: spilled.iter_mut(),
+        }
+    }
+
+    pub fn last_mut(&mut self) -> Option<&mut Proc> {
+        self.iter_mut().next_back()
+    }
+
+    pub(super) fn push(&mut self, child: Proc) {
+        self.0 = match mem::take(&mut self.0) {
+            Repr::Empty => Repr::One(Box::new(child)),
+            Repr::One(first) => Repr::Two([first, Box::new(child)]),
+            Repr::Two([first, second]) => {
+                let mut spilled = Vec::with_capacity(4);
+                spilled.extend([*first, *second, child]);
+                Repr::Spilled(spilled)
+            }
+            Repr::Spilled(mut spilled) => {
+                spilled.push(child);
+                Repr::Spilled(spilled)
+            }
+        };
+    }
+
+    /// Takes out the child at `index`, shifting the ones after it down.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub(super) fn remove(&mut self, index: usize) -> Proc {
+        let len = self.len();
+        assert!(index < len, "child {index} of {len} removed");
+        let (rest, removed) = match mem::take(&mut self.0) {
+            Repr::Empty => unreachable!("checked above"),
+            Repr::One(only) => (Repr::Empty, *only),
+            Repr::Two([first, second]) if index == 0 => (Repr::One(second), *first),
+            Repr::Two([first, second]) => (Repr::One(first), *second),
+            Repr::Spilled(mut spilled) => {
+                let removed = spilled.remove(index);
+                (Repr::Spilled(spilled), removed)
+            }
+        };
+        self.0 = rest;
+        removed
+    }
+
+    /// Moves all of `other`'s children to the end of these, leaving `other` empty.
+    pub(super) fn append(&mut self, other: &mut Children) {
+        self.extend(mem::take(other));
+    }
+
+    // The inline children and the spilled ones, at most one of them non-empty
+    fn parts(&self) -> (&[Box<Proc>], &[Proc]) {
+        match &self.0 {
+            Repr::Empty => (&[], &[]),
+            Repr::One(only) => (std::slice::from_ref(only), &[]),
+            Repr::Two(both) => (both, &[]),
+            Repr::Spilled(spilled) => (&[], spilled),
+        }
+    }
+
+    fn parts_mut(&mut self) -> (&mut [Box<Proc>], &mut [Proc]) {
+        match &mut self.0 {
+            Repr::Empty => (&mut [], &mut []),
+            Repr::One(only) => (std::slice::from_mut(only), &mut []),
+            Repr::Two(both) => (both, &mut []),
+            Repr::Spilled(spilled) => (&mut [], spilled),
+        }
+    }
+}
+
+impl fmt::Debug for Children {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl Index<usize> for Children {
+    type Output = Proc;
+
+    fn index(&self, index: usize) -> &Proc {
+        match &self.0 {
+            Repr::Spilled(spilled) => &spilled[index],
+            _ => &self.parts().0[index],
+        }
+    }
+}
+
+impl IndexMut<usize> for Children {
+    fn index_mut(&mut self, index: usize) -> &mut Proc {
+        let (inline, spilled) = self.parts_mut();
+        if spilled.is_empty() {
+            &mut inline[index]
+        } else {
+            &mut spilled[index]
+        }
+    }
+}
+
+impl Extend<Proc> for Children {
+    fn extend<I: IntoIterator<Item = Proc>>(&mut self, children: I) {
+        for child in children {
+            self.push(child);
+        }
+    }
+}
+
+impl FromIterator<Proc> for Children {
+    fn from_iter<I: IntoIterator<Item = Proc>>(children: I) -> Self {
+        let mut all = Children::default();
+        all.extend(children);
+        all
+    }
+}
+
+pub struct Iter<'a> {
+    inline: std::slice::Iter<'a, Box<Proc>>,
+    spilled: std::slice::Iter<'a, Proc>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a Proc;
+
+    fn next(&mut self) -> Option<&'a Proc> {
+        match self.inline.next() {
+            Some(child) => Some(child),
+            None => self.spilled.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.inline.len() + self.spilled.len();
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for Iter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match self.spilled.next_back() {
+            Some(child) => Some(child),
+            None => self.inline.next_back().map(|child| &**child),
+        }
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+pub struct IterMut<'a> {
+    inline: std::slice::IterMut<'a, Box<Proc>>,
+    spilled: std::slice::IterMut<'a, Proc>,
+}
+
+impl<'a> Iterator for IterMut<'a> {
+    type Item = &'a mut Proc;
+
+    fn next(&mut self) -> Option<&'a mut Proc> {
+        match self.inline.next() {
+            Some(child) => Some(child),
+            None => self.spilled.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.inline.len() + self.spilled.len();
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for IterMut<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match self.spilled.next_back() {
+            Some(child) => Some(child),
+            None => self.inline.next_back().map(|child| &mut **child),
+        }
+    }
+}
+
+impl ExactSizeIterator for IterMut<'_> {}
+
+/// Hands the children out by value, first to last.
+pub struct IntoIter {
+    inline: std::iter::Flatten<std::array::IntoIter<Option<Box<Proc>>, 2>>,
+    spilled: std::vec::IntoIter<Proc>,
+}
+
+impl Iterator for IntoIter {
+    type Item = Proc;
+
+    fn next(&mut self) -> Option<Proc> {
+        match self.inline.next() {
+            Some(child) => Some(*child),
+            None => self.spilled.next(),
+        }
+    }
+}
+
+impl IntoIterator for Children {
+    type Item = Proc;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> IntoIter {
+        let (inline, spilled) = match self.0 {
+            Repr::Empty => ([None, None], Vec::new()),
+            Repr::One(only) => ([Some(only), None], Vec::new()),
+            Repr::Two([first, second]) => ([Some(first), Some(second)], Vec::new()),
+            Repr::Spilled(spilled) => ([None, None], spilled),
+        };
+        IntoIter {
+            inline: inline.into_iter().flatten(),
+            spilled: spilled.into_iter(),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a Children {
+    type Item = &'a Proc;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Children {
+    type Item = &'a mut Proc;
+    type IntoIter = IterMut<'a>;
+
+    fn into_iter(self) -> IterMut<'a> {
+        self.iter_mut()
+    }
+}
+
+#[test]
+fn test_children_keep_their_order() {
+    let pids = super::PidAllocator::default();
+    let mut children: Children = (0..3).map(|_| Proc::new(&pids)).collect();
+    let pids: Vec<_> = children.iter().map(Proc::pid).collect();
+
+    // Removing from the middle shifts the rest down, and spilled children stay on the heap
+    assert_eq!(children.remove(1).pid(), pids[1]);
+    assert_eq!(
+        children.iter().map(Proc::pid).collect::<Vec<_>>(),
+        [pids[0], pids[2]]
+    );
+    assert!(children.spilled());
+
+    let mut inline: Children = children.into_iter().collect();
+    assert!(!inline.spilled());
+    assert_eq!(inline[1].pid(), pids[2]);
+    assert_eq!(inline.remove(0).pid(), pids[0]);
+    assert_eq!(
+        inline.iter().rev().map(Proc::pid).collect::<Vec<_>>(),
+        [pids[2]]
+    );
+}
@@ -0,0 +1,64 @@
+use super::{Pid, Proc, ProcTable};
+
+// Not every process wants the same thing from the scheduler. Interactive ones (shells, editors) run in short bursts
+// and care about latency, batch jobs want long uninterrupted slices, and idle-class work should only soak up time
+// nobody else wants, like Linux's SCHED_IDLE.
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SchedClass {
+    // Half the scheduler's quantum, so it gets back on the CPU sooner
+    Interactive,
+    // The scheduler's quantum as configured
+    #[default]
+    Batch,
+    // Twice the quantum, but only runs when nothing of another class is ready
+    Idle,
+}
+
+impl SchedClass {
+    /// Time slice for this class when the scheduler's base quantum is `base` ticks. Never less than one tick.
+    pub fn quantum(self, base: u32) -> u32 {
+        match self {
+            SchedClass::Interactive => (base / 2).max(1),
+            SchedClass::Batch => base,
+            SchedClass::Idle => base.saturating_mul(2),
+        }
+    }
+}
+
+impl Proc {
+    pub fn sched_class(&self) -> SchedClass {
+        self.class
+    }
+
+    /// Moves the process (and all of its threads) to `class`. Forked children inherit it.
+    pub fn set_sched_class(&mut self, class: SchedClass) {
+        self.class = class;
+    }
+}
+
+impl ProcTable {
+    // Class of the process task `tid` belongs to.
+    pub(super) fn task_class(&self, tid: Pid) -> SchedClass {
+        self.owner_of(tid)
+            .map(Proc::sched_class)
+            .unwrap_or_default()
+    }
+}
+
+#[test]
+fn test_quanta_per_class() {
+    assert_eq!(SchedClass::Interactive.quantum(4), 2);
+    assert_eq!(SchedClass::Interactive.quantum(1), 1);
+    assert_eq!(SchedClass::Batch.quantum(4), 4);
+    assert_eq!(SchedClass::Idle.quantum(4), 8);
+
+    let mut proc = Proc::new(&super::PidAllocator::default());
+    assert_eq!(proc.sched_class(), SchedClass::Batch);
+    proc.set_sched_class(SchedClass::Interactive);
+    let child = proc.fork();
+    assert_eq!(
+        proc.child_mut(child).unwrap().sched_class(),
+        SchedClass::Interactive
+    );
+}
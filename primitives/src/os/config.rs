@@ -0,0 +1,715 @@
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use super::pid::PID_MAX_DEFAULT;
+use super::{
+    BuildError, CgroupScheduler, CgroupTree, Cpu, LotteryScheduler, MlfqScheduler, Pid,
+    PidAllocator, Proc, ProcTable, RoundRobinScheduler, Scheduler, Simulation,
+};
+
+// A simulation described in a `simulation.toml` file instead of code:
+//
+//     cores = 2
+//     quantum = 3
+//     scheduler = "mlfq"        # round-robin, mlfq, lottery or cgroup
+//
+//     [[process]]
+//     pid = 10
+//     name = "sshd"
+//
+//     [[process]]
+//     name = "bash"
+//     args = ["-l"]
+//     parent = 10               # init (1) when left out
+//
+// Only the part of TOML this needs is read, by hand like the JSON: top-level keys, `[[process]]` tables, and values
+// that are strings, integers or one-line arrays of those. Everything is checked while loading, so a bad
+// file is reported with its line before any process is made.
+
+/// Which scheduler the simulation runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchedulerKind {
+    RoundRobin,
+    // Three levels with quanta of 1, 2 and 4 times `quantum`
+    Mlfq,
+    Lottery,
+    // Every process in the root cgroup
+    Cgroup,
+}
+
+impl SchedulerKind {
+    fn name(self) -> &'static str {
+        match self {
+            SchedulerKind::RoundRobin => "round-robin",
+            SchedulerKind::Mlfq => "mlfq",
+            SchedulerKind::Lottery => "lottery",
+            SchedulerKind::Cgroup => "cgroup",
+        }
+    }
+}
+
+impl fmt::Display for SchedulerKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// One `[[process]]` table: a process to start under `parent` before the first tick.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcConfig {
+    pub name: String,
+    pub args: Vec<String>,
+    pub pid: Pid, // As given, or else the PID a fresh allocator would hand out next
+    pub parent: Pid,
+}
+
+/// A whole `simulation.toml`. `Default` is what an empty file gives.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimConfig {
+    pub cores: usize,
+    pub quantum: u32,
+    pub scheduler: SchedulerKind,
+    pub seed: u64, // Only the lottery draws from it
+    pub procs: Vec<ProcConfig>,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        SimConfig {
+            cores: 1,
+            quantum: 2,
+            scheduler: SchedulerKind::RoundRobin,
+            seed: 1,
+            procs: Vec::new(),
+        }
+    }
+}
+
+/// Why a `simulation.toml` was rejected. Lines count from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    // Not the TOML this reads
+    Syntax {
+        line: usize,
+        reason: &'static str,
+    },
+    // A key or table the file format doesn't have
+    UnknownKey {
+        line: usize,
+        key: String,
+    },
+    // The same key twice in one table
+    DuplicateKey {
+        line: usize,
+        key: String,
+    },
+    // A key holding the wrong kind of value
+    Type {
+        line: usize,
+        key: &'static str,
+        expected: &'static str,
+    },
+    // A `[[process]]` table without this key
+    Missing {
+        line: usize,
+        key: &'static str,
+    },
+    // Well-formed, but not a simulation that can be set up
+    Invalid {
+        line: usize,
+        reason: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Syntax { line, reason } => write!(f, "line {}: {}", line, reason),
+            ConfigError::UnknownKey { line, key } => {
+                write!(f, "line {}: unknown key `{}`", line, key)
+            }
+            ConfigError::DuplicateKey { line, key } => {
+                write!(f, "line {}: `{}` is set twice", line, key)
+            }
+            ConfigError::Type {
+                line,
+                key,
+                expected,
+            } => write!(f, "line {}: `{}` must be {}", line, key, expected),
+            ConfigError::Missing { line, key } => {
+                write!(f, "line {}: process is missing `{}`", line, key)
+            }
+            ConfigError::Invalid { line, reason } => write!(f, "line {}: {}", line, reason),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+// As with JSON, a file that reads fine but holds a bad config is bad data.
+impl From<ConfigError> for io::Error {
+    fn from(err: ConfigError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+impl SimConfig {
+    /// Reads and checks a config file.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(fs::read_to_string(path)?.parse()?)
+    }
+
+    /// A CPU with `cores` cores, for `manage_process`.
+    pub fn cpu(&self) -> Cpu {
+        Cpu::new(self.cores)
+    }
+
+    /// The configured scheduler, boxed so every kind has the same type. `build` makes one per core.
+    pub fn scheduler(&self) -> Box<dyn Scheduler> {
+        let q = self.quantum;
+        match self.scheduler {
+            SchedulerKind::RoundRobin => Box::new(RoundRobinScheduler::new(q)),
+            SchedulerKind::Mlfq => Box::new(MlfqScheduler::new(&[q, 2 * q, 4 * q], 20 * q)),
+            SchedulerKind::Lottery => Box::new(LotteryScheduler::new(self.seed)),
+            SchedulerKind::Cgroup => Box::new(CgroupScheduler::new(CgroupTree::new())),
+        }
+    }
+
+    /// Makes init and the configured processes from `pids` and admits them, in file order, to a simulation of
+    /// `cores` cores at tick 0. Every process gets the PID worked out while parsing, so with a fresh `pids` this can't fail; it fails
+    /// if `pids` has already handed out one of those PIDs.
+    pub fn build(&self, pids: &PidAllocator) -> Result<Simulation<Box<dyn Scheduler>>, BuildError> {
+        let init = Proc::builder(pids).pid(Pid(1)).name("init").build()?;
+        let mut table = ProcTable::new(init);
+
+        let mut spawned = Vec::new();
+        for cfg in &self.procs {
+            let mut proc = Proc::builder(pids)
+                .pid(cfg.pid)
+                .name(cfg.name.clone())
+                .build()?;
+            let args: Vec<&str> = cfg.args.iter().map(String::as_str).collect();
+            proc.set_argv(&args);
+            spawned.push(proc.pid());
+            // Parsing made sure the parent was declared earlier
+            table
+                .get_mut(cfg.parent)
+                .expect("parent checked while parsing")
+                .add_child(proc);
+        }
+
+        let cores = (0..self.cores).map(|_| self.scheduler()).collect();
+        let mut sim = Simulation::with_cores(cores, table);
+        for pid in spawned {
+            sim.admit(pid);
+        }
+        Ok(sim)
+    }
+}
+
+impl FromStr for SimConfig {
+    type Err = ConfigError;
+
+    fn from_str(text: &str) -> Result<Self, ConfigError> {
+        let mut config = SimConfig::default();
+        let tables = parse_tables(text)?;
+        let (top, procs) = tables
+            .split_first()
+            .expect("the top-level table is always there");
+
+        for entry in &top.entries {
+            match entry.key.as_str() {
+                "cores" => {
+                    let cores = entry.int("cores", "an integer from 1 to 64")?;
+                    config.cores = match cores {
+                        1..=64 => cores as usize,
+                        _ => return Err(entry.invalid("cores must be from 1 to 64")),
+                    };
+                }
+                "quantum" => {
+                    let quantum = entry.int("quantum", "a positive integer")?;
+                    config.quantum = match u32::try_from(quantum) {
+                        // Leaves room for MLFQ's longest quantum and boost interval
+                        Ok(q @ 1..=100_000) => q,
+                        _ => return Err(entry.invalid("quantum must be from 1 to 100000 ticks")),
+                    };
+                }
+                "scheduler" => {
+                    let name = entry.str("scheduler")?;
+                    config.scheduler = [
+                        SchedulerKind::RoundRobin,
+                        SchedulerKind::Mlfq,
+                        SchedulerKind::Lottery,
+                        SchedulerKind::Cgroup,
+                    ]
+                    .into_iter()
+                    .find(|kind| kind.name() == name)
+                    .ok_or_else(|| {
+                        entry.invalid(&format!(
+                            "unknown scheduler {:?} (expected round-robin, mlfq, lottery or cgroup)",
+                            name
+                        ))
+                    })?;
+                }
+                "seed" => {
+                    let seed = entry.int("seed", "a non-negative integer")?;
+                    config.seed = u64::try_from(seed)
+                        .map_err(|_| entry.invalid("seed must not be negative"))?;
+                }
+                key => return Err(entry.unknown(key)),
+            }
+        }
+
+        let mut pids = ConfigPids {
+            declared: BTreeSet::from([Pid(1)]),
+            next: 1,
+        };
+        for table in procs {
+            let proc = table.process(&mut pids)?;
+            config.procs.push(proc);
+        }
+        crate::debug!(
+            "{} scheduler on {} cores, {} processes configured",
+            config.scheduler,
+            config.cores,
+            config.procs.len()
+        );
+        Ok(config)
+    }
+}
+
+// A parsed value. Integers are as wide as TOML's; nothing in the file format is a boolean or a float yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Value {
+    Str(String),
+    Int(i64),
+    Array(Vec<Value>),
+}
+
+#[derive(Debug)]
+struct Entry {
+    line: usize,
+    key: String,
+    value: Value,
+}
+
+impl Entry {
+    fn int(&self, key: &'static str, expected: &'static str) -> Result<i64, ConfigError> {
+        match self.value {
+            Value::Int(n) => Ok(n),
+            _ => Err(self.wrong_type(key, expected)),
+        }
+    }
+
+    fn str(&self, key: &'static str) -> Result<&str, ConfigError> {
+        match &self.value {
+            Value::Str(s) => Ok(s),
+            _ => Err(self.wrong_type(key, "a string")),
+        }
+    }
+
+    fn pid(&self, key: &'static str) -> Result<Pid, ConfigError> {
+        let pid = self.int(key, "a pid")?;
+        match u32::try_from(pid) {
+            Ok(pid @ 1..=PID_MAX_DEFAULT) => Ok(Pid(pid)),
+            _ => Err(self.invalid(&format!("{} must be from 1 to {}", key, PID_MAX_DEFAULT))),
+        }
+    }
+
+    fn wrong_type(&self, key: &'static str, expected: &'static str) -> ConfigError {
+        ConfigError::Type {
+            line: self.line,
+            key,
+            expected,
+        }
+    }
+
+    fn invalid(&self, reason: &str) -> ConfigError {
+        ConfigError::Invalid {
+            line: self.line,
+            reason: reason.to_string(),
+        }
+    }
+
+    fn unknown(&self, key: &str) -> ConfigError {
+        ConfigError::UnknownKey {
+            line: self.line,
+            key: key.to_string(),
+        }
+    }
+}
+
+// PIDs handed out so far while parsing: init's and those of earlier processes, whether given in the file or not.
+// Processes without a `pid` get theirs here rather than in `build`, the same way a fresh `PidAllocator` would pick it,
+// so a `pid` that clashes with one of them is caught with its line like any other mistake.
+struct ConfigPids {
+    declared: BTreeSet<Pid>,
+    next: u32,
+}
+
+impl ConfigPids {
+    // Counts up from where the last pick left off, wrapping back to 1 and skipping PIDs already handed out, as
+    // `PidAllocator::alloc` does.
+    fn next_free(&mut self) -> Option<Pid> {
+        if self.declared.len() >= PID_MAX_DEFAULT as usize {
+            return None;
+        }
+        loop {
+            let pid = Pid(self.next);
+            self.next = if self.next >= PID_MAX_DEFAULT {
+                1
+            } else {
+                self.next + 1
+            };
+            if !self.declared.contains(&pid) {
+                return Some(pid);
+            }
+        }
+    }
+}
+
+// The keys under one header; the first table holds the keys before any header, at line 0.
+#[derive(Debug, Default)]
+struct Table {
+    line: usize,
+    entries: Vec<Entry>,
+}
+
+impl Table {
+    // Reads a `[[process]]` table and claims its PID. A parent must be one of the PIDs already handed out: init's or
+    // an earlier process's.
+    fn process(&self, pids: &mut ConfigPids) -> Result<ProcConfig, ConfigError> {
+        let declared = &pids.declared;
+        let mut name = None;
+        let mut pid = None;
+        let mut proc = ProcConfig {
+            name: String::new(),
+            args: Vec::new(),
+            pid: Pid(1),
+            parent: Pid(1),
+        };
+        for entry in &self.entries {
+            match entry.key.as_str() {
+                "name" => {
+                    let value = entry.str("name")?;
+                    if value.is_empty() {
+                        return Err(entry.invalid("name must not be empty"));
+                    }
+                    name = Some(value.to_string());
+                }
+                "args" => {
+                    let Value::Array(items) = &entry.value else {
+                        return Err(entry.wrong_type("args", "an array of strings"));
+                    };
+                    proc.args = items
+                        .iter()
+                        .map(|item| match item {
+                            Value::Str(s) => Ok(s.clone()),
+                            _ => Err(entry.wrong_type("args", "an array of strings")),
+                        })
+                        .collect::<Result<_, _>>()?;
+                }
+                "pid" => {
+                    let wanted = entry.pid("pid")?;
+                    if declared.contains(&wanted) {
+                        return Err(entry.invalid(&format!("pid {} is already taken", wanted)));
+                    }
+                    pid = Some(wanted);
+                }
+                "parent" => {
+                    let parent = entry.pid("parent")?;
+                    if !declared.contains(&parent) {
+                        return Err(entry.invalid(&format!(
+                            "parent {} is neither init nor the pid of an earlier process",
+                            parent
+                        )));
+                    }
+                    proc.parent = parent;
+                }
+                key => return Err(entry.unknown(&format!("process.{}", key))),
+            }
+        }
+        proc.name = name.ok_or(ConfigError::Missing {
+            line: self.line,
+            key: "name",
+        })?;
+        proc.pid = match pid {
+            Some(pid) => pid,
+            None => pids.next_free().ok_or_else(|| ConfigError::Invalid {
+                line: self.line,
+                reason: format!("no pid left for process {:?}", proc.name),
+            })?,
+        };
+        pids.declared.insert(proc.pid);
+        Ok(proc)
+    }
+}
+
+// Splits the file into the top-level table and one table per `[[process]]` header.
+fn parse_tables(text: &str) -> Result<Vec<Table>, ConfigError> {
+    let mut tables = vec![Table::default()];
+    for (i, raw) in text.lines().enumerate() {
+        let line = i + 1;
+        let syntax = |reason| ConfigError::Syntax { line, reason };
+        let content = strip_comment(raw).trim();
+        if content.is_empty() {
+            continue;
+        }
+
+        if let Some(header) = content.strip_prefix('[') {
+            let name = header
+                .strip_prefix('[')
+                .and_then(|h| h.strip_suffix("]]"))
+                .ok_or(syntax("only [[process]] tables are supported"))?;
+            if name.trim() != "process" {
+                return Err(ConfigError::UnknownKey {
+                    line,
+                    key: format!("[[{}]]", name.trim()),
+                });
+            }
+            tables.push(Table {
+                line,
+                entries: Vec::new(),
+            });
+            continue;
+        }
+
+        let (key, value) = content
+            .split_once('=')
+            .ok_or(syntax("expected `key = value`"))?;
+        let key = key.trim();
+        if key.is_empty()
+            || !key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(syntax("keys must be bare words"));
+        }
+        let mut chars = value.trim().chars().peekable();
+        let value = parse_value(&mut chars).ok_or(syntax("invalid value"))?;
+        if chars.next().is_some() {
+            return Err(syntax("unexpected text after the value"));
+        }
+
+        let table = tables.last_mut().expect("never empty");
+        if table.entries.iter().any(|e| e.key == key) {
+            return Err(ConfigError::DuplicateKey {
+                line,
+                key: key.to_string(),
+            });
+        }
+        table.entries.push(Entry {
+            line,
+            key: key.to_string(),
+            value,
+        });
+    }
+    Ok(tables)
+}
+
+// Drops a `#` comment, unless the `#` is inside a string.
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+type Chars<'a> = std::iter::Peekable<std::str::Chars<'a>>;
+
+fn skip_spaces(chars: &mut Chars) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+fn parse_value(chars: &mut Chars) -> Option<Value> {
+    match *chars.peek()? {
+        '"' => {
+            chars.next();
+            let mut s = String::new();
+            loop {
+                match chars.next()? {
+                    '"' => return Some(Value::Str(s)),
+                    '\\' => s.push(match chars.next()? {
+                        'n' => '\n',
+                        't' => '\t',
+                        '"' => '"',
+                        '\\' => '\\',
+                        _ => return None,
+                    }),
+                    c => s.push(c),
+                }
+            }
+        }
+        '[' => {
+            chars.next();
+            let mut items = Vec::new();
+            loop {
+                skip_spaces(chars);
+                if chars.next_if_eq(&']').is_some() {
+                    return Some(Value::Array(items));
+                }
+                items.push(parse_value(chars)?);
+                skip_spaces(chars);
+                // A trailing comma before `]` is allowed, as in TOML
+                if chars.next_if_eq(&',').is_none() && chars.peek() != Some(&']') {
+                    return None;
+                }
+            }
+        }
+        _ => {
+            let mut word = String::new();
+            while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || "+-_".contains(*c)) {
+                word.push(c);
+            }
+            // Underscores may only sit between digits, as in `100_000`
+            if word.contains("__") || word.ends_with('_') || word.starts_with('_') {
+                return None;
+            }
+            word.replace('_', "").parse().ok().map(Value::Int)
+        }
+    }
+}
+
+#[test]
+fn test_parse_config() {
+    let config: SimConfig = r#"
+        # Two levels below init
+        cores = 2
+        quantum = 3
+        scheduler = "mlfq"
+
+        [[process]]
+        pid = 10
+        name = "sshd"   # the # in "a#b" below is not a comment
+
+        [[process]]
+        name = "bash"
+        args = ["-l", "a#b",]
+        parent = 10
+    "#
+    .parse()
+    .unwrap();
+
+    assert_eq!(config.cores, 2);
+    assert_eq!(config.quantum, 3);
+    assert_eq!(config.scheduler, SchedulerKind::Mlfq);
+    assert_eq!(
+        config.procs,
+        [
+            ProcConfig {
+                name: "sshd".to_string(),
+                args: Vec::new(),
+                pid: Pid(10),
+                parent: Pid(1),
+            },
+            ProcConfig {
+                name: "bash".to_string(),
+                args: vec!["-l".to_string(), "a#b".to_string()],
+                pid: Pid(2),
+                parent: Pid(10),
+            },
+        ]
+    );
+    assert_eq!("".parse(), Ok(SimConfig::default()));
+}
+
+#[test]
+fn test_config_errors_name_the_line() {
+    let err = |text: &str| text.parse::<SimConfig>().unwrap_err().to_string();
+    assert_eq!(err("cores = 0"), "line 1: cores must be from 1 to 64");
+    assert_eq!(
+        err("\nquantum = \"3\""),
+        "line 2: `quantum` must be a positive integer"
+    );
+    assert_eq!(
+        err("scheduler = \"fifo\""),
+        "line 1: unknown scheduler \"fifo\" (expected round-robin, mlfq, lottery or cgroup)"
+    );
+    assert_eq!(err("cores = 2\ncores = 3"), "line 2: `cores` is set twice");
+    assert_eq!(err("cpus = 2"), "line 1: unknown key `cpus`");
+    assert_eq!(err("cores 2"), "line 1: expected `key = value`");
+    assert_eq!(err("name = \"open"), "line 1: invalid value");
+    assert_eq!(
+        err("[machine]"),
+        "line 1: only [[process]] tables are supported"
+    );
+    assert_eq!(
+        err("[[process]]\npid = 2"),
+        "line 1: process is missing `name`"
+    );
+    assert_eq!(
+        err("[[process]]\nname = \"a\"\nparent = 5"),
+        "line 3: parent 5 is neither init nor the pid of an earlier process"
+    );
+    assert_eq!(
+        err("[[process]]\nname = \"a\"\npid = 1"),
+        "line 3: pid 1 is already taken"
+    );
+    // A process without a pid takes the next free one there and then, so a later process can't ask for it
+    assert_eq!(
+        err("[[process]]\nname = \"a\"\n[[process]]\npid = 2\nname = \"b\""),
+        "line 4: pid 2 is already taken"
+    );
+    assert_eq!(
+        err("[[process]]\nname = \"a\"\nnice = 5"),
+        "line 3: unknown key `process.nice`"
+    );
+}
+
+#[test]
+fn test_build_makes_the_tree_and_schedules_it() {
+    let config: SimConfig = "quantum = 1\n\
+        [[process]]\npid = 10\nname = \"sshd\"\n\
+        [[process]]\nname = \"bash\"\nargs = [\"-l\"]\nparent = 10"
+        .parse()
+        .unwrap();
+    let pids = PidAllocator::default();
+    let mut sim = config.build(&pids).unwrap();
+
+    let sshd = sim.table().get(Pid(10)).unwrap();
+    assert_eq!(sshd.name(), "sshd");
+    let bash = &sshd.children()[0];
+    assert_eq!(
+        (bash.name(), bash.argv()),
+        ("bash", &["-l".to_string()][..])
+    );
+    let bash = bash.pid();
+    assert_eq!(sim.run(3), [Some(Pid(10)), Some(bash), Some(Pid(10))]);
+
+    // Processes without a pid get what a fresh allocator would give them, and can be parents too
+    let auto: SimConfig = "[[process]]\nname = \"a\"\n\
+        [[process]]\npid = 3\nname = \"b\"\n\
+        [[process]]\nname = \"c\"\nparent = 2"
+        .parse()
+        .unwrap();
+    let assigned: Vec<_> = auto.procs.iter().map(|p| p.pid).collect();
+    assert_eq!(assigned, [Pid(2), Pid(3), Pid(4)]);
+    let sim = auto.build(&PidAllocator::default()).unwrap();
+    assert_eq!(sim.table().get(Pid(2)).unwrap().children()[0].name(), "c");
+
+    // Each configured core gets a scheduler, and the processes are spread over them
+    let mut two = config.clone();
+    two.cores = 2;
+    let mut sim = two.build(&PidAllocator::default()).unwrap();
+    assert_eq!(sim.num_cores(), 2);
+    assert_eq!(sim.step_cores(), [Some(Pid(10)), Some(bash)]);
+
+    // PIDs the file asks for must still be free in the allocator it is built from
+    assert_eq!(
+        config.build(&pids).err(),
+        Some(BuildError::PidUnavailable(Pid(1)))
+    );
+}
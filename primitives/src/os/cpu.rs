@@ -0,0 +1,166 @@
+use super::{Pid, Proc, State};
+
+pub type CoreId = usize;
+
+#[derive(Debug, Default, Clone)]
+struct Core {
+    running: Option<Pid>,
+    busy_ticks: u64,
+    idle_ticks: u64,
+    dispatches: u64,
+}
+
+/// One row of `Cpu::load_report`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoreLoad {
+    pub core: CoreId,
+    pub running: Option<Pid>,
+    pub busy_ticks: u64,
+    pub idle_ticks: u64,
+    pub dispatches: u64,
+}
+
+impl CoreLoad {
+    /// Fraction of accounted ticks the core spent running something, 0.0 for a core that never ticked.
+    pub fn utilization(&self) -> f64 {
+        let total = self.busy_ticks + self.idle_ticks;
+        if total == 0 {
+            return 0.0;
+        }
+        self.busy_ticks as f64 / total as f64
+    }
+}
+
+/// A fixed set of cores, each running at most one process at a time.
+#[derive(Debug)]
+pub struct Cpu {
+    cores: Vec<Core>,
+}
+
+impl Cpu {
+    /// # Panics
+    ///
+    /// Panics if `cores` is zero.
+    pub fn new(cores: usize) -> Self {
+        assert!(cores > 0, "a cpu needs at least one core");
+        Cpu {
+            cores: vec![Core::default(); cores],
+        }
+    }
+
+    pub fn num_cores(&self) -> usize {
+        self.cores.len()
+    }
+
+    /// Puts `proc` on the lowest-numbered idle core its affinity allows and marks it Running. Returns `None` when
+    /// every such core is busy; a process that already holds an allowed core just gets that core back, and one
+    /// holding a core it is no longer allowed on is moved off it.
+    pub fn assign(&mut self, proc: &mut Proc) -> Option<CoreId> {
+        let pid = proc.pid();
+        if let Some(core) = self.core_of(pid) {
+            if proc.allows_core(core) {
+                return Some(core);
+            }
+            self.release(pid);
+        }
+        let core = self.idle_cores().find(|&core| proc.allows_core(core))?;
+        self.assign_to(core, proc);
+        Some(core)
+    }
+
+    // Runs `proc` on a specific (idle) core.
+    pub(super) fn assign_to(&mut self, core: CoreId, proc: &mut Proc) {
+        proc.set_state(State::Running);
+        self.cores[core].running = Some(proc.pid());
+        self.cores[core].dispatches += 1;
+    }
+
+    /// Frees whatever core `pid` is running on and returns it.
+    pub fn release(&mut self, pid: Pid) -> Option<CoreId> {
+        let core = self.core_of(pid)?;
+        self.cores[core].running = None;
+        Some(core)
+    }
+
+    /// Core `pid` is currently running on.
+    pub fn core_of(&self, pid: Pid) -> Option<CoreId> {
+        self.cores.iter().position(|c| c.running == Some(pid))
+    }
+
+    /// Process running on `core`, if any.
+    pub fn running_on(&self, core: CoreId) -> Option<Pid> {
+        self.cores.get(core).and_then(|c| c.running)
+    }
+
+    pub fn idle_cores(&self) -> impl Iterator<Item = CoreId> + '_ {
+        self.cores
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.running.is_none())
+            .map(|(id, _)| id)
+    }
+
+    /// Charges one tick to every core, as busy or idle depending on whether it is running something.
+    pub fn tick(&mut self) {
+        for core in self.cores.iter_mut() {
+            if core.running.is_some() {
+                core.busy_ticks += 1;
+            } else {
+                core.idle_ticks += 1;
+            }
+        }
+    }
+
+    pub fn load_report(&self) -> Vec<CoreLoad> {
+        self.cores
+            .iter()
+            .enumerate()
+            .map(|(id, c)| CoreLoad {
+                core: id,
+                running: c.running,
+                busy_ticks: c.busy_ticks,
+                idle_ticks: c.idle_ticks,
+                dispatches: c.dispatches,
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_assign_fills_idle_cores() {
+    let pids = super::PidAllocator::default();
+    let mut procs: Vec<_> = (0..3).map(|_| Proc::new(&pids)).collect();
+    let mut cpu = Cpu::new(2);
+
+    assert_eq!(cpu.assign(&mut procs[0]), Some(0));
+    assert_eq!(cpu.assign(&mut procs[1]), Some(1));
+    assert_eq!(cpu.assign(&mut procs[2]), None);
+    assert_eq!(cpu.assign(&mut procs[1]), Some(1));
+    assert_eq!(procs[0].state(), State::Running);
+    assert_eq!(procs[2].state(), State::Stopped);
+
+    assert_eq!(cpu.release(procs[0].pid()), Some(0));
+    assert_eq!(cpu.idle_cores().collect::<Vec<_>>(), vec![0]);
+    assert_eq!(cpu.assign(&mut procs[2]), Some(0));
+    assert_eq!(cpu.running_on(0), Some(procs[2].pid()));
+    assert_eq!(cpu.core_of(procs[1].pid()), Some(1));
+}
+
+#[test]
+fn test_load_report() {
+    let mut proc = Proc::new(&super::PidAllocator::default());
+    let mut cpu = Cpu::new(2);
+    cpu.assign(&mut proc);
+    for _ in 0..3 {
+        cpu.tick();
+    }
+    cpu.release(proc.pid());
+    cpu.tick();
+
+    let report = cpu.load_report();
+    assert_eq!(report[0].busy_ticks, 3);
+    assert_eq!(report[0].idle_ticks, 1);
+    assert_eq!(report[0].utilization(), 0.75);
+    assert_eq!(report[1].utilization(), 0.0);
+    assert_eq!(report[1].running, None);
+}
@@ -0,0 +1,170 @@
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+use super::{Scheduler, SimSnapshot, Simulation};
+
+// A live view of a running simulation in the terminal, redrawn after every tick like `top`. Everything it shows comes
+// from a `SimSnapshot`, so it never reaches into the scheduler or the table, and a frame can be rendered (and tested)
+// as a plain string. Drawing is done with two ANSI escapes, clear screen and cursor home, so no terminal library is
+// needed.
+
+const BAR_WIDTH: usize = 20;
+
+/// Draws `snap` as one dashboard frame: the process tree ps-style, the run queue, a utilization bar per core and the
+/// latest audit events.
+pub fn render(snap: &SimSnapshot) -> String {
+    let mut out = format!("tick {}\n\n", snap.now);
+
+    out.push_str("  PID  PPID  STATE      NAME\n");
+    for proc in &snap.procs {
+        let parent = proc
+            .parent
+            .map_or_else(|| "-".to_string(), |p| p.0.to_string());
+        writeln!(
+            out,
+            "{:>5} {:>5}  {:<10} {}{}",
+            proc.pid.0,
+            parent,
+            format!("{:?}", proc.state),
+            "  ".repeat(proc.depth),
+            proc.name
+        )
+        .unwrap();
+    }
+
+    let queued: Vec<String> = snap.queued.iter().map(|pid| pid.to_string()).collect();
+    writeln!(out, "\nrun queue: {}", queued.join(" ")).unwrap();
+
+    out.push('\n');
+    for core in &snap.cores {
+        let utilization = core.utilization();
+        let filled = (utilization * BAR_WIDTH as f64).round() as usize;
+        let running = core
+            .running
+            .map_or_else(|| "idle".to_string(), |pid| format!("pid {}", pid));
+        writeln!(
+            out,
+            "core {} [{}{}] {:>3.0}%  {}",
+            core.core,
+            "#".repeat(filled),
+            "-".repeat(BAR_WIDTH - filled),
+            utilization * 100.0,
+            running
+        )
+        .unwrap();
+    }
+
+    out.push_str("\nlatest events:\n");
+    for record in &snap.events {
+        writeln!(out, "  {}", record).unwrap();
+    }
+    out
+}
+
+/// Redraws `render`'s frame in place on a terminal.
+pub struct Dashboard<W: Write> {
+    out: W,
+    events: usize,
+}
+
+impl<W: Write> Dashboard<W> {
+    /// A dashboard drawing to `out`, listing the last five events.
+    pub fn new(out: W) -> Self {
+        Dashboard { out, events: 5 }
+    }
+
+    /// Lists the last `events` audit events instead.
+    pub fn events(mut self, events: usize) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Clears the screen and draws the frame for `snap`.
+    pub fn draw(&mut self, snap: &SimSnapshot) -> io::Result<()> {
+        write!(self.out, "\x1b[2J\x1b[H{}", render(snap))?;
+        self.out.flush()
+    }
+
+    /// Runs `sim` for `ticks` ticks, drawing a frame after each one and waiting `interval` before the next.
+    pub fn watch<S: Scheduler>(
+        &mut self,
+        sim: &mut Simulation<S>,
+        ticks: u64,
+        interval: Duration,
+    ) -> io::Result<()> {
+        self.draw(&sim.snapshot(self.events))?;
+        for _ in 0..ticks {
+            thread::sleep(interval);
+            sim.step();
+            self.draw(&sim.snapshot(self.events))?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_render_frame() {
+    use super::{PidAllocator, Proc, ProcTable, RoundRobinScheduler};
+
+    let pids = PidAllocator::default();
+    let init = Proc::builder(&pids).name("init").build().unwrap();
+    let mut sim = Simulation::new(RoundRobinScheduler::new(1), ProcTable::new(init));
+    let sh = sim.spawn(Proc::builder(&pids).name("sh").build().unwrap());
+    sim.spawn(Proc::builder(&pids).name("vim").build().unwrap());
+    sim.run(1);
+    sim.proc_mut(sh).unwrap().exit(1);
+    sim.run(1);
+
+    assert_eq!(
+        render(&sim.snapshot(2)),
+        "tick 2
+
+  PID  PPID  STATE      NAME
+    1     -  Stopped    init
+    2     1  Zombie       sh
+    3     1  Stopped      vim
+
+run queue: 2 3
+
+core 0 [####################] 100%  pid 3
+
+latest events:
+  1 3 Stopped -> Running
+  1 3 Running -> Stopped
+"
+    );
+}
+
+#[test]
+fn test_watch_draws_every_tick() {
+    use super::{PidAllocator, Proc, ProcTable, RoundRobinScheduler};
+
+    let pids = PidAllocator::default();
+    let mut sim = Simulation::new(
+        RoundRobinScheduler::new(1),
+        ProcTable::new(Proc::new(&pids)),
+    );
+    sim.spawn(Proc::new(&pids));
+    let mut screen = Vec::new();
+    Dashboard::new(&mut screen)
+        .watch(&mut sim, 3, Duration::ZERO)
+        .unwrap();
+
+    let screen = String::from_utf8(screen).unwrap();
+    assert_eq!(screen.matches("\x1b[2J\x1b[H").count(), 4);
+    // The last frame is the state after the third tick
+    let last = screen.rsplit("\x1b[2J\x1b[H").next().unwrap();
+    assert!(last.starts_with("tick 3\n"), "{}", last);
+    assert!(
+        last.contains("core 0 [####################] 100%  pid 2\n"),
+        "{}",
+        last
+    );
+    assert!(
+        last.ends_with("  2 2 Stopped -> Running\n  2 2 Running -> Stopped\n"),
+        "{}",
+        last
+    );
+}
@@ -0,0 +1,91 @@
+use std::fmt::Write;
+
+use super::{Proc, State};
+
+// Graphviz output for process trees: `dot -Tpng tree.dot -o tree.png` turns a snapshot of the simulation into a
+// picture, one box per process, coloured by state, with an arrow from each parent to each of its children.
+
+/// What to put on each node besides the process name.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DotOptions {
+    pub pids: bool,
+    pub cpu_time: bool, // Ticks used by the process itself, as in CpuTimes::own
+}
+
+fn fill_color(state: State) -> &'static str {
+    match state {
+        State::Running => "palegreen",
+        State::Stopped => "khaki",
+        State::Sleeping => "lightblue",
+        State::Zombie => "lightgray",
+        State::Terminated => "gray50",
+    }
+}
+
+impl Proc {
+    /// The subtree as a DOT `digraph`.
+    pub fn to_dot(&self, options: DotOptions) -> String {
+        let mut out = String::from("digraph procs {\n    node [shape=box, style=filled];\n");
+        self.dot_nodes(options, &mut out);
+        out.push_str("}\n");
+        out
+    }
+
+    fn dot_nodes(&self, options: DotOptions, out: &mut String) {
+        let mut label = if self.name.is_empty() {
+            "?".to_string()
+        } else {
+            self.name.replace('\\', "\\\\").replace('"', "\\\"")
+        };
+        if options.pids {
+            write!(label, "\\npid {}", self.pid()).unwrap();
+        }
+        if options.cpu_time {
+            write!(label, "\\ncpu {}", self.times.own()).unwrap();
+        }
+        writeln!(
+            out,
+            "    p{} [label=\"{}\", fillcolor={}];",
+            self.pid(),
+            label,
+            fill_color(self.state)
+        )
+        .unwrap();
+        for child in &self.children {
+            writeln!(out, "    p{} -> p{};", self.pid(), child.pid()).unwrap();
+        }
+        for child in &self.children {
+            child.dot_nodes(options, out);
+        }
+    }
+}
+
+#[test]
+fn test_to_dot_draws_nodes_and_edges() {
+    let pids = super::PidAllocator::default();
+    let (mut table, cron, rsyslogd, bash) = super::table::init_tree(&pids);
+    let init = table.init().pid();
+    table.get_mut(bash).unwrap().set_state(State::Running);
+    table.get_mut(bash).unwrap().charge_user_tick();
+    table.get_mut(cron).unwrap().exit(0);
+
+    let dot = table.init().to_dot(DotOptions::default());
+    assert!(dot.starts_with("digraph procs {\n"));
+    assert!(dot.ends_with("}\n"));
+    assert!(dot.contains(&format!("    p{} -> p{};\n", init, rsyslogd)));
+    assert!(dot.contains(&format!("    p{} -> p{};\n", rsyslogd, bash)));
+    assert!(dot.contains(&format!(
+        "    p{} [label=\"cron\", fillcolor=lightgray];\n",
+        cron
+    )));
+    assert!(!dot.contains("pid "));
+
+    let dot = table.init().to_dot(DotOptions {
+        pids: true,
+        cpu_time: true,
+    });
+    assert!(dot.contains(&format!(
+        "    p{0} [label=\"bash\\npid {0}\\ncpu 1\", fillcolor=palegreen];\n",
+        bash
+    )));
+}
@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+
+use super::Proc;
+
+// Every process carries its environment, a set of NAME=value strings. As on Unix, a child starts out with a copy of
+// its parent's and `exec` leaves it alone, so a variable set in a shell reaches every program the shell starts.
+
+impl Proc {
+    pub fn env(&self) -> &HashMap<String, String> {
+        &self.env
+    }
+
+    pub fn getenv(&self, name: &str) -> Option<&str> {
+        self.env.get(name).map(String::as_str)
+    }
+
+    /// Sets `name` for this process and any process it forks from now on. Returns the old value.
+    pub fn setenv(&mut self, name: impl Into<String>, value: impl Into<String>) -> Option<String> {
+        self.env.insert(name.into(), value.into())
+    }
+
+    pub fn unsetenv(&mut self, name: &str) -> Option<String> {
+        self.env.remove(name)
+    }
+}
+
+#[test]
+fn test_env_is_inherited_on_fork() {
+    let pids = super::PidAllocator::default();
+    let mut shell = Proc::new(&pids);
+    shell.exec("bash", &["-l"]);
+    assert_eq!(shell.setenv("PATH", "/bin"), None);
+    shell.setenv("HOME", "/root");
+    let child = shell.fork();
+    // Changes after the fork don't reach the child
+    assert_eq!(
+        shell.setenv("PATH", "/usr/bin:/bin"),
+        Some("/bin".to_string())
+    );
+
+    let child = shell.child_mut(child).unwrap();
+    assert_eq!(child.argv(), ["-l"]);
+    child.exec("ls", &["-a"]);
+    assert_eq!(child.getenv("PATH"), Some("/bin"));
+    assert_eq!(child.unsetenv("HOME"), Some("/root".to_string()));
+    assert_eq!(child.env().len(), 1);
+    assert_eq!(shell.getenv("HOME"), Some("/root"));
+    assert_eq!(shell.getenv("TERM"), None);
+}
@@ -0,0 +1,93 @@
+use std::error::Error;
+use std::fmt;
+
+use super::repl::{ParseCommandError, ReplError};
+#[cfg(feature = "serde")]
+use super::JsonError;
+use super::{
+    AffinityError, BuildError, CgroupError, ConfigError, GroupError, LockError, MemError,
+    MissingDetail, NiceError, ParseSignalError, ReparentError, TransitionError,
+};
+
+/// Any error from the process simulator, so a function calling into several parts of it can use `?` on all of them.
+/// Each variant holds the error as the part that raised it returned it, and displays as that error does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchedError {
+    Transition(TransitionError),
+    MissingDetail(MissingDetail),
+    Build(BuildError),
+    Reparent(ReparentError),
+    Nice(NiceError),
+    Affinity(AffinityError),
+    Group(GroupError),
+    Cgroup(CgroupError),
+    Lock(LockError),
+    Mem(MemError),
+    Signal(ParseSignalError),
+    Config(ConfigError),
+    Repl(ReplError),
+    #[cfg(feature = "serde")]
+    Json(JsonError),
+}
+
+impl fmt::Display for SchedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchedError::Transition(err) => err.fmt(f),
+            SchedError::MissingDetail(err) => err.fmt(f),
+            SchedError::Build(err) => err.fmt(f),
+            SchedError::Reparent(err) => err.fmt(f),
+            SchedError::Nice(err) => err.fmt(f),
+            SchedError::Affinity(err) => err.fmt(f),
+            SchedError::Group(err) => err.fmt(f),
+            SchedError::Cgroup(err) => err.fmt(f),
+            SchedError::Lock(err) => err.fmt(f),
+            SchedError::Mem(err) => err.fmt(f),
+            SchedError::Signal(err) => err.fmt(f),
+            SchedError::Config(err) => err.fmt(f),
+            SchedError::Repl(err) => err.fmt(f),
+            #[cfg(feature = "serde")]
+            SchedError::Json(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for SchedError {}
+
+macro_rules! from_errors {
+    ($($variant:ident($err:ty)),* $(,)?) => {
+        $(
+            impl From<$err> for SchedError {
+                fn from(err: $err) -> Self {
+                    SchedError::$variant(err)
+                }
+            }
+        )*
+    };
+}
+
+from_errors!(
+    Transition(TransitionError),
+    MissingDetail(MissingDetail),
+    Build(BuildError),
+    Reparent(ReparentError),
+    Nice(NiceError),
+    Affinity(AffinityError),
+    Group(GroupError),
+    Cgroup(CgroupError),
+    Lock(LockError),
+    Mem(MemError),
+    Signal(ParseSignalError),
+    Config(ConfigError),
+    Repl(ReplError),
+);
+
+#[cfg(feature = "serde")]
+from_errors!(Json(JsonError));
+
+// A line the repl couldn't parse is a repl error like any other
+impl From<ParseCommandError> for SchedError {
+    fn from(err: ParseCommandError) -> Self {
+        SchedError::Repl(err.into())
+    }
+}
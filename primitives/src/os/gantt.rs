@@ -0,0 +1,49 @@
+use std::fmt::Write;
+
+use super::sim::TimelineRow;
+
+/// Draws `rows` (from `Simulation::timeline`) as an ASCII Gantt chart over ticks `0..ticks`: a header of tick numbers,
+/// then one line per core with the PID that ran in each tick, or `.` while the core sat idle.
+///
+/// ```text
+/// tick   0 1 2 3 4 5
+/// core 0 1 1 2 2 . 1
+/// ```
+pub fn render(rows: &[TimelineRow], ticks: u64) -> String {
+    let widest_pid = rows
+        .iter()
+        .flatten()
+        .map(|(_, pid)| pid.to_string().len())
+        .max()
+        .unwrap_or(1);
+    let width = widest_pid.max(ticks.saturating_sub(1).to_string().len());
+
+    let mut out = String::from("tick  ");
+    for tick in 0..ticks {
+        write!(out, " {:>width$}", tick).unwrap();
+    }
+    for (core, row) in rows.iter().enumerate() {
+        write!(out, "\ncore {}", core).unwrap();
+        for tick in 0..ticks {
+            let cell = row
+                .iter()
+                .find(|(span, _)| span.contains(&tick))
+                .map_or_else(|| ".".to_string(), |(_, pid)| pid.to_string());
+            write!(out, " {:>width$}", cell).unwrap();
+        }
+    }
+    out.push('\n');
+    out
+}
+
+#[test]
+fn test_render_pads_to_widest_label() {
+    use super::Pid;
+
+    let rows = vec![vec![(0..2, Pid(7)), (3..4, Pid(12))]];
+    assert_eq!(
+        render(&rows, 5),
+        "tick    0  1  2  3  4\ncore 0  7  7  . 12  .\n"
+    );
+    assert_eq!(render(&[], 3), "tick   0 1 2\n");
+}
@@ -0,0 +1,174 @@
+use std::error::Error;
+use std::fmt;
+
+use super::{Pid, Proc, ProcTable, Signal};
+
+// Job control: a session groups everything started from one terminal, and a process group is one shell job
+// (e.g. all the processes of `cat log | grep err`). Signals such as Ctrl+Z's SIGTSTP go to a whole group at once.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupError {
+    NoSuchProcess(Pid),
+    NoSuchGroup(Pid),
+    // Session leaders can't leave their own group or start another session
+    SessionLeader(Pid),
+    // Moving a process into a group that lives in another session
+    DifferentSession { pid: Pid, pgid: Pid },
+    // setsid on a process that already leads a group
+    AlreadyGroupLeader(Pid),
+}
+
+impl fmt::Display for GroupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GroupError::NoSuchProcess(pid) => write!(f, "no process with pid {}", pid),
+            GroupError::NoSuchGroup(pgid) => write!(f, "no process group {}", pgid),
+            GroupError::SessionLeader(pid) => write!(f, "pid {} is a session leader", pid),
+            GroupError::DifferentSession { pid, pgid } => {
+                write!(f, "group {} is not in the session of pid {}", pgid, pid)
+            }
+            GroupError::AlreadyGroupLeader(pid) => {
+                write!(f, "pid {} already leads a process group", pid)
+            }
+        }
+    }
+}
+
+impl Error for GroupError {}
+
+impl Proc {
+    /// Process group ID. A new process leads its own group; forked children join the parent's.
+    pub fn pgid(&self) -> Pid {
+        self.pgid
+    }
+
+    /// Session ID, inherited on fork like the process group.
+    pub fn sid(&self) -> Pid {
+        self.sid
+    }
+}
+
+impl ProcTable {
+    /// Makes `pid` the leader of a brand-new session and process group, both numbered after it.
+    pub fn setsid(&mut self, pid: Pid) -> Result<(), GroupError> {
+        let proc = self.get_mut(pid).ok_or(GroupError::NoSuchProcess(pid))?;
+        if proc.pgid == pid {
+            return Err(GroupError::AlreadyGroupLeader(pid));
+        }
+        proc.sid = pid;
+        proc.pgid = pid;
+        Ok(())
+    }
+
+    /// Moves `pid` into group `pgid`. Passing its own PID creates a new group led by it; any other `pgid` must be an
+    /// existing group in the same session.
+    pub fn setpgid(&mut self, pid: Pid, pgid: Pid) -> Result<(), GroupError> {
+        let proc = self.get(pid).ok_or(GroupError::NoSuchProcess(pid))?;
+        if proc.sid == pid {
+            return Err(GroupError::SessionLeader(pid));
+        }
+        let sid = proc.sid;
+
+        if pgid != pid {
+            let group_sid = self
+                .procs()
+                .into_iter()
+                .find(|p| p.pgid == pgid && !p.state.is_exited())
+                .map(|p| p.sid)
+                .ok_or(GroupError::NoSuchGroup(pgid))?;
+            if group_sid != sid {
+                return Err(GroupError::DifferentSession { pid, pgid });
+            }
+        }
+
+        self.get_mut(pid).unwrap().pgid = pgid;
+        Ok(())
+    }
+
+    /// Live members of process group `pgid`, in tree order.
+    pub fn group_members(&self, pgid: Pid) -> Vec<Pid> {
+        self.procs()
+            .into_iter()
+            .filter(|p| p.pgid == pgid && !p.state.is_exited())
+            .map(Proc::pid)
+            .collect()
+    }
+
+    /// Queues `sig` on every live member of group `pgid`, like `kill(-pgid, sig)`, and returns how many were signalled.
+    pub fn signal_group(&mut self, pgid: Pid, sig: Signal) -> usize {
+        let mut signalled = 0;
+        self.for_each_mut(&mut |proc| {
+            if proc.pgid == pgid && !proc.state.is_exited() {
+                proc.send_signal(sig);
+                signalled += 1;
+            }
+        });
+        signalled
+    }
+}
+
+#[test]
+fn test_shell_job_control() {
+    let pids = super::PidAllocator::default();
+    let (mut table, _, rsyslogd, bash) = super::table::init_tree(&pids);
+    let init = table.init().pid();
+    assert_eq!(table.get(bash).unwrap().pgid(), init);
+
+    // bash starts its own session, then runs a two-process pipeline as one job
+    table.setsid(bash).unwrap();
+    let (cat, grep) = {
+        let shell = table.get_mut(bash).unwrap();
+        (shell.fork(), shell.fork())
+    };
+    table.setpgid(cat, cat).unwrap();
+    table.setpgid(grep, cat).unwrap();
+    assert_eq!(table.group_members(cat), vec![cat, grep]);
+    assert_eq!(table.get(grep).unwrap().sid(), bash);
+
+    // Ctrl+Z stops the job but not the shell
+    assert_eq!(table.signal_group(cat, Signal::Tstp), 2);
+    table.for_each_mut(&mut |p| {
+        p.deliver_signals();
+    });
+    assert!(!table.get(cat).unwrap().is_runnable());
+    assert!(!table.get(grep).unwrap().is_runnable());
+    assert!(table.get(bash).unwrap().is_runnable());
+
+    // rsyslogd lives in init's session, so it can't join bash's job
+    assert_eq!(
+        table.setpgid(rsyslogd, cat),
+        Err(GroupError::DifferentSession {
+            pid: rsyslogd,
+            pgid: cat
+        })
+    );
+}
+
+#[test]
+fn test_group_rules() {
+    let pids = super::PidAllocator::default();
+    let (mut table, cron, _, bash) = super::table::init_tree(&pids);
+    let init = table.init().pid();
+
+    assert_eq!(
+        table.setsid(init),
+        Err(GroupError::AlreadyGroupLeader(init))
+    );
+    assert_eq!(
+        table.setpgid(init, cron),
+        Err(GroupError::SessionLeader(init))
+    );
+    assert_eq!(
+        table.setpgid(cron, Pid(999)),
+        Err(GroupError::NoSuchGroup(Pid(999)))
+    );
+    assert_eq!(
+        table.setpgid(Pid(999), cron),
+        Err(GroupError::NoSuchProcess(Pid(999)))
+    );
+
+    table.setpgid(cron, cron).unwrap();
+    table.setpgid(bash, cron).unwrap();
+    assert_eq!(table.group_members(cron), vec![cron, bash]);
+    assert_eq!(table.group_members(init).len(), 2);
+}
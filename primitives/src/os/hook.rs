@@ -0,0 +1,82 @@
+use std::fmt;
+use std::sync::Arc;
+
+use super::Proc;
+
+// Dropping a Proc drops its subtree: the process itself first, then its children in order, each before its own
+// children. A drop hook lets a caller watch that happen, e.g. to assert the order in a test rather than printing from
+// `Drop` and reading stdout. Hooks are shared (`Arc`) so one can be handed to a whole tree, and `fork` passes the
+// parent's on to the child.
+
+/// Called with each process as it is dropped, while all of it but its children is still intact.
+#[derive(Clone)]
+pub struct DropHook(Arc<dyn Fn(&Proc) + Send + Sync>);
+
+impl fmt::Debug for DropHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DropHook")
+    }
+}
+
+impl Proc {
+    /// Calls `hook` as this process, and every process now below it, is dropped, replacing any hook they had. To
+    /// collect them somewhere else, have the hook send to a channel: `move |p| tx.send(p.pid()).unwrap()`.
+    pub fn on_drop(&mut self, hook: impl Fn(&Proc) + Send + Sync + 'static) {
+        self.set_drop_hook(Some(DropHook(Arc::new(hook))));
+    }
+
+    /// Takes the hook off this process and everything below it.
+    pub fn clear_drop_hook(&mut self) {
+        self.set_drop_hook(None);
+    }
+
+    fn set_drop_hook(&mut self, hook: Option<DropHook>) {
+        for child in &mut self.children {
+            child.set_drop_hook(hook.clone());
+        }
+        self.drop_hook = hook;
+    }
+}
+
+impl Drop for Proc {
+    fn drop(&mut self) {
+        if let Some(hook) = self.drop_hook.take() {
+            (hook.0)(self);
+        }
+    }
+}
+
+#[test]
+fn test_drop_hook_sees_the_deallocation_order() {
+    use std::sync::{mpsc, Mutex};
+
+    let pids = super::PidAllocator::default();
+    let mut init = Proc::builder(&pids).name("init").build().unwrap();
+    let cron = init.fork();
+    init.child_mut(cron).unwrap().exec("cron", &[]);
+    let rsyslogd = init.fork();
+    let rsyslogd = init.child_mut(rsyslogd).unwrap();
+    rsyslogd.exec("rsyslogd", &[]);
+    let bash = rsyslogd.fork();
+    rsyslogd.child_mut(bash).unwrap().exec("bash", &[]);
+    let dropped = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&dropped);
+    init.on_drop(move |p| log.lock().unwrap().push(p.name().to_string()));
+    // Forked after the hook was set, and inherits it
+    init.find_mut("cron").unwrap().fork();
+    drop(init);
+    assert_eq!(
+        *dropped.lock().unwrap(),
+        ["init", "cron", "cron", "rsyslogd", "bash"]
+    );
+
+    // Or through a channel; a cleared hook isn't called
+    let (tx, rx) = mpsc::channel();
+    let mut parent = Proc::new(&pids);
+    let child = parent.fork();
+    parent.on_drop(move |p| tx.send(p.pid()).unwrap());
+    parent.child_mut(child).unwrap().clear_drop_hook();
+    let parent_pid = parent.pid();
+    drop(parent);
+    assert_eq!(rx.try_iter().collect::<Vec<_>>(), [parent_pid]);
+}
@@ -0,0 +1,180 @@
+use std::collections::VecDeque;
+
+#[cfg(feature = "serde")]
+use super::json::{FromJson, Json, JsonError, ToJson};
+use super::{DetailedState, Pid, Proc, State, TransitionError};
+
+pub type DeviceId = usize;
+
+/// A simulated I/O operation: `pid` stays blocked until a device has spent `ticks` ticks serving it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IoRequest {
+    pub pid: Pid,
+    pub ticks: u64,
+}
+
+/// A device that serves one request at a time, first come first served, like a single disk arm.
+#[derive(Debug, Default)]
+pub struct Device {
+    queue: VecDeque<IoRequest>,
+    progress: u64,
+    completed: u64,
+}
+
+impl Device {
+    pub fn new() -> Self {
+        Device::default()
+    }
+
+    pub fn submit(&mut self, req: IoRequest) {
+        self.queue.push_back(req);
+    }
+
+    /// Spends one tick on the request at the head of the queue and returns its PID if that finished it.
+    pub fn tick(&mut self) -> Option<Pid> {
+        let head = *self.queue.front()?;
+        self.progress += 1;
+        if self.progress < head.ticks {
+            return None;
+        }
+        self.queue.pop_front();
+        self.progress = 0;
+        self.completed += 1;
+        Some(head.pid)
+    }
+
+    /// Requests queued or in service.
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Requests served so far.
+    pub fn completed(&self) -> u64 {
+        self.completed
+    }
+}
+
+impl Proc {
+    /// Issues an I/O that takes `ticks` ticks of device time. Only a Running process can do this; it blocks in the
+    /// Sleeping state until the device completes the returned request.
+    pub fn start_io(&mut self, ticks: u64) -> Result<IoRequest, TransitionError> {
+        if self.state != State::Running {
+            return Err(TransitionError {
+                from: self.state,
+                to: State::Sleeping,
+            });
+        }
+        self.set_state(State::Sleeping);
+        self.charge_syscall();
+        Ok(IoRequest {
+            pid: self.pid(),
+            ticks,
+        })
+    }
+
+    /// Tick at which the current sleep or I/O wait began, as recorded by the simulation.
+    pub fn sleeping_since(&self) -> Option<u64> {
+        self.slept_at
+    }
+
+    // Stamps a sleep with the tick the simulation first saw it.
+    pub(super) fn note_sleep_start(&mut self, now: u64) {
+        if self.state == State::Sleeping && self.slept_at.is_none() {
+            self.slept_at = Some(now);
+        }
+    }
+
+    /// The richer view of the state, when there is one: Stopped needs a stop signal to give a reason, and Sleeping a
+    /// recorded start time, so a merely queued process or an unstamped sleep yields `None`.
+    pub fn detailed_state(&self) -> Option<DetailedState> {
+        match self.state {
+            State::Running => Some(DetailedState::Running),
+            State::Stopped => self
+                .stopped_by()
+                .map(|reason| DetailedState::Stopped { reason }),
+            State::Sleeping => self
+                .slept_at
+                .map(|start_time| DetailedState::Sleeping { start_time }),
+            State::Zombie => Some(DetailedState::Zombie {
+                exit_code: self.exit_code.unwrap_or(0),
+            }),
+            State::Terminated => Some(DetailedState::Terminated {
+                exit_code: self.exit_code.unwrap_or(0),
+            }),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ToJson for Device {
+    fn to_json(&self) -> Json {
+        let queue = self
+            .queue
+            .iter()
+            .map(|req| {
+                Json::object(vec![
+                    ("pid", req.pid.to_json()),
+                    ("ticks", Json::Int(req.ticks.into())),
+                ])
+            })
+            .collect();
+        Json::object(vec![
+            ("queue", Json::Array(queue)),
+            ("progress", Json::Int(self.progress.into())),
+            ("completed", Json::Int(self.completed.into())),
+        ])
+    }
+}
+
+#[cfg(feature = "serde")]
+impl FromJson for Device {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        let mut device = Device::new();
+        for req in json.array("queue")? {
+            device.submit(IoRequest {
+                pid: req.parse("pid")?,
+                ticks: req.int("ticks")?,
+            });
+        }
+        device.progress = json.int("progress")?;
+        device.completed = json.int("completed")?;
+        Ok(device)
+    }
+}
+
+#[test]
+fn test_device_serves_requests_in_order() {
+    let mut disk = Device::new();
+    disk.submit(IoRequest {
+        pid: Pid(1),
+        ticks: 2,
+    });
+    disk.submit(IoRequest {
+        pid: Pid(2),
+        ticks: 1,
+    });
+
+    let done: Vec<_> = (0..4).map(|_| disk.tick()).collect();
+    assert_eq!(done, vec![None, Some(Pid(1)), Some(Pid(2)), None]);
+    assert_eq!(disk.pending(), 0);
+    assert_eq!(disk.completed(), 2);
+}
+
+#[test]
+fn test_start_io_blocks_a_running_proc() {
+    let mut proc = Proc::new(&super::PidAllocator::default());
+    assert!(proc.start_io(1).is_err());
+
+    proc.set_state(State::Running);
+    let req = proc.start_io(4).unwrap();
+    assert_eq!(req.pid, proc.pid());
+    assert_eq!(proc.state(), State::Sleeping);
+    assert_eq!(proc.detailed_state(), None);
+
+    proc.note_sleep_start(7);
+    proc.note_sleep_start(9);
+    assert_eq!(
+        proc.detailed_state(),
+        Some(DetailedState::Sleeping { start_time: 7 })
+    );
+}
@@ -0,0 +1,912 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use super::{
+    CpuTimes, DetailedState, MemUsage, Pid, PidAllocator, Proc, ProcTable, SchedClass, Signal,
+    SignalMask, State, StopKind, Task,
+};
+
+// JSON for the simulator's types, so a process tree can be saved and loaded again. The encoding follows serde's
+// defaults: structs are objects keyed by field name, unit enum variants are strings, and variants with data are
+// objects with the variant name as their only key, like `{"Stopped":{"reason":"Mandatory"}}`. Everything is written
+// out by hand to keep the crate free of dependencies.
+
+/// A parsed JSON document. Numbers are integers only, as nothing here needs fractions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Int(i128),
+    Str(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>), // In document order
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JsonError {
+    // Not valid JSON, at this byte offset
+    Syntax(usize),
+    // Valid JSON, but this field is missing or holds the wrong kind of value
+    Field(&'static str),
+    // A process in the document wants a PID the allocator has already handed out
+    PidTaken(Pid),
+    // A saved file written in a format version we can't read
+    Version(u64),
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonError::Syntax(offset) => write!(f, "invalid JSON at byte {}", offset),
+            JsonError::Field(name) => write!(f, "missing or invalid field `{}`", name),
+            JsonError::PidTaken(pid) => write!(f, "pid {} is already in use", pid),
+            JsonError::Version(version) => write!(
+                f,
+                "format version {} is not supported (expected {})",
+                version, FORMAT_VERSION
+            ),
+        }
+    }
+}
+
+impl Error for JsonError {}
+
+// Loading a file reports bad contents as bad data, next to the errors from reading it.
+impl From<JsonError> for io::Error {
+    fn from(err: JsonError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+/// Version written into saved files, bumped whenever their layout changes.
+pub const FORMAT_VERSION: u64 = 1;
+
+/// Types that can be written out as JSON.
+pub trait ToJson {
+    fn to_json(&self) -> Json;
+}
+
+/// Types that can be read back from the JSON their `ToJson` wrote. Processes need a PID allocator as well, so they
+/// are loaded with `Proc::from_json` instead.
+pub trait FromJson: Sized {
+    fn from_json(json: &Json) -> Result<Self, JsonError>;
+}
+
+impl Json {
+    /// An object with these fields, in this order.
+    pub fn object(fields: Vec<(&str, Json)>) -> Json {
+        Json::Object(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect(),
+        )
+    }
+
+    /// Value of field `name`, if this is an object that has it.
+    pub fn get(&self, name: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields.iter().find(|(key, _)| key == name).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Like `get`, but a missing field is an error naming it.
+    pub fn field(&self, name: &'static str) -> Result<&Json, JsonError> {
+        self.get(name).ok_or(JsonError::Field(name))
+    }
+
+    pub fn int<T: TryFrom<i128>>(&self, name: &'static str) -> Result<T, JsonError> {
+        match self.field(name)? {
+            Json::Int(n) => T::try_from(*n).map_err(|_| JsonError::Field(name)),
+            _ => Err(JsonError::Field(name)),
+        }
+    }
+
+    pub(super) fn opt_int<T: TryFrom<i128>>(
+        &self,
+        name: &'static str,
+    ) -> Result<Option<T>, JsonError> {
+        match self.field(name)? {
+            Json::Null => Ok(None),
+            _ => self.int(name).map(Some),
+        }
+    }
+
+    pub fn array(&self, name: &'static str) -> Result<&[Json], JsonError> {
+        match self.field(name)? {
+            Json::Array(items) => Ok(items),
+            _ => Err(JsonError::Field(name)),
+        }
+    }
+
+    pub fn string(&self, name: &'static str) -> Result<&str, JsonError> {
+        match self.field(name)? {
+            Json::Str(s) => Ok(s),
+            _ => Err(JsonError::Field(name)),
+        }
+    }
+
+    pub fn bool(&self, name: &'static str) -> Result<bool, JsonError> {
+        match self.field(name)? {
+            Json::Bool(b) => Ok(*b),
+            _ => Err(JsonError::Field(name)),
+        }
+    }
+
+    pub(super) fn pids(&self, name: &'static str) -> Result<Vec<Pid>, JsonError> {
+        self.array(name)?
+            .iter()
+            .map(|pid| Pid::from_json(pid).map_err(|_| JsonError::Field(name)))
+            .collect()
+    }
+
+    pub(super) fn opt_pid(&self, name: &'static str) -> Result<Option<Pid>, JsonError> {
+        Ok(self.opt_int(name)?.map(Pid))
+    }
+
+    /// Reads field `name` as a `T`.
+    pub fn parse<T: FromJson>(&self, name: &'static str) -> Result<T, JsonError> {
+        T::from_json(self.field(name)?).map_err(|_| JsonError::Field(name))
+    }
+}
+
+impl fmt::Display for Json {
+    /// Compact JSON, with no whitespace between tokens.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Int(n) => write!(f, "{}", n),
+            Json::Str(s) => write_str(f, s),
+            Json::Array(items) => {
+                f.write_char('[')?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                f.write_char(']')
+            }
+            Json::Object(fields) => {
+                f.write_char('{')?;
+                for (i, (name, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_str(f, name)?;
+                    write!(f, ":{}", value)?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+fn write_str(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+impl FromStr for Json {
+    type Err = JsonError;
+
+    fn from_str(text: &str) -> Result<Json, JsonError> {
+        let mut parser = Parser {
+            text: text.as_bytes(),
+            pos: 0,
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.pos != text.len() {
+            return Err(JsonError::Syntax(parser.pos));
+        }
+        Ok(value)
+    }
+}
+
+// Recursive descent over the bytes; strings are re-validated as UTF-8 when they are cut out.
+struct Parser<'a> {
+    text: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error<T>(&self) -> Result<T, JsonError> {
+        Err(JsonError::Syntax(self.pos))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .text
+            .get(self.pos)
+            .is_some_and(|b| b" \t\n\r".contains(b))
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        self.skip_whitespace();
+        if self.text.get(self.pos) == Some(&byte) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn keyword(&mut self, word: &str, value: Json) -> Result<Json, JsonError> {
+        if self.text[self.pos..].starts_with(word.as_bytes()) {
+            self.pos += word.len();
+            Ok(value)
+        } else {
+            self.error()
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, JsonError> {
+        self.skip_whitespace();
+        match self.text.get(self.pos) {
+            Some(b'n') => self.keyword("null", Json::Null),
+            Some(b't') => self.keyword("true", Json::Bool(true)),
+            Some(b'f') => self.keyword("false", Json::Bool(false)),
+            Some(b'"') => self.string().map(Json::Str),
+            Some(b'-' | b'0'..=b'9') => self.int(),
+            Some(b'[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                if !self.eat(b']') {
+                    loop {
+                        items.push(self.value()?);
+                        if self.eat(b']') {
+                            break;
+                        }
+                        if !self.eat(b',') {
+                            return self.error();
+                        }
+                    }
+                }
+                Ok(Json::Array(items))
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                let mut fields = Vec::new();
+                if !self.eat(b'}') {
+                    loop {
+                        self.skip_whitespace();
+                        let name = self.string()?;
+                        if !self.eat(b':') {
+                            return self.error();
+                        }
+                        fields.push((name, self.value()?));
+                        if self.eat(b'}') {
+                            break;
+                        }
+                        if !self.eat(b',') {
+                            return self.error();
+                        }
+                    }
+                }
+                Ok(Json::Object(fields))
+            }
+            _ => self.error(),
+        }
+    }
+
+    fn int(&mut self) -> Result<Json, JsonError> {
+        let start = self.pos;
+        if self.text[self.pos] == b'-' {
+            self.pos += 1;
+        }
+        while self.text.get(self.pos).is_some_and(u8::is_ascii_digit) {
+            self.pos += 1;
+        }
+        // Fractions and exponents aren't needed by anything we write
+        if matches!(self.text.get(self.pos), Some(b'.' | b'e' | b'E')) {
+            return self.error();
+        }
+        std::str::from_utf8(&self.text[start..self.pos])
+            .ok()
+            .and_then(|digits| digits.parse().ok())
+            .map(Json::Int)
+            .ok_or(JsonError::Syntax(start))
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        if self.text.get(self.pos) != Some(&b'"') {
+            return self.error();
+        }
+        self.pos += 1;
+        let mut out = Vec::new();
+        loop {
+            let Some(&byte) = self.text.get(self.pos) else {
+                return self.error();
+            };
+            self.pos += 1;
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let Some(&escape) = self.text.get(self.pos) else {
+                        return self.error();
+                    };
+                    self.pos += 1;
+                    let c = match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.unicode_escape()?,
+                        _ => return self.error(),
+                    };
+                    out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                byte if byte < 0x20 => return self.error(),
+                byte => out.push(byte),
+            }
+        }
+        String::from_utf8(out).or_else(|_| self.error())
+    }
+
+    // The four hex digits after `\u`, and a second escape after them if they are the first half of a surrogate pair.
+    fn unicode_escape(&mut self) -> Result<char, JsonError> {
+        let high = self.hex4()?;
+        if !(0xd800..0xdc00).contains(&high) {
+            return char::from_u32(high).map_or_else(|| self.error(), Ok);
+        }
+        if !self.text[self.pos..].starts_with(b"\\u") {
+            return self.error();
+        }
+        self.pos += 2;
+        let low = self.hex4()?;
+        if !(0xdc00..0xe000).contains(&low) {
+            return self.error();
+        }
+        let c = 0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00);
+        char::from_u32(c).map_or_else(|| self.error(), Ok)
+    }
+
+    fn hex4(&mut self) -> Result<u32, JsonError> {
+        let digits = self
+            .text
+            .get(self.pos..self.pos + 4)
+            .and_then(|d| std::str::from_utf8(d).ok())
+            .and_then(|d| u32::from_str_radix(d, 16).ok());
+        match digits {
+            Some(n) => {
+                self.pos += 4;
+                Ok(n)
+            }
+            None => self.error(),
+        }
+    }
+}
+
+// Fieldless enums are written as their variant names, which Debug already gives us.
+fn unit_variant<T: Copy + fmt::Debug>(value: T) -> Json {
+    Json::Str(format!("{:?}", value))
+}
+
+fn parse_unit_variant<T: Copy + fmt::Debug>(json: &Json, all: &[T]) -> Result<T, JsonError> {
+    let Json::Str(name) = json else {
+        return Err(JsonError::Field("variant"));
+    };
+    all.iter()
+        .copied()
+        .find(|v| format!("{:?}", v) == *name)
+        .ok_or(JsonError::Field("variant"))
+}
+
+const STATES: [State; 5] = [
+    State::Running,
+    State::Stopped,
+    State::Sleeping,
+    State::Zombie,
+    State::Terminated,
+];
+const STOP_KINDS: [StopKind; 2] = [StopKind::Mandatory, StopKind::Ignorable];
+const SIGNALS: [Signal; 5] = [
+    Signal::Kill,
+    Signal::Term,
+    Signal::Stop,
+    Signal::Tstp,
+    Signal::Cont,
+];
+const CLASSES: [SchedClass; 3] = [SchedClass::Interactive, SchedClass::Batch, SchedClass::Idle];
+
+impl ToJson for State {
+    fn to_json(&self) -> Json {
+        unit_variant(*self)
+    }
+}
+
+impl FromJson for State {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        parse_unit_variant(json, &STATES)
+    }
+}
+
+impl ToJson for StopKind {
+    fn to_json(&self) -> Json {
+        unit_variant(*self)
+    }
+}
+
+impl FromJson for StopKind {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        parse_unit_variant(json, &STOP_KINDS)
+    }
+}
+
+impl ToJson for Signal {
+    fn to_json(&self) -> Json {
+        unit_variant(*self)
+    }
+}
+
+impl FromJson for Signal {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        parse_unit_variant(json, &SIGNALS)
+    }
+}
+
+impl ToJson for SchedClass {
+    fn to_json(&self) -> Json {
+        unit_variant(*self)
+    }
+}
+
+impl FromJson for SchedClass {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        parse_unit_variant(json, &CLASSES)
+    }
+}
+
+impl ToJson for Pid {
+    fn to_json(&self) -> Json {
+        Json::Int(self.0.into())
+    }
+}
+
+impl FromJson for Pid {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        match json {
+            Json::Int(n) => u32::try_from(*n)
+                .map(Pid)
+                .map_err(|_| JsonError::Field("pid")),
+            _ => Err(JsonError::Field("pid")),
+        }
+    }
+}
+
+impl ToJson for DetailedState {
+    fn to_json(&self) -> Json {
+        let (variant, fields) = match *self {
+            DetailedState::Running => return Json::Str("Running".to_string()),
+            DetailedState::Stopped { reason } => ("Stopped", vec![("reason", reason.to_json())]),
+            DetailedState::Sleeping { start_time } => (
+                "Sleeping",
+                vec![("start_time", Json::Int(start_time.into()))],
+            ),
+            DetailedState::Zombie { exit_code } => {
+                ("Zombie", vec![("exit_code", Json::Int(exit_code.into()))])
+            }
+            DetailedState::Terminated { exit_code } => (
+                "Terminated",
+                vec![("exit_code", Json::Int(exit_code.into()))],
+            ),
+        };
+        Json::object(vec![(variant, Json::object(fields))])
+    }
+}
+
+impl FromJson for DetailedState {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        let (variant, data) = match json {
+            Json::Str(name) if name == "Running" => return Ok(DetailedState::Running),
+            Json::Object(fields) if fields.len() == 1 => (&fields[0].0, &fields[0].1),
+            _ => return Err(JsonError::Field("variant")),
+        };
+        match variant.as_str() {
+            "Stopped" => Ok(DetailedState::Stopped {
+                reason: data.parse("reason")?,
+            }),
+            "Sleeping" => Ok(DetailedState::Sleeping {
+                start_time: data.int("start_time")?,
+            }),
+            "Zombie" => Ok(DetailedState::Zombie {
+                exit_code: data.int("exit_code")?,
+            }),
+            "Terminated" => Ok(DetailedState::Terminated {
+                exit_code: data.int("exit_code")?,
+            }),
+            _ => Err(JsonError::Field("variant")),
+        }
+    }
+}
+
+// Written as the list of blocked signals rather than the raw bits.
+impl ToJson for SignalMask {
+    fn to_json(&self) -> Json {
+        Json::Array(
+            SIGNALS
+                .iter()
+                .filter(|&&sig| self.contains(sig))
+                .map(Signal::to_json)
+                .collect(),
+        )
+    }
+}
+
+impl FromJson for SignalMask {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        let Json::Array(items) = json else {
+            return Err(JsonError::Field("mask"));
+        };
+        let mut mask = SignalMask::default();
+        for item in items {
+            mask.insert(Signal::from_json(item)?);
+        }
+        Ok(mask)
+    }
+}
+
+impl ToJson for MemUsage {
+    fn to_json(&self) -> Json {
+        Json::object(vec![
+            ("resident", Json::Int(self.resident.into())),
+            ("peak", Json::Int(self.peak.into())),
+        ])
+    }
+}
+
+impl FromJson for MemUsage {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        Ok(MemUsage {
+            resident: json.int("resident")?,
+            peak: json.int("peak")?,
+        })
+    }
+}
+
+impl ToJson for CpuTimes {
+    fn to_json(&self) -> Json {
+        Json::object(vec![
+            ("user", Json::Int(self.user.into())),
+            ("system", Json::Int(self.system.into())),
+            ("children_user", Json::Int(self.children_user.into())),
+            ("children_system", Json::Int(self.children_system.into())),
+        ])
+    }
+}
+
+impl FromJson for CpuTimes {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        Ok(CpuTimes {
+            user: json.int("user")?,
+            system: json.int("system")?,
+            children_user: json.int("children_user")?,
+            children_system: json.int("children_system")?,
+        })
+    }
+}
+
+pub(super) fn pid_list<'a>(pids: impl IntoIterator<Item = &'a Pid>) -> Json {
+    Json::Array(pids.into_iter().map(Pid::to_json).collect())
+}
+
+pub(super) fn opt_int(value: Option<impl Into<i128>>) -> Json {
+    value.map_or(Json::Null, |n| Json::Int(n.into()))
+}
+
+/// The whole subtree, children nested inside their parents. Lifecycle events not yet collected into an `AuditLog`
+/// are left out.
+impl ToJson for Proc {
+    fn to_json(&self) -> Json {
+        let stop = match self.stop {
+            Some((kind, resume)) => {
+                Json::object(vec![("kind", kind.to_json()), ("resume", resume.to_json())])
+            }
+            None => Json::Null,
+        };
+        let threads = self
+            .threads
+            .iter()
+            .map(|t| {
+                Json::object(vec![
+                    ("tid", t.tid().to_json()),
+                    ("state", t.state().to_json()),
+                ])
+            })
+            .collect();
+        // Sorted, so the same environment is always written the same way
+        let mut env: Vec<_> = self
+            .env
+            .iter()
+            .map(|(name, value)| (name.clone(), Json::Str(value.clone())))
+            .collect();
+        env.sort_by(|a, b| a.0.cmp(&b.0));
+        Json::object(vec![
+            ("pid", self.pid().to_json()),
+            ("name", Json::Str(self.name.to_string())),
+            (
+                "argv",
+                Json::Array(self.argv.iter().cloned().map(Json::Str).collect()),
+            ),
+            ("env", Json::Object(env)),
+            ("pgid", self.pgid.to_json()),
+            ("sid", self.sid.to_json()),
+            ("state", self.state.to_json()),
+            ("nice", Json::Int(self.nice.into())),
+            ("class", self.class.to_json()),
+            ("boost", Json::Int(self.boost.into())),
+            ("inherited", opt_int(self.inherited)),
+            ("exit_code", opt_int(self.exit_code)),
+            (
+                "pending",
+                Json::Array(self.pending.iter().map(Signal::to_json).collect()),
+            ),
+            ("mask", self.mask.to_json()),
+            ("stop", stop),
+            ("sleep", opt_int(self.sleep)),
+            ("slept_at", opt_int(self.slept_at)),
+            ("times", self.times.to_json()),
+            ("mem", self.mem.to_json()),
+            ("threads", Json::Array(threads)),
+            ("affinity", Json::Int(self.affinity.into())),
+            (
+                "children",
+                Json::Array(self.children.iter().map(Proc::to_json).collect()),
+            ),
+        ])
+    }
+}
+
+impl Proc {
+    /// Rebuilds a subtree written by `to_json`, claiming each process's (and thread's) PID from `pids`.
+    pub fn from_json(json: &Json, pids: &PidAllocator) -> Result<Proc, JsonError> {
+        let pid: Pid = json.parse("pid")?;
+        let handle = pids.reserve(pid).ok_or(JsonError::PidTaken(pid))?;
+        let mut proc = Proc::with_pid(handle);
+        proc.audit.clear();
+
+        proc.name = json.string("name")?.to_string().into();
+        proc.argv = json
+            .array("argv")?
+            .iter()
+            .map(|arg| match arg {
+                Json::Str(s) => Ok(s.clone()),
+                _ => Err(JsonError::Field("argv")),
+            })
+            .collect::<Result<_, _>>()?;
+        // Older files have no environment
+        if let Some(env) = json.get("env") {
+            let Json::Object(vars) = env else {
+                return Err(JsonError::Field("env"));
+            };
+            for (name, value) in vars {
+                let Json::Str(value) = value else {
+                    return Err(JsonError::Field("env"));
+                };
+                proc.env.insert(name.clone(), value.clone());
+            }
+        }
+        proc.pgid = json.parse("pgid")?;
+        proc.sid = json.parse("sid")?;
+        proc.state = json.parse("state")?;
+        proc.nice = json.int("nice")?;
+        proc.class = json.parse("class")?;
+        proc.boost = json.int("boost")?;
+        proc.inherited = json.opt_int("inherited")?;
+        proc.exit_code = json.opt_int("exit_code")?;
+        proc.pending = json
+            .array("pending")?
+            .iter()
+            .map(Signal::from_json)
+            .collect::<Result<VecDeque<_>, _>>()?;
+        proc.mask = json.parse("mask")?;
+        proc.stop = match json.field("stop")? {
+            Json::Null => None,
+            stop => Some((stop.parse("kind")?, stop.parse("resume")?)),
+        };
+        proc.sleep = json.opt_int("sleep")?;
+        proc.slept_at = json.opt_int("slept_at")?;
+        proc.times = json.parse("times")?;
+        proc.mem = json.parse("mem")?;
+        for thread in json.array("threads")? {
+            let tid: Pid = thread.parse("tid")?;
+            proc.threads.push(Task {
+                tid: pids.reserve(tid).ok_or(JsonError::PidTaken(tid))?,
+                state: thread.parse("state")?,
+            });
+        }
+        proc.affinity = json.int("affinity")?;
+        for child in json.array("children")? {
+            proc.add_child(Proc::from_json(child, pids)?);
+        }
+        Ok(proc)
+    }
+}
+
+// Saved files are one JSON object stamped with the format version. The process tree is always under "table", so
+// `ProcTable::load_json` can also pull just the processes out of a saved simulation.
+pub(super) fn write_file(path: &Path, mut fields: Vec<(&str, Json)>) -> io::Result<()> {
+    fields.insert(0, ("version", Json::Int(FORMAT_VERSION.into())));
+    fs::write(path, format!("{}\n", Json::object(fields)))
+}
+
+pub(super) fn read_file(path: &Path) -> io::Result<Json> {
+    let json: Json = fs::read_to_string(path)?.parse()?;
+    let version: u64 = json.int("version")?;
+    if version != FORMAT_VERSION {
+        return Err(JsonError::Version(version).into());
+    }
+    Ok(json)
+}
+
+impl ProcTable {
+    /// Writes the whole process tree to `path`.
+    pub fn save_json(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        write_file(path.as_ref(), vec![("table", self.init().to_json())])
+    }
+
+    /// Reads back a table written by `save_json` (or `Simulation::save`). Its processes keep their PIDs, which
+    /// are claimed from a fresh allocator.
+    pub fn load_json(path: impl AsRef<Path>) -> io::Result<ProcTable> {
+        let json = read_file(path.as_ref())?;
+        ProcTable::from_json(&json, &PidAllocator::default()).map_err(io::Error::from)
+    }
+
+    // The "table" field of a saved file.
+    pub(super) fn from_json(file: &Json, pids: &PidAllocator) -> Result<ProcTable, JsonError> {
+        Ok(ProcTable::new(Proc::from_json(file.field("table")?, pids)?))
+    }
+}
+
+#[test]
+fn test_json_text_round_trips() {
+    let text = r#"{"a": [1, -20, null, true], "b\"\\": "\u00e9\ud83d\ude00\n", "c": {}}"#;
+    let json: Json = text.parse().unwrap();
+    assert_eq!(
+        json.get("a"),
+        Some(&Json::Array(vec![
+            Json::Int(1),
+            Json::Int(-20),
+            Json::Null,
+            Json::Bool(true),
+        ]))
+    );
+    assert_eq!(json.get("b\"\\"), Some(&Json::Str("é😀\n".to_string())));
+    assert_eq!(json.to_string().parse::<Json>().unwrap(), json);
+    assert_eq!(
+        json.to_string(),
+        r#"{"a":[1,-20,null,true],"b\"\\":"é😀\n","c":{}}"#
+    );
+
+    for bad in ["", "[1,]", "{\"a\" 1}", "1.5", "\"open", "nul", "[1] 2"] {
+        assert!(
+            matches!(bad.parse::<Json>(), Err(JsonError::Syntax(_))),
+            "{}",
+            bad
+        );
+    }
+
+    for state in [
+        DetailedState::Running,
+        DetailedState::Stopped {
+            reason: StopKind::Mandatory,
+        },
+        DetailedState::Sleeping { start_time: 42 },
+        DetailedState::Terminated { exit_code: -1 },
+    ] {
+        let text = state.to_json().to_string();
+        let back = DetailedState::from_json(&text.parse().unwrap()).unwrap();
+        assert_eq!(back, state);
+    }
+    assert_eq!(
+        DetailedState::Stopped {
+            reason: StopKind::Ignorable
+        }
+        .to_json()
+        .to_string(),
+        r#"{"Stopped":{"reason":"Ignorable"}}"#
+    );
+}
+
+#[test]
+fn test_proc_tree_round_trips() {
+    let pids = PidAllocator::default();
+    let (mut table, cron, rsyslogd, bash) = super::table::init_tree(&pids);
+    let rsyslogd_proc = table.get_mut(rsyslogd).unwrap();
+    rsyslogd_proc.alloc(12).unwrap();
+    rsyslogd_proc.block_signal(Signal::Term);
+    rsyslogd_proc.send_signal(Signal::Term);
+    rsyslogd_proc.spawn_thread();
+    rsyslogd_proc.set_sched_class(SchedClass::Interactive);
+    let bash_proc = table.get_mut(bash).unwrap();
+    bash_proc.exec("bash", &["-l", "-c", "echo \"hi\""]);
+    bash_proc.setenv("TERM", "xterm");
+    bash_proc.setenv("HOME", "/root");
+    bash_proc.send_signal(Signal::Tstp);
+    bash_proc.deliver_signals();
+    table.get_mut(cron).unwrap().exit(3);
+
+    let text = table.init().to_json().to_string();
+    // A different allocator, as the original PIDs are still held
+    let loaded_pids = PidAllocator::default();
+    let init = Proc::from_json(&text.parse().unwrap(), &loaded_pids).unwrap();
+    assert_eq!(init.to_json().to_string(), text);
+    assert_eq!(loaded_pids.in_use(), pids.in_use());
+
+    let loaded = super::ProcTable::new(init);
+    let bash_proc = loaded.get(bash).unwrap();
+    assert_eq!(bash_proc.stopped_by(), Some(StopKind::Ignorable));
+    assert_eq!(bash_proc.argv(), ["-l", "-c", "echo \"hi\""]);
+    assert_eq!(bash_proc.getenv("HOME"), Some("/root"));
+    assert_eq!(bash_proc.env().len(), 2);
+    assert_eq!(loaded.get(cron).unwrap().exit_code(), Some(3));
+    let rsyslogd_proc = loaded.get(rsyslogd).unwrap();
+    assert_eq!(rsyslogd_proc.memory().resident, 12);
+    assert_eq!(rsyslogd_proc.threads().len(), 1);
+    assert_eq!(rsyslogd_proc.children()[0].pid(), bash);
+
+    // Loading into the original allocator clashes with the live tree
+    assert_eq!(
+        Proc::from_json(&text.parse().unwrap(), &pids).unwrap_err(),
+        JsonError::PidTaken(table.init().pid())
+    );
+    let mut broken: Json = text.parse().unwrap();
+    if let Json::Object(fields) = &mut broken {
+        fields.retain(|(name, _)| name != "sid");
+    }
+    assert_eq!(
+        Proc::from_json(&broken, &PidAllocator::default()).unwrap_err(),
+        JsonError::Field("sid")
+    );
+}
+
+#[test]
+fn test_table_file_round_trips() {
+    let pids = PidAllocator::default();
+    let (table, _, rsyslogd, bash) = super::table::init_tree(&pids);
+    let path = std::env::temp_dir().join(format!("procs-{}.json", std::process::id()));
+    table.save_json(&path).unwrap();
+    let loaded = ProcTable::load_json(&path).unwrap();
+    assert_eq!(loaded.init().to_json(), table.init().to_json());
+    assert_eq!(loaded.get(bash).unwrap().name(), "bash");
+    assert_eq!(loaded.get(rsyslogd).unwrap().children().len(), 1);
+
+    let text = fs::read_to_string(&path).unwrap();
+    assert!(text.starts_with("{\"version\":1,"));
+    fs::write(&path, text.replacen("\"version\":1", "\"version\":9", 1)).unwrap();
+    let err = ProcTable::load_json(&path).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(err.to_string(), JsonError::Version(9).to_string());
+    fs::remove_file(&path).unwrap();
+    assert_eq!(
+        ProcTable::load_json(&path).unwrap_err().kind(),
+        io::ErrorKind::NotFound
+    );
+}
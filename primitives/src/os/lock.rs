@@ -0,0 +1,299 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::error::Error;
+use std::fmt;
+
+use super::{Pid, ProcTable, State};
+
+pub type ResourceId = usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockError {
+    NoSuchResource(ResourceId),
+    NoSuchProcess(Pid),
+    // Only a process on the CPU can ask for a resource (and block waiting for it)
+    NotRunning(Pid),
+    // Releasing a resource someone else holds
+    NotHolder { pid: Pid, resource: ResourceId },
+}
+
+impl fmt::Display for LockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LockError::NoSuchResource(id) => write!(f, "no resource {}", id),
+            LockError::NoSuchProcess(pid) => write!(f, "no process with pid {}", pid),
+            LockError::NotRunning(pid) => write!(f, "pid {} is not running", pid),
+            LockError::NotHolder { pid, resource } => {
+                write!(f, "pid {} does not hold resource {}", pid, resource)
+            }
+        }
+    }
+}
+
+impl Error for LockError {}
+
+/// An exclusive lock: one holder at a time, with waiters served first come first served.
+#[derive(Debug, Default)]
+struct Resource {
+    holder: Option<Pid>,
+    waiters: VecDeque<Pid>,
+}
+
+/// Owns every lockable resource in the system and tracks who holds and who waits for each.
+#[derive(Debug, Default)]
+pub struct LockManager {
+    resources: Vec<Resource>,
+}
+
+impl LockManager {
+    pub fn new() -> Self {
+        LockManager::default()
+    }
+
+    /// Creates a new, free resource.
+    pub fn create(&mut self) -> ResourceId {
+        self.resources.push(Resource::default());
+        self.resources.len() - 1
+    }
+
+    /// Takes `res` for `pid`. Returns `Ok(true)` if it now holds the resource (or already did), and `Ok(false)` if
+    /// someone else has it: the process then blocks in the Sleeping state until it is handed the resource, and the
+    /// holder inherits its priority if that is better than the holder's own.
+    pub fn acquire(
+        &mut self,
+        pid: Pid,
+        res: ResourceId,
+        table: &mut ProcTable,
+    ) -> Result<bool, LockError> {
+        let proc = table.get_mut(pid).ok_or(LockError::NoSuchProcess(pid))?;
+        if proc.state() != State::Running {
+            return Err(LockError::NotRunning(pid));
+        }
+        let resource = self
+            .resources
+            .get_mut(res)
+            .ok_or(LockError::NoSuchResource(res))?;
+        match resource.holder {
+            None => {
+                resource.holder = Some(pid);
+                Ok(true)
+            }
+            Some(holder) if holder == pid => Ok(true),
+            Some(_) => {
+                resource.waiters.push_back(pid);
+                proc.set_state(State::Sleeping);
+                self.update_inheritance(table);
+                Ok(false)
+            }
+        }
+    }
+
+    /// Gives up `res`. The longest waiter, if any, becomes the new holder and is woken; its PID is returned. Any
+    /// priority `pid` inherited through `res` is handed back.
+    pub fn release(
+        &mut self,
+        pid: Pid,
+        res: ResourceId,
+        table: &mut ProcTable,
+    ) -> Result<Option<Pid>, LockError> {
+        let resource = self
+            .resources
+            .get_mut(res)
+            .ok_or(LockError::NoSuchResource(res))?;
+        if resource.holder != Some(pid) {
+            return Err(LockError::NotHolder { pid, resource: res });
+        }
+        resource.holder = resource.waiters.pop_front();
+        let next = resource.holder;
+        if let Some(proc) = next.and_then(|next| table.get_mut(next)) {
+            proc.wake();
+        }
+        if let Some(proc) = table.get_mut(pid) {
+            proc.inherited = None;
+        }
+        self.update_inheritance(table);
+        Ok(next)
+    }
+
+    pub fn holder(&self, res: ResourceId) -> Option<Pid> {
+        self.resources.get(res).and_then(|r| r.holder)
+    }
+
+    /// Processes queued for `res`, longest waiting first.
+    pub fn waiters(&self, res: ResourceId) -> Vec<Pid> {
+        self.resources
+            .get(res)
+            .map(|r| r.waiters.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    // Priority inheritance: every holder runs at the best priority among the processes waiting for it, directly or
+    // through a chain of other holders, so a low-priority holder can't be starved by medium-priority work while a
+    // high-priority process waits on it. Priorities only ever improve while propagating, so this settles even when
+    // the waits form a cycle.
+    fn update_inheritance(&self, table: &mut ProcTable) {
+        for holder in self.resources.iter().filter_map(|r| r.holder) {
+            if let Some(proc) = table.get_mut(holder) {
+                proc.inherited = None;
+            }
+        }
+        let graph = self.wait_for_graph();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (&waiter, &holder) in &graph {
+                let Some(wanted) = table.get(waiter).map(|p| p.priority()) else {
+                    continue;
+                };
+                if let Some(proc) = table.get_mut(holder) {
+                    if wanted < proc.priority() {
+                        proc.inherited = Some(wanted);
+                        changed = true;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Who is waiting for whom: an edge from each waiting process to the holder of the resource it wants. A blocked
+    /// process waits for exactly one resource, so every PID has at most one outgoing edge.
+    pub fn wait_for_graph(&self) -> BTreeMap<Pid, Pid> {
+        let mut graph = BTreeMap::new();
+        for resource in &self.resources {
+            if let Some(holder) = resource.holder {
+                for &waiter in &resource.waiters {
+                    graph.insert(waiter, holder);
+                }
+            }
+        }
+        graph
+    }
+
+    /// PIDs on a cycle of the wait-for graph, in ascending order. These can never make progress on their own.
+    /// Processes merely queued behind a deadlocked one are stuck too, but aren't part of the deadlock itself.
+    pub fn deadlocked(&self) -> Vec<Pid> {
+        let graph = self.wait_for_graph();
+        let mut on_cycle = BTreeSet::new();
+        let mut seen = BTreeSet::new();
+
+        for &start in graph.keys() {
+            // Follow the single outgoing edge until we run off the graph or meet a PID seen before
+            let mut path = Vec::new();
+            let mut at = start;
+            while seen.insert(at) {
+                path.push(at);
+                match graph.get(&at) {
+                    Some(&next) => at = next,
+                    None => break,
+                }
+            }
+            // Only a walk that came back to its own path closed a new cycle
+            if let Some(idx) = path.iter().position(|&pid| pid == at) {
+                if graph.contains_key(&at) {
+                    on_cycle.extend(&path[idx..]);
+                }
+            }
+        }
+        on_cycle.into_iter().collect()
+    }
+}
+
+#[test]
+fn test_waiters_get_the_resource_in_order() {
+    let pids = super::PidAllocator::default();
+    let (mut table, procs) = super::table::flat_table(&pids, 3);
+    let (a, b, c) = (procs[0], procs[1], procs[2]);
+    for &pid in &procs {
+        table.get_mut(pid).unwrap().set_state(State::Running);
+    }
+    let mut locks = LockManager::new();
+    let disk = locks.create();
+
+    assert_eq!(locks.acquire(a, disk, &mut table), Ok(true));
+    assert_eq!(locks.acquire(b, disk, &mut table), Ok(false));
+    assert_eq!(locks.acquire(c, disk, &mut table), Ok(false));
+    assert_eq!(table.get(b).unwrap().state(), State::Sleeping);
+    assert_eq!(
+        locks.acquire(b, disk, &mut table),
+        Err(LockError::NotRunning(b))
+    );
+    assert_eq!(
+        locks.acquire(Pid(999), disk, &mut table),
+        Err(LockError::NoSuchProcess(Pid(999)))
+    );
+    assert_eq!(locks.waiters(disk), vec![b, c]);
+
+    assert_eq!(
+        locks.release(c, disk, &mut table),
+        Err(LockError::NotHolder {
+            pid: c,
+            resource: disk
+        })
+    );
+    assert_eq!(locks.release(a, disk, &mut table), Ok(Some(b)));
+    assert_eq!(table.get(b).unwrap().state(), State::Running);
+    assert_eq!(locks.holder(disk), Some(b));
+    assert!(locks.deadlocked().is_empty());
+}
+
+#[test]
+fn test_detects_deadlock_cycle() {
+    let pids = super::PidAllocator::default();
+    let (mut table, procs) = super::table::flat_table(&pids, 4);
+    for &pid in &procs {
+        table.get_mut(pid).unwrap().set_state(State::Running);
+    }
+    let (p1, p2, p3, p4) = (procs[0], procs[1], procs[2], procs[3]);
+    let mut locks = LockManager::new();
+    let (a, b, c) = (locks.create(), locks.create(), locks.create());
+
+    // p1 holds a and p2 holds b, then each asks for the other's: the classic two-process deadlock
+    locks.acquire(p1, a, &mut table).unwrap();
+    locks.acquire(p2, b, &mut table).unwrap();
+    locks.acquire(p1, b, &mut table).unwrap();
+    locks.acquire(p2, a, &mut table).unwrap();
+    // p3 queues behind the deadlock, p4 holds an unrelated resource
+    locks.acquire(p3, a, &mut table).unwrap();
+    locks.acquire(p4, c, &mut table).unwrap();
+
+    assert_eq!(locks.wait_for_graph().get(&p3), Some(&p1));
+    assert_eq!(locks.deadlocked(), vec![p1, p2]);
+}
+
+#[test]
+fn test_priority_inheritance_prevents_inversion() {
+    let pids = super::PidAllocator::default();
+    let (mut table, procs) = super::table::flat_table(&pids, 4);
+    let (low, medium, high, highest) = (procs[0], procs[1], procs[2], procs[3]);
+    for (&pid, nice) in procs.iter().zip([10, 0, -10, -15]) {
+        let proc = table.get_mut(pid).unwrap();
+        proc.set_nice(nice).unwrap();
+        proc.set_state(State::Running);
+    }
+    let mut locks = LockManager::new();
+    let (log, db) = (locks.create(), locks.create());
+
+    // low holds the log; once high blocks on it, low outranks medium until it lets go
+    locks.acquire(low, log, &mut table).unwrap();
+    assert!(table.get(low).unwrap().priority() > table.get(medium).unwrap().priority());
+    assert_eq!(locks.acquire(high, log, &mut table), Ok(false));
+    assert_eq!(table.get(low).unwrap().priority(), 10);
+    assert!(table.get(low).unwrap().priority() < table.get(medium).unwrap().priority());
+
+    // Inheritance is transitive: medium holds the db, and low now waits for it too
+    locks.acquire(medium, db, &mut table).unwrap();
+    assert_eq!(locks.acquire(low, db, &mut table), Ok(false));
+    assert_eq!(table.get(medium).unwrap().priority(), 10);
+
+    // The boost follows whoever keeps high waiting, and a better waiter raises it further
+    assert_eq!(locks.acquire(highest, log, &mut table), Ok(false));
+    assert_eq!(table.get(low).unwrap().priority(), 5);
+    assert_eq!(table.get(medium).unwrap().priority(), 5);
+
+    assert_eq!(locks.release(medium, db, &mut table), Ok(Some(low)));
+    assert_eq!(table.get(medium).unwrap().priority(), 20);
+    assert_eq!(table.get(low).unwrap().priority(), 5);
+    assert_eq!(locks.release(low, log, &mut table), Ok(Some(high)));
+    assert_eq!(table.get(low).unwrap().priority(), 30);
+    // high now holds the log with highest still waiting
+    assert_eq!(table.get(high).unwrap().priority(), 5);
+}
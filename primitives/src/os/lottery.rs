@@ -0,0 +1,220 @@
+#[cfg(feature = "serde")]
+use super::json::{self, FromJson, Json, JsonError, ToJson};
+use super::rng::Rng;
+use super::{Pid, Proc, ProcTable, SchedClass, State};
+
+// Lottery scheduling (Waldspurger & Weihl, 1994): every tick, each runnable task holds as many tickets as its
+// process's priority is worth and one ticket is drawn at random. Over time each process gets a share of the CPU in
+// proportion to its tickets, without the scheduler keeping any history. The draws come from a seeded `Rng`, so a
+// given seed always produces the same schedule.
+
+impl Proc {
+    /// Lottery tickets, one for each step of priority above the worst: 40 at priority 0 down to 1 at priority 39.
+    pub fn tickets(&self) -> u64 {
+        40 - self.priority() as u64
+    }
+}
+
+#[derive(Debug)]
+pub struct LotteryScheduler {
+    tasks: Vec<Pid>,
+    current: Option<Pid>,
+    rng: Rng,
+}
+
+impl LotteryScheduler {
+    /// Creates a scheduler whose draws are fully determined by `seed`.
+    pub fn new(seed: u64) -> Self {
+        LotteryScheduler::with_rng(Rng::new(seed))
+    }
+
+    /// Creates a scheduler that draws from `rng`, which may be shared with the rest of the run.
+    pub fn with_rng(rng: Rng) -> Self {
+        LotteryScheduler {
+            tasks: Vec::new(),
+            current: None,
+            rng,
+        }
+    }
+
+    /// Enters `proc` and each of its threads into the lottery. Entrants wait in the Stopped state.
+    pub fn admit(&mut self, proc: &mut Proc) {
+        proc.set_state(State::Stopped);
+        self.tasks.extend(proc.task_ids());
+    }
+
+    /// Holds one draw among the runnable tasks and returns the winner, which gets the CPU for this tick.
+    ///
+    /// Tasks that have left the table or exited are dropped; blocked and signal-stopped ones sit the draw out.
+    pub fn tick(&mut self, table: &mut ProcTable) -> Option<Pid> {
+        if let Some(pid) = self.current.take() {
+            if table.task_state(pid) == Some(State::Running) {
+                table.set_task_state(pid, State::Stopped);
+            }
+        }
+        self.tasks
+            .retain(|&tid| table.task_state(tid).is_some_and(|s| !s.is_exited()));
+
+        let mut entrants: Vec<(Pid, u64)> = self
+            .tasks
+            .iter()
+            .filter(|&&tid| table.task_runnable(tid))
+            .filter_map(|&tid| Some((tid, table.owner_of(tid)?.tickets())))
+            .collect();
+        // Idle-class tasks only get a ticket in the draw when nobody else is in it
+        if entrants
+            .iter()
+            .any(|&(tid, _)| table.task_class(tid) != SchedClass::Idle)
+        {
+            entrants.retain(|&(tid, _)| table.task_class(tid) != SchedClass::Idle);
+        }
+        let total: u64 = entrants.iter().map(|&(_, tickets)| tickets).sum();
+        if total == 0 {
+            return None;
+        }
+
+        let mut draw = self.rng.below(total);
+        let (winner, _) = *entrants
+            .iter()
+            .find(|&&(_, tickets)| {
+                if draw < tickets {
+                    return true;
+                }
+                draw -= tickets;
+                false
+            })
+            .unwrap();
+        table.set_task_state(winner, State::Running);
+        self.current = Some(winner);
+        Some(winner)
+    }
+
+    /// Returns tasks whose sleep just ended to the draw. Every tick is a fresh draw, so there is no queue to jump.
+    pub fn wake(&mut self, table: &mut ProcTable, woken: &[Pid]) {
+        // A zero-length sleep can end before the process was ever taken off the CPU
+        for &pid in woken.iter().filter(|&&pid| self.current != Some(pid)) {
+            if table.task_state(pid) == Some(State::Running) {
+                table.set_task_state(pid, State::Stopped);
+            }
+        }
+    }
+
+    /// Takes `tasks` out of the lottery, e.g. for a process moving to another core. One of them holding the CPU gives it
+    /// up and goes back to Stopped.
+    pub fn remove(&mut self, table: &mut ProcTable, tasks: &[Pid]) {
+        self.tasks.retain(|tid| !tasks.contains(tid));
+        if let Some(pid) = self.current.filter(|pid| tasks.contains(pid)) {
+            self.current = None;
+            if table.task_state(pid) == Some(State::Running) {
+                table.set_task_state(pid, State::Stopped);
+            }
+        }
+    }
+
+    /// Every other task in the lottery, in the order they were admitted. Which of them runs next is down to the draw.
+    pub fn queued(&self) -> Vec<Pid> {
+        self.tasks
+            .iter()
+            .copied()
+            .filter(|&tid| self.current != Some(tid))
+            .collect()
+    }
+
+    /// The task that won the last draw, if it still holds the CPU.
+    pub fn current(&self) -> Option<Pid> {
+        self.current
+    }
+}
+
+// The generator's state is saved with the rest, so a resumed lottery makes the same draws it would have. A resumed
+// scheduler gets an `Rng` of its own, even if the original shared one.
+#[cfg(feature = "serde")]
+impl ToJson for LotteryScheduler {
+    fn to_json(&self) -> Json {
+        Json::object(vec![
+            ("tasks", json::pid_list(&self.tasks)),
+            ("current", json::opt_int(self.current.map(|pid| pid.0))),
+            ("rng", Json::Int(self.rng.state().into())),
+        ])
+    }
+}
+
+#[cfg(feature = "serde")]
+impl FromJson for LotteryScheduler {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        Ok(LotteryScheduler {
+            tasks: json.pids("tasks")?,
+            current: json.opt_pid("current")?,
+            rng: Rng::new(json.int("rng")?),
+        })
+    }
+}
+
+#[test]
+fn test_cpu_share_follows_ticket_share() {
+    let pids = super::PidAllocator::default();
+    let (mut table, procs) = super::table::flat_table(&pids, 3);
+    // Priorities 10, 20 and 30 hold 30, 20 and 10 tickets
+    for (&pid, nice) in procs.iter().zip([-10, 0, 10]) {
+        table.get_mut(pid).unwrap().set_nice(nice).unwrap();
+    }
+    let mut lottery = LotteryScheduler::new(7);
+    for &pid in &procs {
+        lottery.admit(table.get_mut(pid).unwrap());
+    }
+
+    let draws = 60_000;
+    let mut wins = [0u32; 3];
+    for _ in 0..draws {
+        let winner = lottery.tick(&mut table).unwrap();
+        wins[procs.iter().position(|&pid| pid == winner).unwrap()] += 1;
+    }
+    for (won, tickets) in wins.iter().zip([30.0, 20.0, 10.0]) {
+        let share = *won as f64 / draws as f64;
+        assert!((share - tickets / 60.0).abs() < 0.01, "{:?}", wins);
+    }
+}
+
+#[test]
+fn test_same_seed_same_schedule() {
+    let pids = super::PidAllocator::default();
+    let run = |seed| {
+        let (mut table, procs) = super::table::flat_table(&pids, 4);
+        let mut lottery = LotteryScheduler::new(seed);
+        for &pid in &procs {
+            lottery.admit(table.get_mut(pid).unwrap());
+        }
+        // Positions rather than PIDs, since each table gets fresh PIDs
+        let order: Vec<_> = (0..50)
+            .map(|_| {
+                let winner = lottery.tick(&mut table).unwrap();
+                procs.iter().position(|&pid| pid == winner).unwrap()
+            })
+            .collect();
+        order
+    };
+    assert_eq!(run(1), run(1));
+    assert_ne!(run(1), run(2));
+
+    // An idle-class process only wins once it is alone
+    let (mut table, procs) = super::table::flat_table(&pids, 2);
+    let mut lottery = LotteryScheduler::new(3);
+    table
+        .get_mut(procs[0])
+        .unwrap()
+        .set_sched_class(SchedClass::Idle);
+    for &pid in &procs {
+        lottery.admit(table.get_mut(pid).unwrap());
+    }
+    assert!((0..20).all(|_| lottery.tick(&mut table) == Some(procs[1])));
+    table.get_mut(procs[1]).unwrap().exit(0);
+    assert_eq!(lottery.tick(&mut table), Some(procs[0]));
+
+    // Nobody left to run, nobody wins
+    let (mut table, procs) = super::table::flat_table(&pids, 1);
+    let mut lottery = LotteryScheduler::new(1);
+    lottery.admit(table.get_mut(procs[0]).unwrap());
+    table.get_mut(procs[0]).unwrap().exit(0);
+    assert_eq!(lottery.tick(&mut table), None);
+    assert_eq!(lottery.current(), None);
+}
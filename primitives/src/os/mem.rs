@@ -0,0 +1,128 @@
+use std::error::Error;
+use std::fmt;
+
+use super::{Pid, Proc, ProcTable};
+
+/// Memory is accounted in whole pages of this many bytes.
+pub const PAGE_SIZE: u64 = 4096;
+
+/// A process's simulated memory footprint, in pages.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MemUsage {
+    pub resident: u64,
+    pub peak: u64,
+}
+
+impl MemUsage {
+    pub fn resident_bytes(&self) -> u64 {
+        self.resident * PAGE_SIZE
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemError {
+    // Exited processes have already given all their memory back
+    Exited(Pid),
+    // Freeing more pages than the process holds
+    Overfree { requested: u64, resident: u64 },
+}
+
+impl fmt::Display for MemError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MemError::Exited(pid) => write!(f, "pid {} has exited", pid),
+            MemError::Overfree {
+                requested,
+                resident,
+            } => write!(
+                f,
+                "cannot free {} pages, only {} resident",
+                requested, resident
+            ),
+        }
+    }
+}
+
+impl Error for MemError {}
+
+impl Proc {
+    pub fn memory(&self) -> MemUsage {
+        self.mem
+    }
+
+    /// Maps `pages` more pages into the process, raising the peak if needed.
+    pub fn alloc(&mut self, pages: u64) -> Result<(), MemError> {
+        if self.state.is_exited() {
+            return Err(MemError::Exited(self.pid()));
+        }
+        self.mem.resident += pages;
+        self.mem.peak = self.mem.peak.max(self.mem.resident);
+        Ok(())
+    }
+
+    /// Unmaps `pages` pages. Nothing is freed if that is more than the process holds.
+    pub fn free(&mut self, pages: u64) -> Result<(), MemError> {
+        if pages > self.mem.resident {
+            return Err(MemError::Overfree {
+                requested: pages,
+                resident: self.mem.resident,
+            });
+        }
+        self.mem.resident -= pages;
+        Ok(())
+    }
+
+    /// Resident pages of this process and all of its descendants.
+    pub fn tree_memory(&self) -> u64 {
+        self.mem.resident + self.children.iter().map(Proc::tree_memory).sum::<u64>()
+    }
+}
+
+impl ProcTable {
+    /// Resident pages across every process in the table.
+    pub fn total_memory(&self) -> u64 {
+        self.init().tree_memory()
+    }
+}
+
+#[test]
+fn test_alloc_free_and_peak() {
+    let mut proc = Proc::new(&super::PidAllocator::default());
+    proc.alloc(10).unwrap();
+    proc.free(4).unwrap();
+    proc.alloc(2).unwrap();
+    assert_eq!(
+        proc.memory(),
+        MemUsage {
+            resident: 8,
+            peak: 10
+        }
+    );
+    assert_eq!(proc.memory().resident_bytes(), 8 * PAGE_SIZE);
+    assert_eq!(
+        proc.free(9),
+        Err(MemError::Overfree {
+            requested: 9,
+            resident: 8
+        })
+    );
+
+    // Exiting releases everything, and the process can't map anything new
+    proc.exit(0);
+    assert_eq!(proc.memory().resident, 0);
+    assert_eq!(proc.memory().peak, 10);
+    assert_eq!(proc.alloc(1), Err(MemError::Exited(proc.pid())));
+}
+
+#[test]
+fn test_tree_and_table_rollups() {
+    let pids = super::PidAllocator::default();
+    let (mut table, cron, rsyslogd, bash) = super::table::init_tree(&pids);
+    table.init_mut().alloc(1).unwrap();
+    table.get_mut(cron).unwrap().alloc(2).unwrap();
+    table.get_mut(rsyslogd).unwrap().alloc(4).unwrap();
+    table.get_mut(bash).unwrap().alloc(8).unwrap();
+
+    assert_eq!(table.get(rsyslogd).unwrap().tree_memory(), 12);
+    assert_eq!(table.total_memory(), 15);
+}
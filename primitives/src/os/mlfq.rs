@@ -0,0 +1,443 @@
+use std::collections::VecDeque;
+
+#[cfg(feature = "serde")]
+use super::json::{self, FromJson, Json, JsonError, ToJson};
+use super::{Pid, Proc, ProcTable, SchedClass, State};
+
+// Multi-level feedback queue: new processes start in the top (highest priority) queue. A process that burns through
+// its whole quantum is assumed to be CPU-bound and demoted one level; every `boost_interval` ticks all processes are
+// moved back to the top so long-running jobs can't starve.
+
+/// Per-queue counters, useful for comparing the MLFQ against a plain priority sort.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct QueueStats {
+    pub quantum: u32,
+    pub waiting: usize,
+    pub dispatches: u64,
+    pub ticks_served: u64,
+    pub demotions: u64,
+}
+
+#[derive(Debug)]
+struct Level {
+    queue: VecDeque<Pid>,
+    stats: QueueStats,
+}
+
+#[derive(Debug)]
+pub struct MlfqScheduler {
+    levels: Vec<Level>,
+    current: Option<(Pid, usize)>,
+    remaining: u32,
+    boost_interval: u32,
+    since_boost: u32,
+    boosts: u64,
+}
+
+impl MlfqScheduler {
+    /// Creates one queue per entry in `quanta` (index 0 is the highest priority), boosting every
+    /// `boost_interval` ticks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no levels, any quantum is zero, or `boost_interval` is zero.
+    pub fn new(quanta: &[u32], boost_interval: u32) -> Self {
+        assert!(!quanta.is_empty(), "MLFQ needs at least one queue");
+        assert!(
+            quanta.iter().all(|&q| q > 0),
+            "time quanta must be at least one tick"
+        );
+        assert!(
+            boost_interval > 0,
+            "boost interval must be at least one tick"
+        );
+
+        let levels = quanta
+            .iter()
+            .map(|&quantum| Level {
+                queue: VecDeque::new(),
+                stats: QueueStats {
+                    quantum,
+                    ..QueueStats::default()
+                },
+            })
+            .collect();
+
+        MlfqScheduler {
+            levels,
+            current: None,
+            remaining: 0,
+            boost_interval,
+            since_boost: 0,
+            boosts: 0,
+        }
+    }
+
+    /// New arrivals, with all of their threads, enter the top queue in the Stopped state.
+    pub fn admit(&mut self, proc: &mut Proc) {
+        proc.set_state(State::Stopped);
+        self.levels[0].queue.extend(proc.task_ids());
+    }
+
+    /// Advances the scheduler by one tick and returns the PID that used the CPU during it.
+    pub fn tick(&mut self, table: &mut ProcTable) -> Option<Pid> {
+        self.release_if_not_runnable(table);
+        self.since_boost += 1;
+        if self.since_boost >= self.boost_interval {
+            self.boost();
+        }
+
+        // A process waiting in a higher queue preempts the running one, which keeps its level
+        if let Some((_, level)) = self.current {
+            if self.highest_runnable(table).is_some_and(|top| top < level) {
+                self.requeue(table, level);
+            }
+        }
+
+        if self.current.is_none() {
+            self.dispatch(table);
+        }
+
+        let (pid, level) = self.current?;
+        self.remaining -= 1;
+        self.levels[level].stats.ticks_served += 1;
+        if self.remaining == 0 {
+            let lower = (level + 1).min(self.levels.len() - 1);
+            if lower != level {
+                self.levels[level].stats.demotions += 1;
+            }
+            self.requeue(table, lower);
+        }
+        Some(pid)
+    }
+
+    /// Queues processes that just woke from a sleep at the front of the level they slept in, in wake order. A
+    /// process that gave up the CPU before using its quantum keeps its level, which is what favours I/O-bound work.
+    pub fn wake(&mut self, table: &mut ProcTable, woken: &[Pid]) {
+        // A zero-length sleep can end before the process was ever taken off the CPU
+        let running = self.current.map(|(pid, _)| pid);
+        let woken: Vec<Pid> = woken
+            .iter()
+            .copied()
+            .filter(|&pid| running != Some(pid))
+            .collect();
+        for &pid in woken.iter().rev() {
+            let level = self.level_of(pid).unwrap_or(0);
+            self.levels[level].queue.retain(|&p| p != pid);
+            self.levels[level].queue.push_front(pid);
+        }
+        for &pid in &woken {
+            if table.task_state(pid) == Some(State::Running) {
+                table.set_task_state(pid, State::Stopped);
+            }
+        }
+    }
+
+    /// Drops `tasks` from every queue, e.g. for a process moving to another core. One of them holding the CPU gives it
+    /// up and goes back to Stopped.
+    pub fn remove(&mut self, table: &mut ProcTable, tasks: &[Pid]) {
+        for level in &mut self.levels {
+            level.queue.retain(|tid| !tasks.contains(tid));
+        }
+        if let Some((pid, _)) = self.current.filter(|(pid, _)| tasks.contains(pid)) {
+            self.current = None;
+            if table.task_state(pid) == Some(State::Running) {
+                table.set_task_state(pid, State::Stopped);
+            }
+        }
+    }
+
+    /// PID and queue level of the running process, if any.
+    pub fn current(&self) -> Option<(Pid, usize)> {
+        self.current
+    }
+
+    /// Every queue's processes, the top queue first and each queue front first.
+    pub fn queued(&self) -> Vec<Pid> {
+        self.levels
+            .iter()
+            .flat_map(|l| l.queue.iter().copied())
+            .collect()
+    }
+
+    /// Level a queued or running process currently sits in.
+    pub fn level_of(&self, pid: Pid) -> Option<usize> {
+        match self.current {
+            Some((running, level)) if running == pid => Some(level),
+            _ => self.levels.iter().position(|l| l.queue.contains(&pid)),
+        }
+    }
+
+    /// Snapshot of the statistics of every queue, top priority first.
+    pub fn queue_stats(&self) -> Vec<QueueStats> {
+        self.levels
+            .iter()
+            .map(|l| QueueStats {
+                waiting: l.queue.len(),
+                ..l.stats.clone()
+            })
+            .collect()
+    }
+
+    /// How many times all processes have been boosted back to the top queue.
+    pub fn boosts(&self) -> u64 {
+        self.boosts
+    }
+
+    fn highest_ready(&self) -> Option<usize> {
+        self.levels.iter().position(|l| !l.queue.is_empty())
+    }
+
+    // Unlike `highest_ready`, skips queued processes that couldn't take the CPU now: sleeping, stopped, or idle-class
+    // ones that only run when nothing else can.
+    fn highest_runnable(&self, table: &ProcTable) -> Option<usize> {
+        self.levels.iter().position(|l| {
+            l.queue
+                .iter()
+                .any(|&pid| table.task_runnable(pid) && table.task_class(pid) != SchedClass::Idle)
+        })
+    }
+
+    fn dispatch(&mut self, table: &mut ProcTable) {
+        // Signal-stopped processes are set aside so they keep their level without being picked, and so are idle-class
+        // ones until it is clear nothing else is ready
+        let mut held = Vec::new();
+        let mut idle = None;
+        while let Some(level) = self.highest_ready() {
+            let pid = self.levels[level].queue.pop_front().unwrap();
+            match table.task_state(pid) {
+                Some(_) if table.task_runnable(pid) => {
+                    if table.task_class(pid) != SchedClass::Idle {
+                        self.start(table, pid, level);
+                        break;
+                    }
+                    idle.get_or_insert((pid, level));
+                    held.push((pid, level));
+                }
+                Some(state) if !state.is_exited() => held.push((pid, level)),
+                _ => {}
+            }
+        }
+        if let (None, Some((pid, level))) = (self.current, idle) {
+            held.retain(|&(p, _)| p != pid);
+            self.start(table, pid, level);
+        }
+        for (pid, level) in held {
+            self.levels[level].queue.push_back(pid);
+        }
+    }
+
+    fn start(&mut self, table: &mut ProcTable, pid: Pid, level: usize) {
+        table.set_task_state(pid, State::Running);
+        self.current = Some((pid, level));
+        let quantum = self.levels[level].stats.quantum;
+        self.remaining = table.task_class(pid).quantum(quantum);
+        self.levels[level].stats.dispatches += 1;
+    }
+
+    // The running process may have been stopped or killed by a signal since the last tick.
+    fn release_if_not_runnable(&mut self, table: &ProcTable) {
+        let Some((pid, level)) = self.current else {
+            return;
+        };
+        match table.task_state(pid) {
+            Some(_) if table.task_runnable(pid) => {}
+            Some(state) if !state.is_exited() => {
+                self.current = None;
+                self.levels[level].queue.push_back(pid);
+            }
+            _ => self.current = None,
+        }
+    }
+
+    // Takes the running process off the CPU and queues it at `level`.
+    fn requeue(&mut self, table: &mut ProcTable, level: usize) {
+        if let Some((pid, _)) = self.current.take() {
+            if table.task_state(pid).is_some() {
+                table.set_task_state(pid, State::Stopped);
+                self.levels[level].queue.push_back(pid);
+            }
+        }
+    }
+
+    fn boost(&mut self) {
+        self.since_boost = 0;
+        self.boosts += 1;
+        for level in 1..self.levels.len() {
+            let drained: Vec<Pid> = self.levels[level].queue.drain(..).collect();
+            self.levels[0].queue.extend(drained);
+        }
+        if let Some((pid, _)) = self.current {
+            self.current = Some((pid, 0));
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ToJson for MlfqScheduler {
+    fn to_json(&self) -> Json {
+        let levels = self
+            .levels
+            .iter()
+            .map(|level| {
+                Json::object(vec![
+                    ("quantum", Json::Int(level.stats.quantum.into())),
+                    ("queue", json::pid_list(&level.queue)),
+                    ("dispatches", Json::Int(level.stats.dispatches.into())),
+                    ("ticks_served", Json::Int(level.stats.ticks_served.into())),
+                    ("demotions", Json::Int(level.stats.demotions.into())),
+                ])
+            })
+            .collect();
+        let current = match self.current {
+            Some((pid, level)) => Json::object(vec![
+                ("pid", pid.to_json()),
+                ("level", Json::Int(level as i128)),
+            ]),
+            None => Json::Null,
+        };
+        Json::object(vec![
+            ("levels", Json::Array(levels)),
+            ("current", current),
+            ("remaining", Json::Int(self.remaining.into())),
+            ("boost_interval", Json::Int(self.boost_interval.into())),
+            ("since_boost", Json::Int(self.since_boost.into())),
+            ("boosts", Json::Int(self.boosts.into())),
+        ])
+    }
+}
+
+#[cfg(feature = "serde")]
+impl FromJson for MlfqScheduler {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        let mut levels = Vec::new();
+        for level in json.array("levels")? {
+            let quantum = level.int("quantum")?;
+            if quantum == 0 {
+                return Err(JsonError::Field("quantum"));
+            }
+            levels.push(Level {
+                queue: level.pids("queue")?.into(),
+                stats: QueueStats {
+                    quantum,
+                    waiting: 0,
+                    dispatches: level.int("dispatches")?,
+                    ticks_served: level.int("ticks_served")?,
+                    demotions: level.int("demotions")?,
+                },
+            });
+        }
+        let current: Option<(Pid, usize)> = match json.field("current")? {
+            Json::Null => None,
+            current => Some((current.parse("pid")?, current.int("level")?)),
+        };
+        let boost_interval = json.int("boost_interval")?;
+        if levels.is_empty()
+            || boost_interval == 0
+            || current.is_some_and(|(_, level)| level >= levels.len())
+        {
+            return Err(JsonError::Field("levels"));
+        }
+        let remaining = json.int("remaining")?;
+        // A running process has between one tick and the longest slice any class gets at its level left
+        if let Some((_, level)) = current {
+            let longest = SchedClass::Idle.quantum(levels[level].stats.quantum);
+            if !(1..=longest).contains(&remaining) {
+                return Err(JsonError::Field("remaining"));
+            }
+        }
+        Ok(MlfqScheduler {
+            levels,
+            current,
+            remaining,
+            boost_interval,
+            since_boost: json.int("since_boost")?,
+            boosts: json.int("boosts")?,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_saved_slice_must_fit_the_level() {
+    let saved = |remaining: u32| {
+        let level =
+            r#"{"quantum": 2, "queue": [], "dispatches": 0, "ticks_served": 0, "demotions": 0}"#;
+        let text = format!(
+            r#"{{"levels": [{level}, {level}], "current": {{"pid": 2, "level": 1}}, "remaining": {remaining},
+                "boost_interval": 5, "since_boost": 0, "boosts": 0}}"#
+        );
+        MlfqScheduler::from_json(&text.parse().unwrap()).map(|mlfq| mlfq.remaining)
+    };
+    assert_eq!(saved(0).unwrap_err(), JsonError::Field("remaining"));
+    assert_eq!(saved(4), Ok(4));
+    assert_eq!(saved(5).unwrap_err(), JsonError::Field("remaining"));
+}
+
+#[test]
+fn test_mlfq_demotes_cpu_bound_procs() {
+    let pids = super::PidAllocator::default();
+    let (mut table, procs) = super::table::flat_table(&pids, 2);
+    let (a, b) = (procs[0], procs[1]);
+    let mut mlfq = MlfqScheduler::new(&[1, 2, 4], 100);
+    for &pid in &procs {
+        mlfq.admit(table.get_mut(pid).unwrap());
+    }
+
+    let ran: Vec<_> = (0..7).filter_map(|_| mlfq.tick(&mut table)).collect();
+    assert_eq!(ran, [a, b, a, a, b, b, a]);
+    assert_eq!(mlfq.current(), Some((a, 2)));
+    assert_eq!(mlfq.level_of(b), Some(2));
+
+    let stats = mlfq.queue_stats();
+    assert_eq!(stats[0].demotions, 2);
+    assert_eq!(stats[1].demotions, 2);
+    assert_eq!(stats[1].ticks_served, 4);
+    assert_eq!(stats[2].dispatches, 1);
+    assert_eq!(stats[2].waiting, 1);
+}
+
+#[test]
+fn test_mlfq_new_arrival_preempts_and_boost_resets() {
+    let pids = super::PidAllocator::default();
+    let (mut table, procs) = super::table::flat_table(&pids, 2);
+    let (a, b) = (procs[0], procs[1]);
+    let mut mlfq = MlfqScheduler::new(&[1, 8], 5);
+    mlfq.admit(table.get_mut(a).unwrap());
+
+    mlfq.tick(&mut table);
+    mlfq.tick(&mut table);
+    assert_eq!(mlfq.current(), Some((a, 1)));
+
+    // The second process lands in the top queue and takes the CPU on the next tick
+    mlfq.admit(table.get_mut(b).unwrap());
+    assert_eq!(mlfq.tick(&mut table), Some(b));
+    assert_eq!(table.get(a).unwrap().state, State::Stopped);
+
+    // Fifth tick triggers the boost: the first process is back at the top
+    mlfq.tick(&mut table);
+    mlfq.tick(&mut table);
+    assert_eq!(mlfq.boosts(), 1);
+    assert_eq!(mlfq.level_of(a), Some(0));
+}
+
+#[test]
+fn test_mlfq_sleeper_in_higher_queue_does_not_preempt() {
+    let pids = super::PidAllocator::default();
+    let (mut table, procs) = super::table::flat_table(&pids, 2);
+    let (a, b) = (procs[0], procs[1]);
+    let mut mlfq = MlfqScheduler::new(&[1, 8], 100);
+    mlfq.admit(table.get_mut(a).unwrap());
+    mlfq.tick(&mut table);
+    mlfq.tick(&mut table);
+    assert_eq!(mlfq.current(), Some((a, 1)));
+
+    // The top queue holds only a sleeper, so the running process keeps the CPU and its quantum
+    mlfq.admit(table.get_mut(b).unwrap());
+    table.get_mut(b).unwrap().set_state(State::Sleeping);
+    for _ in 0..3 {
+        assert_eq!(mlfq.tick(&mut table), Some(a));
+    }
+    assert_eq!(mlfq.queue_stats()[1].dispatches, 1);
+    assert_eq!(mlfq.level_of(b), Some(0));
+}
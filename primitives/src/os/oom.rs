@@ -0,0 +1,147 @@
+use super::{Pid, Proc, ProcTable, Signal};
+
+// When the machine runs out of memory something has to die. Like the classic `badness()` heuristic of the 2.4
+// kernel, the score starts from the process's size, is divided down for processes that have already done a lot of
+// work (killing them wastes the most), and is doubled for niced processes, which their owner marked as unimportant.
+// The size is counted in thousandths of a page before dividing, and any process holding memory scores at least 1, so
+// a small or long-running process stays a candidate rather than rounding down to nothing.
+
+// Fixed-point scale of a score: 1000 points per resident page, before age and niceness
+const POINTS_PER_PAGE: u64 = 1000;
+
+/// Kills processes whenever the table's resident memory goes over `budget` pages.
+#[derive(Debug)]
+pub struct OomKiller {
+    budget: u64,
+    kills: Vec<Pid>,
+}
+
+impl OomKiller {
+    pub fn new(budget: u64) -> Self {
+        OomKiller {
+            budget,
+            kills: Vec::new(),
+        }
+    }
+
+    pub fn budget(&self) -> u64 {
+        self.budget
+    }
+
+    /// How attractive `proc` is as a victim; higher is killed first, and 0 means killing it would free nothing.
+    pub fn score(proc: &Proc) -> u64 {
+        if proc.state().is_exited() {
+            return 0;
+        }
+        let size = proc.memory().resident;
+        if size == 0 {
+            return 0;
+        }
+        // Age is the CPU time the process has put in
+        let age = proc.cpu_times().own().isqrt().max(1);
+        let points = (size.saturating_mul(POINTS_PER_PAGE) / age).max(1);
+        if proc.nice() > 0 {
+            points.saturating_mul(2)
+        } else {
+            points
+        }
+    }
+
+    /// Scores of every process that could be killed, which is every live one holding memory, in tree order. Init is
+    /// never a candidate.
+    pub fn scores(&self, table: &ProcTable) -> Vec<(Pid, u64)> {
+        table
+            .procs()
+            .into_iter()
+            .skip(1)
+            .map(|p| (p.pid(), OomKiller::score(p)))
+            .filter(|&(_, score)| score > 0)
+            .collect()
+    }
+
+    /// The process that would be killed next: the highest score, the youngest (highest PID) on a tie.
+    pub fn choose_victim(&self, table: &ProcTable) -> Option<Pid> {
+        self.scores(table)
+            .into_iter()
+            .max_by_key(|&(pid, score)| (score, pid))
+            .map(|(pid, _)| pid)
+    }
+
+    /// Kills victims with SIGKILL, one at a time, until the table fits in the budget again. Returns who was killed.
+    pub fn check(&mut self, table: &mut ProcTable) -> Vec<Pid> {
+        let mut killed = Vec::new();
+        while table.total_memory() > self.budget {
+            let Some(victim) = self.choose_victim(table) else {
+                break;
+            };
+            let proc = table.get_mut(victim).unwrap();
+            crate::warn!("out of memory: killing pid {} ({})", victim, proc.name());
+            proc.send_signal(Signal::Kill);
+            proc.deliver_signals();
+            killed.push(victim);
+        }
+        self.kills.extend(&killed);
+        killed
+    }
+
+    /// Every process killed so far, oldest kill first.
+    pub fn kills(&self) -> &[Pid] {
+        &self.kills
+    }
+}
+
+#[test]
+fn test_scoring_weighs_size_age_and_niceness() {
+    let pids = super::PidAllocator::default();
+    let mut big = Proc::new(&pids);
+    big.alloc(100).unwrap();
+    assert_eq!(OomKiller::score(&big), 100_000);
+
+    // Nine ticks of work divide the score by three
+    for _ in 0..9 {
+        big.charge_user_tick();
+    }
+    assert_eq!(OomKiller::score(&big), 33_333);
+
+    let mut niced = Proc::new(&pids);
+    niced.alloc(20).unwrap();
+    niced.set_nice(5).unwrap();
+    assert_eq!(OomKiller::score(&niced), 40_000);
+
+    // A single page held through a long run still makes a candidate
+    let mut small = Proc::new(&pids);
+    small.alloc(1).unwrap();
+    for _ in 0..4_000_000 {
+        small.charge_user_tick();
+    }
+    assert_eq!(OomKiller::score(&small), 1);
+
+    niced.exit(0);
+    assert_eq!(OomKiller::score(&niced), 0);
+}
+
+#[test]
+fn test_kills_until_under_budget() {
+    let pids = super::PidAllocator::default();
+    let (mut table, cron, rsyslogd, bash) = super::table::init_tree(&pids);
+    table.init_mut().alloc(50).unwrap();
+    table.get_mut(cron).unwrap().alloc(10).unwrap();
+    table.get_mut(rsyslogd).unwrap().alloc(30).unwrap();
+    table.get_mut(bash).unwrap().alloc(30).unwrap();
+
+    let mut oom = OomKiller::new(75);
+    assert_eq!(
+        oom.scores(&table),
+        vec![(cron, 10_000), (rsyslogd, 30_000), (bash, 30_000)]
+    );
+    // rsyslogd and bash tie, so the younger bash goes first; init is too big but never a candidate
+    assert_eq!(oom.check(&mut table), vec![bash, rsyslogd]);
+    assert_eq!(table.total_memory(), 60);
+    let victim = table.get(bash).unwrap();
+    assert_eq!(victim.exit_code(), Some(137));
+    // Killed through signal delivery, so the kill is in its audit trail
+    let kill = super::AuditEvent::Signal(Signal::Kill);
+    assert!(victim.audit.iter().any(|&(_, event)| event == kill));
+    assert!(oom.check(&mut table).is_empty());
+    assert_eq!(oom.kills(), &[bash, rsyslogd]);
+}
@@ -0,0 +1,169 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// Linux's default `/proc/sys/kernel/pid_max`.
+pub const PID_MAX_DEFAULT: u32 = 32_768;
+
+// Newtype: a Pid is just a u32 at runtime, but the compiler won't let us mix it up with any other integer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Pid(pub u32);
+
+impl fmt::Display for Pid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug)]
+struct PidPool {
+    next: u32,
+    max: u32,
+    in_use: BTreeSet<u32>,
+}
+
+impl PidPool {
+    // Hands out PIDs in increasing order like the kernel does, wrapping back to 1 after `max`
+    // and skipping any PID that is still taken.
+    fn alloc(&mut self) -> Option<u32> {
+        if self.in_use.len() as u32 >= self.max {
+            return None;
+        }
+        loop {
+            let pid = self.next;
+            self.next = if pid >= self.max { 1 } else { pid + 1 };
+            if self.in_use.insert(pid) {
+                return Some(pid);
+            }
+        }
+    }
+}
+
+/// Shared source of unique PIDs in `1..=max`. Cloning gives another handle to the same pool.
+#[derive(Debug, Clone)]
+pub struct PidAllocator {
+    pool: Arc<Mutex<PidPool>>,
+}
+
+impl PidAllocator {
+    /// # Panics
+    ///
+    /// Panics if `max` is zero.
+    pub fn new(max: u32) -> Self {
+        assert!(max > 0, "pid space must hold at least one pid");
+        PidAllocator {
+            pool: Arc::new(Mutex::new(PidPool {
+                next: 1,
+                max,
+                in_use: BTreeSet::new(),
+            })),
+        }
+    }
+
+    /// Reserves the next free PID, or `None` once every PID up to `max` is taken.
+    pub fn alloc(&self) -> Option<PidHandle> {
+        let pid = self.pool.lock().unwrap().alloc()?;
+        Some(PidHandle {
+            pid: Pid(pid),
+            pool: Arc::clone(&self.pool),
+        })
+    }
+
+    /// Claims a specific PID rather than the next one, e.g. to mirror a process that already exists elsewhere.
+    /// Returns `None` if it is out of range or already taken.
+    pub fn reserve(&self, pid: Pid) -> Option<PidHandle> {
+        let mut pool = self.pool.lock().unwrap();
+        if pid.0 == 0 || pid.0 > pool.max || !pool.in_use.insert(pid.0) {
+            return None;
+        }
+        Some(PidHandle {
+            pid,
+            pool: Arc::clone(&self.pool),
+        })
+    }
+
+    /// Number of PIDs currently held by live handles.
+    pub fn in_use(&self) -> usize {
+        self.pool.lock().unwrap().in_use.len()
+    }
+}
+
+impl Default for PidAllocator {
+    fn default() -> Self {
+        PidAllocator::new(PID_MAX_DEFAULT)
+    }
+}
+
+/// Ownership of one allocated PID. Dropping the handle returns the PID to its allocator.
+pub struct PidHandle {
+    pid: Pid,
+    pool: Arc<Mutex<PidPool>>,
+}
+
+impl PidHandle {
+    pub fn pid(&self) -> Pid {
+        self.pid
+    }
+
+    /// Allocator this PID was taken from, so forked children draw from the same pool.
+    pub fn allocator(&self) -> PidAllocator {
+        PidAllocator {
+            pool: Arc::clone(&self.pool),
+        }
+    }
+}
+
+impl fmt::Debug for PidHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.pid)
+    }
+}
+
+impl Drop for PidHandle {
+    fn drop(&mut self) {
+        // A poisoned lock means some other thread panicked mid-allocation; leaking one PID is the lesser evil
+        if let Ok(mut pool) = self.pool.lock() {
+            pool.in_use.remove(&self.pid.0);
+        }
+    }
+}
+
+#[test]
+fn test_pid_allocator_recycles_and_wraps() {
+    let pids = PidAllocator::new(3);
+    let a = pids.alloc().unwrap();
+    let b = pids.alloc().unwrap();
+    let c = pids.alloc().unwrap();
+    assert_eq!((a.pid(), b.pid(), c.pid()), (Pid(1), Pid(2), Pid(3)));
+    assert!(pids.alloc().is_none());
+
+    // Freeing pid 2 makes it the only candidate after wrapping around
+    drop(b);
+    assert_eq!(pids.in_use(), 2);
+    assert_eq!(pids.alloc().unwrap().pid(), Pid(2));
+}
+
+#[test]
+fn test_pid_allocator_does_not_reuse_eagerly() {
+    let pids = PidAllocator::new(10);
+    let first = pids.alloc().unwrap();
+    drop(first);
+    // Like the kernel, keep counting up rather than immediately handing out the freed pid
+    assert_eq!(pids.alloc().unwrap().pid(), Pid(2));
+}
+
+#[test]
+fn test_pid_allocator_reserves_specific_pids() {
+    let pids = PidAllocator::new(10);
+    let seven = pids.reserve(Pid(7)).unwrap();
+    assert!(pids.reserve(Pid(7)).is_none());
+    assert!(pids.reserve(Pid(0)).is_none());
+    assert!(pids.reserve(Pid(11)).is_none());
+    assert_eq!(seven.pid(), Pid(7));
+
+    // Sequential allocation steps over the reserved pid, and dropping it frees it as usual
+    let allocated: Vec<_> = (0..9).map(|_| pids.alloc().unwrap().pid()).collect();
+    assert!(!allocated.contains(&Pid(7)));
+    drop(seven);
+    assert_eq!(pids.in_use(), 0);
+}
@@ -0,0 +1,115 @@
+use std::error::Error;
+use std::fmt;
+
+use super::Proc;
+
+/// Nice range, as on Linux: -20 is the most favourable, 19 the least.
+pub const NICE_MIN: i8 = -20;
+pub const NICE_MAX: i8 = 19;
+
+/// Cap on the dynamic bonus, like the ±5 interactivity bonus of the old O(1) scheduler.
+pub const MAX_BOOST: u8 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NiceError {
+    pub requested: i32,
+}
+
+impl fmt::Display for NiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "nice value {} outside {}..={}",
+            self.requested, NICE_MIN, NICE_MAX
+        )
+    }
+}
+
+impl Error for NiceError {}
+
+impl Proc {
+    pub fn nice(&self) -> i8 {
+        self.nice
+    }
+
+    /// Sets an absolute nice value, rejecting anything outside `NICE_MIN..=NICE_MAX`.
+    pub fn set_nice(&mut self, nice: i32) -> Result<(), NiceError> {
+        if !(NICE_MIN as i32..=NICE_MAX as i32).contains(&nice) {
+            return Err(NiceError { requested: nice });
+        }
+        self.nice = nice as i8;
+        Ok(())
+    }
+
+    /// Adjusts the nice value by `delta`, like the `renice -n` command. The value is left untouched on error.
+    pub fn renice(&mut self, delta: i32) -> Result<(), NiceError> {
+        // Saturating, so a huge delta is refused as out of range rather than overflowing
+        self.set_nice((self.nice as i32).saturating_add(delta))
+    }
+
+    /// Effective priority in `0..=39`, lower runs first: the static `20 + nice` minus the dynamic boost, or the
+    /// priority inherited through a lock if that is better.
+    pub fn priority(&self) -> u8 {
+        let base = 20 + self.nice as i32;
+        let own = (base - self.boost as i32).clamp(0, 39) as u8;
+        self.inherited.map_or(own, |inherited| own.min(inherited))
+    }
+
+    /// Grants a temporary bonus (e.g. for a process that just woke up), capped at `MAX_BOOST`.
+    pub fn boost_priority(&mut self, by: u8) {
+        self.boost = self.boost.saturating_add(by).min(MAX_BOOST);
+    }
+
+    /// Drops any dynamic bonus, falling back to the static priority.
+    pub fn clear_boost(&mut self) {
+        self.boost = 0;
+    }
+}
+
+#[test]
+fn test_renice_range_checking() {
+    let mut proc = Proc::new(&super::PidAllocator::default());
+    assert_eq!(proc.priority(), 20);
+
+    proc.renice(-15).unwrap();
+    assert_eq!(proc.nice(), -15);
+    assert_eq!(proc.renice(-10), Err(NiceError { requested: -25 }));
+    assert_eq!(proc.nice(), -15);
+    assert_eq!(
+        proc.renice(i32::MIN),
+        Err(NiceError {
+            requested: i32::MIN
+        })
+    );
+    assert_eq!(
+        proc.renice(i32::MAX),
+        Err(NiceError {
+            requested: i32::MAX - 15
+        })
+    );
+    assert_eq!(proc.nice(), -15);
+    assert_eq!(proc.set_nice(20), Err(NiceError { requested: 20 }));
+    proc.set_nice(NICE_MAX as i32).unwrap();
+    assert_eq!(proc.priority(), 39);
+}
+
+#[test]
+fn test_boost_combines_with_nice() {
+    let pids = super::PidAllocator::default();
+    let mut nice = Proc::new(&pids);
+    let mut boosted = Proc::new(&pids);
+    nice.set_nice(-3).unwrap();
+    boosted.boost_priority(3);
+    boosted.boost_priority(3);
+    assert_eq!(boosted.priority(), 15);
+    assert!(boosted.priority() < nice.priority());
+
+    boosted.clear_boost();
+    assert_eq!(boosted.priority(), 20);
+
+    // Children inherit niceness but not the parent's bonus
+    nice.boost_priority(2);
+    let child = nice.fork();
+    assert_eq!(nice.children()[0].pid(), child);
+    assert_eq!(nice.children()[0].priority(), 17);
+}
@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use super::mem::PAGE_SIZE;
+use super::{Pid, PidAllocator, Proc, ProcTable, State};
+
+// The real kernel publishes its process table as text under /proc. Reading it back into our own types gives the
+// simulator a live tree to look at, the one `pstree -n -g` draws.
+
+/// What we take from `/proc/<pid>/stat` and `/proc/<pid>/status` for one host process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostProc {
+    pub pid: Pid,
+    pub ppid: Pid, // 0 for init and kernel threads started by the kernel itself
+    pub name: String,
+    pub state: State,
+    pub resident_pages: u64,
+}
+
+/// Parses a `stat` line, `pid (comm) state ppid ...`. The name is found by the last `)` because it may itself
+/// contain spaces and parentheses.
+fn parse_stat(line: &str) -> Option<(Pid, String, char, Pid)> {
+    let open = line.find('(')?;
+    let close = line.rfind(')')?;
+    let pid = line[..open].trim().parse().ok()?;
+    let comm = line.get(open + 1..close)?.to_string();
+    let mut rest = line[close + 1..].split_whitespace();
+    let state = rest.next()?.chars().next()?;
+    let ppid = rest.next()?.parse().ok()?;
+    Some((Pid(pid), comm, state, Pid(ppid)))
+}
+
+/// Pulls the full name and the resident set size, in pages, out of `status`. Kernel threads have no `VmRSS`.
+fn parse_status(text: &str) -> (Option<String>, u64) {
+    let mut name = None;
+    let mut rss_kb = 0;
+    for line in text.lines() {
+        if let Some(value) = line.strip_prefix("Name:") {
+            name = Some(value.trim().to_string());
+        } else if let Some(value) = line.strip_prefix("VmRSS:") {
+            rss_kb = value
+                .trim()
+                .trim_end_matches("kB")
+                .trim()
+                .parse()
+                .unwrap_or(0);
+        }
+    }
+    (name, rss_kb * 1024 / PAGE_SIZE)
+}
+
+// Linux has more states than we model; each maps to the closest one.
+fn state_from_code(code: char) -> State {
+    match code {
+        'R' => State::Running,
+        'T' | 't' => State::Stopped,
+        'Z' => State::Zombie,
+        'X' | 'x' => State::Terminated,
+        // S (interruptible), D (disk), I (idle kernel thread), W, P...
+        _ => State::Sleeping,
+    }
+}
+
+fn read_host_proc(dir: &Path) -> io::Result<HostProc> {
+    let stat = fs::read_to_string(dir.join("stat"))?;
+    let (pid, comm, code, ppid) = parse_stat(&stat)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed stat"))?;
+    let (name, resident_pages) = parse_status(&fs::read_to_string(dir.join("status"))?);
+    Ok(HostProc {
+        pid,
+        ppid,
+        name: name.unwrap_or(comm),
+        state: state_from_code(code),
+        resident_pages,
+    })
+}
+
+/// Every process currently listed under `/proc`, in PID order. Processes that exit while we read are skipped.
+pub fn host_procs() -> io::Result<Vec<HostProc>> {
+    let mut procs = Vec::new();
+    for entry in fs::read_dir("/proc")? {
+        let entry = entry?;
+        if !entry
+            .file_name()
+            .to_string_lossy()
+            .bytes()
+            .all(|b| b.is_ascii_digit())
+        {
+            continue;
+        }
+        match read_host_proc(&entry.path()) {
+            Ok(proc) => procs.push(proc),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err),
+        }
+    }
+    procs.sort_by_key(|p| p.pid);
+    Ok(procs)
+}
+
+impl ProcTable {
+    /// Builds a table mirroring the host's process tree, with the real PIDs, names, states and resident memory.
+    /// The lowest PID becomes init (PID 1 outside a PID namespace), and anything whose parent we can't see (like
+    /// kernel threads, whose parent is PID 0) is placed under it. PIDs come from a fresh allocator sized to the
+    /// host's `pid_max`, so processes forked from the result don't collide with the ones read in.
+    pub fn from_host() -> io::Result<ProcTable> {
+        let pid_max = fs::read_to_string("/proc/sys/kernel/pid_max")
+            .ok()
+            .and_then(|max| max.trim().parse().ok())
+            .unwrap_or(super::pid::PID_MAX_DEFAULT);
+        let pids = PidAllocator::new(pid_max);
+
+        let host = host_procs()?;
+        let Some(root) = host.first() else {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "no processes in /proc",
+            ));
+        };
+        let init = root.pid;
+        let mut children: BTreeMap<Pid, Vec<&HostProc>> = BTreeMap::new();
+        for proc in host.iter().filter(|p| p.pid != init) {
+            let parent = if host.binary_search_by_key(&proc.ppid, |p| p.pid).is_ok() {
+                proc.ppid
+            } else {
+                init
+            };
+            children.entry(parent).or_default().push(proc);
+        }
+
+        Ok(ProcTable::new(build(root, &children, &pids)))
+    }
+}
+
+fn build(host: &HostProc, children: &BTreeMap<Pid, Vec<&HostProc>>, pids: &PidAllocator) -> Proc {
+    let handle = pids.reserve(host.pid).expect("host pids are unique");
+    let mut proc = Proc::with_pid(handle);
+    proc.name = host.name.clone().into();
+    proc.state = host.state;
+    proc.mem.resident = host.resident_pages;
+    proc.mem.peak = host.resident_pages;
+    for child in children.get(&host.pid).into_iter().flatten() {
+        proc.add_child(build(child, children, pids));
+    }
+    proc
+}
+
+#[test]
+fn test_parse_stat_with_awkward_names() {
+    assert_eq!(
+        parse_stat("42 (tmux: server) S 1 42 42 0 -1"),
+        Some((Pid(42), "tmux: server".to_string(), 'S', Pid(1)))
+    );
+    assert_eq!(
+        parse_stat("7 (a) b) (c) R 3 7"),
+        Some((Pid(7), "a) b) (c".to_string(), 'R', Pid(3)))
+    );
+    assert_eq!(parse_stat("garbage"), None);
+    assert_eq!(
+        parse_status("Name:\tbash\nState:\tS (sleeping)\nVmRSS:\t    8192 kB\n"),
+        (Some("bash".to_string()), 2048)
+    );
+    assert_eq!(state_from_code('D'), State::Sleeping);
+}
+
+#[test]
+fn test_reads_this_process_from_host() {
+    let table = ProcTable::from_host().unwrap();
+    let me = table.get(Pid(std::process::id())).unwrap();
+    assert!(me.memory().resident > 0);
+    assert!(!me.name().is_empty());
+}
@@ -0,0 +1,412 @@
+// An interactive shell over a running simulation. Each line is parsed into a `Command`, carried out through the
+// same `Simulation`/`ProcTable` calls the demos make, and answered with a `Reply` value; only `run` turns replies
+// into text, so the commands can be driven and checked without any terminal in the way.
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+use super::{Pid, PidAllocator, Proc, ProcSnapshot, ProcTable, Scheduler, Signal, Simulation};
+
+/// One line of input, e.g. `spawn bash -l`, `signal 3 STOP`, `ps` or `tick 10`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Spawn { name: String, args: Vec<String> },
+    Signal { pid: Pid, signal: Signal },
+    Ps,
+    Tick(u64),
+    Help,
+    Quit,
+}
+
+/// Why a line could not be turned into a `Command`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseCommandError {
+    // The line was blank
+    Empty,
+    // The first word is not a command
+    Unknown(String),
+    // The command is missing an argument, or has one too many
+    Usage(&'static str),
+    // An argument that should be a number isn't
+    Number(String),
+    // `signal` was given a signal name it doesn't know
+    Signal(String),
+}
+
+impl fmt::Display for ParseCommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseCommandError::Empty => write!(f, "empty command"),
+            ParseCommandError::Unknown(word) => {
+                write!(f, "unknown command {:?}, try `help`", word)
+            }
+            ParseCommandError::Usage(usage) => write!(f, "usage: {}", usage),
+            ParseCommandError::Number(arg) => write!(f, "{:?} is not a number", arg),
+            ParseCommandError::Signal(arg) => write!(f, "unknown signal {:?}", arg),
+        }
+    }
+}
+
+impl Error for ParseCommandError {}
+
+/// Most ticks one `tick` command runs, as every tick that runs is part of the reply.
+pub const MAX_TICKS: u64 = 10_000;
+
+// Keep in step with MAX_TICKS
+const TICK_USAGE: &str = "tick [n], with n at most 10000";
+
+const HELP: &str = "\
+spawn <name> [args...]  start a process under init
+signal <pid> <signal>   send KILL, TERM, STOP, TSTP or CONT (name or number)
+ps                      list the process table
+tick [n]                run the scheduler for n ticks (default 1, at most 10000)
+help                    show this list
+quit                    leave the shell";
+
+impl FromStr for Command {
+    type Err = ParseCommandError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut words = line.split_whitespace();
+        let word = words.next().ok_or(ParseCommandError::Empty)?;
+        let rest: Vec<&str> = words.collect();
+        let number = |arg: &str| {
+            arg.parse::<u64>()
+                .map_err(|_| ParseCommandError::Number(arg.to_string()))
+        };
+
+        match (word, rest.as_slice()) {
+            ("spawn", [name, args @ ..]) => Ok(Command::Spawn {
+                name: name.to_string(),
+                args: args.iter().map(|arg| arg.to_string()).collect(),
+            }),
+            ("spawn", []) => Err(ParseCommandError::Usage("spawn <name> [args...]")),
+            ("signal" | "kill", [pid, signal]) => {
+                let pid = u32::try_from(number(pid)?)
+                    .map_err(|_| ParseCommandError::Number(pid.to_string()))?;
+                let signal = signal
+                    .parse()
+                    .map_err(|_| ParseCommandError::Signal(signal.to_string()))?;
+                Ok(Command::Signal {
+                    pid: Pid(pid),
+                    signal,
+                })
+            }
+            ("signal" | "kill", _) => Err(ParseCommandError::Usage("signal <pid> <signal>")),
+            ("ps", []) => Ok(Command::Ps),
+            ("ps", _) => Err(ParseCommandError::Usage("ps")),
+            ("tick", []) => Ok(Command::Tick(1)),
+            ("tick", [n]) => match number(n)? {
+                n @ 0..=MAX_TICKS => Ok(Command::Tick(n)),
+                _ => Err(ParseCommandError::Usage(TICK_USAGE)),
+            },
+            ("tick", _) => Err(ParseCommandError::Usage(TICK_USAGE)),
+            ("help", []) => Ok(Command::Help),
+            ("help", _) => Err(ParseCommandError::Usage("help")),
+            ("quit" | "exit", []) => Ok(Command::Quit),
+            ("quit" | "exit", _) => Err(ParseCommandError::Usage("quit")),
+            _ => Err(ParseCommandError::Unknown(word.to_string())),
+        }
+    }
+}
+
+/// Why a well-formed command could not be carried out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplError {
+    Parse(ParseCommandError),
+    // No process in the table has this PID
+    NoProcess(Pid),
+    // The allocator has no PID left for `spawn`
+    PidsExhausted,
+}
+
+impl fmt::Display for ReplError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplError::Parse(err) => write!(f, "{}", err),
+            ReplError::NoProcess(pid) => write!(f, "no process with pid {}", pid),
+            ReplError::PidsExhausted => write!(f, "pid space exhausted"),
+        }
+    }
+}
+
+impl Error for ReplError {}
+
+impl From<ParseCommandError> for ReplError {
+    fn from(err: ParseCommandError) -> Self {
+        ReplError::Parse(err)
+    }
+}
+
+/// What a command did, for the caller to show however it likes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reply {
+    Spawned(Pid),
+    // The signals that were delivered; a blocked one stays pending and isn't listed
+    Signalled { pid: Pid, delivered: Vec<Signal> },
+    Table(Vec<ProcSnapshot>),
+    // Who held the CPU in each tick that ran, starting at tick `from`
+    Ran { from: u64, ran: Vec<Option<Pid>> },
+    Help,
+    Quit,
+}
+
+impl fmt::Display for Reply {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reply::Spawned(pid) => write!(f, "spawned pid {}", pid),
+            Reply::Signalled { pid, delivered } if delivered.is_empty() => {
+                write!(f, "pid {}: signal pending", pid)
+            }
+            Reply::Signalled { pid, delivered } => {
+                let names: Vec<String> = delivered.iter().map(Signal::to_string).collect();
+                write!(f, "pid {}: delivered {}", pid, names.join(", "))
+            }
+            Reply::Table(snaps) => {
+                write!(f, "{:>5} {:>5}  {:<10} NAME", "PID", "PPID", "STATE")?;
+                for snap in snaps {
+                    let parent = snap
+                        .parent
+                        .map_or_else(|| "-".to_string(), |p| p.to_string());
+                    write!(
+                        f,
+                        "\n{:>5} {:>5}  {:<10} {}",
+                        snap.pid.0,
+                        parent,
+                        format!("{:?}", snap.state),
+                        snap.name
+                    )?;
+                }
+                Ok(())
+            }
+            Reply::Ran { from, ran } => {
+                let mut first = true;
+                for (tick, pid) in (*from..).zip(ran) {
+                    if !first {
+                        writeln!(f)?;
+                    }
+                    first = false;
+                    match pid {
+                        Some(pid) => write!(f, "tick {}: pid {}", tick, pid)?,
+                        None => write!(f, "tick {}: idle", tick)?,
+                    }
+                }
+                Ok(())
+            }
+            Reply::Help => f.write_str(HELP),
+            Reply::Quit => Ok(()),
+        }
+    }
+}
+
+/// A simulation with an init process and a PID allocator of its own, taking commands one at a time.
+pub struct Repl<S: Scheduler> {
+    pids: PidAllocator,
+    sim: Simulation<S>,
+}
+
+impl<S: Scheduler> Repl<S> {
+    /// A fresh system: just init (PID 1), nothing scheduled, at tick 0.
+    pub fn new(scheduler: S) -> Self {
+        let pids = PidAllocator::default();
+        let init = Proc::builder(&pids)
+            .name("init")
+            .build()
+            .expect("fresh allocator");
+        Repl::with_simulation(pids, Simulation::new(scheduler, ProcTable::new(init)))
+    }
+
+    /// Takes over a simulation that is already set up, e.g. by `SimConfig::build`. `pids` must be the allocator its
+    /// processes came from, so spawned processes don't reuse their PIDs.
+    pub fn with_simulation(pids: PidAllocator, sim: Simulation<S>) -> Self {
+        Repl { pids, sim }
+    }
+
+    pub fn simulation(&self) -> &Simulation<S> {
+        &self.sim
+    }
+
+    /// Parses and carries out one line of input.
+    pub fn eval(&mut self, line: &str) -> Result<Reply, ReplError> {
+        let command = line.parse()?;
+        self.execute(command)
+    }
+
+    pub fn execute(&mut self, command: Command) -> Result<Reply, ReplError> {
+        match command {
+            Command::Spawn { name, args } => {
+                // With no PID or state asked for, running out of PIDs is the only way this can fail
+                let mut proc = Proc::builder(&self.pids)
+                    .name(name)
+                    .build()
+                    .map_err(|_| ReplError::PidsExhausted)?;
+                let args: Vec<&str> = args.iter().map(String::as_str).collect();
+                proc.set_argv(&args);
+                Ok(Reply::Spawned(self.sim.spawn(proc)))
+            }
+            Command::Signal { pid, signal } => {
+                let proc = self.sim.proc_mut(pid).ok_or(ReplError::NoProcess(pid))?;
+                proc.send_signal(signal);
+                let delivered = proc.deliver_signals();
+                Ok(Reply::Signalled { pid, delivered })
+            }
+            Command::Ps => Ok(Reply::Table(self.sim.table().snapshot())),
+            // Commands can be built without parsing, so the cap is checked here too
+            Command::Tick(n) if n > MAX_TICKS => Err(ParseCommandError::Usage(TICK_USAGE).into()),
+            Command::Tick(n) => {
+                let from = self.sim.now();
+                Ok(Reply::Ran {
+                    from,
+                    ran: self.sim.run(n),
+                })
+            }
+            Command::Help => Ok(Reply::Help),
+            Command::Quit => Ok(Reply::Quit),
+        }
+    }
+
+    /// Reads commands from `input` until `quit` or end of input, writing a prompt before each and the reply (or
+    /// error) after it. Errors only end the session if they come from `input` or `output` themselves.
+    pub fn run(&mut self, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        write!(output, "> ")?;
+        output.flush()?;
+        for line in input.lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                match self.eval(&line) {
+                    Ok(Reply::Quit) => return Ok(()),
+                    Ok(reply) => writeln!(output, "{}", reply)?,
+                    Err(err) => writeln!(output, "error: {}", err)?,
+                }
+            }
+            write!(output, "> ")?;
+            output.flush()?;
+        }
+        writeln!(output)
+    }
+}
+
+#[test]
+fn test_parse_commands() {
+    assert_eq!(
+        "spawn bash -l".parse(),
+        Ok(Command::Spawn {
+            name: "bash".to_string(),
+            args: vec!["-l".to_string()]
+        })
+    );
+    assert_eq!(
+        "signal 3 STOP".parse(),
+        Ok(Command::Signal {
+            pid: Pid(3),
+            signal: Signal::Stop
+        })
+    );
+    assert_eq!("tick".parse(), Ok(Command::Tick(1)));
+    assert_eq!("  tick 10 ".parse(), Ok(Command::Tick(10)));
+    assert_eq!(
+        "tick 10001".parse::<Command>(),
+        Err(ParseCommandError::Usage(TICK_USAGE))
+    );
+    assert_eq!(
+        "tick 18446744073709551615".parse::<Command>(),
+        Err(ParseCommandError::Usage(TICK_USAGE))
+    );
+    assert!(TICK_USAGE.ends_with(&MAX_TICKS.to_string()));
+    assert_eq!(
+        "tick ten".parse::<Command>(),
+        Err(ParseCommandError::Number("ten".to_string()))
+    );
+    assert_eq!(
+        "signal 3".parse::<Command>(),
+        Err(ParseCommandError::Usage("signal <pid> <signal>"))
+    );
+    assert_eq!(
+        "signal 3 HUP".parse::<Command>(),
+        Err(ParseCommandError::Signal("HUP".to_string()))
+    );
+    assert_eq!(
+        "top".parse::<Command>(),
+        Err(ParseCommandError::Unknown("top".to_string()))
+    );
+}
+
+#[test]
+fn test_commands_drive_the_table() {
+    let mut repl = Repl::new(super::RoundRobinScheduler::new(2));
+    assert_eq!(repl.eval("spawn bash"), Ok(Reply::Spawned(Pid(2))));
+    assert_eq!(repl.eval("spawn vim notes.txt"), Ok(Reply::Spawned(Pid(3))));
+
+    let ran = match repl.eval("tick 4") {
+        Ok(Reply::Ran { from: 0, ran }) => ran,
+        other => panic!("unexpected reply {:?}", other),
+    };
+    assert_eq!(
+        ran,
+        [Some(Pid(2)), Some(Pid(2)), Some(Pid(3)), Some(Pid(3))]
+    );
+
+    assert_eq!(
+        repl.eval("signal 3 STOP"),
+        Ok(Reply::Signalled {
+            pid: Pid(3),
+            delivered: vec![Signal::Stop]
+        })
+    );
+    // Only bash is left to run while vim is held
+    assert_eq!(
+        repl.eval("tick 3"),
+        Ok(Reply::Ran {
+            from: 4,
+            ran: vec![Some(Pid(2)); 3]
+        })
+    );
+    // Nor can a command built by hand run past the cap
+    assert_eq!(
+        repl.execute(Command::Tick(u64::MAX)),
+        Err(ReplError::Parse(ParseCommandError::Usage(TICK_USAGE)))
+    );
+    assert_eq!(repl.simulation().now(), 7);
+
+    repl.eval("kill 2 TERM").unwrap();
+    let table = match repl.eval("ps") {
+        Ok(Reply::Table(snaps)) => snaps,
+        other => panic!("unexpected reply {:?}", other),
+    };
+    let states: Vec<_> = table
+        .iter()
+        .map(|s| (s.pid, s.name.as_str(), s.state))
+        .collect();
+    assert_eq!(
+        states,
+        [
+            (Pid(1), "init", super::State::Stopped),
+            (Pid(2), "bash", super::State::Zombie),
+            (Pid(3), "vim", super::State::Stopped),
+        ]
+    );
+    assert_eq!(
+        repl.simulation().table().get(Pid(3)).unwrap().argv(),
+        ["notes.txt"]
+    );
+    assert_eq!(
+        repl.eval("signal 9 KILL"),
+        Err(ReplError::NoProcess(Pid(9)))
+    );
+}
+
+#[test]
+fn test_run_reads_until_quit() {
+    let mut repl = Repl::new(super::RoundRobinScheduler::new(1));
+    let input = "spawn sh\nbogus\n\ntick 2\nquit\nspawn never\n";
+    let mut output = Vec::new();
+    repl.run(input.as_bytes(), &mut output).unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(
+        output,
+        "> spawned pid 2\n> error: unknown command \"bogus\", try `help`\n> > tick 0: pid 2\ntick 1: pid 2\n> "
+    );
+    assert_eq!(repl.simulation().table().len(), 2);
+}
@@ -0,0 +1,72 @@
+use std::sync::{Arc, Mutex};
+
+// Everything random in the simulator, from lottery draws to made-up workloads, comes out of an `Rng`. There is no
+// other source: nothing reads the clock or the OS for entropy, so a run is fully determined by the seeds it is given.
+// One `Rng` can also be handed to several parts of a run, so that a single seed reproduces all of it.
+
+// SplitMix64: tiny, fast and good enough for lottery draws and made-up workloads. Any seed, zero included, is fine.
+#[derive(Debug, Clone)]
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// Shared, seeded source of random numbers. Cloning gives another handle to the same stream, so whatever draws
+/// from the clones, in whatever order, the run as a whole depends only on the seed.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    gen: Arc<Mutex<SplitMix64>>,
+}
+
+impl Rng {
+    /// A stream fully determined by `seed`.
+    pub fn new(seed: u64) -> Self {
+        Rng {
+            gen: Arc::new(Mutex::new(SplitMix64 { state: seed })),
+        }
+    }
+
+    pub fn next_u64(&self) -> u64 {
+        self.gen.lock().unwrap().next_u64()
+    }
+
+    /// Uniform enough in `0..n` for n far below 2^64.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero.
+    pub fn below(&self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+
+    // Where the stream has got to; `new` with it carries on from the same point.
+    #[cfg(feature = "serde")]
+    pub(super) fn state(&self) -> u64 {
+        self.gen.lock().unwrap().state
+    }
+}
+
+#[test]
+fn test_clones_share_one_stream() {
+    let a = Rng::new(4);
+    let b = a.clone();
+    let draws = [a.next_u64(), b.next_u64(), a.next_u64()];
+
+    // The same draws come out of one handle on the same seed, in the same order
+    let single = Rng::new(4);
+    assert_eq!(
+        draws,
+        [single.next_u64(), single.next_u64(), single.next_u64()]
+    );
+    assert_ne!(draws[0], Rng::new(5).next_u64());
+    assert!((0..100).all(|_| a.below(6) < 6));
+}
@@ -0,0 +1,310 @@
+use std::collections::VecDeque;
+
+#[cfg(feature = "serde")]
+use super::json::{self, FromJson, Json, JsonError, ToJson};
+use super::{Pid, Proc, ProcTable, SchedClass, State};
+
+// Round-robin: every runnable process gets the CPU for at most `quantum` ticks (as adjusted for its scheduling
+// class), then goes to the back of the line.
+// The scheduler only keeps task IDs in its run queue (a process's PID for its leader, a TID for each further thread);
+// the processes themselves live in the process table.
+#[derive(Debug)]
+pub struct RoundRobinScheduler {
+    run_queue: VecDeque<Pid>,
+    current: Option<Pid>,
+    quantum: u32,
+    remaining: u32,
+}
+
+impl RoundRobinScheduler {
+    /// Creates a scheduler handing out time slices of `quantum` ticks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `quantum` is zero, since no process could ever make progress.
+    pub fn new(quantum: u32) -> Self {
+        assert!(quantum > 0, "time quantum must be at least one tick");
+        RoundRobinScheduler {
+            run_queue: VecDeque::new(),
+            current: None,
+            quantum,
+            remaining: 0,
+        }
+    }
+
+    /// Puts `proc`, and each of its threads, at the back of the run queue. Queued processes wait in the Stopped state.
+    pub fn admit(&mut self, proc: &mut Proc) {
+        proc.set_state(State::Stopped);
+        self.run_queue.extend(proc.task_ids());
+    }
+
+    /// Advances the scheduler by one tick and returns the PID that used the CPU during it.
+    ///
+    /// PIDs whose process is no longer in the table or has exited are silently dropped from the queue; processes
+    /// held by a stop signal keep their place but are skipped until continued.
+    pub fn tick(&mut self, table: &mut ProcTable) -> Option<Pid> {
+        self.release_if_not_runnable(table);
+        if self.current.is_none() {
+            self.dispatch(table);
+        }
+
+        let pid = self.current?;
+        self.remaining -= 1;
+        if self.remaining == 0 {
+            self.preempt(table);
+        }
+        Some(pid)
+    }
+
+    /// Queues processes that just woke from a sleep at the front of the run queue, in wake order, so they get the CPU
+    /// back before processes that were preempted.
+    pub fn wake(&mut self, table: &mut ProcTable, woken: &[Pid]) {
+        // A zero-length sleep can end before the process was ever taken off the CPU
+        let woken: Vec<Pid> = woken
+            .iter()
+            .copied()
+            .filter(|&pid| self.current != Some(pid))
+            .collect();
+        self.run_queue.retain(|pid| !woken.contains(pid));
+        for &pid in woken.iter().rev() {
+            self.run_queue.push_front(pid);
+        }
+        for &pid in &woken {
+            if table.task_state(pid) == Some(State::Running) {
+                table.set_task_state(pid, State::Stopped);
+            }
+        }
+    }
+
+    /// Drops `tasks` from the queue, e.g. for a process moving to another core. One of them holding the CPU gives it
+    /// up and goes back to Stopped.
+    pub fn remove(&mut self, table: &mut ProcTable, tasks: &[Pid]) {
+        self.run_queue.retain(|tid| !tasks.contains(tid));
+        if let Some(pid) = self.current.filter(|pid| tasks.contains(pid)) {
+            self.current = None;
+            if table.task_state(pid) == Some(State::Running) {
+                table.set_task_state(pid, State::Stopped);
+            }
+        }
+    }
+
+    /// The run queue, front first. The running process isn't in it until preempted.
+    pub fn queued(&self) -> Vec<Pid> {
+        self.run_queue.iter().copied().collect()
+    }
+
+    /// PID of the process currently holding the CPU, if any.
+    pub fn current(&self) -> Option<Pid> {
+        self.current
+    }
+
+    /// Number of processes waiting in the run queue (excluding the running one).
+    pub fn len(&self) -> usize {
+        self.run_queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.run_queue.is_empty()
+    }
+
+    fn dispatch(&mut self, table: &mut ProcTable) {
+        // Idle-class tasks keep their turn but only run once a full pass has found nothing else ready
+        let mut idle = None;
+        for _ in 0..self.run_queue.len() {
+            let pid = self.run_queue.pop_front().unwrap();
+            match table.task_state(pid) {
+                Some(_) if table.task_runnable(pid) => {
+                    if table.task_class(pid) != SchedClass::Idle {
+                        self.start(table, pid);
+                        return;
+                    }
+                    idle.get_or_insert(pid);
+                    self.run_queue.push_back(pid);
+                }
+                Some(state) if !state.is_exited() => self.run_queue.push_back(pid),
+                _ => {}
+            }
+        }
+        if let Some(pid) = idle {
+            self.run_queue.retain(|&p| p != pid);
+            self.start(table, pid);
+        }
+    }
+
+    fn start(&mut self, table: &mut ProcTable, pid: Pid) {
+        table.set_task_state(pid, State::Running);
+        self.current = Some(pid);
+        self.remaining = table.task_class(pid).quantum(self.quantum);
+    }
+
+    // The running process may have been stopped or killed by a signal since the last tick.
+    fn release_if_not_runnable(&mut self, table: &ProcTable) {
+        let Some(pid) = self.current else {
+            return;
+        };
+        match table.task_state(pid) {
+            Some(_) if table.task_runnable(pid) => {}
+            Some(state) if !state.is_exited() => {
+                self.current = None;
+                self.run_queue.push_back(pid);
+            }
+            _ => self.current = None,
+        }
+    }
+
+    // Quantum expired: the running process goes back to Stopped and rejoins the tail of the queue.
+    fn preempt(&mut self, table: &mut ProcTable) {
+        if let Some(pid) = self.current.take() {
+            if table.task_state(pid).is_some() {
+                table.set_task_state(pid, State::Stopped);
+                self.run_queue.push_back(pid);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ToJson for RoundRobinScheduler {
+    fn to_json(&self) -> Json {
+        Json::object(vec![
+            ("quantum", Json::Int(self.quantum.into())),
+            ("remaining", Json::Int(self.remaining.into())),
+            ("current", json::opt_int(self.current.map(|pid| pid.0))),
+            ("run_queue", json::pid_list(&self.run_queue)),
+        ])
+    }
+}
+
+#[cfg(feature = "serde")]
+impl FromJson for RoundRobinScheduler {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        let quantum = json.int("quantum")?;
+        if quantum == 0 {
+            return Err(JsonError::Field("quantum"));
+        }
+        let current = json.opt_pid("current")?;
+        let remaining = json.int("remaining")?;
+        // A running process has between one tick and the longest slice any class gets left
+        if current.is_some() && !(1..=SchedClass::Idle.quantum(quantum)).contains(&remaining) {
+            return Err(JsonError::Field("remaining"));
+        }
+        Ok(RoundRobinScheduler {
+            run_queue: json.pids("run_queue")?.into(),
+            current,
+            quantum,
+            remaining,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_saved_slice_must_fit_the_quantum() {
+    let saved = |remaining: u32| {
+        let text =
+            format!(r#"{{"quantum": 2, "remaining": {remaining}, "current": 2, "run_queue": []}}"#);
+        RoundRobinScheduler::from_json(&text.parse().unwrap()).map(|rr| rr.remaining)
+    };
+    assert_eq!(saved(0).unwrap_err(), JsonError::Field("remaining"));
+    assert_eq!(saved(1), Ok(1));
+    // An idle-class process gets twice the quantum
+    assert_eq!(saved(4), Ok(4));
+    assert_eq!(saved(5).unwrap_err(), JsonError::Field("remaining"));
+}
+
+#[test]
+fn test_round_robin_rotation() {
+    let pids = super::PidAllocator::default();
+    let (mut table, procs) = super::table::flat_table(&pids, 3);
+    let (a, b, c) = (procs[0], procs[1], procs[2]);
+    let mut rr = RoundRobinScheduler::new(2);
+    for &pid in &procs {
+        rr.admit(table.get_mut(pid).unwrap());
+    }
+
+    let ran: Vec<_> = (0..8).filter_map(|_| rr.tick(&mut table)).collect();
+    assert_eq!(ran, [a, a, b, b, c, c, a, a]);
+
+    // The first process just used up its slice, so nobody holds the CPU until the next tick
+    assert_eq!(rr.current(), None);
+    assert!(procs
+        .iter()
+        .all(|&pid| table.get(pid).unwrap().state == State::Stopped));
+
+    rr.tick(&mut table);
+    assert_eq!(rr.current(), Some(b));
+    assert_eq!(table.get(b).unwrap().state, State::Running);
+}
+
+#[test]
+fn test_round_robin_skips_departed_procs() {
+    let pids = super::PidAllocator::default();
+    let (mut table, procs) = super::table::flat_table(&pids, 2);
+    let mut rr = RoundRobinScheduler::new(1);
+    for &pid in &procs {
+        rr.admit(table.get_mut(pid).unwrap());
+    }
+
+    // The first process leaves the table before it is ever dispatched
+    table.remove(procs[0]);
+    assert_eq!(rr.tick(&mut table), Some(procs[1]));
+    assert_eq!(rr.tick(&mut table), Some(procs[1]));
+}
+
+#[test]
+fn test_round_robin_skips_stopped_procs() {
+    use super::Signal;
+
+    let pids = super::PidAllocator::default();
+    let (mut table, procs) = super::table::flat_table(&pids, 2);
+    let (a, b) = (procs[0], procs[1]);
+    let mut rr = RoundRobinScheduler::new(1);
+    for &pid in &procs {
+        rr.admit(table.get_mut(pid).unwrap());
+    }
+    assert_eq!(rr.tick(&mut table), Some(a));
+
+    table.get_mut(a).unwrap().send_signal(Signal::Stop);
+    table.get_mut(a).unwrap().deliver_signals();
+    let ran: Vec<_> = (0..3).filter_map(|_| rr.tick(&mut table)).collect();
+    assert_eq!(ran, [b, b, b]);
+
+    table.get_mut(a).unwrap().send_signal(Signal::Cont);
+    table.get_mut(a).unwrap().deliver_signals();
+    table.get_mut(b).unwrap().send_signal(Signal::Kill);
+    table.get_mut(b).unwrap().deliver_signals();
+    let ran: Vec<_> = (0..2).filter_map(|_| rr.tick(&mut table)).collect();
+    assert_eq!(ran, [a, a]);
+    // The killed process was dropped from the rotation
+    assert_eq!(rr.len(), 1);
+}
+
+#[test]
+fn test_round_robin_honours_sched_classes() {
+    let pids = super::PidAllocator::default();
+    let (mut table, procs) = super::table::flat_table(&pids, 3);
+    let (shell, job, idle) = (procs[0], procs[1], procs[2]);
+    table
+        .get_mut(shell)
+        .unwrap()
+        .set_sched_class(SchedClass::Interactive);
+    table
+        .get_mut(idle)
+        .unwrap()
+        .set_sched_class(SchedClass::Idle);
+    let mut rr = RoundRobinScheduler::new(4);
+    for &pid in &[idle, shell, job] {
+        rr.admit(table.get_mut(pid).unwrap());
+    }
+
+    // The idle task is first in line but waits for the others, and the shell gets half a slice
+    let ran: Vec<_> = (0..8).filter_map(|_| rr.tick(&mut table)).collect();
+    assert_eq!(ran, [shell, shell, job, job, job, job, shell, shell]);
+
+    // Only with both of them gone does the idle task run, for a double slice
+    table.get_mut(shell).unwrap().exit(0);
+    table.get_mut(job).unwrap().exit(0);
+    let ran: Vec<_> = (0..9).filter_map(|_| rr.tick(&mut table)).collect();
+    assert_eq!(ran, [idle; 9]);
+    assert_eq!(rr.current(), Some(idle));
+}
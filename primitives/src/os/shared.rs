@@ -0,0 +1,137 @@
+use std::cell::{Ref, RefCell, RefMut};
+use std::mem;
+use std::rc::{Rc, Weak};
+
+use super::{Proc, ReparentError};
+
+// Between a tree that owns its children and one that borrows them: every node is shared through an `Rc`, so any number
+// of handles can keep one alive, and a child points back at its parent. That back-pointer is a `Weak`, which doesn't
+// keep the parent alive; two `Rc`s pointing at each other would never be freed. The nodes are changed through
+// `RefCell`s, as everything behind an `Rc` is shared and so can't be borrowed mutably the usual way.
+
+/// A process in a shared tree. Children are owned (`Rc`), the parent is only referred to (`Weak`).
+#[derive(Debug)]
+pub struct SharedProc {
+    proc: RefCell<Proc>,
+    parent: RefCell<Weak<SharedProc>>,
+    children: RefCell<Vec<Rc<SharedProc>>>,
+}
+
+impl SharedProc {
+    /// Wraps `proc` as the root of a shared tree. Any children it owns become shared nodes below it.
+    pub fn new(mut proc: Proc) -> Rc<SharedProc> {
+        let owned = mem::take(&mut proc.children);
+        let node = Rc::new(SharedProc {
+            proc: RefCell::new(proc),
+            parent: RefCell::new(Weak::new()),
+            children: RefCell::new(Vec::new()),
+        });
+        for child in owned {
+            node.add_child(SharedProc::new(child))
+                .expect("a new node has no ancestors");
+        }
+        node
+    }
+
+    pub fn proc(&self) -> Ref<'_, Proc> {
+        self.proc.borrow()
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the process is already borrowed, through this handle or any other.
+    pub fn proc_mut(&self) -> RefMut<'_, Proc> {
+        self.proc.borrow_mut()
+    }
+
+    /// Makes `child` the last child of this node, taking it away from its old parent if it had one. Refused if
+    /// `child` is this node or one of its ancestors, which would make the tree contain itself.
+    pub fn add_child(self: &Rc<Self>, child: Rc<SharedProc>) -> Result<(), ReparentError> {
+        if Rc::ptr_eq(self, &child) || self.ancestors().any(|a| Rc::ptr_eq(&a, &child)) {
+            return Err(ReparentError::Cycle);
+        }
+        if let Some(old) = child.parent() {
+            old.children.borrow_mut().retain(|c| !Rc::ptr_eq(c, &child));
+        }
+        *child.parent.borrow_mut() = Rc::downgrade(self);
+        self.children.borrow_mut().push(child);
+        Ok(())
+    }
+
+    /// The parent, while it is still alive. `None` for a root.
+    pub fn parent(&self) -> Option<Rc<SharedProc>> {
+        self.parent.borrow().upgrade()
+    }
+
+    /// Handles to the children, in the order they were added.
+    pub fn children(&self) -> Vec<Rc<SharedProc>> {
+        self.children.borrow().clone()
+    }
+
+    /// The parent, its parent and so on up to the root.
+    pub fn ancestors(&self) -> Ancestors {
+        Ancestors {
+            next: self.parent(),
+        }
+    }
+}
+
+pub struct Ancestors {
+    next: Option<Rc<SharedProc>>,
+}
+
+impl Iterator for Ancestors {
+    type Item = Rc<SharedProc>;
+
+    fn next(&mut self) -> Option<Rc<SharedProc>> {
+        let proc = self.next.take()?;
+        self.next = proc.parent();
+        Some(proc)
+    }
+}
+
+#[test]
+fn test_shared_tree_walks_up() {
+    use super::{PidAllocator, State};
+
+    let pids = PidAllocator::default();
+    let named = |name, children: Vec<Proc>| {
+        Proc::builder(&pids)
+            .name(name)
+            .children(children)
+            .build()
+            .unwrap()
+    };
+    let bash = named("bash", Vec::new());
+    let rsyslogd = named("rsyslogd", vec![bash]);
+    let init = SharedProc::new(named("init", vec![named("cron", Vec::new()), rsyslogd]));
+
+    let rsyslogd = init.children()[1].clone();
+    let bash = rsyslogd.children()[0].clone();
+    let names: Vec<_> = bash
+        .ancestors()
+        .map(|a| a.proc().name().to_string())
+        .collect();
+    assert_eq!(names, ["rsyslogd", "init"]);
+    assert!(init.parent().is_none());
+    assert!(Rc::ptr_eq(&bash.parent().unwrap(), &rsyslogd));
+
+    // Any handle can change the process, and every other handle sees it
+    bash.proc_mut().set_state(State::Running);
+    assert_eq!(rsyslogd.children()[0].proc().state(), State::Running);
+
+    // Moving a node updates both directions
+    let cron = init.children()[0].clone();
+    cron.add_child(bash.clone()).unwrap();
+    assert!(rsyslogd.children().is_empty());
+    assert_eq!(bash.parent().unwrap().proc().name(), "cron");
+    assert_eq!(bash.ancestors().count(), 2);
+    assert_eq!(bash.add_child(init.clone()), Err(ReparentError::Cycle));
+    assert_eq!(cron.add_child(cron.clone()), Err(ReparentError::Cycle));
+
+    // The parent pointers don't keep anything alive: once the last strong handle to the root goes, it's freed and
+    // the nodes still held find no parent
+    drop((init, cron, rsyslogd));
+    assert!(bash.parent().is_none());
+    assert_eq!(pids.in_use(), 1);
+}
@@ -0,0 +1,271 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use super::audit::AuditEvent;
+use super::{Proc, State, StopKind};
+
+/// The handful of job-control signals the simulator understands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Signal {
+    Kill, // SIGKILL: terminate, cannot be blocked
+    Term, // SIGTERM: polite terminate, can be blocked
+    Stop, // SIGSTOP: mandatory stop, cannot be blocked
+    Tstp, // SIGTSTP: terminal stop (Ctrl+Z), can be blocked
+    Cont, // SIGCONT: resume a stopped process
+}
+
+impl Signal {
+    /// Linux x86-64 signal number.
+    pub fn number(self) -> i32 {
+        match self {
+            Signal::Kill => 9,
+            Signal::Term => 15,
+            Signal::Stop => 19,
+            Signal::Tstp => 20,
+            Signal::Cont => 18,
+        }
+    }
+
+    /// Only these can be held back by a process's signal mask; SIGKILL and SIGSTOP always get through.
+    pub fn is_blockable(self) -> bool {
+        matches!(self, Signal::Term | Signal::Tstp)
+    }
+
+    /// Which kind of stop this signal causes, if it is a stop signal at all.
+    pub fn stop_kind(self) -> Option<StopKind> {
+        match self {
+            Signal::Stop => Some(StopKind::Mandatory),
+            Signal::Tstp => Some(StopKind::Ignorable),
+            _ => None,
+        }
+    }
+
+    fn bit(self) -> u32 {
+        1 << self.number()
+    }
+}
+
+impl fmt::Display for Signal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Signal::Kill => "SIGKILL",
+            Signal::Term => "SIGTERM",
+            Signal::Stop => "SIGSTOP",
+            Signal::Tstp => "SIGTSTP",
+            Signal::Cont => "SIGCONT",
+        };
+        f.write_str(name)
+    }
+}
+
+/// `Signal::from_str` was given something other than a signal it knows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseSignalError(pub String);
+
+impl fmt::Display for ParseSignalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown signal {:?}", self.0)
+    }
+}
+
+impl Error for ParseSignalError {}
+
+// Accepts what `kill` does: a name with or without the SIG prefix, in any case, or the signal number.
+impl FromStr for Signal {
+    type Err = ParseSignalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let upper = s.to_ascii_uppercase();
+        let name = upper.strip_prefix("SIG").unwrap_or(&upper);
+        [
+            Signal::Kill,
+            Signal::Term,
+            Signal::Stop,
+            Signal::Tstp,
+            Signal::Cont,
+        ]
+        .into_iter()
+        .find(|sig| sig.to_string()[3..] == *name || sig.number().to_string() == name)
+        .ok_or_else(|| ParseSignalError(s.to_string()))
+    }
+}
+
+/// Set of blocked signals, one bit per signal number like a kernel `sigset_t`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SignalMask(u32);
+
+impl SignalMask {
+    pub fn contains(self, sig: Signal) -> bool {
+        self.0 & sig.bit() != 0
+    }
+
+    pub(super) fn insert(&mut self, sig: Signal) {
+        self.0 |= sig.bit();
+    }
+
+    fn remove(&mut self, sig: Signal) {
+        self.0 &= !sig.bit();
+    }
+}
+
+impl Proc {
+    /// Queues `sig` for delivery. Like standard Unix signals, a signal that is already pending is not queued twice,
+    /// and exited processes ignore signals altogether.
+    pub fn send_signal(&mut self, sig: Signal) {
+        if self.state.is_exited() || self.pending.contains(&sig) {
+            return;
+        }
+        self.pending.push_back(sig);
+    }
+
+    /// Signals queued but not yet delivered, oldest first.
+    pub fn pending_signals(&self) -> impl Iterator<Item = Signal> + '_ {
+        self.pending.iter().copied()
+    }
+
+    /// Adds `sig` to the signal mask. Blocking SIGKILL, SIGSTOP or SIGCONT is silently ignored, as with `sigprocmask`.
+    pub fn block_signal(&mut self, sig: Signal) {
+        if sig.is_blockable() {
+            self.mask.insert(sig);
+        }
+    }
+
+    pub fn unblock_signal(&mut self, sig: Signal) {
+        self.mask.remove(sig);
+    }
+
+    pub fn signal_mask(&self) -> SignalMask {
+        self.mask
+    }
+
+    /// Why the process is stopped, if a stop signal is what is keeping it off the CPU.
+    pub fn stopped_by(&self) -> Option<StopKind> {
+        self.stop.map(|(kind, _)| kind)
+    }
+
+    /// Delivers every pending signal that is not blocked and returns them in delivery order. Blocked signals stay
+    /// pending until unblocked.
+    ///
+    /// - SIGKILL/SIGTERM terminate the process (exit code `128 + signo`, as a shell reports it).
+    /// - SIGSTOP/SIGTSTP move it to Stopped, remembering the state to return to.
+    /// - SIGCONT puts a signal-stopped process back into the state it was stopped from.
+    pub fn deliver_signals(&mut self) -> Vec<Signal> {
+        let mask = self.mask;
+        let (blocked, deliverable) = self.pending.drain(..).partition(|&sig| mask.contains(sig));
+        self.pending = blocked;
+
+        let mut delivered = Vec::new();
+        for sig in deliverable {
+            if self.state.is_exited() {
+                break;
+            }
+            self.note(AuditEvent::Signal(sig));
+            crate::debug!("pid {}: {} delivered", self.pid(), sig);
+            match sig {
+                Signal::Kill | Signal::Term => self.exit(128 + sig.number()),
+                Signal::Stop | Signal::Tstp => {
+                    if self.stop.is_none() {
+                        let kind = sig.stop_kind().unwrap();
+                        self.stop = Some((kind, self.state));
+                        self.change_state(State::Stopped);
+                    }
+                }
+                Signal::Cont => {
+                    if let Some((_, resume)) = self.stop.take() {
+                        self.change_state(resume);
+                    }
+                }
+            }
+            delivered.push(sig);
+        }
+        delivered
+    }
+
+    /// Whether a scheduler may put this process on a CPU: it must be alive, not blocked and not held by a stop signal.
+    pub fn is_runnable(&self) -> bool {
+        matches!(self.state, State::Running | State::Stopped) && self.stop.is_none()
+    }
+}
+
+#[test]
+fn test_stop_and_cont_restore_previous_state() {
+    let mut proc = Proc::new(&super::PidAllocator::default());
+    proc.set_state(State::Running);
+
+    proc.send_signal(Signal::Stop);
+    proc.send_signal(Signal::Stop);
+    assert_eq!(proc.pending_signals().count(), 1);
+    assert_eq!(proc.deliver_signals(), vec![Signal::Stop]);
+    assert_eq!(proc.state(), State::Stopped);
+    assert_eq!(proc.stopped_by(), Some(StopKind::Mandatory));
+    assert!(!proc.is_runnable());
+
+    proc.send_signal(Signal::Cont);
+    proc.deliver_signals();
+    assert_eq!(proc.state(), State::Running);
+    assert!(proc.is_runnable());
+}
+
+#[test]
+fn test_kill_terminates_even_when_stopped() {
+    let mut proc = Proc::new(&super::PidAllocator::default());
+    proc.send_signal(Signal::Tstp);
+    proc.send_signal(Signal::Kill);
+    proc.send_signal(Signal::Cont);
+
+    // Delivery stops at the first fatal signal
+    assert_eq!(proc.deliver_signals(), vec![Signal::Tstp, Signal::Kill]);
+    assert_eq!(proc.state(), State::Zombie);
+    assert_eq!(proc.exit_code(), Some(137));
+
+    proc.send_signal(Signal::Term);
+    assert_eq!(proc.pending_signals().count(), 0);
+}
+
+#[test]
+fn test_mask_holds_back_ignorable_signals_only() {
+    let mut proc = Proc::new(&super::PidAllocator::default());
+    proc.set_state(State::Running);
+    proc.block_signal(Signal::Tstp);
+    proc.block_signal(Signal::Stop);
+    assert!(proc.signal_mask().contains(Signal::Tstp));
+    assert!(!proc.signal_mask().contains(Signal::Stop));
+
+    proc.send_signal(Signal::Tstp);
+    assert!(proc.deliver_signals().is_empty());
+    assert_eq!(proc.state(), State::Running);
+    assert_eq!(
+        proc.pending_signals().collect::<Vec<_>>(),
+        vec![Signal::Tstp]
+    );
+
+    proc.unblock_signal(Signal::Tstp);
+    assert_eq!(proc.deliver_signals(), vec![Signal::Tstp]);
+    assert_eq!(proc.stopped_by(), Some(StopKind::Ignorable));
+}
+
+#[test]
+fn test_parse_signal_by_name_or_number() {
+    assert_eq!("STOP".parse(), Ok(Signal::Stop));
+    assert_eq!("sigcont".parse(), Ok(Signal::Cont));
+    assert_eq!("9".parse(), Ok(Signal::Kill));
+    assert_eq!(
+        "HUP".parse::<Signal>(),
+        Err(ParseSignalError("HUP".to_string()))
+    );
+}
+
+#[test]
+fn test_delivery_is_logged() {
+    let mut proc = Proc::new(&super::PidAllocator::default());
+    proc.send_signal(Signal::Stop);
+    let (_, records) = crate::log::capture(|| proc.deliver_signals());
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].level, crate::log::Level::Debug);
+    assert_eq!(records[0].target, "rust_test::os::signal");
+    assert_eq!(
+        records[0].message,
+        format!("pid {}: SIGSTOP delivered", proc.pid())
+    );
+}
@@ -0,0 +1,855 @@
+use std::collections::BTreeMap;
+#[cfg(feature = "serde")]
+use std::io;
+use std::ops::Range;
+#[cfg(feature = "serde")]
+use std::path::Path;
+
+use super::io::{DeviceId, IoRequest};
+#[cfg(feature = "serde")]
+use super::json::{self, FromJson, Json, JsonError, ToJson};
+#[cfg(feature = "serde")]
+use super::{audit, PidAllocator};
+use super::{
+    AffinityError, AuditLog, CgroupScheduler, CoreId, Device, LotteryScheduler, MlfqScheduler, Pid,
+    Proc, ProcTable, RoundRobinScheduler, SchedStats, TimerWheel,
+};
+
+/// Simulated time, counted in whole ticks from zero. Nothing in the simulator reads the wall clock, so the same inputs
+/// always produce the same schedule.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Clock {
+    now: u64,
+}
+
+impl Clock {
+    pub fn new() -> Self {
+        Clock::default()
+    }
+
+    /// The tick currently being simulated.
+    pub fn now(&self) -> u64 {
+        self.now
+    }
+
+    /// Moves on to the next tick and returns it.
+    pub fn tick(&mut self) -> u64 {
+        self.now += 1;
+        self.now
+    }
+}
+
+/// Anything that picks, one tick at a time, which process in the table gets the CPU.
+pub trait Scheduler {
+    /// Hands a new process to the scheduler.
+    fn admit(&mut self, proc: &mut Proc);
+
+    /// Runs one tick and returns the PID that held the CPU during it.
+    fn tick(&mut self, table: &mut ProcTable) -> Option<Pid>;
+
+    /// Takes back processes in `woken` (in wake order) whose sleep just ended, ahead of the next tick.
+    fn wake(&mut self, table: &mut ProcTable, woken: &[Pid]);
+
+    /// Forgets `tasks`, the TIDs of one process, e.g. when it moves to another core's scheduler. A task holding the CPU
+    /// gives it up.
+    fn remove(&mut self, table: &mut ProcTable, tasks: &[Pid]);
+
+    /// Tasks waiting for the CPU, in the order the scheduler would pick them as things stand, leaving out the one
+    /// holding it. Tasks that can't run right now may be in the list too.
+    fn queued(&self) -> Vec<Pid>;
+}
+
+// Lets a simulation be handed any scheduler picked at run time, as the benchmarks do.
+impl<S: Scheduler + ?Sized> Scheduler for Box<S> {
+    fn admit(&mut self, proc: &mut Proc) {
+        (**self).admit(proc)
+    }
+
+    fn tick(&mut self, table: &mut ProcTable) -> Option<Pid> {
+        (**self).tick(table)
+    }
+
+    fn wake(&mut self, table: &mut ProcTable, woken: &[Pid]) {
+        (**self).wake(table, woken)
+    }
+
+    fn remove(&mut self, table: &mut ProcTable, tasks: &[Pid]) {
+        (**self).remove(table, tasks)
+    }
+
+    fn queued(&self) -> Vec<Pid> {
+        (**self).queued()
+    }
+}
+
+impl Scheduler for RoundRobinScheduler {
+    fn admit(&mut self, proc: &mut Proc) {
+        RoundRobinScheduler::admit(self, proc)
+    }
+
+    fn tick(&mut self, table: &mut ProcTable) -> Option<Pid> {
+        RoundRobinScheduler::tick(self, table)
+    }
+
+    fn wake(&mut self, table: &mut ProcTable, woken: &[Pid]) {
+        RoundRobinScheduler::wake(self, table, woken)
+    }
+
+    fn remove(&mut self, table: &mut ProcTable, tasks: &[Pid]) {
+        RoundRobinScheduler::remove(self, table, tasks)
+    }
+
+    fn queued(&self) -> Vec<Pid> {
+        RoundRobinScheduler::queued(self)
+    }
+}
+
+impl Scheduler for CgroupScheduler {
+    fn admit(&mut self, proc: &mut Proc) {
+        CgroupScheduler::admit(self, proc)
+    }
+
+    fn tick(&mut self, table: &mut ProcTable) -> Option<Pid> {
+        CgroupScheduler::tick(self, table)
+    }
+
+    fn wake(&mut self, table: &mut ProcTable, woken: &[Pid]) {
+        CgroupScheduler::wake(self, table, woken)
+    }
+
+    fn remove(&mut self, table: &mut ProcTable, tasks: &[Pid]) {
+        CgroupScheduler::remove(self, table, tasks)
+    }
+
+    fn queued(&self) -> Vec<Pid> {
+        CgroupScheduler::queued(self)
+    }
+}
+
+impl Scheduler for LotteryScheduler {
+    fn admit(&mut self, proc: &mut Proc) {
+        LotteryScheduler::admit(self, proc)
+    }
+
+    fn tick(&mut self, table: &mut ProcTable) -> Option<Pid> {
+        LotteryScheduler::tick(self, table)
+    }
+
+    fn wake(&mut self, table: &mut ProcTable, woken: &[Pid]) {
+        LotteryScheduler::wake(self, table, woken)
+    }
+
+    fn remove(&mut self, table: &mut ProcTable, tasks: &[Pid]) {
+        LotteryScheduler::remove(self, table, tasks)
+    }
+
+    fn queued(&self) -> Vec<Pid> {
+        LotteryScheduler::queued(self)
+    }
+}
+
+impl Scheduler for MlfqScheduler {
+    fn admit(&mut self, proc: &mut Proc) {
+        MlfqScheduler::admit(self, proc)
+    }
+
+    fn tick(&mut self, table: &mut ProcTable) -> Option<Pid> {
+        MlfqScheduler::tick(self, table)
+    }
+
+    fn wake(&mut self, table: &mut ProcTable, woken: &[Pid]) {
+        MlfqScheduler::wake(self, table, woken)
+    }
+
+    fn remove(&mut self, table: &mut ProcTable, tasks: &[Pid]) {
+        MlfqScheduler::remove(self, table, tasks)
+    }
+
+    fn queued(&self) -> Vec<Pid> {
+        MlfqScheduler::queued(self)
+    }
+}
+
+/// A machine with one or more cores: a clock, a scheduler per core and the processes they are scheduling, with
+/// statistics collected as it goes.
+///
+/// Like Linux's per-CPU run queues, each core schedules only the processes placed on it. A process is placed when it
+/// is admitted, on the core its affinity allows with the fewest processes, and stays there, threads and all, unless
+/// its affinity changes to leave that core out.
+#[derive(Debug)]
+pub struct Simulation<S: Scheduler> {
+    clock: Clock,
+    cores: Vec<S>,
+    homes: BTreeMap<Pid, CoreId>, // Core each admitted process is queued on
+    table: ProcTable,
+    stats: SchedStats,
+    timers: TimerWheel,
+    devices: Vec<Device>,
+    audit: AuditLog,
+    // Who ran on each core, one entry per simulated tick
+    history: Vec<Vec<Option<Pid>>>,
+}
+
+/// One core's schedule: consecutive ticks spent running the same process, in time order. Idle ticks are left out.
+pub type TimelineRow = Vec<(Range<u64>, Pid)>;
+
+impl<S: Scheduler> Simulation<S> {
+    /// Runs `scheduler` over `table` on a single core. Processes already in the table are only scheduled once passed
+    /// to `admit`.
+    pub fn new(scheduler: S, table: ProcTable) -> Self {
+        Simulation::with_cores(vec![scheduler], table)
+    }
+
+    /// As `new`, but on one core per scheduler in `cores`, core 0 first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cores` is empty or has more than the 64 cores an affinity mask can name.
+    pub fn with_cores(cores: Vec<S>, table: ProcTable) -> Self {
+        assert!(
+            (1..=64).contains(&cores.len()),
+            "a simulation needs 1 to 64 cores"
+        );
+        Simulation {
+            clock: Clock::new(),
+            cores,
+            homes: BTreeMap::new(),
+            table,
+            stats: SchedStats::new(),
+            timers: TimerWheel::default(),
+            devices: Vec::new(),
+            audit: AuditLog::new(),
+            history: Vec::new(),
+        }
+    }
+
+    /// Adds `proc` to the process table, under init, and admits it to the scheduler of a core it may run on. One
+    /// allowed on none of the simulated cores is added but never scheduled.
+    pub fn spawn(&mut self, mut proc: Proc) -> Pid {
+        self.place(&mut proc);
+        self.table.insert(proc)
+    }
+
+    /// Hands a process that is already in the table to the scheduler of a core it may run on. Returns false if there
+    /// is no such process, or its affinity allows none of the simulated cores.
+    pub fn admit(&mut self, pid: Pid) -> bool {
+        let Some(proc) = self.table.get_mut(pid) else {
+            return false;
+        };
+        let Some(core) = least_loaded(&self.homes, self.cores.len(), proc) else {
+            return false;
+        };
+        self.cores[core].admit(proc);
+        self.homes.insert(pid, core);
+        true
+    }
+
+    fn place(&mut self, proc: &mut Proc) {
+        match least_loaded(&self.homes, self.cores.len(), proc) {
+            Some(core) => {
+                self.cores[core].admit(proc);
+                self.homes.insert(proc.pid(), core);
+            }
+            None => crate::warn!("pid {} may run on none of the cores", proc.pid()),
+        }
+    }
+
+    /// Restricts `pid` to the cores set in `mask`, as `ProcTable::set_affinity` does, but refusing a mask that leaves
+    /// out every simulated core. A process placed on a core it is no longer allowed on moves to an allowed one before
+    /// the next tick, or once it is runnable again if it is asleep or stopped.
+    pub fn set_affinity(&mut self, pid: Pid, mask: u64) -> Result<(), AffinityError> {
+        let cores = self.cores.len();
+        let on_machine = if cores == 64 {
+            u64::MAX
+        } else {
+            (1 << cores) - 1
+        };
+        if mask & on_machine == 0 {
+            return Err(AffinityError::EmptyMask);
+        }
+        self.table.set_affinity(pid, mask)
+    }
+
+    /// Core `pid` is queued on, if it has been admitted.
+    pub fn core_of(&self, pid: Pid) -> Option<CoreId> {
+        self.homes.get(&pid).copied()
+    }
+
+    pub fn num_cores(&self) -> usize {
+        self.cores.len()
+    }
+
+    // Moves runnable processes off cores their affinity no longer allows. Ones asleep or stopped are left where they
+    // are until they can run again, as they won't be picked before then.
+    fn migrate(&mut self) {
+        let homes = self.homes.clone();
+        for (pid, core) in homes {
+            let Some(proc) = self.table.get_mut(pid) else {
+                self.homes.remove(&pid);
+                continue;
+            };
+            if proc.state().is_exited() {
+                self.homes.remove(&pid);
+                continue;
+            }
+            if proc.allows_core(core) || !proc.is_runnable() {
+                continue;
+            }
+            self.homes.remove(&pid);
+            let tasks = proc.task_ids();
+            self.cores[core].remove(&mut self.table, &tasks);
+            let proc = self.table.get_mut(pid).expect("found above");
+            let to = least_loaded(&self.homes, self.cores.len(), proc)
+                .expect("set_affinity refuses masks without a simulated core");
+            crate::debug!("pid {}: moved from core {} to core {}", pid, core, to);
+            self.cores[to].admit(proc);
+            self.homes.insert(pid, to);
+        }
+    }
+
+    /// Attaches a device that processes can issue I/O to.
+    pub fn add_device(&mut self, device: Device) -> DeviceId {
+        self.devices.push(device);
+        self.devices.len() - 1
+    }
+
+    /// Queues an I/O request (from `Proc::start_io`) on `device`. The process is woken once the device has served it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `device` was not returned by `add_device`.
+    pub fn submit_io(&mut self, device: DeviceId, req: IoRequest) {
+        self.devices[device].submit(req);
+    }
+
+    pub fn device(&self, device: DeviceId) -> Option<&Device> {
+        self.devices.get(device)
+    }
+
+    /// Simulates one tick and returns the PID that ran on core 0 during it, the only core of a simulation made with
+    /// `new`. `step_cores` returns what every core ran.
+    pub fn step(&mut self) -> Option<Pid> {
+        self.step_cores()[0]
+    }
+
+    /// Simulates one tick and returns the PID each core ran during it, core 0 first. Sleeps requested since the last
+    /// tick get their timers armed first, processes whose timers fire now are woken and handed back to their core's
+    /// scheduler, and processes whose affinity changed move cores. Devices then do a tick of work, waking processes
+    /// whose I/O completed in time for the next tick. Lifecycle events, including any from changes made between ticks,
+    /// are logged against the current tick.
+    pub fn step_cores(&mut self) -> Vec<Option<Pid>> {
+        let now = self.clock.now();
+        self.audit.collect(now, &mut self.table);
+        let timers = &mut self.timers;
+        self.table.for_each_mut(&mut |proc| {
+            proc.note_sleep_start(now);
+            if let Some(ticks) = proc.take_sleep_request() {
+                // A sleep too long to end before the clock runs out simply never ends
+                timers.schedule(now.saturating_add(ticks), proc.pid());
+            }
+        });
+        let expired = self.timers.expire(now);
+        self.wake(expired);
+        self.migrate();
+
+        let mut ran = Vec::with_capacity(self.cores.len());
+        for (core, scheduler) in self.cores.iter_mut().enumerate() {
+            let pid = scheduler.tick(&mut self.table);
+            // Whichever thread ran, the tick is charged to its process
+            if let Some(proc) = pid.and_then(|tid| self.table.owner_of_mut(tid)) {
+                proc.charge_user_tick();
+            }
+            match pid {
+                Some(pid) => crate::trace!("tick {}: pid {} ran on core {}", now, pid, core),
+                None => crate::trace!("tick {}: core {} idle", now, core),
+            }
+            ran.push(pid);
+        }
+        self.stats.record_tick(now, &ran, &self.table);
+        self.history.push(ran.clone());
+
+        let completed = self.devices.iter_mut().filter_map(Device::tick).collect();
+        self.wake(completed);
+        self.audit.collect(now, &mut self.table);
+        self.clock.tick();
+        ran
+    }
+
+    /// Simulates `ticks` ticks and returns who ran in each of them.
+    pub fn run(&mut self, ticks: u64) -> Vec<Option<Pid>> {
+        (0..ticks).map(|_| self.step()).collect()
+    }
+
+    pub fn now(&self) -> u64 {
+        self.clock.now()
+    }
+
+    pub fn table(&self) -> &ProcTable {
+        &self.table
+    }
+
+    /// Mutable access to one process, e.g. to signal it or make it exit between ticks.
+    pub fn proc_mut(&mut self, pid: Pid) -> Option<&mut Proc> {
+        self.table.get_mut(pid)
+    }
+
+    /// Core 0's scheduler, the only one of a simulation made with `new`.
+    pub fn scheduler(&self) -> &S {
+        &self.cores[0]
+    }
+
+    /// Every core's scheduler, core 0 first.
+    pub fn schedulers(&self) -> &[S] {
+        &self.cores
+    }
+
+    pub fn stats(&self) -> &SchedStats {
+        &self.stats
+    }
+
+    pub fn audit(&self) -> &AuditLog {
+        &self.audit
+    }
+
+    /// Per-core rows of what ran when, e.g. `[(0..2, pid 1), (2..3, pid 2)]`, ready for `gantt::render`.
+    pub fn timeline(&self) -> Vec<TimelineRow> {
+        let cores = self.history.iter().map(Vec::len).max().unwrap_or(0);
+        let mut rows = vec![TimelineRow::new(); cores];
+        for (tick, running) in self.history.iter().enumerate() {
+            let tick = tick as u64;
+            for (row, &pid) in rows.iter_mut().zip(running) {
+                let Some(pid) = pid else { continue };
+                match row.last_mut() {
+                    Some((span, last)) if *last == pid && span.end == tick => span.end += 1,
+                    _ => row.push((tick..tick + 1, pid)),
+                }
+            }
+        }
+        rows
+    }
+
+    // Each core's scheduler takes back its own woken tasks, still in wake order.
+    fn wake(&mut self, mut woken: Vec<Pid>) {
+        woken.retain(|&pid| self.table.get_mut(pid).is_some_and(|p| p.wake()));
+        for core in 0..self.cores.len() {
+            let mine: Vec<Pid> = woken
+                .iter()
+                .copied()
+                .filter(|&tid| {
+                    let owner = self.table.owner_of(tid).map(Proc::pid);
+                    owner.and_then(|pid| self.homes.get(&pid)) == Some(&core)
+                })
+                .collect();
+            if !mine.is_empty() {
+                self.cores[core].wake(&mut self.table, &mine);
+            }
+        }
+    }
+
+    /// Ends the simulation, handing the process table back.
+    pub fn into_table(self) -> ProcTable {
+        self.table
+    }
+}
+
+// The allowed core with the fewest processes placed on it, the lowest-numbered on a tie.
+fn least_loaded(homes: &BTreeMap<Pid, CoreId>, cores: usize, proc: &Proc) -> Option<CoreId> {
+    (0..cores)
+        .filter(|&core| proc.allows_core(core))
+        .min_by_key(|&core| homes.values().filter(|&&home| home == core).count())
+}
+
+#[cfg(feature = "serde")]
+impl<S: Scheduler + ToJson> Simulation<S> {
+    /// Checkpoints the whole simulation to `path`: the clock, the process table, each core's scheduler with its queues
+    /// (and its random generator, if it draws) and which core each process is on, armed timers, devices with their queued I/O, and everything recorded so far,
+    /// statistics, the audit log and the timeline. `resume` carries on from exactly this point.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let devices = self.devices.iter().map(Device::to_json).collect();
+        let homes = self
+            .homes
+            .iter()
+            .map(|(pid, &core)| Json::Array(vec![pid.to_json(), Json::Int(core as i128)]))
+            .collect();
+        let history = self
+            .history
+            .iter()
+            .map(|running| {
+                Json::Array(
+                    running
+                        .iter()
+                        .map(|pid| json::opt_int(pid.map(|pid| pid.0)))
+                        .collect(),
+                )
+            })
+            .collect();
+        json::write_file(
+            path.as_ref(),
+            vec![
+                ("clock", Json::Int(self.clock.now.into())),
+                ("table", self.table.init().to_json()),
+                ("uncollected", audit::uncollected_to_json(&self.table)),
+                (
+                    "cores",
+                    Json::Array(self.cores.iter().map(S::to_json).collect()),
+                ),
+                ("homes", Json::Array(homes)),
+                ("timers", self.timers.to_json()),
+                ("devices", Json::Array(devices)),
+                ("stats", self.stats.to_json()),
+                ("audit", self.audit.to_json()),
+                ("history", Json::Array(history)),
+            ],
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<S: Scheduler + FromJson> Simulation<S> {
+    /// Picks up a simulation checkpointed by `save`, at the tick it was saved on. From there it runs, logs and counts
+    /// exactly as the original would have. Processes keep their PIDs, which are claimed from a fresh allocator.
+    ///
+    /// Files saved before statistics, the audit log and the timeline were kept still load, with those starting out
+    /// empty, and so do those from before simulations had more than one core, on a single core.
+    pub fn resume(path: impl AsRef<Path>) -> io::Result<Self> {
+        let json = json::read_file(path.as_ref())?;
+        let mut table = ProcTable::from_json(&json, &PidAllocator::default())?;
+        if let Some(uncollected) = json.get("uncollected") {
+            audit::uncollected_from_json(uncollected, &mut table)?;
+        }
+        let mut sim = match json.get("cores") {
+            Some(_) => {
+                let cores = json
+                    .array("cores")?
+                    .iter()
+                    .map(S::from_json)
+                    .collect::<Result<Vec<_>, _>>()?;
+                if !(1..=64).contains(&cores.len()) {
+                    return Err(JsonError::Field("cores").into());
+                }
+                let mut sim = Simulation::with_cores(cores, table);
+                for home in json.array("homes")? {
+                    let (pid, core) = match home {
+                        Json::Array(pair) if pair.len() == 2 => (
+                            Pid::from_json(&pair[0])?,
+                            usize::try_from(match &pair[1] {
+                                Json::Int(core) => *core,
+                                _ => -1,
+                            })
+                            .ok()
+                            .filter(|&core| core < sim.cores.len())
+                            .ok_or(JsonError::Field("homes"))?,
+                        ),
+                        _ => return Err(JsonError::Field("homes").into()),
+                    };
+                    sim.homes.insert(pid, core);
+                }
+                sim
+            }
+            // Saved by a single-core simulation, whose one scheduler had every process
+            None => {
+                let mut sim = Simulation::new(json.parse("scheduler")?, table);
+                let init = sim.table.init().pid();
+                sim.homes = sim
+                    .table
+                    .iter()
+                    .map(Proc::pid)
+                    .filter(|&pid| pid != init)
+                    .map(|pid| (pid, 0))
+                    .collect();
+                sim
+            }
+        };
+        sim.clock.now = json.int("clock")?;
+        sim.timers = json.parse("timers")?;
+        sim.devices = json
+            .array("devices")?
+            .iter()
+            .map(Device::from_json)
+            .collect::<Result<_, _>>()?;
+        if json.get("stats").is_some() {
+            sim.stats = json.parse("stats")?;
+        }
+        if json.get("audit").is_some() {
+            sim.audit = json.parse("audit")?;
+        }
+        if json.get("history").is_some() {
+            sim.history = json
+                .array("history")?
+                .iter()
+                .map(|running| match running {
+                    Json::Array(pids) => pids
+                        .iter()
+                        .map(|pid| match pid {
+                            Json::Null => Ok(None),
+                            pid => Pid::from_json(pid).map(Some),
+                        })
+                        .collect(),
+                    _ => Err(JsonError::Field("history")),
+                })
+                .collect::<Result<_, _>>()?;
+        }
+        Ok(sim)
+    }
+}
+
+#[test]
+fn test_runs_are_reproducible() {
+    let run = || {
+        let pids = super::PidAllocator::default();
+        let mut sim = Simulation::new(
+            MlfqScheduler::new(&[1, 2, 4], 10),
+            ProcTable::new(Proc::new(&pids)),
+        );
+        for _ in 0..3 {
+            sim.spawn(Proc::new(&pids));
+        }
+        sim.run(25)
+    };
+    let first = run();
+    assert_eq!(first.len(), 25);
+    assert!(first.iter().all(Option::is_some));
+    assert_eq!(first, run());
+}
+
+#[test]
+fn test_step_advances_clock_and_stats() {
+    let pids = super::PidAllocator::default();
+    let mut sim = Simulation::new(
+        RoundRobinScheduler::new(1),
+        ProcTable::new(Proc::new(&pids)),
+    );
+    let a = sim.spawn(Proc::new(&pids));
+    let b = sim.spawn(Proc::new(&pids));
+
+    assert_eq!(sim.step(), Some(a));
+    assert_eq!(sim.step(), Some(b));
+    assert_eq!(sim.now(), 2);
+
+    sim.proc_mut(a).unwrap().exit(0);
+    assert_eq!(sim.run(2), vec![Some(b), Some(b)]);
+    assert_eq!(sim.now(), 4);
+    assert_eq!(sim.scheduler().current(), None);
+
+    let report = sim.stats().report();
+    assert_eq!(report.ticks, 4);
+    assert_eq!(report.context_switches, 1);
+    assert_eq!(report.procs[0].turnaround(), Some(2));
+}
+
+#[test]
+fn test_timeline_merges_consecutive_ticks() {
+    let pids = super::PidAllocator::default();
+    let mut sim = Simulation::new(
+        RoundRobinScheduler::new(2),
+        ProcTable::new(Proc::new(&pids)),
+    );
+    let a = sim.spawn(Proc::new(&pids));
+    let b = sim.spawn(Proc::new(&pids));
+    sim.run(3);
+    sim.proc_mut(b).unwrap().exit(0);
+    sim.run(3);
+
+    // b exits while holding the CPU, so a gets it back at tick 3 and keeps it through preemption
+    assert_eq!(sim.timeline(), vec![vec![(0..2, a), (2..3, b), (3..6, a)]]);
+}
+
+#[test]
+fn test_pinned_proc_never_runs_on_another_core() {
+    let pids = super::PidAllocator::default();
+    let cores = vec![RoundRobinScheduler::new(1), RoundRobinScheduler::new(1)];
+    let mut sim = Simulation::with_cores(cores, ProcTable::new(Proc::new(&pids)));
+    let a = sim.spawn(Proc::new(&pids));
+    let b = sim.spawn(Proc::new(&pids));
+    let c = sim.spawn(Proc::new(&pids));
+    assert_eq!([a, b, c].map(|pid| sim.core_of(pid)), [0, 1, 0].map(Some));
+    assert_eq!(sim.step_cores(), [Some(a), Some(b)]);
+
+    // Pinned to core 1, a moves there before the next tick and shares it with b from then on
+    assert_eq!(sim.set_affinity(a, 0b100), Err(AffinityError::EmptyMask));
+    sim.set_affinity(a, 0b10).unwrap();
+    for _ in 0..10 {
+        let ran = sim.step_cores();
+        assert_ne!(ran[0], Some(a));
+        assert_eq!(ran[0], Some(c));
+    }
+    assert_eq!(sim.core_of(a), Some(1));
+    let timeline = sim.timeline();
+    assert_eq!(timeline.len(), 2);
+    assert!(timeline[1].iter().any(|&(_, pid)| pid == a));
+}
+
+#[test]
+fn test_sleepers_wake_together_and_queue_in_order() {
+    let pids = super::PidAllocator::default();
+    let mut sim = Simulation::new(
+        RoundRobinScheduler::new(2),
+        ProcTable::new(Proc::new(&pids)),
+    );
+    let a = sim.spawn(Proc::new(&pids));
+    let b = sim.spawn(Proc::new(&pids));
+    let c = sim.spawn(Proc::new(&pids));
+
+    // a and b each put themselves to sleep after one tick; both timers fire at tick 4
+    assert_eq!(sim.step(), Some(a));
+    sim.proc_mut(a).unwrap().sleep_for(3).unwrap();
+    assert_eq!(sim.step(), Some(b));
+    sim.proc_mut(b).unwrap().sleep_for(2).unwrap();
+    assert_eq!(sim.run(2), vec![Some(c), Some(c)]);
+    assert_eq!(sim.table().get(a).unwrap().state(), super::State::Sleeping);
+
+    // Both sleepers jump the queue ahead of c, in the order their timers were armed
+    assert_eq!(
+        sim.run(5),
+        vec![Some(a), Some(a), Some(b), Some(b), Some(c)]
+    );
+}
+
+#[test]
+fn test_endless_sleep_does_not_overflow_the_clock() {
+    let pids = super::PidAllocator::default();
+    let mut sim = Simulation::new(
+        RoundRobinScheduler::new(5),
+        ProcTable::new(Proc::new(&pids)),
+    );
+    let a = sim.spawn(Proc::new(&pids));
+    let b = sim.spawn(Proc::new(&pids));
+    assert_eq!(sim.step(), Some(a));
+    sim.proc_mut(a).unwrap().sleep_for(u64::MAX).unwrap();
+
+    // a never wakes up again, and b carries on without it
+    assert_eq!(sim.run(3), [Some(b); 3]);
+    assert_eq!(sim.table().get(a).unwrap().state(), super::State::Sleeping);
+}
+
+#[test]
+fn test_io_blocks_until_device_completes() {
+    let pids = super::PidAllocator::default();
+    let mut sim = Simulation::new(
+        RoundRobinScheduler::new(4),
+        ProcTable::new(Proc::new(&pids)),
+    );
+    let disk = sim.add_device(Device::new());
+    let a = sim.spawn(Proc::new(&pids));
+    let b = sim.spawn(Proc::new(&pids));
+
+    assert_eq!(sim.step(), Some(a));
+    let req = sim.proc_mut(a).unwrap().start_io(2).unwrap();
+    sim.submit_io(disk, req);
+
+    assert_eq!(sim.step(), Some(b));
+    assert_eq!(
+        sim.table().get(a).unwrap().detailed_state(),
+        Some(super::DetailedState::Sleeping { start_time: 1 })
+    );
+    assert_eq!(sim.device(disk).unwrap().pending(), 1);
+
+    // Served during ticks 1 and 2, so a is ready again from tick 3 but waits for b's quantum to run out
+    assert_eq!(sim.run(4), vec![Some(b), Some(b), Some(b), Some(a)]);
+    assert_eq!(sim.device(disk).unwrap().completed(), 1);
+    assert_eq!(sim.table().get(a).unwrap().sleeping_since(), None);
+    // The tick that issued the read counts as system time
+    let times = sim.table().get(a).unwrap().cpu_times();
+    assert_eq!((times.user, times.system), (1, 1));
+}
+
+#[test]
+fn test_clock() {
+    let mut clock = Clock::new();
+    assert_eq!(clock.now(), 0);
+    assert_eq!(clock.tick(), 1);
+    assert_eq!(clock.now(), 1);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_saved_simulation_resumes_where_it_left_off() {
+    // A file from before the statistics, audit log and timeline were saved, and from when there was only ever one
+    // core, still resumes, with them empty
+    fn check_old_file() {
+        let pids = super::PidAllocator::default();
+        let mut sim = Simulation::new(
+            RoundRobinScheduler::new(2),
+            ProcTable::new(Proc::new(&pids)),
+        );
+        sim.spawn(Proc::new(&pids));
+        sim.run(3);
+        let path = std::env::temp_dir().join(format!("sim-old-{}.json", std::process::id()));
+        sim.save(&path).unwrap();
+        let mut old: Json = std::fs::read_to_string(&path).unwrap().parse().unwrap();
+        if let Json::Object(fields) = &mut old {
+            fields.retain(|(name, _)| {
+                !["uncollected", "stats", "audit", "history", "homes"].contains(&name.as_str())
+            });
+            for (name, value) in fields.iter_mut() {
+                if let ("cores", Json::Array(cores)) = (name.as_str(), &value) {
+                    (*name, *value) = ("scheduler".to_string(), cores[0].clone());
+                }
+            }
+        }
+        std::fs::write(&path, old.to_string()).unwrap();
+        let mut resumed = Simulation::<RoundRobinScheduler>::resume(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(resumed.now(), 3);
+        assert_eq!(resumed.stats().report().ticks, 0);
+        assert!(resumed.audit().records().is_empty());
+        assert_eq!(resumed.run(2), sim.run(2));
+    }
+
+    fn check<S: Scheduler + ToJson + FromJson>(cores: Vec<S>, name: &str) {
+        let pids = super::PidAllocator::default();
+        let mut sim = Simulation::with_cores(cores, ProcTable::new(Proc::new(&pids)));
+        let disk = sim.add_device(Device::new());
+        let procs: Vec<_> = (0..4).map(|_| sim.spawn(Proc::new(&pids))).collect();
+        // Leave sleepers, I/O in flight and a pending signal behind in the saved state. Whoever is still on the CPU
+        // after a tick blocks, half of them on a timer and half on the disk.
+        for _ in 0..5 {
+            for pid in sim.step_cores().into_iter().flatten() {
+                let proc = sim.proc_mut(pid).unwrap();
+                if pid == procs[0] || pid == procs[2] {
+                    proc.sleep_for(4).ok();
+                } else if let Ok(req) = proc.start_io(3) {
+                    sim.submit_io(disk, req);
+                }
+            }
+        }
+        sim.proc_mut(procs[3])
+            .unwrap()
+            .send_signal(super::Signal::Tstp);
+        // And lifecycle events the next tick has yet to log
+        let proc = sim.proc_mut(procs[1]).unwrap();
+        proc.send_signal(super::Signal::Term);
+        proc.deliver_signals();
+
+        let path = std::env::temp_dir().join(format!("sim-{}-{}.json", name, std::process::id()));
+        sim.save(&path).unwrap();
+        let mut resumed = Simulation::<S>::resume(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(resumed.now(), sim.now());
+        assert_eq!(
+            resumed.device(disk).unwrap().pending(),
+            sim.device(disk).unwrap().pending()
+        );
+        for _ in 0..20 {
+            assert_eq!(resumed.step_cores(), sim.step_cores(), "{}", name);
+        }
+        assert_eq!(resumed.stats().report(), sim.stats().report(), "{}", name);
+        assert_eq!(resumed.audit().records(), sim.audit().records(), "{}", name);
+        assert_eq!(resumed.timeline(), sim.timeline(), "{}", name);
+    }
+
+    check(vec![RoundRobinScheduler::new(2)], "rr");
+    check_old_file();
+    check(vec![MlfqScheduler::new(&[1, 2, 4], 6)], "mlfq");
+    check(vec![LotteryScheduler::new(11)], "lottery");
+    // Each core's queues and which processes are on it
+    let two = vec![RoundRobinScheduler::new(2), RoundRobinScheduler::new(3)];
+    check(two, "rr-2");
+    let mut cgroups = super::CgroupTree::new();
+    let batch = cgroups.create(super::ROOT_CGROUP, "batch").unwrap();
+    cgroups.set_cpu_shares(batch, 512).unwrap();
+    cgroups.attach(super::Pid(3), batch).unwrap();
+    check(vec![CgroupScheduler::new(cgroups)], "cgroup");
+}
@@ -0,0 +1,288 @@
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use super::audit::AuditRecord;
+use super::cpu::CoreLoad;
+use super::{Pid, Proc, ProcTable, Scheduler, Simulation, State};
+
+/// A copy of one process as it was when the snapshot was taken, like a read of `/proc/<pid>/stat`. It owns all of
+/// its data, so holding on to it borrows nothing from the table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcSnapshot {
+    pub pid: Pid,
+    pub name: String,
+    pub state: State,
+    pub parent: Option<Pid>, // None only for init, or for the top of a flattened subtree
+    pub children: Vec<Pid>,
+    pub depth: usize, // Levels below init, or below the top of a flattened subtree
+}
+
+impl ProcTable {
+    /// Copies out every process in the table, in ascending PID order.
+    pub fn snapshot(&self) -> Vec<ProcSnapshot> {
+        let mut snaps = Vec::new();
+        self.init().snapshot_into(None, 0, &mut snaps);
+        snaps.sort_by_key(|s| s.pid);
+        snaps
+    }
+}
+
+impl Proc {
+    /// Copies out this process and everything below it, in depth-first order (parents first, as `pstree` lists
+    /// them). Depths count from this process, at 0.
+    pub fn flatten(&self) -> Vec<ProcSnapshot> {
+        let mut snaps = Vec::new();
+        self.snapshot_into(None, 0, &mut snaps);
+        snaps
+    }
+
+    fn snapshot_into(&self, parent: Option<Pid>, depth: usize, snaps: &mut Vec<ProcSnapshot>) {
+        snaps.push(ProcSnapshot {
+            pid: self.pid(),
+            name: self.name().to_string(),
+            state: self.state(),
+            parent,
+            children: self.children.iter().map(Proc::pid).collect(),
+            depth,
+        });
+        for child in &self.children {
+            child.snapshot_into(Some(self.pid()), depth + 1, snaps);
+        }
+    }
+}
+
+/// A simulation as it stood after its last tick, copied out for anything that watches it run: the processes, who is
+/// waiting for the CPU, how busy each core has been and what happened lately.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimSnapshot {
+    pub now: u64,
+    pub procs: Vec<ProcSnapshot>, // Ascending PID order
+    pub queued: Vec<Pid>,         // As `Scheduler::queued` lists them, core by core
+    pub cores: Vec<CoreLoad>, // Over every tick so far; `running` is who had the core in the last one
+    pub events: Vec<AuditRecord>, // The latest audit records, oldest first
+}
+
+impl<S: Scheduler> Simulation<S> {
+    /// Takes a `SimSnapshot`, keeping the last `events` audit records.
+    pub fn snapshot(&self, events: usize) -> SimSnapshot {
+        let now = self.now();
+        let cores = self
+            .timeline()
+            .into_iter()
+            .enumerate()
+            .map(|(core, row)| {
+                let busy_ticks = row.iter().map(|(span, _)| span.end - span.start).sum();
+                CoreLoad {
+                    core,
+                    running: row
+                        .last()
+                        .filter(|(span, _)| span.end == now)
+                        .map(|&(_, pid)| pid),
+                    busy_ticks,
+                    idle_ticks: now - busy_ticks,
+                    dispatches: row.len() as u64,
+                }
+            })
+            .collect();
+        let records = self.audit().records();
+
+        SimSnapshot {
+            now,
+            procs: self.table().snapshot(),
+            queued: self
+                .schedulers()
+                .iter()
+                .flat_map(Scheduler::queued)
+                .collect(),
+            cores,
+            events: records[records.len().saturating_sub(events)..].to_vec(),
+        }
+    }
+}
+
+/// A read-only copy of a process tree, with every process behind an `Arc`: cloning it, or handing out any subtree, only
+/// bumps a reference count, and copies share all their data until one of them is changed. A change copies just the
+/// processes on the way down to it (copy-on-write), leaving the rest shared. Taking the snapshot walks the tree once;
+/// for an independent, live copy of the processes themselves, `clone` the Proc instead.
+#[derive(Debug, Clone)]
+pub struct CowSnapshot(Arc<CowNode>);
+
+#[derive(Debug, Clone)]
+struct CowNode {
+    pid: Pid,
+    name: Cow<'static, str>,
+    argv: Vec<String>,
+    state: State,
+    children: Vec<CowSnapshot>,
+}
+
+impl CowSnapshot {
+    pub fn pid(&self) -> Pid {
+        self.0.pid
+    }
+
+    pub fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    pub fn argv(&self) -> &[String] {
+        &self.0.argv
+    }
+
+    pub fn state(&self) -> State {
+        self.0.state
+    }
+
+    pub fn children(&self) -> &[CowSnapshot] {
+        &self.0.children
+    }
+
+    /// Changes the recorded state, first copying this process if another snapshot shares it.
+    pub fn set_state(&mut self, state: State) {
+        Arc::make_mut(&mut self.0).state = state;
+    }
+
+    /// The children, for changing one of them; this process is copied first if another snapshot shares it.
+    pub fn children_mut(&mut self) -> &mut [CowSnapshot] {
+        &mut Arc::make_mut(&mut self.0).children
+    }
+
+    /// Whether the two are the very same copy, not merely equal.
+    pub fn ptr_eq(&self, other: &CowSnapshot) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Proc {
+    /// A `CowSnapshot` of this process and everything below it.
+    pub fn cow_snapshot(&self) -> CowSnapshot {
+        CowSnapshot(Arc::new(CowNode {
+            pid: self.pid(),
+            name: self.name.clone(),
+            argv: self.argv.clone(),
+            state: self.state,
+            children: self.children.iter().map(Proc::cow_snapshot).collect(),
+        }))
+    }
+}
+
+#[test]
+fn test_snapshot_outlives_changes_to_the_table() {
+    let pids = super::PidAllocator::default();
+    let (mut table, cron, rsyslogd, bash) = super::table::init_tree(&pids);
+    let init = table.init().pid();
+
+    let snaps = table.snapshot();
+    table.get_mut(bash).unwrap().exit(0);
+    table.remove(cron);
+
+    let pids: Vec<_> = snaps.iter().map(|s| s.pid).collect();
+    assert_eq!(pids, vec![init, cron, rsyslogd, bash]);
+    assert_eq!(
+        snaps[2],
+        ProcSnapshot {
+            pid: rsyslogd,
+            name: "rsyslogd".to_string(),
+            state: State::Stopped,
+            parent: Some(init),
+            children: vec![bash],
+            depth: 1,
+        }
+    );
+    assert_eq!(snaps[0].parent, None);
+    assert_eq!(snaps[3].state, State::Stopped);
+}
+
+#[test]
+fn test_flatten_lists_the_subtree_in_walk_order() {
+    let pids = super::PidAllocator::default();
+    let (mut table, cron, rsyslogd, bash) = super::table::init_tree(&pids);
+    let init = table.init().pid();
+
+    let flat = table.init().flatten();
+    let order: Vec<_> = flat.iter().map(|s| (s.pid, s.depth)).collect();
+    let walk: Vec<_> = table.init().iter_dfs().map(Proc::pid).collect();
+    assert_eq!(order.iter().map(|&(pid, _)| pid).collect::<Vec<_>>(), walk);
+    assert!(order.contains(&(init, 0)) && order.contains(&(cron, 1)) && order.contains(&(bash, 2)));
+
+    // Owned copies: the tree can change underneath them
+    table.remove(rsyslogd);
+    assert_eq!(flat.len(), 4);
+    let sub = flat.iter().find(|s| s.pid == rsyslogd).unwrap();
+    assert_eq!(
+        (sub.parent, sub.depth, &sub.children[..]),
+        (Some(init), 1, &[bash][..])
+    );
+
+    // Below the top of a subtree, depths start again from 0
+    let flat = table.get(cron).unwrap().flatten();
+    assert_eq!(flat.len(), 1);
+    assert_eq!((flat[0].parent, flat[0].depth), (None, 0));
+}
+
+#[test]
+fn test_clone_copies_and_cow_snapshot_shares() {
+    let pids = super::PidAllocator::default();
+    let (table, cron, rsyslogd, bash) = super::table::init_tree(&pids);
+    let init = table.init();
+
+    // A deep copy is a new set of processes
+    let copy = init.clone();
+    assert_eq!(pids.in_use(), 8);
+    assert_ne!(copy, *init);
+    let names: Vec<_> = copy.iter_dfs().map(Proc::name).collect();
+    assert_eq!(names, ["init", "cron", "rsyslogd", "bash"]);
+    assert!(copy
+        .iter_dfs()
+        .all(|p| ![cron, rsyslogd, bash].contains(&p.pid())));
+    drop(copy);
+    assert_eq!(pids.in_use(), 4);
+
+    let snap = init.cow_snapshot();
+    let mut changed = snap.clone();
+    assert!(changed.ptr_eq(&snap));
+    assert_eq!(changed.children()[1].children()[0].pid(), bash);
+    changed.children_mut()[1].children_mut()[0].set_state(State::Running);
+
+    // Only the path down to bash was copied; the original snapshot is untouched and cron is still shared
+    assert_eq!(snap.children()[1].children()[0].state(), State::Stopped);
+    assert_eq!(changed.children()[1].children()[0].state(), State::Running);
+    assert!(!changed.ptr_eq(&snap));
+    assert!(!changed.children()[1].ptr_eq(&snap.children()[1]));
+    assert!(changed.children()[0].ptr_eq(&snap.children()[0]));
+    assert_eq!(pids.in_use(), 4);
+}
+
+#[test]
+fn test_sim_snapshot_sees_queue_cores_and_events() {
+    let pids = super::PidAllocator::default();
+    let mut sim = Simulation::new(
+        super::RoundRobinScheduler::new(2),
+        ProcTable::new(Proc::new(&pids)),
+    );
+    let a = sim.spawn(Proc::new(&pids));
+    let b = sim.spawn(Proc::new(&pids));
+    sim.run(3);
+    sim.proc_mut(b).unwrap().exit(0);
+    sim.run(2);
+
+    let snap = sim.snapshot(2);
+    assert_eq!(snap.now, 5);
+    assert_eq!(snap.procs.len(), 3);
+    assert_eq!(snap.procs[2].state, State::Zombie);
+    // a, a, b, then b has exited and a runs on alone, back in the queue once its quantum is up
+    assert_eq!(snap.queued, [a]);
+    assert_eq!(
+        snap.cores,
+        [CoreLoad {
+            core: 0,
+            running: Some(a),
+            busy_ticks: 5,
+            idle_ticks: 0,
+            dispatches: 3,
+        }]
+    );
+    assert_eq!(snap.events.len(), 2);
+    let latest: Vec<_> = snap.events.iter().map(|r| (r.tick, r.pid)).collect();
+    assert_eq!(latest, [(3, a), (4, a)]);
+}
@@ -0,0 +1,243 @@
+use std::collections::BTreeMap;
+
+#[cfg(feature = "serde")]
+use super::json::{self, FromJson, Json, JsonError, ToJson};
+use super::{Pid, ProcTable, State};
+
+/// What the collector knows about one process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcStats {
+    pub pid: Pid,
+    pub arrival: u64,
+    pub cpu_time: u64,
+    pub wait_time: u64,
+    pub finished_at: Option<u64>,
+}
+
+impl ProcStats {
+    /// Ticks from first being seen to exiting, once the process has exited.
+    pub fn turnaround(&self) -> Option<u64> {
+        self.finished_at.map(|end| end - self.arrival)
+    }
+}
+
+/// Summary returned by `SchedStats::report`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchedReport {
+    pub ticks: u64,
+    pub context_switches: u64,
+    pub busy_core_ticks: u64,
+    pub avg_wait: f64,
+    /// Average over processes that have exited, `None` if none has.
+    pub avg_turnaround: Option<f64>,
+    pub procs: Vec<ProcStats>,
+}
+
+/// Passive observer fed once per tick with what each core ran, so it works with any scheduler.
+#[derive(Debug, Default)]
+pub struct SchedStats {
+    ticks: u64,
+    context_switches: u64,
+    busy_core_ticks: u64,
+    last_on_core: Vec<Option<Pid>>,
+    procs: BTreeMap<Pid, ProcStats>,
+}
+
+impl SchedStats {
+    pub fn new() -> Self {
+        SchedStats::default()
+    }
+
+    /// Records tick `now`: `running[core]` is the PID that core ran (if any), `table` the processes in the system.
+    /// Init only adopts and reaps, it is never scheduled, so it is left out.
+    ///
+    /// Runnable processes that didn't get a core accumulate wait time, a core moving from one process straight to
+    /// another counts as a context switch, and a process is considered finished the first tick it is seen exited.
+    pub fn record_tick(&mut self, now: u64, running: &[Option<Pid>], table: &ProcTable) {
+        self.ticks += 1;
+        if self.last_on_core.len() < running.len() {
+            self.last_on_core.resize(running.len(), None);
+        }
+
+        for (core, &pid) in running.iter().enumerate() {
+            let previous = self.last_on_core[core];
+            if let (Some(prev), Some(next)) = (previous, pid) {
+                if prev != next {
+                    self.context_switches += 1;
+                }
+            }
+            self.last_on_core[core] = pid;
+        }
+
+        for proc in table.iter().filter(|p| p.pid() != table.init().pid()) {
+            let pid = proc.pid();
+            let stats = self.procs.entry(pid).or_insert_with(|| ProcStats {
+                pid,
+                arrival: now,
+                cpu_time: 0,
+                wait_time: 0,
+                finished_at: None,
+            });
+            if stats.finished_at.is_some() {
+                continue;
+            }
+
+            // A process with several threads can be on more than one core at once
+            let cores = running
+                .iter()
+                .flatten()
+                .filter(|&&tid| proc.owns_task(tid))
+                .count() as u64;
+            if proc.state().is_exited() {
+                stats.finished_at = Some(now);
+            } else if cores > 0 {
+                stats.cpu_time += cores;
+                self.busy_core_ticks += cores;
+            } else if proc.state() == State::Stopped && proc.is_runnable() {
+                stats.wait_time += 1;
+            }
+        }
+    }
+
+    /// Statistics for one process, if it has been observed.
+    pub fn proc_stats(&self, pid: Pid) -> Option<&ProcStats> {
+        self.procs.get(&pid)
+    }
+
+    pub fn report(&self) -> SchedReport {
+        let procs: Vec<ProcStats> = self.procs.values().cloned().collect();
+        let avg_wait = if procs.is_empty() {
+            0.0
+        } else {
+            procs.iter().map(|p| p.wait_time).sum::<u64>() as f64 / procs.len() as f64
+        };
+        let turnarounds: Vec<u64> = procs.iter().filter_map(ProcStats::turnaround).collect();
+        let avg_turnaround = if turnarounds.is_empty() {
+            None
+        } else {
+            Some(turnarounds.iter().sum::<u64>() as f64 / turnarounds.len() as f64)
+        };
+
+        SchedReport {
+            ticks: self.ticks,
+            context_switches: self.context_switches,
+            busy_core_ticks: self.busy_core_ticks,
+            avg_wait,
+            avg_turnaround,
+            procs,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ToJson for SchedStats {
+    fn to_json(&self) -> Json {
+        let procs = self
+            .procs
+            .values()
+            .map(|p| {
+                Json::object(vec![
+                    ("pid", p.pid.to_json()),
+                    ("arrival", Json::Int(p.arrival.into())),
+                    ("cpu_time", Json::Int(p.cpu_time.into())),
+                    ("wait_time", Json::Int(p.wait_time.into())),
+                    ("finished_at", json::opt_int(p.finished_at)),
+                ])
+            })
+            .collect();
+        Json::object(vec![
+            ("ticks", Json::Int(self.ticks.into())),
+            ("context_switches", Json::Int(self.context_switches.into())),
+            ("busy_core_ticks", Json::Int(self.busy_core_ticks.into())),
+            (
+                "last_on_core",
+                Json::Array(
+                    self.last_on_core
+                        .iter()
+                        .map(|pid| json::opt_int(pid.map(|pid| pid.0)))
+                        .collect(),
+                ),
+            ),
+            ("procs", Json::Array(procs)),
+        ])
+    }
+}
+
+#[cfg(feature = "serde")]
+impl FromJson for SchedStats {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        let mut procs = BTreeMap::new();
+        for p in json.array("procs")? {
+            let pid = p.parse("pid")?;
+            procs.insert(
+                pid,
+                ProcStats {
+                    pid,
+                    arrival: p.int("arrival")?,
+                    cpu_time: p.int("cpu_time")?,
+                    wait_time: p.int("wait_time")?,
+                    finished_at: p.opt_int("finished_at")?,
+                },
+            );
+        }
+        let last_on_core = json
+            .array("last_on_core")?
+            .iter()
+            .map(|pid| match pid {
+                Json::Null => Ok(None),
+                pid => Pid::from_json(pid).map(Some),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(SchedStats {
+            ticks: json.int("ticks")?,
+            context_switches: json.int("context_switches")?,
+            busy_core_ticks: json.int("busy_core_ticks")?,
+            last_on_core,
+            procs,
+        })
+    }
+}
+
+#[test]
+fn test_stats_follow_round_robin() {
+    let pids = super::PidAllocator::default();
+    let (mut table, procs) = super::table::flat_table(&pids, 2);
+    let mut rr = super::RoundRobinScheduler::new(2);
+    for &pid in &procs {
+        rr.admit(table.get_mut(pid).unwrap());
+    }
+
+    let mut stats = SchedStats::new();
+    for now in 0..6 {
+        let ran = rr.tick(&mut table);
+        stats.record_tick(now, &[ran], &table);
+    }
+    table.get_mut(procs[1]).unwrap().exit(0);
+    stats.record_tick(6, &[None], &table);
+
+    let report = stats.report();
+    assert_eq!(report.ticks, 7);
+    // 1,1 -> 2,2 -> 1,1
+    assert_eq!(report.context_switches, 2);
+    assert_eq!(report.busy_core_ticks, 6);
+    // init is never scheduled, so it gets no row
+    assert_eq!(report.procs.len(), 2);
+
+    let first = stats.proc_stats(procs[0]).unwrap();
+    // The first process is still waiting in the run queue during the final idle tick
+    assert_eq!((first.cpu_time, first.wait_time), (4, 3));
+    let second = stats.proc_stats(procs[1]).unwrap();
+    assert_eq!((second.cpu_time, second.wait_time), (2, 4));
+    assert_eq!(second.turnaround(), Some(6));
+
+    assert_eq!(report.avg_wait, 3.5);
+    assert_eq!(report.avg_turnaround, Some(6.0));
+}
+
+#[test]
+fn test_empty_report() {
+    let report = SchedStats::new().report();
+    assert_eq!(report.avg_wait, 0.0);
+    assert_eq!(report.avg_turnaround, None);
+    assert!(report.procs.is_empty());
+}
@@ -0,0 +1,227 @@
+use std::mem;
+
+use super::{Pid, Proc};
+
+// The process table is the tree rooted at init: every live process is init or one of its descendants.
+#[derive(Debug)]
+pub struct ProcTable {
+    init: Proc,
+}
+
+impl ProcTable {
+    /// Builds a table around `init`, which adopts any process whose parent goes away.
+    pub fn new(init: Proc) -> Self {
+        ProcTable { init }
+    }
+
+    pub fn init(&self) -> &Proc {
+        &self.init
+    }
+
+    pub fn init_mut(&mut self) -> &mut Proc {
+        &mut self.init
+    }
+
+    /// Moves the children of every exited (Zombie or Terminated) process under init, the way the kernel
+    /// re-parents orphans, and returns their PIDs. Init itself never gives its children away.
+    pub fn reparent_orphans(&mut self) -> Vec<Pid> {
+        let mut orphans = Vec::new();
+        for child in self.init.children.iter_mut() {
+            collect_orphans(child, &mut orphans);
+        }
+
+        let pids = orphans.iter().map(Proc::pid).collect();
+        self.init.children.extend(orphans);
+        pids
+    }
+
+    /// Adds `proc` (with any children it has) to the table as a child of init, and returns its PID.
+    pub fn insert(&mut self, proc: Proc) -> Pid {
+        let pid = proc.pid();
+        self.init.add_child(proc);
+        pid
+    }
+
+    /// Takes `pid` and its whole subtree out of the table. Init can't be removed.
+    pub fn remove(&mut self, pid: Pid) -> Option<Proc> {
+        self.init.take_descendant(pid)
+    }
+
+    pub fn get(&self, pid: Pid) -> Option<&Proc> {
+        self.init.find_pid(pid)
+    }
+
+    pub fn get_mut(&mut self, pid: Pid) -> Option<&mut Proc> {
+        self.init.find_pid_mut(pid)
+    }
+
+    /// Every process in the table, init included, in ascending PID order.
+    pub fn iter(&self) -> impl Iterator<Item = &Proc> {
+        let mut procs = self.procs();
+        procs.sort_by_key(|p| p.pid());
+        procs.into_iter()
+    }
+
+    /// Number of processes in the table, init included.
+    pub fn len(&self) -> usize {
+        self.procs().len()
+    }
+
+    /// Always false: a table at least holds init.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Every process in the table, parents before their children.
+    pub(super) fn procs(&self) -> Vec<&Proc> {
+        let mut procs = Vec::new();
+        self.init.collect_into(&mut procs);
+        procs
+    }
+
+    pub(super) fn for_each_mut(&mut self, f: &mut impl FnMut(&mut Proc)) {
+        self.init.for_each_mut(f);
+    }
+}
+
+// Recursive helpers over a Proc and its descendants
+impl Proc {
+    fn find_pid(&self, pid: Pid) -> Option<&Proc> {
+        if self.pid() == pid {
+            return Some(self);
+        }
+        self.children.iter().find_map(|c| c.find_pid(pid))
+    }
+
+    fn find_pid_mut(&mut self, pid: Pid) -> Option<&mut Proc> {
+        if self.pid() == pid {
+            return Some(self);
+        }
+        self.children.iter_mut().find_map(|c| c.find_pid_mut(pid))
+    }
+
+    fn take_descendant(&mut self, pid: Pid) -> Option<Proc> {
+        if let Some(idx) = self.children.iter().position(|c| c.pid() == pid) {
+            return Some(self.children.remove(idx));
+        }
+        self.children
+            .iter_mut()
+            .find_map(|c| c.take_descendant(pid))
+    }
+
+    fn collect_into<'a>(&'a self, procs: &mut Vec<&'a Proc>) {
+        procs.push(self);
+        for child in &self.children {
+            child.collect_into(procs);
+        }
+    }
+
+    fn for_each_mut(&mut self, f: &mut impl FnMut(&mut Proc)) {
+        f(self);
+        for child in self.children.iter_mut() {
+            child.for_each_mut(f);
+        }
+    }
+}
+
+// Depth-first, so orphans of an exited process nested under another exited process are found too.
+fn collect_orphans(proc: &mut Proc, orphans: &mut Vec<Proc>) {
+    for child in proc.children.iter_mut() {
+        collect_orphans(child, orphans);
+    }
+    if proc.state.is_exited() {
+        orphans.extend(mem::take(&mut proc.children));
+    }
+}
+
+#[cfg(test)]
+pub(super) fn init_tree(pids: &super::PidAllocator) -> (ProcTable, Pid, Pid, Pid) {
+    // init
+    //  |- cron
+    //  |- rsyslogd
+    //      |- bash
+    let mut init = Proc::new(pids);
+    init.exec("init", &[]);
+    let cron = init.fork();
+    init.child_mut(cron).unwrap().exec("cron", &[]);
+    let rsyslogd = init.fork();
+    let rsyslog = init.child_mut(rsyslogd).unwrap();
+    rsyslog.exec("rsyslogd", &[]);
+    let bash = rsyslog.fork();
+    rsyslog.child_mut(bash).unwrap().exec("bash", &[]);
+    (ProcTable::new(init), cron, rsyslogd, bash)
+}
+
+// A table of init plus `n` fresh processes directly under it, for tests that only need a flat set to schedule.
+#[cfg(test)]
+pub(super) fn flat_table(pids: &super::PidAllocator, n: usize) -> (ProcTable, Vec<Pid>) {
+    let mut table = ProcTable::new(Proc::new(pids));
+    let procs = (0..n).map(|_| table.insert(Proc::new(pids))).collect();
+    (table, procs)
+}
+
+#[test]
+fn test_insert_remove_and_pid_order() {
+    let pids = super::PidAllocator::default();
+    let (mut table, cron, rsyslogd, bash) = init_tree(&pids);
+    let init = table.init().pid();
+    let shell = table.get_mut(bash).unwrap().fork();
+
+    // Taking rsyslogd out takes its whole subtree with it
+    let rsyslog = table.remove(rsyslogd).unwrap();
+    assert!(table.get(bash).is_none());
+    assert_eq!(table.len(), 2);
+    assert!(table.remove(init).is_none());
+
+    // Re-inserting puts it under init, and iteration is by PID whatever the tree shape
+    let job = Proc::new(&pids);
+    let job = table.insert(job);
+    table.insert(rsyslog);
+    assert_eq!(table.get(shell).unwrap().pid(), shell);
+    let order: Vec<_> = table.iter().map(Proc::pid).collect();
+    assert_eq!(order, vec![init, cron, rsyslogd, bash, shell, job]);
+    table.get_mut(job).unwrap().exit(0);
+    assert_eq!(table.get(job).unwrap().state(), super::State::Zombie);
+}
+
+#[test]
+fn test_orphans_move_to_init() {
+    let pids = super::PidAllocator::default();
+    let (mut table, cron, rsyslogd, bash) = init_tree(&pids);
+
+    // Nobody has exited yet, so nobody is orphaned
+    assert!(table.reparent_orphans().is_empty());
+
+    table.init_mut().child_mut(rsyslogd).unwrap().exit(1);
+    assert_eq!(table.reparent_orphans(), vec![bash]);
+
+    let children: Vec<_> = table.init().children().iter().map(Proc::pid).collect();
+    assert_eq!(children, vec![cron, rsyslogd, bash]);
+    assert!(table
+        .init_mut()
+        .child_mut(rsyslogd)
+        .unwrap()
+        .children()
+        .is_empty());
+
+    // Reaping the zombie no longer takes bash down with it
+    table.init_mut().wait();
+    assert_eq!(table.init().children().len(), 2);
+    assert_eq!(pids.in_use(), 3);
+}
+
+#[test]
+fn test_nested_exits_all_reparent_to_init() {
+    let pids = super::PidAllocator::default();
+    let (mut table, _, rsyslogd, bash) = init_tree(&pids);
+    let rsyslog = table.init_mut().child_mut(rsyslogd).unwrap();
+    let bash_proc = rsyslog.child_mut(bash).unwrap();
+    let vim = bash_proc.fork();
+    bash_proc.exit(0);
+    rsyslog.exit(0);
+
+    let mut moved = table.reparent_orphans();
+    moved.sort();
+    assert_eq!(moved, vec![bash, vim]);
+    assert_eq!(table.init().children().len(), 4);
+}
@@ -0,0 +1,147 @@
+use super::{Pid, PidHandle, Proc, ProcTable, State};
+
+// Like Linux, a thread is just another schedulable task. Each has its own TID, drawn from the same space as PIDs, and
+// its own state, but everything else belongs to the process: PID (the TGID), memory and CPU time accounting. The
+// process's own state doubles as the state of its first thread, the leader, whose TID is the PID.
+
+/// A thread of a process, other than the leader.
+#[derive(Debug)]
+pub struct Task {
+    pub(super) tid: PidHandle,
+    pub(super) state: State,
+}
+
+impl Task {
+    pub fn tid(&self) -> Pid {
+        self.tid.pid()
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+}
+
+impl Proc {
+    /// Starts a new thread in this process and returns its TID. Like a forked child it starts out Stopped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the allocator has no free PID left.
+    pub fn spawn_thread(&mut self) -> Pid {
+        let task = Task {
+            tid: self.pid.allocator().alloc().expect("pid space exhausted"),
+            state: State::Stopped,
+        };
+        let tid = task.tid();
+        self.threads.push(task);
+        tid
+    }
+
+    /// Threads other than the leader, oldest first.
+    pub fn threads(&self) -> &[Task] {
+        &self.threads
+    }
+
+    /// Ends thread `tid`, releasing its TID. The leader can't exit this way, as that is the whole process exiting.
+    /// Returns false if there is no such thread.
+    pub fn exit_thread(&mut self, tid: Pid) -> bool {
+        let Some(idx) = self.threads.iter().position(|t| t.tid() == tid) else {
+            return false;
+        };
+        self.threads.remove(idx);
+        true
+    }
+
+    /// Whether `tid` is this process's leader or one of its threads.
+    pub fn owns_task(&self, tid: Pid) -> bool {
+        self.pid() == tid || self.threads.iter().any(|t| t.tid() == tid)
+    }
+
+    // TIDs of every task, leader first, for a scheduler taking in the whole process.
+    pub(super) fn task_ids(&self) -> Vec<Pid> {
+        let mut tids = vec![self.pid()];
+        tids.extend(self.threads.iter().map(Task::tid));
+        tids
+    }
+}
+
+// Schedulers run tasks, so they look them up by TID. A signal stop holds every thread of the process.
+impl ProcTable {
+    /// The process that task `tid` belongs to.
+    pub fn owner_of(&self, tid: Pid) -> Option<&Proc> {
+        self.procs().into_iter().find(|p| p.owns_task(tid))
+    }
+
+    pub fn owner_of_mut(&mut self, tid: Pid) -> Option<&mut Proc> {
+        let pid = self.owner_of(tid)?.pid();
+        self.get_mut(pid)
+    }
+
+    pub(super) fn task_state(&self, tid: Pid) -> Option<State> {
+        let proc = self.owner_of(tid)?;
+        if proc.pid() == tid {
+            return Some(proc.state());
+        }
+        proc.threads
+            .iter()
+            .find(|t| t.tid() == tid)
+            .map(Task::state)
+    }
+
+    pub(super) fn task_runnable(&self, tid: Pid) -> bool {
+        let Some(proc) = self.owner_of(tid) else {
+            return false;
+        };
+        if proc.pid() == tid {
+            return proc.is_runnable();
+        }
+        proc.stop.is_none()
+            && !proc.state().is_exited()
+            && matches!(self.task_state(tid), Some(State::Running | State::Stopped))
+    }
+
+    pub(super) fn set_task_state(&mut self, tid: Pid, state: State) {
+        let Some(proc) = self.owner_of_mut(tid) else {
+            return;
+        };
+        if proc.pid() == tid {
+            proc.set_state(state);
+        } else if let Some(task) = proc.threads.iter_mut().find(|t| t.tid() == tid) {
+            task.state = state;
+        }
+    }
+}
+
+#[test]
+fn test_threads_share_the_cpu_and_the_process_accounting() {
+    use super::{RoundRobinScheduler, Signal, Simulation};
+
+    let pids = super::PidAllocator::default();
+    let (mut table, procs) = super::table::flat_table(&pids, 2);
+    let (server, other) = (procs[0], procs[1]);
+    let worker = table.get_mut(server).unwrap().spawn_thread();
+    assert_eq!(table.owner_of(worker).unwrap().pid(), server);
+    assert!(table.get(worker).is_none());
+
+    let mut sim = Simulation::new(RoundRobinScheduler::new(1), table);
+    sim.admit(server);
+    sim.admit(other);
+    assert_eq!(
+        sim.run(6),
+        [server, worker, other, server, worker, other].map(Some)
+    );
+    assert_eq!(sim.table().get(server).unwrap().cpu_times().own(), 4);
+    assert_eq!(sim.stats().proc_stats(server).unwrap().cpu_time, 4);
+
+    // Stopping the process holds all of its threads; an exited thread leaves the rotation for good
+    let proc = sim.proc_mut(server).unwrap();
+    proc.send_signal(Signal::Stop);
+    proc.deliver_signals();
+    assert_eq!(sim.run(2), [Some(other), Some(other)]);
+    let proc = sim.proc_mut(server).unwrap();
+    proc.send_signal(Signal::Cont);
+    proc.deliver_signals();
+    assert!(proc.exit_thread(worker));
+    assert!(!proc.exit_thread(server));
+    assert_eq!(sim.run(3), [server, other, server].map(Some));
+}
@@ -0,0 +1,186 @@
+#[cfg(feature = "serde")]
+use super::json::{FromJson, Json, JsonError, ToJson};
+use super::{Pid, Proc, State, TransitionError};
+
+// Hashed timer wheel: a timer due at tick `t` lives in slot `t % slots`, so arming a timer and checking the current
+// slot are both cheap no matter how many timers are pending. Timers more than one turn of the wheel away share a slot
+// with nearer ones and are simply skipped until their own turn comes round.
+
+#[derive(Debug)]
+pub struct TimerWheel {
+    slots: Vec<Vec<(u64, Pid)>>,
+}
+
+impl TimerWheel {
+    /// # Panics
+    ///
+    /// Panics if `slots` is zero.
+    pub fn new(slots: usize) -> Self {
+        assert!(slots > 0, "a timer wheel needs at least one slot");
+        TimerWheel {
+            slots: vec![Vec::new(); slots],
+        }
+    }
+
+    /// Arms a timer that wakes `pid` at tick `deadline`.
+    pub fn schedule(&mut self, deadline: u64, pid: Pid) {
+        let slot = self.slot(deadline);
+        self.slots[slot].push((deadline, pid));
+    }
+
+    /// Disarms every timer for `pid`, returning how many there were.
+    pub fn cancel(&mut self, pid: Pid) -> usize {
+        let before = self.len();
+        for slot in self.slots.iter_mut() {
+            slot.retain(|&(_, p)| p != pid);
+        }
+        before - self.len()
+    }
+
+    /// Removes and returns the timers due at tick `now`, in the order they were armed. Meant to be called once per
+    /// tick without skipping any.
+    pub fn expire(&mut self, now: u64) -> Vec<Pid> {
+        let slot = self.slot(now);
+        let (due, later) = self.slots[slot]
+            .drain(..)
+            .partition(|&(deadline, _)| deadline <= now);
+        self.slots[slot] = later;
+        due.into_iter().map(|(_, pid)| pid).collect()
+    }
+
+    /// Number of armed timers.
+    pub fn len(&self) -> usize {
+        self.slots.iter().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn slot(&self, tick: u64) -> usize {
+        (tick % self.slots.len() as u64) as usize
+    }
+}
+
+impl Default for TimerWheel {
+    fn default() -> Self {
+        TimerWheel::new(64)
+    }
+}
+
+impl Proc {
+    /// Blocks a Running process for `ticks` ticks. The simulation arms a timer for it on its next tick, and when that
+    /// fires the process is woken straight back to Running.
+    pub fn sleep_for(&mut self, ticks: u64) -> Result<(), TransitionError> {
+        if self.state != State::Running {
+            return Err(TransitionError {
+                from: self.state,
+                to: State::Sleeping,
+            });
+        }
+        self.set_state(State::Sleeping);
+        self.charge_syscall();
+        self.sleep = Some(ticks);
+        Ok(())
+    }
+
+    // Hands over a sleep requested since the last tick, so a timer can be armed for it.
+    pub(super) fn take_sleep_request(&mut self) -> Option<u64> {
+        self.sleep.take()
+    }
+
+    /// Ends a sleep, returning whether the process was actually asleep. A process stopped by a signal mid-sleep
+    /// stays stopped, but will resume as ready rather than blocked.
+    pub(super) fn wake(&mut self) -> bool {
+        self.slept_at = None;
+        match &mut self.stop {
+            Some((_, resume)) if *resume == State::Sleeping => {
+                *resume = State::Stopped;
+                true
+            }
+            _ if self.state == State::Sleeping => {
+                self.set_state(State::Running);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+// Saved with its size as well as its timers: a timer's slot depends on both.
+#[cfg(feature = "serde")]
+impl ToJson for TimerWheel {
+    fn to_json(&self) -> Json {
+        let timers = self
+            .slots
+            .iter()
+            .flatten()
+            .map(|&(deadline, pid)| {
+                Json::object(vec![
+                    ("deadline", Json::Int(deadline.into())),
+                    ("pid", pid.to_json()),
+                ])
+            })
+            .collect();
+        Json::object(vec![
+            ("slots", Json::Int(self.slots.len() as i128)),
+            ("timers", Json::Array(timers)),
+        ])
+    }
+}
+
+#[cfg(feature = "serde")]
+impl FromJson for TimerWheel {
+    fn from_json(json: &Json) -> Result<Self, JsonError> {
+        let slots: usize = json.int("slots")?;
+        if slots == 0 {
+            return Err(JsonError::Field("slots"));
+        }
+        let mut wheel = TimerWheel::new(slots);
+        for timer in json.array("timers")? {
+            wheel.schedule(timer.int("deadline")?, timer.parse("pid")?);
+        }
+        Ok(wheel)
+    }
+}
+
+#[test]
+fn test_wheel_fires_simultaneous_timers_in_order() {
+    let mut wheel = TimerWheel::new(4);
+    wheel.schedule(3, Pid(1));
+    wheel.schedule(7, Pid(2));
+    wheel.schedule(3, Pid(3));
+    wheel.schedule(3, Pid(4));
+    assert_eq!(wheel.len(), 4);
+
+    assert!(wheel.expire(2).is_empty());
+    assert_eq!(wheel.expire(3), vec![Pid(1), Pid(3), Pid(4)]);
+    // Pid 2 shares slot 3 but is one full turn further out
+    assert_eq!(wheel.len(), 1);
+    assert_eq!(wheel.expire(7), vec![Pid(2)]);
+    assert!(wheel.is_empty());
+
+    wheel.schedule(9, Pid(5));
+    assert_eq!(wheel.cancel(Pid(5)), 1);
+    assert!(wheel.expire(9).is_empty());
+}
+
+#[test]
+fn test_sleep_needs_a_running_proc() {
+    let mut proc = Proc::new(&super::PidAllocator::default());
+    assert_eq!(
+        proc.sleep_for(3),
+        Err(TransitionError {
+            from: State::Stopped,
+            to: State::Sleeping,
+        })
+    );
+
+    proc.set_state(State::Running);
+    proc.sleep_for(3).unwrap();
+    assert!(!proc.is_runnable());
+    assert_eq!(proc.take_sleep_request(), Some(3));
+    assert!(proc.wake());
+    assert_eq!(proc.state(), State::Running);
+    assert!(!proc.wake());
+}
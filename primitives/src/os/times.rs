@@ -0,0 +1,86 @@
+use super::Proc;
+
+/// CPU ticks consumed, split like the `struct tms` filled in by `times(2)`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CpuTimes {
+    pub user: u64,
+    pub system: u64,
+    // Totals of children that have been reaped with `wait`, including their own reaped children
+    pub children_user: u64,
+    pub children_system: u64,
+}
+
+impl CpuTimes {
+    /// Ticks used by the process itself.
+    pub fn own(&self) -> u64 {
+        self.user + self.system
+    }
+
+    /// Ticks used by the process and all of its reaped descendants.
+    pub fn total(&self) -> u64 {
+        self.own() + self.children_user + self.children_system
+    }
+}
+
+impl Proc {
+    pub fn cpu_times(&self) -> CpuTimes {
+        self.times
+    }
+
+    // One tick on a CPU running the program's own code.
+    pub(super) fn charge_user_tick(&mut self) {
+        self.times.user += 1;
+    }
+
+    // A blocking system call (sleep, I/O) is issued at the end of the tick the process just ran, so that tick was
+    // spent in the kernel rather than in user code.
+    pub(super) fn charge_syscall(&mut self) {
+        if self.times.user > 0 {
+            self.times.user -= 1;
+            self.times.system += 1;
+        }
+    }
+
+    // Called by `wait` as `child` is reaped, so its usage is not lost along with it.
+    pub(super) fn collect_child_times(&mut self, child: &Proc) {
+        self.times.children_user += child.times.user + child.times.children_user;
+        self.times.children_system += child.times.system + child.times.children_system;
+    }
+}
+
+#[test]
+fn test_reaped_children_roll_up_into_parent() {
+    let pids = super::PidAllocator::default();
+    let mut init = Proc::new(&pids);
+    let shell = init.fork();
+    let job = init.child_mut(shell).unwrap().fork();
+
+    {
+        let shell = init.child_mut(shell).unwrap();
+        let job = shell.child_mut(job).unwrap();
+        for _ in 0..3 {
+            job.charge_user_tick();
+        }
+        job.charge_syscall();
+        job.exit(0);
+        shell.wait().unwrap();
+        shell.charge_user_tick();
+        assert_eq!(
+            shell.cpu_times(),
+            CpuTimes {
+                user: 1,
+                system: 0,
+                children_user: 2,
+                children_system: 1,
+            }
+        );
+        shell.exit(0);
+    }
+
+    // Running children don't count until they are reaped
+    assert_eq!(init.cpu_times().total(), 0);
+    init.wait().unwrap();
+    assert_eq!(init.cpu_times().children_user, 3);
+    assert_eq!(init.cpu_times().total(), 4);
+    assert_eq!(init.cpu_times().own(), 0);
+}
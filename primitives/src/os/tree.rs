@@ -0,0 +1,484 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, VecDeque};
+use std::error::Error;
+use std::fmt;
+
+use super::{AuditEvent, Children, Pid, Proc, State, TransitionError};
+
+// Working with a process tree as a whole: walking it, searching it by name, measuring it and moving subtrees around.
+// These started out on the toy tree in `examples/moves.rs`, which now builds its tree out of this Proc instead.
+
+// Draws the tree the way `pstree -p` does, one process per line with its PID and state:
+//
+// init(1) Running
+// ├── cron(3) Sleeping
+// └── rsyslogd(2) Running
+//     └── bash(4) Running
+//
+// The alternate form, `{:#}`, adds each command line as `pstree -a` would: `bash(4) Running -l`.
+impl fmt::Display for Proc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_line(f)?;
+        self.fmt_children(f, "")
+    }
+}
+
+impl Proc {
+    fn fmt_line(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = if self.name.is_empty() {
+            "?"
+        } else {
+            &self.name
+        };
+        write!(f, "{}({}) {:?}", name, self.pid(), self.state)?;
+        if f.alternate() {
+            for arg in &self.argv {
+                write!(f, " {}", arg)?;
+            }
+        }
+        writeln!(f)
+    }
+
+    // `prefix` carries the vertical lines of the ancestors that still have siblings to come.
+    fn fmt_children(&self, f: &mut fmt::Formatter<'_>, prefix: &str) -> fmt::Result {
+        for (i, child) in self.children.iter().enumerate() {
+            let last = i + 1 == self.children.len();
+            let (branch, indent) = if last {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+            write!(f, "{}{}", prefix, branch)?;
+            child.fmt_line(f)?;
+            child.fmt_children(f, &format!("{}{}", prefix, indent))?;
+        }
+        Ok(())
+    }
+}
+
+// Walking the tree. Both orders visit a parent before its children: depth-first goes down each branch in turn, like
+// `pstree`, while breadth-first finishes each generation before starting on the next.
+impl Proc {
+    pub fn iter_dfs(&self) -> Dfs<'_> {
+        Dfs { stack: vec![self] }
+    }
+
+    pub fn iter_bfs(&self) -> Bfs<'_> {
+        Bfs {
+            queue: VecDeque::from([self]),
+        }
+    }
+
+    pub fn iter_dfs_mut(&mut self) -> DfsMut<'_> {
+        DfsMut { stack: vec![self] }
+    }
+
+    pub fn iter_bfs_mut(&mut self) -> BfsMut<'_> {
+        BfsMut {
+            queue: VecDeque::from([self]),
+        }
+    }
+}
+
+// Searching builds on the walk, so a match nearer the top of the tree (or earlier among siblings) is found first.
+impl Proc {
+    /// The first process in the subtree, this one included, named `name`.
+    pub fn find(&self, name: &str) -> Option<&Proc> {
+        self.iter_dfs().find(|p| p.name == name)
+    }
+
+    /// As `find`, for changing the process.
+    pub fn find_mut(&mut self, name: &str) -> Option<&mut Proc> {
+        if self.name == name {
+            return Some(self);
+        }
+        self.children.iter_mut().find_map(|c| c.find_mut(name))
+    }
+
+    /// Every process in the subtree, this one included, that `pred` accepts, in depth-first order.
+    pub fn filter(&self, pred: impl Fn(&Proc) -> bool) -> Vec<&Proc> {
+        self.iter_dfs().filter(|p| pred(p)).collect()
+    }
+}
+
+// Shape of the tree, for asserting on in tests rather than reading it off `dbg!` output.
+impl Proc {
+    /// Number of levels in the subtree: 1 for a process without children, 3 for init -> rsyslogd -> bash.
+    pub fn depth(&self) -> usize {
+        1 + self.children.iter().map(Proc::depth).max().unwrap_or(0)
+    }
+
+    /// Number of processes in the subtree, this one included.
+    pub fn subtree_size(&self) -> usize {
+        self.iter_dfs().count()
+    }
+
+    /// How many processes in the subtree are in each state. States nobody is in are left out.
+    pub fn count_by_state(&self) -> BTreeMap<State, usize> {
+        let mut counts = BTreeMap::new();
+        for proc in self.iter_dfs() {
+            *counts.entry(proc.state).or_insert(0) += 1;
+        }
+        counts
+    }
+}
+
+/// Why `Proc::reparent` refused a move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReparentError {
+    NotFound, // Either process isn't in the tree
+    Cycle,    // The new parent is the child itself or below it
+}
+
+impl fmt::Display for ReparentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReparentError::NotFound => write!(f, "no such process in the tree"),
+            ReparentError::Cycle => write!(f, "a process can't be moved under itself"),
+        }
+    }
+}
+
+impl Error for ReparentError {}
+
+// Changing the tree after it's built. Processes are found by name, in the same order as `find`, and every move is a
+// real move: a removed process is handed back to the caller, who owns it (and its PID) from then on.
+impl Proc {
+    /// Adopts an already built process. Unlike `fork`, nothing is copied from the parent.
+    pub fn add_child(&mut self, child: Proc) {
+        self.children.push(child);
+    }
+
+    /// Takes the first process named `name` out of the subtree below this one, along with its own children.
+    pub fn remove_child(&mut self, name: &str) -> Option<Proc> {
+        for i in 0..self.children.len() {
+            if self.children[i].name == name {
+                return Some(self.children.remove(i));
+            }
+            if let Some(proc) = self.children[i].remove_child(name) {
+                return Some(proc);
+            }
+        }
+        None
+    }
+
+    /// Moves `child`, with its subtree, under `new_parent`. Both are looked up in this process's subtree. Refused if
+    /// `new_parent` is `child` or one of its descendants, as the subtree would then have to contain itself.
+    pub fn reparent(&mut self, child: &str, new_parent: &str) -> Result<(), ReparentError> {
+        let moving = self.find(child).ok_or(ReparentError::NotFound)?;
+        if moving.find(new_parent).is_some() {
+            return Err(ReparentError::Cycle);
+        }
+        if self.find(new_parent).is_none() {
+            return Err(ReparentError::NotFound);
+        }
+        // `child` isn't this process: that would have made every process in the tree its descendant
+        let moving = self.remove_child(child).expect("found above");
+        self.find_mut(new_parent)
+            .expect("found above and not under the moved subtree")
+            .add_child(moving);
+        Ok(())
+    }
+}
+
+pub struct Dfs<'a> {
+    stack: Vec<&'a Proc>,
+}
+
+impl<'a> Iterator for Dfs<'a> {
+    type Item = &'a Proc;
+
+    fn next(&mut self) -> Option<&'a Proc> {
+        let proc = self.stack.pop()?;
+        // Reversed, so the first child comes off the stack first
+        self.stack.extend(proc.children.iter().rev());
+        Some(proc)
+    }
+}
+
+pub struct Bfs<'a> {
+    queue: VecDeque<&'a Proc>,
+}
+
+impl<'a> Iterator for Bfs<'a> {
+    type Item = &'a Proc;
+
+    fn next(&mut self) -> Option<&'a Proc> {
+        let proc = self.queue.pop_front()?;
+        self.queue.extend(proc.children.iter());
+        Some(proc)
+    }
+}
+
+/// One process handed out by `iter_dfs_mut` or `iter_bfs_mut`.
+///
+/// A `&mut Proc` for a parent would also reach its children, which the iterator hands out later, so two mutable
+/// references to the same child would exist at once. Splitting the borrow fixes that: the caller gets the parent's
+/// name and state and the iterator keeps its children.
+pub struct ProcMut<'a> {
+    pid: Pid,
+    name: &'a mut Cow<'static, str>,
+    argv: &'a mut Vec<String>,
+    state: &'a mut State,
+    audit: &'a mut Vec<(Pid, AuditEvent)>,
+}
+
+impl<'a> ProcMut<'a> {
+    fn split(proc: &'a mut Proc) -> (ProcMut<'a>, &'a mut Children) {
+        let pid = proc.pid();
+        let Proc {
+            name,
+            argv,
+            state,
+            audit,
+            children,
+            ..
+        } = proc;
+        let proc = ProcMut {
+            pid,
+            name,
+            argv,
+            state,
+            audit,
+        };
+        (proc, children)
+    }
+
+    pub fn pid(&self) -> Pid {
+        self.pid
+    }
+
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    pub fn state(&self) -> State {
+        *self.state
+    }
+
+    /// As `Proc::exec`.
+    pub fn exec(&mut self, name: impl Into<Cow<'static, str>>, args: &[&str]) {
+        *self.name = name.into();
+        *self.argv = args.iter().map(|arg| arg.to_string()).collect();
+    }
+
+    /// As `Proc::set_state`, logging the change the same way.
+    pub fn set_state(&mut self, new_state: State) {
+        if *self.state != new_state {
+            let event = AuditEvent::StateChange {
+                from: *self.state,
+                to: new_state,
+            };
+            self.audit.push((self.pid, event));
+            *self.state = new_state;
+        }
+    }
+
+    /// As `Proc::try_set_state`.
+    pub fn try_set_state(&mut self, new_state: State) -> Result<(), TransitionError> {
+        if !self.state.can_transition_to(new_state) {
+            return Err(TransitionError {
+                from: *self.state,
+                to: new_state,
+            });
+        }
+        self.set_state(new_state);
+        Ok(())
+    }
+}
+
+pub struct DfsMut<'a> {
+    stack: Vec<&'a mut Proc>,
+}
+
+impl<'a> Iterator for DfsMut<'a> {
+    type Item = ProcMut<'a>;
+
+    fn next(&mut self) -> Option<ProcMut<'a>> {
+        let (proc, children) = ProcMut::split(self.stack.pop()?);
+        self.stack.extend(children.iter_mut().rev());
+        Some(proc)
+    }
+}
+
+pub struct BfsMut<'a> {
+    queue: VecDeque<&'a mut Proc>,
+}
+
+impl<'a> Iterator for BfsMut<'a> {
+    type Item = ProcMut<'a>;
+
+    fn next(&mut self) -> Option<ProcMut<'a>> {
+        let (proc, children) = ProcMut::split(self.queue.pop_front()?);
+        self.queue.extend(children.iter_mut());
+        Some(proc)
+    }
+}
+
+// init -> {cron, rsyslogd -> bash}, the tree from `examples/`.
+#[cfg(test)]
+fn init_tree(pids: &super::PidAllocator) -> Proc {
+    let proc = |name, state, children: Vec<Proc>| {
+        Proc::builder(pids)
+            .name(name)
+            .state(state)
+            .children(children)
+            .build()
+            .unwrap()
+    };
+    let bash = proc("bash", State::Running, Vec::new());
+    let rsyslogd = proc("rsyslogd", State::Running, vec![bash]);
+    let cron = proc("cron", State::Sleeping, Vec::new());
+    proc("init", State::Running, vec![cron, rsyslogd])
+}
+
+#[test]
+fn test_traversal_orders() {
+    let pids = super::PidAllocator::default();
+    let init = init_tree(&pids);
+    let dfs: Vec<_> = init.iter_dfs().map(|p| p.name()).collect();
+    assert_eq!(dfs, ["init", "cron", "rsyslogd", "bash"]);
+    let mut extended = init_tree(&pids);
+    extended
+        .find_mut("cron")
+        .unwrap()
+        .add_child(Proc::builder(&pids).name("backup").build().unwrap());
+    let bfs: Vec<_> = extended.iter_bfs().map(|p| p.name()).collect();
+    assert_eq!(bfs, ["init", "cron", "rsyslogd", "backup", "bash"]);
+
+    let mut init = init_tree(&pids);
+    for mut proc in init.iter_bfs_mut() {
+        proc.set_state(State::Zombie);
+    }
+    assert!(init.iter_dfs().all(|p| p.state() == State::Zombie));
+    for mut proc in init.iter_dfs_mut().filter(|p| p.name() == "bash") {
+        proc.exec("zsh", &["-l"]);
+        assert_eq!(
+            proc.try_set_state(State::Running),
+            Err(TransitionError {
+                from: State::Zombie,
+                to: State::Running,
+            })
+        );
+    }
+    let zsh = init.iter_bfs().last().unwrap();
+    assert_eq!((zsh.name(), zsh.argv()), ("zsh", &["-l".to_string()][..]));
+    // Changes made through the iterator are logged like any other
+    assert!(zsh.audit.contains(&(
+        zsh.pid(),
+        AuditEvent::StateChange {
+            from: State::Running,
+            to: State::Zombie,
+        }
+    )));
+}
+
+#[test]
+fn test_search_the_whole_subtree() {
+    let init = init_tree(&super::PidAllocator::default());
+
+    assert_eq!(init.find("bash").unwrap().state(), State::Running);
+    assert_eq!(init.find("init").unwrap().children().len(), 2);
+    assert!(init.find("sshd").is_none());
+    // Only below the starting point
+    assert!(init.find("rsyslogd").unwrap().find("cron").is_none());
+
+    let names: Vec<_> = init
+        .filter(|p| p.state() == State::Running)
+        .iter()
+        .map(|p| p.name())
+        .collect();
+    assert_eq!(names, ["init", "rsyslogd", "bash"]);
+    assert!(init.filter(|p| p.children().len() > 2).is_empty());
+}
+
+#[test]
+fn test_display_draws_the_tree() {
+    let pids = super::PidAllocator::default();
+    let mut init = init_tree(&pids);
+    let logrotate = Proc::builder(&pids)
+        .pid(Pid(9))
+        .name("logrotate")
+        .build()
+        .unwrap();
+    init.find_mut("cron").unwrap().add_child(logrotate);
+    assert_eq!(
+        init.to_string(),
+        "init(4) Running\n\
+         ├── cron(3) Sleeping\n\
+         │   └── logrotate(9) Stopped\n\
+         └── rsyslogd(2) Running\n\
+         \x20   └── bash(1) Running\n"
+    );
+    assert_eq!(Proc::new(&pids).to_string(), "?(5) Stopped\n");
+
+    init.find_mut("bash").unwrap().set_argv(&["-l", "-i"]);
+    let with_args = format!("{:#}", init.find("rsyslogd").unwrap());
+    assert_eq!(
+        with_args,
+        "rsyslogd(2) Running\n└── bash(1) Running -l -i\n"
+    );
+}
+
+#[test]
+fn test_tree_shape() {
+    let init = init_tree(&super::PidAllocator::default());
+
+    assert_eq!(init.depth(), 3);
+    assert_eq!(init.subtree_size(), 4);
+    assert_eq!(init.find("cron").unwrap().depth(), 1);
+    assert_eq!(init.find("rsyslogd").unwrap().subtree_size(), 2);
+    assert_eq!(
+        init.count_by_state(),
+        BTreeMap::from([(State::Running, 3), (State::Sleeping, 1)])
+    );
+}
+
+#[test]
+fn test_mutate_the_tree() {
+    let pids = super::PidAllocator::default();
+    let mut init = init_tree(&pids);
+
+    init.find_mut("cron")
+        .unwrap()
+        .add_child(Proc::builder(&pids).name("logrotate").build().unwrap());
+    assert_eq!(init.find("cron").unwrap().children()[0].name(), "logrotate");
+
+    // Removing takes the whole subtree
+    let rsyslogd = init.remove_child("rsyslogd").unwrap();
+    assert_eq!(rsyslogd.children()[0].name(), "bash");
+    assert!(init.find("bash").is_none());
+    assert!(init.remove_child("rsyslogd").is_none());
+    // Only below this process
+    assert!(init.remove_child("init").is_none());
+    init.add_child(rsyslogd);
+
+    init.reparent("rsyslogd", "logrotate").unwrap();
+    let dfs: Vec<_> = init.iter_dfs().map(|p| p.name()).collect();
+    assert_eq!(dfs, ["init", "cron", "logrotate", "rsyslogd", "bash"]);
+    assert_eq!(init.depth(), 5);
+
+    assert_eq!(init.reparent("cron", "bash"), Err(ReparentError::Cycle));
+    assert_eq!(init.reparent("cron", "cron"), Err(ReparentError::Cycle));
+    assert_eq!(init.reparent("init", "cron"), Err(ReparentError::Cycle));
+    assert_eq!(init.reparent("sshd", "cron"), Err(ReparentError::NotFound));
+    assert_eq!(init.reparent("bash", "sshd"), Err(ReparentError::NotFound));
+    // Failed moves leave the tree alone
+    assert_eq!(init.iter_dfs().count(), 5);
+    assert_eq!(
+        init.find("logrotate").unwrap().children()[0].name(),
+        "rsyslogd"
+    );
+}
+
+#[test]
+fn test_first_two_children_stay_inline() {
+    let pids = super::PidAllocator::default();
+    let mut parent = Proc::new(&pids);
+    parent.fork();
+    parent.add_child(Proc::new(&pids));
+    assert!(!parent.children().spilled());
+    parent.fork();
+    assert!(parent.children().spilled());
+    assert_eq!(parent.children().len(), 3);
+}
@@ -0,0 +1,267 @@
+use std::ops::RangeInclusive;
+
+use super::rng::Rng;
+use super::sim::{Scheduler, Simulation};
+use super::{Pid, Proc, State};
+
+// Schedulers are easiest to compare on the same made-up load. A workload is a stream of jobs, each arriving at some
+// tick and needing some CPU; I/O-bound jobs also stop every tick or two to wait on I/O, while CPU-bound ones run until
+// they are done. The stream comes from a seeded `Rng`, so the same seed always makes the same workload.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobKind {
+    CpuBound,
+    IoBound,
+}
+
+/// One process to start during a run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Job {
+    pub arrival: u64, // Ticks after the start of the run
+    pub kind: JobKind,
+    pub cpu: u64,   // Ticks of CPU it needs in total
+    pub burst: u64, // Ticks it runs between waits on I/O
+    pub io: u64,    // Ticks each wait lasts, 0 for CPU-bound jobs
+}
+
+/// Shape of the workload `WorkloadGen` makes up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkloadConfig {
+    /// Percentage of jobs that are I/O-bound, 0 to 100.
+    pub io_bound_percent: u64,
+    /// Ticks between one arrival and the next.
+    pub interarrival: RangeInclusive<u64>,
+    /// Total CPU each job needs.
+    pub cpu: RangeInclusive<u64>,
+    /// How long an I/O-bound job runs before waiting, and how long it waits.
+    pub burst: RangeInclusive<u64>,
+    pub io: RangeInclusive<u64>,
+}
+
+impl Default for WorkloadConfig {
+    fn default() -> Self {
+        WorkloadConfig {
+            io_bound_percent: 50,
+            interarrival: 0..=4,
+            cpu: 2..=12,
+            burst: 1..=2,
+            io: 2..=6,
+        }
+    }
+}
+
+/// An endless, reproducible stream of jobs, in arrival order. Take as many as the run needs. A clone draws from the
+/// same `Rng`, so the two share out one stream of jobs rather than each repeating it.
+#[derive(Debug, Clone)]
+pub struct WorkloadGen {
+    config: WorkloadConfig,
+    rng: Rng,
+    next_arrival: u64,
+}
+
+impl WorkloadGen {
+    pub fn new(seed: u64, config: WorkloadConfig) -> Self {
+        WorkloadGen::with_rng(Rng::new(seed), config)
+    }
+
+    /// A workload made up from `rng`, which may be shared with the rest of the run.
+    pub fn with_rng(rng: Rng, config: WorkloadConfig) -> Self {
+        WorkloadGen {
+            config,
+            rng,
+            next_arrival: 0,
+        }
+    }
+
+    // Uniform in `range`; an empty range gives its start.
+    fn pick(&mut self, range: &RangeInclusive<u64>) -> u64 {
+        let (lo, hi) = (*range.start(), *range.end());
+        if hi <= lo {
+            return lo;
+        }
+        lo + self.rng.below(hi - lo + 1)
+    }
+}
+
+impl Iterator for WorkloadGen {
+    type Item = Job;
+
+    fn next(&mut self) -> Option<Job> {
+        let config = self.config.clone();
+        let arrival = self.next_arrival;
+        self.next_arrival += self.pick(&config.interarrival);
+        let cpu = self.pick(&config.cpu).max(1);
+        let job = if self.rng.below(100) < config.io_bound_percent {
+            Job {
+                arrival,
+                kind: JobKind::IoBound,
+                cpu,
+                burst: self.pick(&config.burst).max(1),
+                io: self.pick(&config.io),
+            }
+        } else {
+            Job {
+                arrival,
+                kind: JobKind::CpuBound,
+                cpu,
+                burst: cpu,
+                io: 0,
+            }
+        };
+        Some(job)
+    }
+}
+
+// A job that has been spawned and hasn't finished yet.
+struct Running {
+    pid: Pid,
+    job: Job,
+    used: u64,
+    since_io: u64,
+}
+
+impl<S: Scheduler> Simulation<S> {
+    /// Spawns each job as it arrives and plays it out: after every tick it runs, a job either exits, having had all
+    /// the CPU it needs, or if a burst just ended goes to sleep for its I/O. Stops once every job has finished, or
+    /// after `max_ticks`. Returns the jobs' PIDs, in job order.
+    pub fn run_workload(
+        &mut self,
+        jobs: impl IntoIterator<Item = Job>,
+        max_ticks: u64,
+    ) -> Vec<Pid> {
+        let pids = self.table().init().pid.allocator();
+        let start = self.now();
+        let mut jobs = jobs.into_iter().peekable();
+        let mut spawned = Vec::new();
+        let mut running: Vec<Running> = Vec::new();
+
+        while self.now() - start < max_ticks && (jobs.peek().is_some() || !running.is_empty()) {
+            while let Some(job) = jobs.next_if(|job| job.arrival <= self.now() - start) {
+                let mut proc = Proc::new(&pids);
+                let name = match job.kind {
+                    JobKind::CpuBound => "cpu-bound",
+                    JobKind::IoBound => "io-bound",
+                };
+                proc.exec(name, &[]);
+                let pid = self.spawn(proc);
+                spawned.push(pid);
+                running.push(Running {
+                    pid,
+                    job,
+                    used: 0,
+                    since_io: 0,
+                });
+            }
+
+            let Some(ran) = self.step() else { continue };
+            let Some(idx) = running.iter().position(|r| r.pid == ran) else {
+                continue;
+            };
+            let job = &mut running[idx];
+            let proc = self.proc_mut(ran).expect("running jobs are in the table");
+            job.used += 1;
+            job.since_io += 1;
+            if job.used >= job.job.cpu {
+                proc.exit(0);
+                running.remove(idx);
+            } else if job.job.io > 0 && job.since_io >= job.job.burst {
+                job.since_io = 0;
+                // A scheduler may take a process off the CPU as its quantum ends, but it issued the I/O in that tick
+                if proc.state() == State::Stopped {
+                    proc.set_state(State::Running);
+                }
+                proc.sleep_for(job.job.io).expect("the job ran this tick");
+            }
+        }
+        // Statistics only see an exit on the tick after it, so give them that tick for the last job
+        if running.is_empty() && jobs.peek().is_none() && self.now() - start < max_ticks {
+            self.step();
+        }
+        spawned
+    }
+}
+
+#[test]
+fn test_same_seed_same_workload() {
+    let config = WorkloadConfig {
+        io_bound_percent: 25,
+        ..WorkloadConfig::default()
+    };
+    let jobs: Vec<_> = WorkloadGen::new(9, config.clone()).take(400).collect();
+    assert_eq!(
+        jobs,
+        WorkloadGen::new(9, config.clone())
+            .take(400)
+            .collect::<Vec<_>>()
+    );
+    assert_ne!(
+        jobs,
+        WorkloadGen::new(10, config).take(400).collect::<Vec<_>>()
+    );
+
+    assert!(jobs.windows(2).all(|w| w[0].arrival <= w[1].arrival));
+    assert!(jobs.iter().all(|j| (2..=12).contains(&j.cpu)));
+    let io_bound = jobs.iter().filter(|j| j.kind == JobKind::IoBound).count();
+    assert!((70..=130).contains(&io_bound), "{} I/O-bound", io_bound);
+    assert!(jobs
+        .iter()
+        .filter(|j| j.kind == JobKind::CpuBound)
+        .all(|j| j.io == 0 && j.burst == j.cpu));
+}
+
+#[test]
+fn test_run_workload_plays_every_job_out() {
+    use super::{ProcTable, RoundRobinScheduler};
+
+    let pids = super::PidAllocator::default();
+    let mut sim = Simulation::new(
+        RoundRobinScheduler::new(2),
+        ProcTable::new(Proc::new(&pids)),
+    );
+    let jobs: Vec<_> = WorkloadGen::new(3, WorkloadConfig::default())
+        .take(10)
+        .collect();
+    let spawned = sim.run_workload(jobs.clone(), 1_000);
+
+    assert_eq!(spawned.len(), 10);
+    for (pid, job) in spawned.iter().zip(&jobs) {
+        let stats = sim.stats().proc_stats(*pid).unwrap();
+        assert_eq!(stats.cpu_time, job.cpu);
+        assert!(stats.arrival >= job.arrival);
+        assert!(stats.turnaround().is_some());
+        assert!(sim.table().get(*pid).unwrap().state().is_exited());
+    }
+
+    // A run cut short leaves jobs unfinished
+    let mut sim = Simulation::new(
+        RoundRobinScheduler::new(2),
+        ProcTable::new(Proc::new(&pids)),
+    );
+    let spawned = sim.run_workload(jobs, 5);
+    assert_eq!(sim.now(), 5);
+    assert!(spawned
+        .iter()
+        .any(|&pid| !sim.table().get(pid).unwrap().state().is_exited()));
+}
+
+#[test]
+fn test_one_seed_reproduces_the_whole_run() {
+    use super::{LotteryScheduler, PidAllocator, ProcTable};
+
+    // Jobs are made up as they arrive, so the workload's draws and the lottery's interleave on the one stream
+    let run = |seed| {
+        let rng = Rng::new(seed);
+        let pids = PidAllocator::default();
+        let mut sim = Simulation::new(
+            LotteryScheduler::with_rng(rng.clone()),
+            ProcTable::new(Proc::new(&pids)),
+        );
+        let jobs = WorkloadGen::with_rng(rng, WorkloadConfig::default()).take(8);
+        let spawned = sim.run_workload(jobs, 500);
+        (spawned, sim.timeline(), sim.stats().report())
+    };
+    let first = run(3);
+    assert_eq!(first.0.len(), 8);
+    assert_eq!(first, run(3));
+    assert_ne!(first.1, run(4).1);
+}